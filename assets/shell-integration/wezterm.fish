@@ -0,0 +1,127 @@
+# This file hooks up shell integration for wezterm with fish.
+#
+# It provides the same functionality as wezterm.sh does for bash and zsh,
+# and honors the same bypasses:
+#
+# WEZTERM_SHELL_SKIP_ALL - disables all
+# WEZTERM_SHELL_SKIP_SEMANTIC_ZONES - disables zones
+# WEZTERM_SHELL_SKIP_CWD - disables OSC 7 cwd setting
+# WEZTERM_SHELL_SKIP_USER_VARS - disable user vars that capture information
+#                                about running programs
+
+# When wezterm automatically injects this file into fish, it does so by
+# adding a directory to XDG_DATA_DIRS.  Remove it again so that it isn't
+# inherited by the programs that are run from this shell.
+if set -q WEZTERM_SHELL_INTEGRATION_XDG_DIR
+  set -gx XDG_DATA_DIRS (string replace -- "$WEZTERM_SHELL_INTEGRATION_XDG_DIR:" "" "$XDG_DATA_DIRS")
+  if test -z "$XDG_DATA_DIRS"
+    set -e XDG_DATA_DIRS
+  end
+  set -e WEZTERM_SHELL_INTEGRATION_XDG_DIR
+end
+
+if test "$WEZTERM_SHELL_SKIP_ALL" = "1"
+  exit 0
+end
+
+if not status is-interactive
+  # Shell integration is only useful in interactive sessions
+  exit 0
+end
+
+if contains -- "$TERM" linux dumb
+  # Avoid terminals that don't like OSC sequences
+  exit 0
+end
+
+if set -q __wezterm_shell_integration_loaded
+  # Avoid installing the hooks twice if this file is sourced again
+  exit 0
+end
+set -g __wezterm_shell_integration_loaded 1
+
+function __wezterm_set_user_var
+  if command -q base64
+    set -l value (printf %s "$argv[2]" | base64 | string join '')
+    if set -q TMUX
+      # <https://github.com/tmux/tmux/wiki/FAQ#what-is-the-passthrough-escape-sequence-and-how-do-i-use-it>
+      # Note that you ALSO need to add "set -g allow-passthrough on" to your tmux.conf
+      printf "\033Ptmux;\033\033]1337;SetUserVar=%s=%s\007\033\\" $argv[1] $value
+    else
+      printf "\033]1337;SetUserVar=%s=%s\007" $argv[1] $value
+    end
+  end
+end
+
+# The semantic zone functions mark up the prompt, the user input and
+# the command output so that the terminal can better reason about
+# the display, and report the exit status of each command.
+if test -z "$WEZTERM_SHELL_SKIP_SEMANTIC_ZONES"
+  function __wezterm_semantic_prompt --on-event fish_prompt
+    # Fresh line and start the prompt
+    printf "\033]133;A;cl=m;aid=%s\007" $fish_pid
+    # Wrap the prompt so that the end of the prompt is marked.
+    # This is deferred until the first prompt so that it applies
+    # to the prompt defined by the user's config.fish.
+    if not functions -q __wezterm_original_fish_prompt
+      functions -c fish_prompt __wezterm_original_fish_prompt
+      function fish_prompt
+        __wezterm_original_fish_prompt
+        printf "\033]133;B\007"
+      end
+    end
+  end
+
+  function __wezterm_semantic_preexec --on-event fish_preexec
+    # Indicate that the command output begins here
+    printf "\033]133;C;\007"
+  end
+
+  function __wezterm_semantic_postexec --on-event fish_postexec
+    # Report last command status
+    printf "\033]133;D;%s;aid=%s\007" $status $fish_pid
+  end
+end
+
+if test -z "$WEZTERM_SHELL_SKIP_USER_VARS"
+  function __wezterm_user_vars_precmd --on-event fish_prompt
+    __wezterm_set_user_var WEZTERM_PROG ""
+    __wezterm_set_user_var WEZTERM_USER (id -un)
+
+    # Indicate whether this pane is running inside tmux or not
+    if set -q TMUX
+      __wezterm_set_user_var WEZTERM_IN_TMUX 1
+    else
+      __wezterm_set_user_var WEZTERM_IN_TMUX 0
+    end
+
+    # You may set WEZTERM_HOSTNAME to a name you want to use instead
+    # of the hostname of the system.
+    if set -q WEZTERM_HOSTNAME
+      __wezterm_set_user_var WEZTERM_HOST $WEZTERM_HOSTNAME
+    else
+      __wezterm_set_user_var WEZTERM_HOST $hostname
+    end
+  end
+
+  function __wezterm_user_vars_preexec --on-event fish_preexec
+    # Tell wezterm the full command that is being run
+    __wezterm_set_user_var WEZTERM_PROG "$argv"
+  end
+end
+
+# This function emits an OSC 7 sequence to inform the terminal
+# of the current working directory.  It prefers to use a helper
+# command provided by wezterm if wezterm is installed, but falls
+# back to a simple printf command otherwise.
+if test -z "$WEZTERM_SHELL_SKIP_CWD"
+  function __wezterm_osc7 --on-variable PWD
+    if command -q wezterm
+      wezterm set-working-directory 2>/dev/null; and return 0
+      # If the command failed (perhaps the installed wezterm
+      # is too old?) then fall back to the simple version below.
+    end
+    printf "\033]7;file://%s%s\033\\" $hostname $PWD
+  end
+  __wezterm_osc7
+end
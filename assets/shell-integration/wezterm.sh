@@ -36,6 +36,14 @@ case "$TERM" in
   ;;
 esac
 
+if [[ -n "${__wezterm_shell_integration_loaded}" ]] ; then
+  # Avoid installing the hooks twice if this file is sourced again,
+  # for example when it was injected automatically by wezterm and
+  # is also sourced from the user's rc file
+  return 0
+fi
+__wezterm_shell_integration_loaded=1
+
 # This function wraps bash-preexec.sh so that it can be included verbatim
 # in this file, even though it uses `return` to short-circuit in some cases.
 __wezterm_install_bash_prexec() {
@@ -457,13 +457,16 @@ TOC = [
         "cli/general.md",
         children=[
             Gen("wezterm cli", "cli/cli"),
+            Page("wezterm check-config", "cli/check-config.md"),
             Page("wezterm connect", "cli/connect.md"),
             Page("wezterm imgcat", "cli/imgcat.md"),
             Page("wezterm ls-fonts", "cli/ls-fonts.md"),
             Page("wezterm record", "cli/record.md"),
             Page("wezterm replay", "cli/replay.md"),
+            Page("wezterm screenshot", "cli/screenshot.md"),
             Page("wezterm serial", "cli/serial.md"),
             Page("wezterm set-working-directory", "cli/set-working-directory.md"),
+            Page("wezterm shell-integration", "cli/shell-integration.md"),
             Page("wezterm show-keys", "cli/show-keys.md"),
             Page("wezterm ssh", "cli/ssh.md"),
             Page("wezterm start", "cli/start.md"),
@@ -441,7 +441,7 @@ macro_rules! pdu {
 /// The overall version of the codec.
 /// This must be bumped when backwards incompatible changes
 /// are made to the types and protocol.
-pub const CODEC_VERSION: usize = 43;
+pub const CODEC_VERSION: usize = 50;
 
 // Defines the Pdu enum.
 // Each struct has an explicit identifying number.
@@ -502,6 +502,9 @@ pdu! {
     GetPaneDirection: 60,
     GetPaneDirectionResponse: 61,
     AdjustPaneSize: 62,
+    RecordPane: 63,
+    RecordPaneResponse: 64,
+    ScrollViewportIntoScrollback: 65,
 }
 
 impl Pdu {
@@ -683,6 +686,9 @@ pub struct SpawnV2 {
     pub command_dir: Option<String>,
     pub size: TerminalSize,
     pub workspace: String,
+    /// Overrides the windowing system class of the gui window
+    /// when creating a new window
+    pub class: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, PartialEq, Debug)]
@@ -881,6 +887,24 @@ pub struct GetPaneDirectionResponse {
     pub pane_id: Option<PaneId>,
 }
 
+/// Starts or stops recording a pane as an asciicast
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct RecordPane {
+    pub pane_id: PaneId,
+    /// If true, stop an existing recording rather than starting one
+    pub stop: bool,
+    /// Where to save the recording.  If None, a new file
+    /// in the recording_directory is used.
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct RecordPaneResponse {
+    /// The path to the recording, or None if stop was
+    /// requested and the pane was not being recorded
+    pub path: Option<PathBuf>,
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Debug)]
 pub struct ActivatePaneDirection {
     pub pane_id: PaneId,
@@ -914,6 +938,7 @@ pub struct LivenessResponse {
 pub struct GetPaneRenderChangesResponse {
     pub pane_id: PaneId,
     pub mouse_grabbed: bool,
+    pub alt_screen_active: bool,
     pub cursor_position: StableCursorPosition,
     pub dimensions: RenderableDimensions,
     pub dirty_lines: Vec<Range<StableRowIndex>>,
@@ -1115,6 +1140,12 @@ pub struct EraseScrollbackRequest {
     pub erase_mode: ScrollbackEraseMode,
 }
 
+/// Moves the lines above the current prompt of a pane into its scrollback
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+pub struct ScrollViewportIntoScrollback {
+    pub pane_id: PaneId,
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Debug)]
 pub struct SearchScrollbackRequest {
     pub pane_id: PaneId,
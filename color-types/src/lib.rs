@@ -867,6 +867,21 @@ impl LinearRgba {
         Self(self.0, self.1, self.2, self.3 * alpha)
     }
 
+    /// Composites self over other according to the alpha of self,
+    /// returning a color with the alpha of other.
+    /// A fully transparent self returns other unchanged, while a
+    /// fully opaque self replaces its rgb components.
+    pub fn blend_over(self, other: Self) -> Self {
+        let alpha = self.3;
+        let mix = |a: f32, b: f32| a * alpha + b * (1.0 - alpha);
+        Self(
+            mix(self.0, other.0),
+            mix(self.1, other.1),
+            mix(self.2, other.2),
+            other.3,
+        )
+    }
+
     /// Convert to an SRGB u32 pixel
     pub fn srgba_pixel(self) -> SrgbaPixel {
         SrgbaPixel::rgba(
@@ -896,6 +911,18 @@ impl LinearRgba {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[test]
+    fn blend_over() {
+        let red = LinearRgba::with_components(1., 0., 0., 1.);
+        let blue = LinearRgba::with_components(0., 0., 1., 1.);
+        assert_eq!(LinearRgba::TRANSPARENT.blend_over(red), red);
+        assert_eq!(blue.blend_over(red), blue);
+        assert_eq!(
+            blue.mul_alpha(0.5).blend_over(red.mul_alpha(0.8)),
+            LinearRgba::with_components(0.5, 0., 0.5, 0.8)
+        );
+    }
+
     #[test]
     fn named_rgb() {
         let dark_green = SrgbaTuple::from_named("DarkGreen").unwrap();
@@ -112,7 +112,7 @@ impl BackgroundLayer {
         } else {
             return None;
         };
-        Some(BackgroundLayer {
+        let mut layer = BackgroundLayer {
             source,
             opacity: cfg.window_background_opacity,
             hsb: cfg.window_background_image_hsb.unwrap_or_default(),
@@ -128,7 +128,52 @@ impl BackgroundLayer {
             horizontal_offset: None,
             width: BackgroundSize::Dimension(Dimension::Percent(1.)),
             height: BackgroundSize::Dimension(Dimension::Percent(1.)),
-        })
+        };
+        if cfg.window_background_image.is_some() {
+            cfg.window_background_image_mode.apply_to(&mut layer);
+        }
+        Some(layer)
+    }
+}
+
+/// A simplified way to describe how the legacy `window_background_image`
+/// should be scaled and positioned within the window.
+#[derive(Debug, Copy, Clone, FromDynamic, ToDynamic, PartialEq, Eq, Default)]
+pub enum BackgroundImageMode {
+    /// Stretch the image to the size of the window, ignoring
+    /// its aspect ratio.
+    #[default]
+    Stretch,
+    /// Scale the image, preserving its aspect ratio, so that it
+    /// is entirely visible, and center it in the window.
+    Fit,
+    /// Scale the image, preserving its aspect ratio, so that it
+    /// covers the whole window, cropping as needed, and center it.
+    Fill,
+    /// Repeat the image at its natural size to cover the window.
+    Tile,
+    /// Display a single copy of the image at its natural size
+    /// in the center of the window.
+    Center,
+}
+
+impl BackgroundImageMode {
+    fn apply_to(self, layer: &mut BackgroundLayer) {
+        let (size, repeat, centered) = match self {
+            Self::Stretch => return,
+            Self::Fit => (BackgroundSize::Contain, BackgroundRepeat::NoRepeat, true),
+            Self::Fill => (BackgroundSize::Cover, BackgroundRepeat::NoRepeat, true),
+            Self::Tile => (BackgroundSize::Auto, BackgroundRepeat::Repeat, false),
+            Self::Center => (BackgroundSize::Auto, BackgroundRepeat::NoRepeat, true),
+        };
+        layer.width = size;
+        layer.height = size;
+        layer.repeat_x = repeat;
+        layer.repeat_y = repeat;
+        if centered {
+            layer.horizontal_align = BackgroundHorizontalAlignment::Center;
+            layer.vertical_align = BackgroundVerticalAlignment::Middle;
+        }
     }
 }
 
@@ -144,6 +189,8 @@ pub enum BackgroundSize {
     /// If the aspect ratio differs from the background, the image is
     /// cropped.
     Cover,
+    /// Uses the natural size of the image.
+    Auto,
     /// Stretches the image to the specified length in pixels
     Dimension(Dimension),
 }
@@ -157,6 +204,7 @@ impl FromDynamic for BackgroundSize {
             Value::String(label) => match label.as_str() {
                 "Contain" => return Ok(Self::Contain),
                 "Cover" => return Ok(Self::Cover),
+                "Auto" => return Ok(Self::Auto),
                 _ => {}
             },
             _ => {}
@@ -164,7 +212,7 @@ impl FromDynamic for BackgroundSize {
         match PixelUnit::from_dynamic(value, options) {
             Ok(pix) => Ok(Self::Dimension(pix.into())),
             Err(_) => Err(wezterm_dynamic::Error::Message(format!(
-                "expected either 'Contain', 'Cover', \
+                "expected either 'Contain', 'Cover', 'Auto', \
                         a number, or a string of \
                         the form '123px' where 'px' is a unit and \
                         can be one of 'px', '%', 'pt' or 'cell', \
@@ -180,6 +228,7 @@ impl ToDynamic for BackgroundSize {
         let s = match self {
             Self::Cover => "Cover".to_string(),
             Self::Contain => "Contain".to_string(),
+            Self::Auto => "Auto".to_string(),
             Self::Dimension(d) => return d.to_dynamic(),
         };
         Value::String(s)
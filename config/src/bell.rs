@@ -1,4 +1,4 @@
-use wezterm_dynamic::{FromDynamic, ToDynamic};
+use wezterm_dynamic::{Error as DynError, FromDynamic, FromDynamicOptions, ToDynamic, Value};
 
 /// <https://developer.mozilla.org/en-US/docs/Web/CSS/easing-function>
 #[derive(Debug, Clone, Copy, FromDynamic, ToDynamic, PartialEq)]
@@ -70,10 +70,12 @@ impl Default for VisualBellTarget {
     }
 }
 
-#[derive(Debug, Clone, FromDynamic, ToDynamic)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AudibleBell {
     SystemBeep,
     Disabled,
+    /// Play the sound file at the specified path
+    SoundFile(String),
 }
 
 impl Default for AudibleBell {
@@ -81,3 +83,68 @@ impl Default for AudibleBell {
         Self::SystemBeep
     }
 }
+
+/// The table form of `audible_bell`: `{ sound_file = "/path/to/bell.wav" }`
+#[derive(Debug, Clone, FromDynamic, ToDynamic)]
+struct AudibleBellSoundFile {
+    sound_file: String,
+}
+
+impl FromDynamic for AudibleBell {
+    fn from_dynamic(value: &Value, options: FromDynamicOptions) -> Result<Self, DynError> {
+        match value {
+            Value::String(s) => match s.as_str() {
+                "SystemBeep" => Ok(Self::SystemBeep),
+                "Disabled" => Ok(Self::Disabled),
+                s => Err(DynError::Message(format!(
+                    "`{s}` is not valid, use one of `SystemBeep`, `Disabled` \
+                     or `{{ sound_file = \"/path/to/sound\" }}`"
+                ))),
+            },
+            _ => {
+                let file = AudibleBellSoundFile::from_dynamic(value, options)?;
+                Ok(Self::SoundFile(file.sound_file))
+            }
+        }
+    }
+}
+
+impl ToDynamic for AudibleBell {
+    fn to_dynamic(&self) -> Value {
+        match self {
+            Self::SystemBeep => "SystemBeep".to_dynamic(),
+            Self::Disabled => "Disabled".to_dynamic(),
+            Self::SoundFile(sound_file) => AudibleBellSoundFile {
+                sound_file: sound_file.clone(),
+            }
+            .to_dynamic(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn audible_bell_round_trip() {
+        for bell in [
+            AudibleBell::SystemBeep,
+            AudibleBell::Disabled,
+            AudibleBell::SoundFile("/tmp/bell.wav".to_string()),
+        ] {
+            let value = bell.to_dynamic();
+            assert_eq!(
+                AudibleBell::from_dynamic(&value, FromDynamicOptions::default()).unwrap(),
+                bell
+            );
+        }
+    }
+
+    #[test]
+    fn audible_bell_invalid() {
+        assert!(
+            AudibleBell::from_dynamic(&"Loud".to_dynamic(), FromDynamicOptions::default()).is_err()
+        );
+    }
+}
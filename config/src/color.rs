@@ -8,7 +8,7 @@ pub use termwiz::color::{AnsiColor, ColorAttribute, RgbColor, SrgbaTuple};
 use wezterm_dynamic::{FromDynamic, ToDynamic};
 use wezterm_term::color::ColorPalette;
 
-#[derive(Debug, Copy, Clone, FromDynamic, ToDynamic)]
+#[derive(Debug, Copy, Clone, PartialEq, FromDynamic, ToDynamic)]
 pub struct HsbTransform {
     #[dynamic(default = "default_one_point_oh")]
     pub hue: f32,
@@ -155,6 +155,12 @@ pub struct Palette {
     pub scrollbar_thumb: Option<RgbaColor>,
     /// The color of the split line between panes
     pub split: Option<RgbaColor>,
+    /// The color of a split line while the mouse is over it
+    /// or it is being dragged. If unspecified, `split` is used.
+    pub split_hover: Option<RgbaColor>,
+    /// The color of the split lines that border the active pane.
+    /// If unspecified, `split` is used.
+    pub split_active: Option<RgbaColor>,
     /// The color of the visual bell. If unspecified, the foreground
     /// color is used instead.
     pub visual_bell: Option<RgbaColor>,
@@ -165,11 +171,19 @@ pub struct Palette {
     pub copy_mode_active_highlight_bg: Option<ColorSpec>,
     pub copy_mode_inactive_highlight_fg: Option<ColorSpec>,
     pub copy_mode_inactive_highlight_bg: Option<ColorSpec>,
+    /// The colors of the cursor while in copy mode or search mode.
+    /// If unspecified, the cursor_* colors are used instead.
+    pub copy_mode_cursor_fg: Option<RgbaColor>,
+    pub copy_mode_cursor_bg: Option<RgbaColor>,
+    pub copy_mode_cursor_border: Option<RgbaColor>,
 
     pub quick_select_label_fg: Option<ColorSpec>,
     pub quick_select_label_bg: Option<ColorSpec>,
     pub quick_select_match_fg: Option<ColorSpec>,
     pub quick_select_match_bg: Option<ColorSpec>,
+
+    /// The background of the cells highlighted by TogglePaneDiffHighlight
+    pub diff_highlight_bg: Option<ColorSpec>,
 }
 impl_lua_conversion_dynamic!(Palette);
 
@@ -209,16 +223,22 @@ impl Palette {
             },
             scrollbar_thumb: overlay!(scrollbar_thumb),
             split: overlay!(split),
+            split_hover: overlay!(split_hover),
+            split_active: overlay!(split_active),
             visual_bell: overlay!(visual_bell),
             compose_cursor: overlay!(compose_cursor),
             copy_mode_active_highlight_fg: overlay!(copy_mode_active_highlight_fg),
             copy_mode_active_highlight_bg: overlay!(copy_mode_active_highlight_bg),
             copy_mode_inactive_highlight_fg: overlay!(copy_mode_inactive_highlight_fg),
             copy_mode_inactive_highlight_bg: overlay!(copy_mode_inactive_highlight_bg),
+            copy_mode_cursor_fg: overlay!(copy_mode_cursor_fg),
+            copy_mode_cursor_bg: overlay!(copy_mode_cursor_bg),
+            copy_mode_cursor_border: overlay!(copy_mode_cursor_border),
             quick_select_label_fg: overlay!(quick_select_label_fg),
             quick_select_label_bg: overlay!(quick_select_label_bg),
             quick_select_match_fg: overlay!(quick_select_match_fg),
             quick_select_match_bg: overlay!(quick_select_match_bg),
+            diff_highlight_bg: overlay!(diff_highlight_bg),
         }
     }
 }
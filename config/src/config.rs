@@ -1,4 +1,4 @@
-use crate::background::{BackgroundLayer, Gradient};
+use crate::background::{BackgroundImageMode, BackgroundLayer, Gradient};
 use crate::bell::{AudibleBell, EasingFunction, VisualBell};
 use crate::color::{
     ColorSchemeFile, HsbTransform, Palette, SrgbaTuple, TabBarStyle, WindowFrameConfig,
@@ -7,11 +7,13 @@ use crate::daemon::DaemonOptions;
 use crate::exec_domain::ExecDomain;
 use crate::font::{
     AllowSquareGlyphOverflow, DisplayPixelGeometry, FontLocatorSelection, FontRasterizerSelection,
-    FontShaperSelection, FreeTypeLoadFlags, FreeTypeLoadTarget, StyleRule, TextStyle,
+    FontShaperSelection, FreeTypeLcdFilter, FreeTypeLoadFlags, FreeTypeLoadTarget, StyleRule,
+    TextStyle,
 };
-use crate::frontend::FrontEndSelection;
+use crate::frontend::{FrontEndSelection, PresentMode};
 use crate::keyassignment::{
-    KeyAssignment, KeyTable, KeyTableEntry, KeyTables, MouseEventTrigger, SpawnCommand,
+    ClipboardCopyDestination, ClipboardPasteSource, CopyModeReplacement, KeyAssignment, KeyTable,
+    KeyTableEntry, KeyTables, MouseEventTrigger, PaneArrangement, PaneLayout, SpawnCommand,
 };
 use crate::keys::{Key, LeaderKey, Mouse};
 use crate::lua::make_lua_context;
@@ -19,6 +21,7 @@ use crate::ssh::{SshBackend, SshDomain};
 use crate::tls::{TlsDomainClient, TlsDomainServer};
 use crate::units::Dimension;
 use crate::unix::UnixDomain;
+use crate::window::UserAttentionType;
 use crate::wsl::WslDomain;
 use crate::{
     default_config_with_overrides_applied, default_one_point_oh, default_one_point_oh_f64,
@@ -66,6 +69,17 @@ pub struct Config {
     #[dynamic(try_from = "crate::units::OptPixelUnit", default)]
     pub cursor_thickness: Option<Dimension>,
 
+    /// If specified, overrides cursor_thickness for the underline
+    /// cursor styles.
+    #[dynamic(try_from = "crate::units::OptPixelUnit", default)]
+    pub cursor_underline_thickness: Option<Dimension>,
+
+    /// If specified, overrides cursor_thickness for the bar
+    /// cursor styles.  Cell based units are relative to the
+    /// width of the cell.
+    #[dynamic(try_from = "crate::units::OptPixelUnit", default)]
+    pub cursor_bar_width: Option<Dimension>,
+
     #[dynamic(try_from = "crate::units::OptPixelUnit", default)]
     pub underline_thickness: Option<Dimension>,
 
@@ -75,12 +89,34 @@ pub struct Config {
     #[dynamic(try_from = "crate::units::OptPixelUnit", default)]
     pub strikethrough_position: Option<Dimension>,
 
+    #[dynamic(try_from = "crate::units::OptPixelUnit", default)]
+    pub strikethrough_thickness: Option<Dimension>,
+
+    #[dynamic(try_from = "crate::units::OptPixelUnit", default)]
+    pub overline_position: Option<Dimension>,
+
     #[dynamic(default)]
     pub allow_square_glyphs_to_overflow_width: AllowSquareGlyphOverflow,
 
+    /// Scales color glyphs, such as emoji, about their center.
+    /// Values smaller than 1.0 help to prevent oversized emoji
+    /// from being clipped by the cell.
+    #[dynamic(default = "default_one_point_oh_f64")]
+    pub emoji_scale: f64,
+
     #[dynamic(default)]
     pub window_decorations: WindowDecorations,
 
+    /// The path to an image to use as the icon of the window,
+    /// in place of the built-in wezterm icon
+    #[dynamic(default)]
+    pub window_icon: Option<PathBuf>,
+
+    /// Maps a window class to the path of the icon to use for
+    /// windows with that class, in place of window_icon
+    #[dynamic(default)]
+    pub window_icon_by_class: HashMap<String, PathBuf>,
+
     #[dynamic(default = "default_integrated_title_buttons")]
     pub integrated_title_buttons: Vec<IntegratedTitleButton>,
 
@@ -127,6 +163,12 @@ pub struct Config {
     #[dynamic(default)]
     pub bold_brightens_ansi_colors: BoldBrightening,
 
+    /// Controls whether reverse video swaps the colors of a cell
+    /// after they have been resolved from the palette (the default),
+    /// or swaps the color attributes before resolving them
+    #[dynamic(default)]
+    pub reverse_video_swap: ReverseVideoSwap,
+
     /// The color palette
     pub colors: Option<Palette>,
 
@@ -180,6 +222,14 @@ pub struct Config {
     /// by the colors setting.
     pub color_scheme: Option<String>,
 
+    /// How long it takes to cross-fade from the old colors to the
+    /// new colors when the color scheme or palette changes.
+    /// 0 means that the change takes effect immediately.
+    #[dynamic(default)]
+    pub color_scheme_transition_duration_ms: u64,
+    #[dynamic(default)]
+    pub color_scheme_transition_function: EasingFunction,
+
     /// Named color schemes
     #[dynamic(default)]
     pub color_schemes: HashMap<String, Palette>,
@@ -188,6 +238,42 @@ pub struct Config {
     #[dynamic(default = "default_scrollback_lines")]
     pub scrollback_lines: usize,
 
+    /// The command that ViewScrollback runs to show the scrollback.
+    /// The path to a file holding the scrollback text is appended
+    /// to these arguments.  If empty, the file is opened with the
+    /// application associated with text files.
+    #[dynamic(default = "default_scrollback_viewer")]
+    pub scrollback_viewer: Vec<String>,
+
+    /// Where CaptureScreenshot saves its images.
+    /// If not set, the Pictures directory is used, falling back
+    /// to the home directory.
+    #[dynamic(default)]
+    pub screenshot_directory: Option<PathBuf>,
+
+    /// Where TogglePaneRecording saves its asciicast recordings.
+    /// If not set, the home directory is used.
+    #[dynamic(default)]
+    pub recording_directory: Option<PathBuf>,
+
+    /// When true, pane recordings also capture the input that
+    /// is sent to the pane, which may include passwords.
+    #[dynamic(default)]
+    pub recording_include_input: bool,
+
+    /// While the search overlay has a pattern, the text that doesn't
+    /// match it is shown with its foreground color scaled towards its
+    /// background color by this factor.
+    /// 1.0 (the default) leaves the text unchanged.
+    #[dynamic(default = "default_one_point_oh")]
+    pub search_unmatched_text_brightness: f32,
+
+    /// When true, show the duration, exit status and completion time
+    /// at the end of the output of each command that was reported by
+    /// OSC 133 shell integration
+    #[dynamic(default)]
+    pub show_command_annotations: bool,
+
     /// If no `prog` is specified on the command line, use this
     /// instead of running the user's shell.
     /// For example, to have `wezterm` always run `top` by default,
@@ -202,6 +288,18 @@ pub struct Config {
     /// as the positional arguments to that command.
     pub default_prog: Option<Vec<String>>,
 
+    /// Maps a domain name to the program that should be spawned into
+    /// that domain when no explicit command is given, taking precedence
+    /// over `default_prog` and the `default_prog` of the domain itself
+    #[dynamic(default)]
+    pub default_prog_per_domain: HashMap<String, Vec<String>>,
+
+    /// When true, zsh and fish shells that are spawned by the local
+    /// domain have the wezterm shell integration loaded automatically,
+    /// by adjusting their environment
+    #[dynamic(default)]
+    pub shell_integration_auto_inject: bool,
+
     #[dynamic(default = "default_gui_startup_args")]
     pub default_gui_startup_args: Vec<String>,
 
@@ -245,6 +343,11 @@ pub struct Config {
     #[dynamic(default)]
     pub enable_title_reporting: bool,
 
+    /// Whether the terminal should honor requests from the application
+    /// to resize or maximize the window
+    #[dynamic(default)]
+    pub window_resize_requests: WindowResizeRequests,
+
     /// Specifies the width of a new window, expressed in character cells
     #[dynamic(default = "default_initial_cols", validate = "validate_row_or_col")]
     pub initial_cols: u16,
@@ -252,6 +355,18 @@ pub struct Config {
     #[dynamic(default = "default_hyperlink_rules")]
     pub hyperlink_rules: Vec<hyperlink::Rule>,
 
+    /// When true, references to a file and line number, such as
+    /// `src/main.rs:12:5` in compiler output, are turned into
+    /// hyperlinks that open the file in editor_command.
+    #[dynamic(default)]
+    pub detect_file_path_links: bool,
+
+    /// The command used to open file path links.  The strings
+    /// `{file}`, `{line}` and `{column}` in the arguments are
+    /// replaced with the corresponding values from the link.
+    #[dynamic(default)]
+    pub editor_command: Option<Vec<String>>,
+
     /// What to set the TERM variable to
     #[dynamic(default = "default_term")]
     pub term: String,
@@ -274,6 +389,21 @@ pub struct Config {
     #[dynamic(default)]
     pub freetype_load_flags: Option<FreeTypeLoadFlags>,
 
+    /// Selects the filter that freetype applies to subpixel
+    /// antialiased glyphs to reduce color fringing
+    #[dynamic(default)]
+    pub freetype_lcd_filter: FreeTypeLcdFilter,
+
+    /// Adjusts the coverage of glyphs rendered by freetype; values
+    /// larger than 1.0 make text heavier, smaller values make it lighter
+    #[dynamic(default = "default_one_point_oh")]
+    pub freetype_gamma: f32,
+
+    /// Adjusts the sharpness of the antialiased edges of glyphs
+    /// rendered by freetype; values larger than 1.0 increase it
+    #[dynamic(default = "default_one_point_oh")]
+    pub freetype_contrast: f32,
+
     /// Selects the freetype interpret version to use.
     /// Likely values are 35, 38 and 40 which have different
     /// characteristics with respective to subpixel hinting.
@@ -319,6 +449,12 @@ pub struct Config {
     #[dynamic(default = "default_harfbuzz_features")]
     pub harfbuzz_features: Vec<String>,
 
+    /// When the cursor is over a run of text that would otherwise be
+    /// rendered using ligatures, render that run without them so that
+    /// the individual characters can be seen while editing
+    #[dynamic(default)]
+    pub disable_ligatures_at_cursor: bool,
+
     #[dynamic(default)]
     pub front_end: FrontEndSelection,
 
@@ -390,6 +526,27 @@ pub struct Config {
     #[dynamic(default = "default_mux_output_parser_coalesce_delay_ms")]
     pub mux_output_parser_coalesce_delay_ms: u64,
 
+    /// The minimum number of milliseconds between notifying the
+    /// frontend that a pane has produced output.  When a program
+    /// floods the pane with many small writes, the output is still
+    /// applied to the terminal model as it arrives, but the renderer
+    /// is woken up at most once per interval.
+    #[dynamic(default = "default_mux_output_notify_interval_ms")]
+    pub mux_output_notify_interval_ms: u64,
+
+    /// The minimum number of milliseconds between sending the
+    /// changes in a pane to a connected mux client.  Changes made
+    /// in the meantime are merged into a single update that reflects
+    /// the latest state of the pane.
+    #[dynamic(default)]
+    pub mux_client_update_interval_ms: u64,
+
+    /// The maximum number of milliseconds to hold back output
+    /// while a program has enabled synchronized output (DEC 2026)
+    /// before rendering it anyway.
+    #[dynamic(default = "default_synchronized_output_timeout_ms")]
+    pub synchronized_output_timeout_ms: u64,
+
     #[dynamic(default = "default_mux_env_remove")]
     pub mux_env_remove: Vec<String>,
 
@@ -401,6 +558,79 @@ pub struct Config {
     #[dynamic(default = "default_bypass_mouse_reporting_modifiers")]
     pub bypass_mouse_reporting_modifiers: Modifiers,
 
+    /// Holding these modifiers while pressing the left mouse button
+    /// over the selection and then dragging drags the selected text
+    /// out of the window.  An empty set disables this.
+    #[dynamic(default = "default_selection_drag_modifiers")]
+    pub selection_drag_modifiers: Modifiers,
+
+    /// The maximum time between successive clicks for them to be
+    /// counted as a double or triple click
+    #[dynamic(default = "default_double_click_time_ms")]
+    pub double_click_time_ms: u64,
+
+    /// How far, in pixels, the mouse must move while a button is held
+    /// before it is considered to be a drag.  Successive clicks within
+    /// this distance of each other count towards a double click.
+    #[dynamic(default)]
+    pub mouse_drag_threshold: usize,
+
+    /// If set, clicking on a hyperlink will only open it when these
+    /// modifiers are held down, which helps to avoid accidentally
+    /// launching links when clicking to focus or select.
+    #[dynamic(default)]
+    pub hyperlink_click_modifiers: Modifiers,
+
+    /// Where the default mouse bindings copy the selected text
+    /// when a selection is completed
+    #[dynamic(default)]
+    pub selection_copy_destination: ClipboardCopyDestination,
+
+    /// When copying the selection, omit the cells that shell
+    /// integration marked as being part of a prompt
+    #[dynamic(default)]
+    pub selection_strip_prompts: bool,
+
+    /// When copying the selection, join lines that were wrapped
+    /// by the terminal rather than separating them with newlines
+    #[dynamic(default = "default_true")]
+    pub selection_join_wrapped_lines: bool,
+
+    /// When copying the selection, remove trailing whitespace from
+    /// each line
+    #[dynamic(default = "default_true")]
+    pub selection_trim_trailing_whitespace: bool,
+
+    /// Named presets of regex replacements that copy mode can apply
+    /// to the selected text before copying it
+    #[dynamic(default)]
+    pub copy_mode_replacements: HashMap<String, Vec<CopyModeReplacement>>,
+
+    /// Where the default middle click mouse binding pastes from
+    #[dynamic(default = "default_middle_click_paste_source")]
+    pub middle_click_paste_source: ClipboardPasteSource,
+
+    /// Whether to show the URI of the hyperlink under the mouse
+    /// cursor at the bottom of the window.
+    #[dynamic(default = "default_true")]
+    pub show_hyperlink_tooltip: bool,
+
+    /// Whether to show a `[Z]` badge in the top right corner of
+    /// a pane while it is zoomed
+    #[dynamic(default)]
+    pub show_zoom_indicator: bool,
+
+    /// What to show in the bottom right corner of the active pane
+    /// while it is scrolled back into the scrollback
+    #[dynamic(default)]
+    pub scroll_position_indicator: ScrollPositionIndicator,
+
+    /// Whether to briefly show the size of the terminal in
+    /// rows and columns in the middle of the window when it
+    /// is resized
+    #[dynamic(default)]
+    pub show_resize_overlay: bool,
+
     #[dynamic(default)]
     pub debug_key_events: bool,
 
@@ -463,6 +693,17 @@ pub struct Config {
     #[dynamic(default = "default_true")]
     pub show_tab_index_in_tab_bar: bool,
 
+    /// If true, tab bar titles are prefixed with a glyph that depicts
+    /// the progress reported by the active pane via OSC 9;4
+    #[dynamic(default)]
+    pub show_progress_in_tab_bar: bool,
+
+    /// If set, the default tab titles for tabs whose active pane
+    /// belongs to a domain other than `local` are prefixed with
+    /// this text, with `{domain}` replaced by the name of the domain
+    #[dynamic(default)]
+    pub tab_domain_label: Option<String>,
+
     #[dynamic(default = "default_true")]
     pub show_tabs_in_tab_bar: bool,
 
@@ -502,6 +743,13 @@ pub struct Config {
     #[dynamic(default)]
     pub enable_zwlr_output_manager: bool,
 
+    /// When using the default window_decorations on Wayland, whether
+    /// to ask the compositor to draw the window frame via xdg-decoration.
+    /// If the compositor declines, or this is false, wezterm draws
+    /// its own frame.
+    #[dynamic(default = "default_true")]
+    pub wayland_prefer_server_side_decorations: bool,
+
     /// Whether to prefer EGL over other GL implementations.
     /// EGL on Windows has jankier resize behavior than WGL (which
     /// is used if EGL is unavailable), but EGL survives graphics
@@ -509,6 +757,17 @@ pub struct Config {
     #[dynamic(default = "default_prefer_egl")]
     pub prefer_egl: bool,
 
+    /// Whether the OpenGL front end should ask for the higher
+    /// powered discrete GPU when the system has a choice of
+    /// integrated or discrete.  Defaults to low power.
+    #[dynamic(default)]
+    pub opengl_power_preference: WebGpuPowerPreference,
+
+    /// Selects a specific GPU for the OpenGL front end.
+    /// On systems using Mesa this is passed through as `DRI_PRIME`.
+    #[dynamic(default)]
+    pub opengl_preferred_adapter: Option<String>,
+
     #[dynamic(default = "default_true")]
     pub custom_block_glyphs: bool,
     #[dynamic(default = "default_true")]
@@ -527,12 +786,24 @@ pub struct Config {
     /// The image will be scaled to fit the window.
     #[dynamic(default)]
     pub window_background_image: Option<PathBuf>,
+    /// The path to a GLSL snippet defining a `background_effect`
+    /// function that is applied to the window background
+    #[dynamic(default)]
+    pub window_background_shader: Option<PathBuf>,
     #[dynamic(default)]
     pub window_background_gradient: Option<Gradient>,
     #[dynamic(default)]
     pub window_background_image_hsb: Option<HsbTransform>,
+    /// Controls how window_background_image is scaled and
+    /// positioned within the window.
+    #[dynamic(default)]
+    pub window_background_image_mode: BackgroundImageMode,
     #[dynamic(default)]
     pub foreground_text_hsb: HsbTransform,
+    /// A transform that is applied to everything in the window,
+    /// after all of the other color transforms
+    #[dynamic(default)]
+    pub window_hsb: HsbTransform,
 
     #[dynamic(default)]
     pub background: Vec<BackgroundLayer>,
@@ -596,6 +867,15 @@ pub struct Config {
     #[dynamic(default = "default_one_point_oh")]
     pub text_background_opacity: f32,
 
+    /// When the window has a background image or is transparent,
+    /// the pane background color is normally omitted so that the
+    /// window background shows through.  This specifies the alpha
+    /// value to use when drawing a translucent layer of the pane
+    /// background color over the top of the window background.
+    /// The default is 0.0, which doesn't draw that layer at all.
+    #[dynamic(default)]
+    pub pane_background_opacity: f32,
+
     /// Specifies how often a blinking cursor transitions between visible
     /// and invisible, expressed in milliseconds.
     /// Setting this to 0 disables blinking.
@@ -609,12 +889,26 @@ pub struct Config {
     #[dynamic(default = "linear_ease")]
     pub cursor_blink_ease_out: EasingFunction,
 
+    /// Specifies how long, in milliseconds, the cursor takes to glide
+    /// from its previous cell to its new position when it moves.
+    /// Setting this to 0 (the default) disables the animation.
+    #[dynamic(default)]
+    pub cursor_animation_duration_ms: u64,
+    #[dynamic(default = "ease_out")]
+    pub cursor_animation_ease: EasingFunction,
+
     #[dynamic(default = "default_anim_fps")]
     pub animation_fps: u8,
 
     #[dynamic(default)]
     pub force_reverse_video_cursor: bool,
 
+    /// When the text under a block cursor has less than this contrast
+    /// ratio against the cursor background, its color is adjusted
+    /// so that it remains readable.  The default of 1.0 disables this.
+    #[dynamic(default = "default_one_point_oh")]
+    pub cursor_min_contrast_ratio: f32,
+
     /// Specifies the default cursor style.  various escape sequences
     /// can override the default style in different situations (eg:
     /// an editor can change it depending on the mode), but this value
@@ -626,6 +920,11 @@ pub struct Config {
     #[dynamic(default)]
     pub default_cursor_style: DefaultCursorStyle,
 
+    /// Specifies how the cursor is rendered when the window is not
+    /// focused, or when it is in an inactive pane.
+    #[dynamic(default)]
+    pub unfocused_cursor_style: UnfocusedCursorStyle,
+
     /// Specifies how often blinking text (normal speed) transitions
     /// between visible and invisible, expressed in milliseconds.
     /// Setting this to 0 disables slow text blinking.  Note that this
@@ -652,6 +951,18 @@ pub struct Config {
     #[dynamic(default = "linear_ease")]
     pub text_blink_rapid_ease_out: EasingFunction,
 
+    /// Controls how text with the blink attribute is rendered.
+    /// Steady and BackgroundTint are useful for those that find
+    /// blinking text distracting or uncomfortable.
+    #[dynamic(default)]
+    pub text_blink_style: TextBlinkStyle,
+
+    /// When text_blink_style is BackgroundTint, how far the background
+    /// of blinking text is blended towards its foreground color,
+    /// in the range 0.0 through 1.0.
+    #[dynamic(default = "default_text_blink_tint_amount")]
+    pub text_blink_tint_amount: f32,
+
     /// If true, the mouse cursor will be hidden while typing.
     /// This option is true by default.
     #[dynamic(default = "default_true")]
@@ -680,12 +991,42 @@ pub struct Config {
     #[dynamic(default)]
     pub notification_handling: NotificationHandling,
 
+    /// Whether to ask the window system to draw attention to an
+    /// unfocused window when one of its panes rings the bell
+    #[dynamic(default)]
+    pub bell_request_attention: RequestAttention,
+
+    /// Whether to ask the window system to draw attention to an
+    /// unfocused window when one of its panes shows a notification
+    #[dynamic(default)]
+    pub notification_request_attention: RequestAttention,
+
+    /// How long a pane that is being monitored for silence with
+    /// TogglePaneMonitor must be quiet before a notification is shown
+    #[dynamic(default = "default_monitor_silence_seconds")]
+    pub monitor_silence_seconds: u64,
+
     #[dynamic(default = "default_true")]
     pub use_dead_keys: bool,
 
+    /// If set, holding CapsLock acts as these modifiers for the
+    /// purposes of key assignments and terminal input
+    #[dynamic(default)]
+    pub caps_lock_modifier: Option<Modifiers>,
+
+    /// Exchange the ALT and SUPER modifiers for the purposes of
+    /// key assignments and terminal input
+    #[dynamic(default)]
+    pub swap_alt_and_super: bool,
+
     #[dynamic(default)]
     pub launch_menu: Vec<SpawnCommand>,
 
+    /// Named layouts that can be applied to the panes of a tab
+    /// with the ApplyPaneLayout key assignment
+    #[dynamic(default = "default_pane_layouts")]
+    pub pane_layouts: Vec<PaneLayout>,
+
     #[dynamic(default)]
     pub use_box_model_render: bool,
 
@@ -736,6 +1077,12 @@ pub struct Config {
     #[dynamic(default = "default_alternate_buffer_wheel_scroll_speed")]
     pub alternate_buffer_wheel_scroll_speed: u8,
 
+    /// If true, scrolling the scrollback with a touchpad moves the
+    /// display by pixels rather than whole lines, and continues with
+    /// momentum after the gesture ends
+    #[dynamic(default)]
+    pub smooth_scrolling: bool,
+
     #[dynamic(default = "default_status_update_interval")]
     pub status_update_interval: u64,
 
@@ -784,8 +1131,17 @@ pub struct Config {
     #[dynamic(default = "default_max_fps")]
     pub max_fps: u8,
 
+    /// Controls whether frames are synchronized with the display
+    #[dynamic(default)]
+    pub present_mode: PresentMode,
+
     #[dynamic(default = "default_shape_cache_size")]
     pub shape_cache_size: usize,
+
+    /// When painting a frame takes too long, shape the remaining
+    /// text on background threads rather than on the GUI thread
+    #[dynamic(default)]
+    pub background_shaping: bool,
     #[dynamic(default = "default_line_state_cache_size")]
     pub line_state_cache_size: usize,
     #[dynamic(default = "default_line_quad_cache_size")]
@@ -804,6 +1160,17 @@ pub struct Config {
     #[dynamic(default)]
     pub canonicalize_pasted_newlines: Option<NewlineCanon>,
 
+    /// Whether to remove escape and other control characters
+    /// from text that is pasted from the clipboard
+    #[dynamic(default = "default_true")]
+    pub sanitize_pasted_text: bool,
+
+    /// Pastes into panes belonging to a remote domain that are larger
+    /// than this many bytes will prompt for confirmation first.
+    /// 0 disables the confirmation.
+    #[dynamic(default = "default_remote_paste_confirmation_threshold")]
+    pub remote_paste_confirmation_threshold: usize,
+
     #[dynamic(default = "default_unicode_version")]
     pub unicode_version: u8,
 
@@ -1078,6 +1445,7 @@ impl Config {
                         .set_name(p.to_string_lossy())
                         .eval_async(),
                 )?;
+                let config = Config::apply_fragments_to(&lua, config, p)?;
                 let config = Config::apply_overrides_to(&lua, config)?;
                 let config = Config::apply_overrides_obj_to(&lua, config, overrides)?;
                 cfg = Config::from_lua(config, &lua).with_context(|| {
@@ -1108,6 +1476,113 @@ impl Config {
         }))
     }
 
+    /// Returns the `config.d` directory that holds the configuration
+    /// fragments that accompany the config file at `config_file`
+    fn fragment_dir(config_file: &Path) -> Option<PathBuf> {
+        let parent = config_file.parent()?;
+        if parent == &*HOME_DIR {
+            // `~/.wezterm.lua` keeps its fragments in the config dir
+            // rather than littering the home directory
+            CONFIG_DIRS.first().map(|dir| dir.join("config.d"))
+        } else {
+            Some(parent.join("config.d"))
+        }
+    }
+
+    /// Returns the `*.lua` files in `dir` in the order that they
+    /// should be applied: sorted by name, followed by the fragment
+    /// for this host from the `hosts` subdirectory, if any
+    fn fragment_files(dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension() == Some(OsStr::new("lua")) && path.is_file())
+                .collect(),
+            Err(_) => vec![],
+        };
+        files.sort();
+
+        if let Some(host) = hostname::get().ok().and_then(|h| h.into_string().ok()) {
+            let path = dir.join("hosts").join(format!("{host}.lua"));
+            if path.is_file() {
+                files.push(path);
+            }
+        }
+        files
+    }
+
+    /// Evaluates the fragments in the `config.d` directory alongside
+    /// `config_file`, applying each of them to `config` in turn.
+    /// A fragment may return a table of values to assign to the config,
+    /// or a function that accepts the config and modifies it.
+    fn apply_fragments_to<'l>(
+        lua: &'l mlua::Lua,
+        mut config: mlua::Value<'l>,
+        config_file: &Path,
+    ) -> anyhow::Result<mlua::Value<'l>> {
+        let dir = match Self::fragment_dir(config_file) {
+            Some(dir) => dir,
+            None => return Ok(config),
+        };
+
+        // Watch the directories themselves so that adding or removing
+        // a fragment also causes the config to be reloaded
+        let mut watch_paths = vec![
+            dir.to_string_lossy().to_string(),
+            dir.join("hosts").to_string_lossy().to_string(),
+        ];
+
+        let setter: mlua::Function = lua
+            .load(
+                r#"
+                    return function(config, key, value)
+                        config[key] = value;
+                        return config;
+                    end
+                    "#,
+            )
+            .eval()?;
+
+        for path in Self::fragment_files(&dir) {
+            log::trace!("apply config fragment {}", path.display());
+            watch_paths.push(path.to_string_lossy().to_string());
+            let code = std::fs::read_to_string(&path)
+                .with_context(|| format!("Error reading {}", path.display()))?;
+            let value: mlua::Value = smol::block_on(
+                lua.load(code.trim_start_matches('\u{FEFF}'))
+                    .set_name(path.to_string_lossy())
+                    .eval_async(),
+            )?;
+            config = match value {
+                mlua::Value::Nil => config,
+                mlua::Value::Table(tbl) => {
+                    for pair in tbl.pairs::<mlua::Value, mlua::Value>() {
+                        let (key, value) = pair?;
+                        config = setter.call((config, key, value))?;
+                    }
+                    config
+                }
+                mlua::Value::Function(func) => {
+                    // The function may modify the config in place
+                    // rather than returning it
+                    match smol::block_on(func.call_async::<_, mlua::Value>(config.clone()))? {
+                        mlua::Value::Nil => config,
+                        value => value,
+                    }
+                }
+                other => anyhow::bail!(
+                    "{} must return a table or a function, not a {}",
+                    path.display(),
+                    other.type_name()
+                ),
+            };
+        }
+
+        crate::lua::add_to_config_reload_watch_list(lua, watch_paths.into_iter().collect())?;
+        Ok(config)
+    }
+
     pub(crate) fn apply_overrides_obj_to<'l>(
         lua: &'l mlua::Lua,
         mut config: mlua::Value<'l>,
@@ -1272,6 +1747,33 @@ impl Config {
         map
     }
 
+    /// Returns the names of the top level options whose values
+    /// differ between `self` and `other`
+    pub fn changed_keys(&self, other: &Config) -> Vec<String> {
+        let (ours, theirs) = match (self.to_dynamic(), other.to_dynamic()) {
+            (wezterm_dynamic::Value::Object(ours), wezterm_dynamic::Value::Object(theirs)) => {
+                (ours, theirs)
+            }
+            _ => return vec![],
+        };
+        let mut changed = vec![];
+        for (key, value) in ours.iter() {
+            if theirs.get(key) != Some(value) {
+                if let wezterm_dynamic::Value::String(key) = key {
+                    changed.push(key.to_string());
+                }
+            }
+        }
+        for key in theirs.keys() {
+            if !ours.contains_key(key) {
+                if let wezterm_dynamic::Value::String(key) = key {
+                    changed.push(key.to_string());
+                }
+            }
+        }
+        changed
+    }
+
     /// In some cases we need to compute expanded values based
     /// on those provided by the user.  This is where we do that.
     pub fn compute_extra_defaults(&self, config_path: Option<&Path>) -> Self {
@@ -1293,6 +1795,10 @@ impl Config {
             }
         }
 
+        if cfg.detect_file_path_links {
+            cfg.hyperlink_rules.push(file_path_hyperlink_rule());
+        }
+
         // Add some reasonable default font rules
         let reduced = self.font.reduce_first_font_to_family();
 
@@ -1505,6 +2011,37 @@ impl Config {
         Ok(cmd)
     }
 
+    /// Substitutes the `default_prog_per_domain` entry for the named
+    /// domain when `command` is absent, or only carries environment and
+    /// cwd overrides for the default program.  Otherwise, `command` is
+    /// returned unchanged and the domain uses its own default program.
+    pub fn resolve_domain_default_prog(
+        &self,
+        domain_name: &str,
+        command: Option<CommandBuilder>,
+    ) -> Option<CommandBuilder> {
+        let prog = match self.default_prog_per_domain.get(domain_name) {
+            Some(prog)
+                if !prog.is_empty()
+                    && command.as_ref().map_or(true, |cmd| cmd.is_default_prog()) =>
+            {
+                prog
+            }
+            _ => return command,
+        };
+
+        let mut cmd = CommandBuilder::from_argv(prog.iter().map(Into::into).collect());
+        if let Some(overrides) = command {
+            for (k, v) in overrides.iter_extra_env_as_str() {
+                cmd.env(k, v);
+            }
+            if let Some(cwd) = overrides.get_cwd() {
+                cmd.cwd(cwd);
+            }
+        }
+        Some(cmd)
+    }
+
     pub fn apply_cmd_defaults(&self, cmd: &mut CommandBuilder, default_cwd: Option<&PathBuf>) {
         // Apply `default_cwd` only if `cwd` is not already set, allows `--cwd`
         // option to take precedence
@@ -1527,6 +2064,10 @@ impl Config {
         cmd.env_remove("APPDIR");
         cmd.env_remove("OWD");
 
+        for name in crate::spawned_env_remove() {
+            cmd.env_remove(name);
+        }
+
         for (k, v) in &self.set_environment_variables {
             if k == "WSLENV" {
                 wsl_env.replace(v.clone());
@@ -1576,6 +2117,14 @@ fn default_integrated_title_buttons() -> Vec<IntegratedTitleButton> {
     vec![Hide, Maximize, Close]
 }
 
+fn default_pane_layouts() -> Vec<PaneLayout> {
+    vec![
+        PaneLayout::new("main-vertical", PaneArrangement::MainVertical),
+        PaneLayout::new("main-horizontal", PaneArrangement::MainHorizontal),
+        PaneLayout::new("tiled", PaneArrangement::Tiled),
+    ]
+}
+
 fn default_char_select_font_size() -> f64 {
     18.0
 }
@@ -1608,6 +2157,14 @@ fn default_mux_output_parser_coalesce_delay_ms() -> u64 {
     3
 }
 
+fn default_mux_output_notify_interval_ms() -> u64 {
+    10
+}
+
+fn default_synchronized_output_timeout_ms() -> u64 {
+    1000
+}
+
 fn default_mux_output_parser_buffer_size() -> usize {
     128 * 1024
 }
@@ -1620,6 +2177,14 @@ fn default_cursor_blink_rate() -> u64 {
     800
 }
 
+fn default_text_blink_tint_amount() -> f32 {
+    0.25
+}
+
+fn default_monitor_silence_seconds() -> u64 {
+    10
+}
+
 fn default_text_blink_rate() -> u64 {
     500
 }
@@ -1638,6 +2203,15 @@ fn default_scrollback_lines() -> usize {
     3500
 }
 
+fn default_scrollback_viewer() -> Vec<String> {
+    if cfg!(windows) {
+        // Open the file with the application associated with text files
+        vec![]
+    } else {
+        vec!["less".to_string(), "+G".to_string()]
+    }
+}
+
 fn default_initial_rows() -> u16 {
     24
 }
@@ -1661,6 +2235,16 @@ pub fn default_hyperlink_rules() -> Vec<hyperlink::Rule> {
     ]
 }
 
+/// Matches a reference to a file and line number, with an optional
+/// column, as commonly emitted by compilers and linters.
+pub fn file_path_hyperlink_rule() -> hyperlink::Rule {
+    hyperlink::Rule::new(
+        r"(?:~|\.{1,2})?/?(?:[\w.@+-]+/)*[\w@+-][\w.@+-]*\.\w+:\d+(?::\d+)?\b",
+        "file:$0",
+    )
+    .unwrap()
+}
+
 fn default_harfbuzz_features() -> Vec<String> {
     ["kern", "liga", "clig"]
         .iter()
@@ -1716,10 +2300,22 @@ pub fn default_local_echo_threshold_ms() -> Option<u64> {
     Some(100)
 }
 
+fn default_double_click_time_ms() -> u64 {
+    500
+}
+
 fn default_bypass_mouse_reporting_modifiers() -> Modifiers {
     Modifiers::SHIFT
 }
 
+fn default_selection_drag_modifiers() -> Modifiers {
+    Modifiers::CTRL
+}
+
+fn default_middle_click_paste_source() -> ClipboardPasteSource {
+    ClipboardPasteSource::PrimarySelection
+}
+
 fn default_gui_startup_args() -> Vec<String> {
     vec!["start".to_string()]
 }
@@ -1848,10 +2444,36 @@ impl DefaultCursorStyle {
     }
 }
 
+#[derive(FromDynamic, ToDynamic, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnfocusedCursorStyle {
+    /// Render an outlined, hollow block
+    #[default]
+    HollowBlock,
+    /// Render the same shape as when focused
+    Unchanged,
+    /// Don't render the cursor at all
+    Hidden,
+}
+
+#[derive(FromDynamic, ToDynamic, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextBlinkStyle {
+    /// Animate between visible and invisible
+    #[default]
+    Blink,
+    /// Render the text normally, without blinking
+    Steady,
+    /// Render the text normally, but tint its background
+    BackgroundTint,
+}
+
 const fn linear_ease() -> EasingFunction {
     EasingFunction::Linear
 }
 
+const fn ease_out() -> EasingFunction {
+    EasingFunction::EaseOut
+}
+
 const fn default_one_cell() -> Dimension {
     Dimension::Cells(1.)
 }
@@ -1883,6 +2505,28 @@ impl Default for WindowPadding {
     }
 }
 
+#[derive(FromDynamic, ToDynamic, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScrollPositionIndicator {
+    /// Don't show an indicator
+    #[default]
+    None,
+    /// Show the number of lines between the viewport and the bottom
+    LinesBelow,
+    /// Show how far through the scrollback the viewport is
+    Percentage,
+}
+
+#[derive(FromDynamic, ToDynamic, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WindowResizeRequests {
+    /// Ignore the requests
+    #[default]
+    Deny,
+    /// Resize the window as requested
+    Allow,
+    /// Ask the user whether to resize the window
+    Ask,
+}
+
 #[derive(FromDynamic, ToDynamic, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum NewlineCanon {
     // FIXME: also allow deserialziing from bool
@@ -2007,6 +2651,10 @@ fn default_line_to_ele_shape_cache_size() -> usize {
     1024
 }
 
+fn default_remote_paste_confirmation_threshold() -> usize {
+    64 * 1024
+}
+
 #[derive(Debug, ToDynamic, Clone, Copy, PartialEq, Eq, Default)]
 pub enum BoldBrightening {
     /// Bold doesn't influence palette selection
@@ -2041,6 +2689,18 @@ impl FromDynamic for BoldBrightening {
     }
 }
 
+#[derive(Debug, FromDynamic, ToDynamic, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReverseVideoSwap {
+    /// The colors are resolved from the palette, including any bold
+    /// brightening of the foreground, and are then swapped
+    #[default]
+    AfterPalette,
+    /// The foreground and background color attributes are swapped
+    /// before they are resolved, so bold brightening applies to the
+    /// color that is displayed as the foreground
+    BeforePalette,
+}
+
 #[derive(Debug, FromDynamic, ToDynamic, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ImePreeditRendering {
     /// IME preedit is rendered by WezTerm itself
@@ -2060,6 +2720,24 @@ pub enum NotificationHandling {
     SuppressFromFocusedWindow,
 }
 
+#[derive(Debug, FromDynamic, ToDynamic, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RequestAttention {
+    #[default]
+    Never,
+    Informational,
+    Critical,
+}
+
+impl RequestAttention {
+    pub fn attention_type(self) -> Option<UserAttentionType> {
+        match self {
+            Self::Never => None,
+            Self::Informational => Some(UserAttentionType::Informational),
+            Self::Critical => Some(UserAttentionType::Critical),
+        }
+    }
+}
+
 fn validate_row_or_col(value: &u16) -> Result<(), String> {
     if *value < 1 {
         Err("initial_cols and initial_rows must be non-zero".to_string())
@@ -2100,3 +2778,89 @@ fn default_macos_forward_mods() -> Modifiers {
 fn default_colr_rasterizer() -> FontRasterizerSelection {
     FontRasterizerSelection::Harfbuzz
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn fragment_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        write(&dir.join("20-b.lua"), "");
+        write(&dir.join("10-a.lua"), "");
+        write(&dir.join("notes.txt"), "");
+        std::fs::create_dir(dir.join("30-dir.lua")).unwrap();
+        write(&dir.join("hosts").join("not-this-host.lua"), "");
+
+        let mut expected = vec![dir.join("10-a.lua"), dir.join("20-b.lua")];
+        if let Some(host) = hostname::get().ok().and_then(|h| h.into_string().ok()) {
+            let path = dir.join("hosts").join(format!("{host}.lua"));
+            write(&path, "");
+            expected.push(path);
+        }
+
+        assert_eq!(Config::fragment_files(dir), expected);
+        assert_eq!(
+            Config::fragment_files(&dir.join("does-not-exist")),
+            Vec::<PathBuf>::new()
+        );
+    }
+
+    #[test]
+    fn apply_fragments() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config_file = dir.path().join("wezterm.lua");
+        let fragments = dir.path().join("config.d");
+        write(
+            &fragments.join("10-table.lua"),
+            "return { font_size = 12.0, line_height = 1.5 }",
+        );
+        write(
+            &fragments.join("20-func.lua"),
+            "return function(config) config.font_size = config.font_size + 1 end",
+        );
+        write(&fragments.join("30-nothing.lua"), "local unused = 1");
+
+        let lua = make_lua_context(&config_file)?;
+        let config: mlua::Value = lua.load("return { font_size = 10.0 }").eval()?;
+        let config = match Config::apply_fragments_to(&lua, config, &config_file)? {
+            mlua::Value::Table(tbl) => tbl,
+            other => panic!("expected a table, got {}", other.type_name()),
+        };
+        assert_eq!(config.get::<_, f64>("font_size")?, 13.0);
+        assert_eq!(config.get::<_, f64>("line_height")?, 1.5);
+
+        let watch_paths: Vec<String> = lua.named_registry_value("wezterm-watch-paths")?;
+        for name in ["10-table.lua", "20-func.lua", "30-nothing.lua"] {
+            let path = fragments.join(name).to_string_lossy().to_string();
+            assert!(watch_paths.contains(&path), "{path} is not watched");
+        }
+        assert!(watch_paths.contains(&fragments.to_string_lossy().to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragment_must_return_table_or_function() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config_file = dir.path().join("wezterm.lua");
+        write(&dir.path().join("config.d").join("bad.lua"), "return 42");
+
+        let lua = make_lua_context(&config_file)?;
+        let config: mlua::Value = lua.load("return {}").eval()?;
+        let err = Config::apply_fragments_to(&lua, config, &config_file)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.ends_with("bad.lua must return a table or a function, not a integer"),
+            "{err}"
+        );
+        Ok(())
+    }
+}
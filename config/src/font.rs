@@ -225,6 +225,22 @@ pub enum DisplayPixelGeometry {
     BGR,
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, FromDynamic, ToDynamic)]
+pub enum FreeTypeLcdFilter {
+    /// The default five-tap FIR filter, which balances color fringing
+    /// against sharpness
+    #[default]
+    Default,
+    /// A lighter filter that produces sharper, but more colorful,
+    /// glyph edges
+    Light,
+    /// The filter that was used by older versions of freetype,
+    /// which produces blurrier results
+    Legacy,
+    /// Disables filtering entirely; color fringes will be very visible
+    None,
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, FromDynamic, ToDynamic)]
 pub enum FreeTypeLoadTarget {
     /// This corresponds to the default hinting algorithm, optimized
@@ -644,6 +660,9 @@ pub struct StyleRule {
     /// If present, this rule matches when CellAttributes::invisible holds
     /// a value that matches this rule.
     pub invisible: Option<bool>,
+    /// If present, this rule matches when the style class assigned
+    /// to the cell via OSC 1339 is the same as this value.
+    pub style_class: Option<String>,
 
     /// When this rule matches, `font` specifies the styling to be used.
     pub font: TextStyle,
@@ -44,6 +44,22 @@ impl ToString for GpuInfo {
     }
 }
 
+/// Controls how rendered frames are handed off to the display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic, Default)]
+pub enum PresentMode {
+    /// Use the front end specific default: OpenGL presents immediately,
+    /// relying on `max_fps` for throttling, while WebGpu waits for vsync
+    #[default]
+    Auto,
+    /// Wait for the vertical blank before presenting each frame
+    VSync,
+    /// Present as soon as the frame is ready, which may tear
+    Immediate,
+    /// Present at the vertical blank, replacing any frame that is
+    /// still waiting with the latest one
+    Mailbox,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
 pub enum WebGpuPowerPreference {
     LowPower,
@@ -1,3 +1,4 @@
+use crate::color::HsbTransform;
 use crate::default_true;
 use crate::keys::KeyNoAction;
 use crate::window::WindowLevel;
@@ -31,6 +32,7 @@ bitflags::bitflags! {
         const KEY_ASSIGNMENTS = 16;
         const WORKSPACES = 32;
         const COMMANDS = 64;
+        const RECORDINGS = 128;
     }
 }
 
@@ -70,6 +72,9 @@ impl ToString for LauncherFlags {
         if self.contains(Self::COMMANDS) {
             s.push("COMMANDS");
         }
+        if self.contains(Self::RECORDINGS) {
+            s.push("RECORDINGS");
+        }
         s.join("|")
     }
 }
@@ -89,6 +94,7 @@ impl TryFrom<String> for LauncherFlags {
                 "KEY_ASSIGNMENTS" => flags |= Self::KEY_ASSIGNMENTS,
                 "WORKSPACES" => flags |= Self::WORKSPACES,
                 "COMMANDS" => flags |= Self::COMMANDS,
+                "RECORDINGS" => flags |= Self::RECORDINGS,
                 _ => {
                     return Err(format!("invalid LauncherFlags `{}` in `{}`", ele, s));
                 }
@@ -108,12 +114,27 @@ pub enum SelectionMode {
     Block,
 }
 
+/// What a pane is being monitored for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum PaneMonitor {
+    /// Notify when the pane produces output
+    Activity,
+    /// Notify when the pane has not produced output for
+    /// monitor_silence_seconds
+    Silence,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
 pub enum Pattern {
     CaseSensitiveString(String),
     CaseInSensitiveString(String),
     Regex(String),
     CurrentSelectionOrEmptyString,
+    /// The current selection text, or the most recently used
+    /// pattern in the window if there is no selection
+    CurrentSelectionText,
+    /// The most recently used pattern in the window
+    LastPattern,
 }
 
 impl Pattern {
@@ -122,7 +143,9 @@ impl Pattern {
             Self::CaseSensitiveString(s) | Self::CaseInSensitiveString(s) | Self::Regex(s) => {
                 s.is_empty()
             }
-            Self::CurrentSelectionOrEmptyString => true,
+            Self::CurrentSelectionOrEmptyString
+            | Self::CurrentSelectionText
+            | Self::LastPattern => true,
         }
     }
 }
@@ -167,6 +190,28 @@ impl Default for SpawnTabDomain {
     }
 }
 
+/// Controls how the working directory of a spawned command is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, FromDynamic, ToDynamic)]
+pub enum SpawnCwdStrategy {
+    /// Use `cwd` if it is set, otherwise the working directory of the
+    /// current pane if it is in the same domain, otherwise the default
+    /// directory for the domain
+    Default,
+    /// Start in the home directory of the user
+    Home,
+    /// Start in the working directory of the current pane, even if
+    /// the command is spawned into a different domain
+    Inherit,
+    /// Start in `cwd`, which must be set
+    Fixed,
+}
+
+impl Default for SpawnCwdStrategy {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
 #[derive(Default, Clone, PartialEq, FromDynamic, ToDynamic)]
 pub struct SpawnCommand {
     /// Optional descriptive label
@@ -186,7 +231,13 @@ pub struct SpawnCommand {
     /// other location appropriate to the domain.
     pub cwd: Option<PathBuf>,
 
+    /// Specifies how the working directory for the command is chosen.
+    #[dynamic(default)]
+    pub cwd_strategy: SpawnCwdStrategy,
+
     /// Specifies a map of environment variables that should be set.
+    /// The values may reference `${pane_cwd}`, `${domain}` and
+    /// `${hostname}`, which are expanded at spawn time.
     /// Whether this is used depends on the domain.
     #[dynamic(default)]
     pub set_environment_variables: HashMap<String, String>,
@@ -195,6 +246,10 @@ pub struct SpawnCommand {
     pub domain: SpawnTabDomain,
 
     pub position: Option<crate::GuiPosition>,
+
+    /// Overrides the windowing system class of the window when
+    /// spawning a new window
+    pub class: Option<String>,
 }
 impl_lua_conversion_dynamic!(SpawnCommand);
 
@@ -217,6 +272,12 @@ impl std::fmt::Display for SpawnCommand {
         if let Some(cwd) = &self.cwd {
             write!(fmt, " cwd={}", cwd.display())?;
         }
+        if self.cwd_strategy != SpawnCwdStrategy::Default {
+            write!(fmt, " cwd_strategy={:?}", self.cwd_strategy)?;
+        }
+        if let Some(class) = &self.class {
+            write!(fmt, " class={}", class)?;
+        }
         for (k, v) in &self.set_environment_variables {
             write!(fmt, " {}={}", k, v)?;
         }
@@ -258,9 +319,44 @@ impl SpawnCommand {
             args: if args.is_empty() { None } else { Some(args) },
             set_environment_variables,
             cwd,
+            cwd_strategy: SpawnCwdStrategy::Default,
             position: None,
+            class: None,
         })
     }
+
+    /// Returns `set_environment_variables` with the `${pane_cwd}`,
+    /// `${domain}` and `${hostname}` placeholders in their values
+    /// replaced by the supplied values.  If `hostname` is None then
+    /// the hostname of the local system is used.
+    pub fn expand_environment_variables(
+        &self,
+        pane_cwd: Option<&str>,
+        domain: &str,
+        hostname: Option<&str>,
+    ) -> HashMap<String, String> {
+        let local_hostname;
+        let hostname = match hostname {
+            Some(hostname) => hostname,
+            None => {
+                local_hostname = hostname::get()
+                    .ok()
+                    .and_then(|name| name.into_string().ok())
+                    .unwrap_or_default();
+                &local_hostname
+            }
+        };
+        self.set_environment_variables
+            .iter()
+            .map(|(k, v)| {
+                let v = v
+                    .replace("${pane_cwd}", pane_cwd.unwrap_or(""))
+                    .replace("${domain}", domain)
+                    .replace("${hostname}", hostname);
+                (k.clone(), v)
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, FromDynamic, ToDynamic)]
@@ -329,6 +425,19 @@ impl Default for ClipboardPasteSource {
         Self::Clipboard
     }
 }
+impl_lua_conversion_dynamic!(ClipboardPasteSource);
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub struct ViewScrollbackArguments {
+    /// Overrides the main scrollback_viewer config
+    #[dynamic(default)]
+    pub args: Option<Vec<String>>,
+
+    /// Show the viewer in a split in this direction, rather
+    /// than in a new tab
+    #[dynamic(default)]
+    pub split: Option<PaneDirection>,
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
 pub enum PaneSelectMode {
@@ -501,6 +610,28 @@ fn default_fuzzy_description() -> String {
     "Fuzzy matching: ".to_string()
 }
 
+#[derive(Debug, Clone, PartialEq, FromDynamic, ToDynamic)]
+pub struct ShowOverlay {
+    /// Called as `action(window, pane, line)` to produce the text
+    /// of the overlay.  `line` is nil for the first call, and the
+    /// line entered by the user for subsequent calls.  Returning
+    /// nil closes the overlay.
+    pub action: Box<KeyAssignment>,
+    #[dynamic(default)]
+    pub title: String,
+    #[dynamic(default)]
+    pub scope: OverlayScope,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum OverlayScope {
+    /// The overlay covers only the pane
+    #[default]
+    Pane,
+    /// The overlay covers the whole tab
+    Tab,
+}
+
 #[derive(Debug, Clone, PartialEq, FromDynamic, ToDynamic)]
 pub enum KeyAssignment {
     SpawnTab(SpawnTabDomain),
@@ -552,7 +683,9 @@ pub enum KeyAssignment {
     ShowLauncher,
     ShowLauncherArgs(LauncherActionArgs),
     ClearScrollback(ScrollbackEraseMode),
+    ClearScreenPreservingScrollback,
     Search(Pattern),
+    SearchAllTabs(Pattern),
     ActivateCopyMode,
 
     SelectTextAtMouseCursor(SelectionMode),
@@ -568,12 +701,18 @@ pub enum KeyAssignment {
     ActivatePaneByIndex(usize),
     TogglePaneZoomState,
     SetPaneZoomState(bool),
+    TogglePaneMonitor(PaneMonitor),
+    TogglePaneReadOnly,
+    TogglePaneDiffHighlight,
+    TogglePaneRecording,
+    ReplayRecording(String),
     CloseCurrentPane {
         confirm: bool,
     },
     EmitEvent(String),
     QuickSelect,
     QuickSelectArgs(QuickSelectArguments),
+    LinkHints,
 
     Multiple(Vec<KeyAssignment>),
 
@@ -603,6 +742,12 @@ pub enum KeyAssignment {
 
     CopyMode(CopyModeAssignment),
     RotatePanes(RotationDirection),
+    BalancePanes {
+        #[dynamic(default)]
+        axis: Option<PaneAxis>,
+    },
+    ApplyPaneLayout(String),
+    ViewScrollback(ViewScrollbackArguments),
     SplitPane(SplitPane),
     PaneSelect(PaneSelectArguments),
     CharSelect(CharSelectArguments),
@@ -610,11 +755,15 @@ pub enum KeyAssignment {
     ResetTerminal,
     OpenUri(String),
     ActivateCommandPalette,
+    CaptureScreenshot,
     ActivateWindow(usize),
     ActivateWindowRelative(isize),
     ActivateWindowRelativeNoWrap(isize),
     PromptInputLine(PromptInputLine),
     InputSelector(InputSelector),
+    AdjustWindowHsb(HsbTransform),
+    ResetWindowHsb,
+    ShowOverlay(ShowOverlay),
 }
 impl_lua_conversion_dynamic!(KeyAssignment);
 
@@ -647,6 +796,62 @@ pub enum RotationDirection {
     CounterClockwise,
 }
 
+/// Selects which panes are resized by BalancePanes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum PaneAxis {
+    /// Panes that are side by side
+    Horizontal,
+    /// Panes that are stacked one above the other
+    Vertical,
+}
+
+/// How ApplyPaneLayout arranges the panes of a tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub enum PaneArrangement {
+    /// The active pane fills the left of the tab, and the other
+    /// panes are stacked one above the other to its right
+    MainVertical,
+    /// The active pane fills the top of the tab, and the other
+    /// panes are placed side by side beneath it
+    MainHorizontal,
+    /// The panes are placed in a grid of equally sized cells
+    Tiled,
+}
+
+/// A named layout that can be applied with ApplyPaneLayout
+#[derive(Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub struct PaneLayout {
+    pub name: String,
+    pub arrangement: PaneArrangement,
+    /// The percentage of the width (MainVertical) or height
+    /// (MainHorizontal) of the tab that is given to the main pane
+    #[dynamic(default = "default_main_pane_percent")]
+    pub main_pane_percent: u8,
+}
+
+impl PaneLayout {
+    pub fn new(name: &str, arrangement: PaneArrangement) -> Self {
+        Self {
+            name: name.to_string(),
+            arrangement,
+            main_pane_percent: default_main_pane_percent(),
+        }
+    }
+}
+
+fn default_main_pane_percent() -> u8 {
+    60
+}
+
+/// A regex replacement that is applied to the selected text
+/// by CopyMode's CopyWithReplacement
+#[derive(Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
+pub struct CopyModeReplacement {
+    pub regex: String,
+    #[dynamic(default)]
+    pub replacement: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, FromDynamic, ToDynamic)]
 pub enum CopyModeAssignment {
     MoveToViewportBottom,
@@ -677,6 +882,10 @@ pub enum CopyModeAssignment {
     NextMatch,
     PriorMatchPage,
     NextMatchPage,
+    FirstMatch,
+    LastMatch,
+    ToggleSearchAllPanes,
+    ListAllPanesMatches,
     CycleMatchType,
     ClearPattern,
     EditPattern,
@@ -685,10 +894,18 @@ pub enum CopyModeAssignment {
     MoveForwardSemanticZone,
     MoveBackwardZoneOfType(SemanticType),
     MoveForwardZoneOfType(SemanticType),
-    JumpForward { prev_char: bool },
-    JumpBackward { prev_char: bool },
+    JumpForward {
+        prev_char: bool,
+    },
+    JumpBackward {
+        prev_char: bool,
+    },
     JumpAgain,
     JumpReverse,
+    CopyWithReplacement {
+        preset: String,
+        destination: ClipboardCopyDestination,
+    },
 }
 
 pub type KeyTable = HashMap<(KeyCode, Modifiers), KeyTableEntry>;
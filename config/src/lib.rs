@@ -74,6 +74,8 @@ lazy_static! {
     static ref CONFIG_OVERRIDES: Mutex<Vec<(String, String)>> = Mutex::new(vec![]);
     static ref SHOW_ERROR: Mutex<Option<ErrorCallback>> =
         Mutex::new(Some(|e| log::error!("{}", e)));
+    static ref SHOW_RELOAD_ERRORS: AtomicBool = AtomicBool::new(true);
+    static ref SPAWNED_ENV_REMOVE: Mutex<Vec<String>> = Mutex::new(vec![]);
     static ref LUA_PIPE: LuaPipe = LuaPipe::new();
     pub static ref COLOR_SCHEMES: HashMap<String, Palette> = build_default_schemes();
 }
@@ -359,6 +361,14 @@ pub fn assign_error_callback(cb: ErrorCallback) {
     factory.replace(cb);
 }
 
+/// Controls whether errors encountered while reloading the configuration
+/// are passed to the error callback.  The GUI turns this off because
+/// it reports those errors in its windows instead.  The errors are
+/// logged either way.
+pub fn set_show_reload_errors(show: bool) {
+    SHOW_RELOAD_ERRORS.store(show, Ordering::Relaxed);
+}
+
 pub fn show_error(err: &str) {
     let factory = SHOW_ERROR.lock().unwrap();
     if let Some(cb) = factory.as_ref() {
@@ -366,6 +376,17 @@ pub fn show_error(err: &str) {
     }
 }
 
+/// Arranges for `name` to be removed from the environment of the
+/// programs that we spawn.  This is for variables that wezterm sets
+/// in its own environment only to influence the libraries it uses.
+pub fn remove_from_spawned_env(name: &str) {
+    SPAWNED_ENV_REMOVE.lock().unwrap().push(name.to_string());
+}
+
+pub(crate) fn spawned_env_remove() -> Vec<String> {
+    SPAWNED_ENV_REMOVE.lock().unwrap().clone()
+}
+
 pub fn create_user_owned_dirs(p: &Path) -> anyhow::Result<()> {
     let mut builder = DirBuilder::new();
     builder.recursive(true);
@@ -623,7 +644,11 @@ impl ConfigInner {
                 let err = format!("{:#}", err);
                 if self.generation > 0 {
                     // Only generate the message for an actual reload
-                    show_error(&err);
+                    if SHOW_RELOAD_ERRORS.load(Ordering::Relaxed) {
+                        show_error(&err);
+                    } else {
+                        log::error!("{}", err);
+                    }
                 }
                 self.error.replace(err);
             }
@@ -250,6 +250,31 @@ impl FromStr for GuiPosition {
     }
 }
 
+/// The size of a window, in cells, as specified on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuiSize {
+    pub cols: u16,
+    pub rows: u16,
+}
+
+impl FromStr for GuiSize {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<GuiSize> {
+        let (cols, rows) = s
+            .split_once('x')
+            .ok_or_else(|| anyhow::anyhow!("expected COLSxROWS, but got {}", s))?;
+        let size = GuiSize {
+            cols: cols.parse()?,
+            rows: rows.parse()?,
+        };
+        anyhow::ensure!(
+            size.cols > 0 && size.rows > 0,
+            "the number of cols and rows must be greater than zero"
+        );
+        Ok(size)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -310,4 +335,18 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn size() {
+        assert_eq!(
+            GuiSize::from_str("120x40").unwrap(),
+            GuiSize {
+                cols: 120,
+                rows: 40
+            }
+        );
+        assert!(GuiSize::from_str("120").is_err());
+        assert!(GuiSize::from_str("0x40").is_err());
+        assert!(GuiSize::from_str("ax40").is_err());
+    }
 }
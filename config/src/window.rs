@@ -1,3 +1,4 @@
+use luahelper::impl_lua_conversion_dynamic;
 use wezterm_dynamic::{FromDynamic, ToDynamic};
 
 #[derive(Debug, Default, Clone, ToDynamic, PartialEq, Eq, FromDynamic)]
@@ -7,3 +8,30 @@ pub enum WindowLevel {
     Normal = 0,
     AlwaysOnTop = 3,
 }
+
+/// The progress of a long running operation, as shown in the
+/// taskbar entry or dock icon of a window.
+/// Percentages are in the range 0-100.
+#[derive(Debug, Default, Clone, Copy, ToDynamic, PartialEq, Eq, FromDynamic)]
+pub enum WindowProgress {
+    /// No progress is shown
+    #[default]
+    None,
+    Normal(u8),
+    Error(u8),
+    /// The operation is ongoing but its progress is unknown
+    Indeterminate,
+    Paused(u8),
+}
+impl_lua_conversion_dynamic!(WindowProgress);
+
+/// How insistently a window should ask for the user's attention
+#[derive(Debug, Clone, Copy, ToDynamic, PartialEq, Eq, FromDynamic)]
+pub enum UserAttentionType {
+    /// Keep drawing attention until the window is focused:
+    /// the taskbar entry flashes or the dock icon bounces repeatedly
+    Critical,
+    /// Draw attention briefly: the taskbar entry flashes or the
+    /// dock icon bounces once
+    Informational,
+}
@@ -219,6 +219,7 @@ struct SpawnWindow {
     height: Option<usize>,
     workspace: Option<String>,
     position: Option<config::GuiPosition>,
+    class: Option<String>,
     #[dynamic(flatten)]
     cmd_builder: CommandBuilderFrag,
 }
@@ -252,6 +253,7 @@ impl SpawnWindow {
                 None,
                 self.workspace.unwrap_or_else(|| mux.active_workspace()),
                 self.position,
+                self.class,
             )
             .await
             .map_err(|e| mlua::Error::external(format!("{:#?}", e)))?;
@@ -303,6 +305,7 @@ impl SpawnTab {
                 pane,
                 String::new(),
                 None, // optional gui window position
+                None, // optional gui window class
             )
             .await
             .map_err(|e| mlua::Error::external(format!("{:#?}", e)))?;
@@ -4,10 +4,11 @@ use mlua::Value;
 use mux::pane::CachePolicy;
 use std::cmp::Ordering;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use termwiz::cell::SemanticType;
 use termwiz_funcs::lines_to_escapes;
 use url_funcs::Url;
-use wezterm_term::{SemanticZone, StableRowIndex};
+use wezterm_term::{CommandRecord, SemanticZone, StableRowIndex};
 
 #[derive(Clone, Copy, Debug)]
 pub struct MuxPane(pub PaneId);
@@ -202,6 +203,19 @@ impl UserData for MuxPane {
             Ok(pane.is_alt_screen_active())
         });
 
+        methods.add_method("is_read_only", |_, this, _: ()| {
+            let mux = get_mux()?;
+            let pane = this.resolve(&mux)?;
+            Ok(mux.is_pane_read_only(pane.pane_id()))
+        });
+
+        methods.add_method("set_read_only", |_, this, read_only: bool| {
+            let mux = get_mux()?;
+            let pane = this.resolve(&mux)?;
+            mux.set_pane_read_only(pane.pane_id(), read_only);
+            Ok(())
+        });
+
         // When called with no arguments, returns the lines from the
         // viewport as plain text (no escape sequences).
         // When called with an optional integer argument, returns the
@@ -353,6 +367,17 @@ impl UserData for MuxPane {
             },
         );
 
+        methods.add_method("get_command_history", |lua, this, _: ()| {
+            let mux = get_mux()?;
+            let pane = this.resolve(&mux)?;
+            let history: Vec<CommandInfo> = pane
+                .get_command_history()
+                .iter()
+                .map(CommandInfo::from)
+                .collect();
+            to_lua(lua, history)
+        });
+
         methods.add_method("get_text_from_semantic_zone", |_lua, this, zone: Value| {
             let zone: SemanticZone = from_lua(zone)?;
             this.get_text_from_semantic_zone(zone)
@@ -428,6 +453,37 @@ impl UserData for MuxPane {
     }
 }
 
+/// The lua representation of a `CommandRecord`, with times expressed
+/// as seconds since the unix epoch
+#[derive(Debug, FromDynamic, ToDynamic)]
+struct CommandInfo {
+    output_start_y: StableRowIndex,
+    end_y: Option<StableRowIndex>,
+    started: f64,
+    finished: Option<f64>,
+    duration_seconds: Option<f64>,
+    exit_status: Option<i32>,
+}
+
+fn unix_seconds(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+impl From<&CommandRecord> for CommandInfo {
+    fn from(record: &CommandRecord) -> Self {
+        Self {
+            output_start_y: record.output_start_y,
+            end_y: record.end_y,
+            started: unix_seconds(record.started),
+            finished: record.finished.map(unix_seconds),
+            duration_seconds: record.duration().map(|d| d.as_secs_f64()),
+            exit_status: record.exit_status,
+        }
+    }
+}
+
 #[derive(Debug, Default, FromDynamic, ToDynamic)]
 struct SplitPane {
     #[dynamic(flatten)]
@@ -80,6 +80,12 @@ impl UserData for MuxTab {
             Ok(was_zoomed)
         });
 
+        methods.add_method("get_zoomed_pane", |_, this, _: ()| {
+            let mux = get_mux()?;
+            let tab = this.resolve(&mux)?;
+            Ok(tab.get_zoomed_pane().map(|pane| MuxPane(pane.pane_id())))
+        });
+
         methods.add_method("panes_with_info", |lua, this, _: ()| {
             let mux = get_mux()?;
             let tab = this.resolve(&mux)?;
@@ -7,12 +7,13 @@
 
 use crate::localpane::LocalPane;
 use crate::pane::{alloc_pane_id, Pane, PaneId};
+use crate::shell_integration::inject_shell_integration;
 use crate::tab::{SplitRequest, Tab, TabId};
 use crate::window::WindowId;
 use crate::Mux;
 use anyhow::{bail, Context, Error};
 use async_trait::async_trait;
-use config::keyassignment::{SpawnCommand, SpawnTabDomain};
+use config::keyassignment::{SpawnCommand, SpawnCwdStrategy, SpawnTabDomain};
 use config::{configuration, ExecDomain, SerialDomain, ValueOrFunc, WslDomain};
 use downcast_rs::{impl_downcast, Downcast};
 use parking_lot::Mutex;
@@ -22,6 +23,7 @@ use std::ffi::OsString;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use wezterm_term::TerminalSize;
 
 static DOMAIN_ID: ::std::sync::atomic::AtomicUsize = ::std::sync::atomic::AtomicUsize::new(0);
@@ -159,6 +161,13 @@ pub trait Domain: Downcast + Send + Sync {
         true
     }
 
+    /// Returns the `command_dir` to spawn with in order to start in
+    /// the home directory of the user in this domain, or None if the
+    /// domain has no way to do that.
+    fn home_dir_cwd(&self) -> Option<String> {
+        None
+    }
+
     /// Returns true if the `detach` method can be used
     /// to detach the domain, preserving the associated
     /// panes, or false if the `detach` method will never
@@ -178,6 +187,16 @@ pub trait Domain: Downcast + Send + Sync {
         self.domain_name().to_string()
     }
 
+    /// Returns the round trip time above which characters typed into
+    /// the panes of this domain should be echoed immediately by the
+    /// GUI, rather than waiting for the output of the pane to echo them.
+    /// This is for domains whose panes are local, but whose output may
+    /// arrive with a high latency; panes in multiplexer client domains
+    /// do their own prediction.
+    fn local_echo_threshold(&self) -> Option<Duration> {
+        None
+    }
+
     /// Re-attach to any tabs that might be pre-existing in this domain
     async fn attach(&self, window_id: Option<WindowId>) -> anyhow::Result<()>;
 
@@ -323,7 +342,9 @@ impl LocalDomain {
                 args: if args.is_empty() { None } else { Some(args) },
                 set_environment_variables,
                 cwd,
+                cwd_strategy: SpawnCwdStrategy::Default,
                 position: None,
+                class: None,
             };
 
             let spawn_command = config::with_lua_config_on_main_thread(|lua| async {
@@ -443,26 +464,48 @@ impl LocalDomain {
         pane_id: PaneId,
     ) -> anyhow::Result<CommandBuilder> {
         let config = configuration();
+        let command = config.resolve_domain_default_prog(&self.name, command);
+        let build_default_prog = || {
+            let wsl = self.resolve_wsl_domain();
+            config.build_prog(
+                None,
+                wsl.as_ref()
+                    .map(|wsl| wsl.default_prog.as_ref())
+                    .unwrap_or(config.default_prog.as_ref()),
+                wsl.as_ref()
+                    .map(|wsl| wsl.default_cwd.as_ref())
+                    .unwrap_or(config.default_cwd.as_ref()),
+            )
+        };
         let mut cmd = match command {
+            // A command that only carries environment and cwd overrides
+            // for the default program of the domain
+            Some(overrides) if overrides.is_default_prog() => {
+                let mut cmd = build_default_prog()?;
+                for (k, v) in overrides.iter_extra_env_as_str() {
+                    cmd.env(k, v);
+                }
+                if let Some(cwd) = overrides.get_cwd() {
+                    cmd.cwd(cwd);
+                }
+                cmd
+            }
             Some(mut cmd) => {
                 config.apply_cmd_defaults(&mut cmd, config.default_cwd.as_ref());
                 cmd
             }
-            None => {
-                let wsl = self.resolve_wsl_domain();
-                config.build_prog(
-                    None,
-                    wsl.as_ref()
-                        .map(|wsl| wsl.default_prog.as_ref())
-                        .unwrap_or(config.default_prog.as_ref()),
-                    wsl.as_ref()
-                        .map(|wsl| wsl.default_cwd.as_ref())
-                        .unwrap_or(config.default_cwd.as_ref()),
-                )?
-            }
+            None => build_default_prog()?,
         };
-        if let Some(dir) = command_dir {
-            cmd.cwd(dir);
+        match command_dir.as_deref() {
+            // wsl.exe expands this itself to the home directory in the
+            // distribution, so it is passed through for WSL domains
+            Some("~") if self.resolve_wsl_domain().is_none() => {
+                cmd.cwd(&*config::HOME_DIR);
+            }
+            Some(dir) => {
+                cmd.cwd(dir);
+            }
+            None => {}
         }
         if let Ok(sock) = std::env::var("WEZTERM_UNIX_SOCKET") {
             cmd.env("WEZTERM_UNIX_SOCKET", sock);
@@ -471,6 +514,11 @@ impl LocalDomain {
         if let Some(agent) = Mux::get().agent.as_ref() {
             cmd.env("SSH_AUTH_SOCK", agent.path());
         }
+        if config.shell_integration_auto_inject && self.resolve_wsl_domain().is_none() {
+            if let Err(err) = inject_shell_integration(&mut cmd) {
+                log::error!("Failed to inject shell integration: {:#}", err);
+            }
+        }
         self.fixup_command(&mut cmd).await?;
         Ok(cmd)
     }
@@ -481,22 +529,42 @@ impl LocalDomain {
 /// teach the Pane impl to reference the writer in the Termninal,
 /// but the Pane trait returns a RefMut and that makes it a bit
 /// awkward at the moment.
+/// Input that is written to the pane is captured here when the pane
+/// is being recorded.
 #[derive(Clone)]
 pub(crate) struct WriterWrapper {
+    pane_id: PaneId,
     writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    record_input: bool,
 }
 
 impl WriterWrapper {
-    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+    pub fn new(pane_id: PaneId, writer: Box<dyn Write + Send>) -> Self {
         Self {
+            pane_id,
             writer: Arc::new(Mutex::new(writer)),
+            record_input: true,
+        }
+    }
+
+    /// Returns a writer for the Terminal to use.  The Terminal writes
+    /// both keyboard input and its replies to queries through it, so
+    /// it isn't recorded here; LocalPane records the keyboard input.
+    pub fn without_input_recording(&self) -> Self {
+        Self {
+            record_input: false,
+            ..self.clone()
         }
     }
 }
 
 impl std::io::Write for WriterWrapper {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.writer.lock().write(buf)
+        let size = self.writer.lock().write(buf)?;
+        if self.record_input {
+            crate::recording::record_input(self.pane_id, &buf[..size]);
+        }
+        Ok(size)
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
@@ -604,14 +672,14 @@ impl Domain for LocalDomain {
             self.name
         );
         let child_result = pair.slave.spawn_command(cmd);
-        let mut writer = WriterWrapper::new(pair.master.take_writer()?);
+        let mut writer = WriterWrapper::new(pane_id, pair.master.take_writer()?);
 
         let mut terminal = wezterm_term::Terminal::new(
             size,
             std::sync::Arc::new(config::TermConfig::new()),
             "WezTerm",
             config::wezterm_version(),
-            Box::new(writer.clone()),
+            Box::new(writer.without_input_recording()),
         );
         if self.is_conpty() {
             terminal.enable_conpty_quirks();
@@ -660,6 +728,12 @@ impl Domain for LocalDomain {
         &self.name
     }
 
+    fn home_dir_cwd(&self) -> Option<String> {
+        // build_command resolves this to the appropriate directory
+        // for both native and WSL domains
+        Some("~".to_string())
+    }
+
     async fn domain_label(&self) -> String {
         if let Some(ed) = self.resolve_exec_domain() {
             match &ed.label {
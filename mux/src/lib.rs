@@ -25,7 +25,7 @@ use std::sync::{Arc, Weak};
 use std::thread;
 use std::time::{Duration, Instant};
 use termwiz::escape::csi::{DecPrivateMode, DecPrivateModeCode, Device, Mode};
-use termwiz::escape::{Action, CSI};
+use termwiz::escape::{Action, Esc, EscCode, CSI};
 use thiserror::*;
 use wezterm_term::{Clipboard, ClipboardSelection, DownloadHandler, TerminalSize};
 #[cfg(windows)]
@@ -37,7 +37,10 @@ pub mod connui;
 pub mod domain;
 pub mod localpane;
 pub mod pane;
+pub mod recording;
 pub mod renderable;
+pub mod replay;
+pub mod shell_integration;
 pub mod ssh;
 pub mod ssh_agent;
 pub mod tab;
@@ -56,6 +59,9 @@ pub enum MuxNotification {
     PaneOutput(PaneId),
     PaneAdded(PaneId),
     PaneRemoved(PaneId),
+    /// The process in the pane has exited.  Depending on the
+    /// exit_behavior, the pane may remain open after this.
+    PaneExited(PaneId),
     WindowCreated(WindowId),
     WindowRemoved(WindowId),
     WindowInvalidated(WindowId),
@@ -97,6 +103,21 @@ pub enum MuxNotification {
 
 static SUB_ID: AtomicUsize = AtomicUsize::new(0);
 
+/// Converts the file URL reported as the working directory of a pane
+/// into a path string that is suitable for passing to a spawned command
+pub fn cwd_url_to_path(url: &url::Url) -> Option<String> {
+    let path = percent_decode_str(url.path()).decode_utf8().ok()?;
+    // On Windows the file URI can produce a path like:
+    // `/C:\Users` which is valid in a file URI, but the leading slash
+    // is not liked by the windows file APIs, so we strip it off here.
+    let bytes = path.as_bytes();
+    if bytes.len() > 2 && bytes[0] == b'/' && bytes[2] == b':' {
+        Some(path[1..].to_owned())
+    } else {
+        Some(path.into_owned())
+    }
+}
+
 pub struct Mux {
     tabs: RwLock<HashMap<TabId, Arc<Tab>>>,
     panes: RwLock<HashMap<PaneId, Arc<dyn Pane>>>,
@@ -109,21 +130,81 @@ pub struct Mux {
     clients: RwLock<HashMap<ClientId, ClientInfo>>,
     identity: RwLock<Option<Arc<ClientId>>>,
     num_panes_by_workspace: RwLock<HashMap<String, usize>>,
+    /// Panes in which keyboard input is discarded
+    read_only_panes: RwLock<HashSet<PaneId>>,
     main_thread_id: std::thread::ThreadId,
     agent: Option<AgentProxy>,
 }
 
 const BUFSIZE: usize = 1024 * 1024;
 
+/// Rate limits the PaneOutput notifications for a pane, so that a
+/// flood of small writes doesn't wake up the renderer for every chunk.
+/// The first output after a quiet period is notified immediately;
+/// subsequent output within mux_output_notify_interval_ms is
+/// notified once that interval has elapsed.
+struct OutputNotifier {
+    pane_id: Option<PaneId>,
+    interval: Duration,
+    last_notified: Option<Instant>,
+    pending: bool,
+}
+
+impl OutputNotifier {
+    fn new() -> Self {
+        Self {
+            pane_id: None,
+            interval: Duration::from_millis(configuration().mux_output_notify_interval_ms),
+            last_notified: None,
+            pending: false,
+        }
+    }
+
+    /// Called after output has been applied to the pane
+    fn output(&mut self, pane_id: PaneId) {
+        self.pane_id.replace(pane_id);
+        self.pending = true;
+        if self.due_in() == Some(Duration::ZERO) {
+            self.flush();
+        }
+    }
+
+    /// If there is a deferred notification, returns how long
+    /// remains until it should be sent
+    fn due_in(&self) -> Option<Duration> {
+        if !self.pending {
+            return None;
+        }
+        match self.last_notified {
+            Some(last) => Some((last + self.interval).saturating_duration_since(Instant::now())),
+            None => Some(Duration::ZERO),
+        }
+    }
+
+    /// Send any deferred notification
+    fn flush(&mut self) {
+        if let (true, Some(pane_id)) = (self.pending, self.pane_id) {
+            Mux::notify_from_any_thread(MuxNotification::PaneOutput(pane_id));
+            self.last_notified.replace(Instant::now());
+            self.pending = false;
+        }
+    }
+}
+
 /// This function applies parsed actions to the pane and notifies any
 /// mux subscribers about the output event
-fn send_actions_to_mux(pane: &Weak<dyn Pane>, dead: &Arc<AtomicBool>, actions: Vec<Action>) {
+fn send_actions_to_mux(
+    pane: &Weak<dyn Pane>,
+    dead: &Arc<AtomicBool>,
+    notifier: &mut OutputNotifier,
+    actions: Vec<Action>,
+) {
     let start = Instant::now();
     match pane.upgrade() {
         Some(pane) => {
             pane.perform_actions(actions);
             histogram!("send_actions_to_mux.perform_actions.latency").record(start.elapsed());
-            Mux::notify_from_any_thread(MuxNotification::PaneOutput(pane.pane_id()));
+            notifier.output(pane.pane_id());
         }
         None => {
             // Something else removed the pane from
@@ -139,12 +220,57 @@ fn parse_buffered_data(pane: Weak<dyn Pane>, dead: &Arc<AtomicBool>, mut rx: Fil
     let mut buf = vec![0; configuration().mux_output_parser_buffer_size];
     let mut parser = termwiz::escape::parser::Parser::new();
     let mut actions = vec![];
-    let mut hold = false;
+    // When synchronized output is active, the time at which we
+    // give up waiting for it to be reset
+    let mut hold: Option<Instant> = None;
     let mut action_size = 0;
     let mut delay = Duration::from_millis(configuration().mux_output_parser_coalesce_delay_ms);
+    let mut hold_timeout = Duration::from_millis(configuration().synchronized_output_timeout_ms);
     let mut deadline = None;
+    let mut notifier = OutputNotifier::new();
 
     loop {
+        if let Some(hold_deadline) = hold {
+            // The program may have been interrupted before it got
+            // around to resetting synchronized output, or may keep
+            // producing output without ever resetting it; don't hold
+            // on to its output forever
+            let now = Instant::now();
+            let expired = now >= hold_deadline || {
+                let mut pfd = [pollfd {
+                    fd: rx.as_socket_descriptor(),
+                    events: POLLIN,
+                    revents: 0,
+                }];
+                !matches!(poll(&mut pfd, Some(hold_deadline - now)), Ok(1))
+            };
+            if expired {
+                hold = None;
+                // Reset the mode in the terminal too, so that it
+                // doesn't report that output is still synchronized
+                Action::CSI(CSI::Mode(Mode::ResetDecPrivateMode(DecPrivateMode::Code(
+                    DecPrivateModeCode::SynchronizedOutput,
+                ))))
+                .append_to(&mut actions);
+                send_actions_to_mux(&pane, &dead, &mut notifier, std::mem::take(&mut actions));
+                action_size = 0;
+            }
+        }
+
+        if let Some(due_in) = notifier.due_in() {
+            // We owe the frontend a notification; wait for more data,
+            // but no longer than it takes for that notification to
+            // become due
+            let mut pfd = [pollfd {
+                fd: rx.as_socket_descriptor(),
+                events: POLLIN,
+                revents: 0,
+            }];
+            if !matches!(poll(&mut pfd, Some(due_in)), Ok(1)) {
+                notifier.flush();
+            }
+        }
+
         match rx.read(&mut buf) {
             Ok(size) if size == 0 => {
                 dead.store(true, Ordering::Relaxed);
@@ -161,22 +287,31 @@ fn parse_buffered_data(pane: Weak<dyn Pane>, dead: &Arc<AtomicBool>, mut rx: Fil
                         Action::CSI(CSI::Mode(Mode::SetDecPrivateMode(DecPrivateMode::Code(
                             DecPrivateModeCode::SynchronizedOutput,
                         )))) => {
-                            hold = true;
+                            hold.get_or_insert_with(|| Instant::now() + hold_timeout);
 
                             // Flush prior actions
                             if !actions.is_empty() {
-                                send_actions_to_mux(&pane, &dead, std::mem::take(&mut actions));
+                                send_actions_to_mux(
+                                    &pane,
+                                    &dead,
+                                    &mut notifier,
+                                    std::mem::take(&mut actions),
+                                );
                                 action_size = 0;
                             }
                         }
                         Action::CSI(CSI::Mode(Mode::ResetDecPrivateMode(
                             DecPrivateMode::Code(DecPrivateModeCode::SynchronizedOutput),
                         ))) => {
-                            hold = false;
+                            hold = None;
                             flush = true;
                         }
                         Action::CSI(CSI::Device(dev)) if matches!(**dev, Device::SoftReset) => {
-                            hold = false;
+                            hold = None;
+                            flush = true;
+                        }
+                        Action::Esc(Esc::Code(EscCode::FullReset)) => {
+                            hold = None;
                             flush = true;
                         }
                         _ => {}
@@ -184,12 +319,17 @@ fn parse_buffered_data(pane: Weak<dyn Pane>, dead: &Arc<AtomicBool>, mut rx: Fil
                     action.append_to(&mut actions);
 
                     if flush && !actions.is_empty() {
-                        send_actions_to_mux(&pane, &dead, std::mem::take(&mut actions));
+                        send_actions_to_mux(
+                            &pane,
+                            &dead,
+                            &mut notifier,
+                            std::mem::take(&mut actions),
+                        );
                         action_size = 0;
                     }
                 });
                 action_size += size;
-                if !actions.is_empty() && !hold {
+                if !actions.is_empty() && hold.is_none() {
                     // If we haven't accumulated too much data,
                     // pause for a short while to increase the chances
                     // that we coalesce a full "frame" from an unoptimized
@@ -219,7 +359,7 @@ fn parse_buffered_data(pane: Weak<dyn Pane>, dead: &Arc<AtomicBool>, mut rx: Fil
                         }
                     }
 
-                    send_actions_to_mux(&pane, &dead, std::mem::take(&mut actions));
+                    send_actions_to_mux(&pane, &dead, &mut notifier, std::mem::take(&mut actions));
                     deadline = None;
                     action_size = 0;
                 }
@@ -227,6 +367,8 @@ fn parse_buffered_data(pane: Weak<dyn Pane>, dead: &Arc<AtomicBool>, mut rx: Fil
                 let config = configuration();
                 buf.resize(config.mux_output_parser_buffer_size, 0);
                 delay = Duration::from_millis(config.mux_output_parser_coalesce_delay_ms);
+                hold_timeout = Duration::from_millis(config.synchronized_output_timeout_ms);
+                notifier.interval = Duration::from_millis(config.mux_output_notify_interval_ms);
             }
         }
     }
@@ -236,8 +378,9 @@ fn parse_buffered_data(pane: Weak<dyn Pane>, dead: &Arc<AtomicBool>, mut rx: Fil
     // for very short lived commands so that we don't forget to
     // display what they displayed.
     if !actions.is_empty() {
-        send_actions_to_mux(&pane, &dead, std::mem::take(&mut actions));
+        send_actions_to_mux(&pane, &dead, &mut notifier, std::mem::take(&mut actions));
     }
+    notifier.flush();
 }
 
 fn set_socket_buffer(fd: &mut FileDescriptor, option: i32, size: usize) -> anyhow::Result<()> {
@@ -322,6 +465,7 @@ fn read_from_pane_pty(
             Ok(size) => {
                 histogram!("read_from_pane_pty.bytes.rate").record(size as f64);
                 log::trace!("read_pty pane {pane_id} read {size} bytes");
+                recording::record_output(pane_id, &buf[..size]);
                 if let Err(err) = tx.write_all(&buf[..size]) {
                     error!(
                         "read_pty failed to write to parser: pane {} {:?}",
@@ -333,6 +477,8 @@ fn read_from_pane_pty(
         }
     }
 
+    Mux::notify_from_any_thread(MuxNotification::PaneExited(pane_id));
+
     match exit_behavior.unwrap_or_else(|| configuration().exit_behavior) {
         ExitBehavior::Hold | ExitBehavior::CloseOnCleanExit => {
             // We don't know if we can unilaterally close
@@ -439,6 +585,7 @@ impl Mux {
             clients: RwLock::new(HashMap::new()),
             identity: RwLock::new(None),
             num_panes_by_workspace: RwLock::new(HashMap::new()),
+            read_only_panes: RwLock::new(HashSet::new()),
             main_thread_id: std::thread::current().id(),
             agent,
         }
@@ -760,6 +907,21 @@ impl Mux {
         self.panes.read().get(&pane_id).map(Arc::clone)
     }
 
+    /// Marks the pane as read-only, so that the gui discards
+    /// keyboard input and pastes to it, while still displaying
+    /// its output.
+    pub fn set_pane_read_only(&self, pane_id: PaneId, read_only: bool) {
+        if read_only {
+            self.read_only_panes.write().insert(pane_id);
+        } else {
+            self.read_only_panes.write().remove(&pane_id);
+        }
+    }
+
+    pub fn is_pane_read_only(&self, pane_id: PaneId) -> bool {
+        self.read_only_panes.read().contains(&pane_id)
+    }
+
     pub fn get_tab(&self, tab_id: TabId) -> Option<Arc<Tab>> {
         self.tabs.read().get(&tab_id).map(Arc::clone)
     }
@@ -807,6 +969,8 @@ impl Mux {
         if let Some(pane) = self.panes.write().remove(&pane_id).clone() {
             log::debug!("killing pane {}", pane_id);
             pane.kill();
+            recording::stop_recording(pane_id);
+            self.read_only_panes.write().remove(&pane_id);
             self.recompute_pane_count();
             self.notify(MuxNotification::PaneRemoved(pane_id));
         }
@@ -1143,29 +1307,11 @@ impl Mux {
         target_domain: DomainId,
         policy: CachePolicy,
     ) -> Option<String> {
-        command_dir.or_else(|| {
-            match pane {
-                Some(pane) if pane.domain_id() == target_domain => pane
-                    .get_current_working_dir(policy)
-                    .and_then(|url| {
-                        percent_decode_str(url.path())
-                            .decode_utf8()
-                            .ok()
-                            .map(|path| path.into_owned())
-                    })
-                    .map(|path| {
-                        // On Windows the file URI can produce a path like:
-                        // `/C:\Users` which is valid in a file URI, but the leading slash
-                        // is not liked by the windows file APIs, so we strip it off here.
-                        let bytes = path.as_bytes();
-                        if bytes.len() > 2 && bytes[0] == b'/' && bytes[2] == b':' {
-                            path[1..].to_owned()
-                        } else {
-                            path
-                        }
-                    }),
-                _ => None,
-            }
+        command_dir.or_else(|| match pane {
+            Some(pane) if pane.domain_id() == target_domain => pane
+                .get_current_working_dir(policy)
+                .and_then(|url| cwd_url_to_path(&url)),
+            _ => None,
         })
     }
 
@@ -1299,6 +1445,7 @@ impl Mux {
         current_pane_id: Option<PaneId>,
         workspace_for_new_window: String,
         window_position: Option<GuiPosition>,
+        window_class: Option<String>,
     ) -> anyhow::Result<(Arc<Tab>, Arc<dyn Pane>, WindowId)> {
         let domain = self
             .resolve_spawn_tab_domain(current_pane_id, &domain)
@@ -1325,6 +1472,9 @@ impl Mux {
         } else {
             term_config = None;
             window_builder = self.new_empty_window(Some(workspace_for_new_window), window_position);
+            if let Some(mut window) = self.get_window_mut(*window_builder) {
+                window.set_initial_class(window_class);
+            }
             (*window_builder, size)
         };
 
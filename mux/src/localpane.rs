@@ -24,6 +24,7 @@ use std::ops::Range;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use termwiz::escape::csi::{Sgr, CSI};
+use termwiz::escape::osc::Progress;
 use termwiz::escape::{Action, DeviceControlMode};
 use termwiz::input::KeyboardEncoding;
 use termwiz::surface::{Line, SequenceNo};
@@ -31,8 +32,8 @@ use url::Url;
 use wezterm_dynamic::Value;
 use wezterm_term::color::ColorPalette;
 use wezterm_term::{
-    Alert, AlertHandler, Clipboard, DownloadHandler, KeyCode, KeyModifiers, MouseEvent,
-    SemanticZone, StableRowIndex, Terminal, TerminalConfiguration, TerminalSize,
+    Alert, AlertHandler, Clipboard, CommandRecord, DownloadHandler, KeyCode, KeyModifiers,
+    MouseEvent, SemanticZone, StableRowIndex, Terminal, TerminalConfiguration, TerminalSize,
 };
 
 const PROC_INFO_CACHE_TTL: Duration = Duration::from_millis(300);
@@ -284,6 +285,7 @@ impl Pane for LocalPane {
                 };
 
                 if let Some(status) = status {
+                    self.terminal.lock().reset_progress();
                     let success = match status.success() {
                         true => true,
                         false => configuration()
@@ -401,13 +403,17 @@ impl Pane for LocalPane {
             }
             return Ok(());
         } else {
-            self.terminal.lock().key_down(key, mods)
+            let mut terminal = self.terminal.lock();
+            self.record_key(&terminal, key, mods, true);
+            terminal.key_down(key, mods)
         }
     }
 
     fn key_up(&self, key: KeyCode, mods: KeyModifiers) -> Result<(), Error> {
         Mux::get().record_input_for_current_identity();
-        self.terminal.lock().key_up(key, mods)
+        let mut terminal = self.terminal.lock();
+        self.record_key(&terminal, key, mods, false);
+        terminal.key_up(key, mods)
     }
 
     fn resize(&self, size: TerminalSize) -> Result<(), Error> {
@@ -418,6 +424,7 @@ impl Pane for LocalPane {
             pixel_height: size.pixel_height.try_into()?,
         })?;
         self.terminal.lock().resize(size);
+        crate::recording::record_resize(self.pane_id, size.cols, size.rows);
         Ok(())
     }
 
@@ -438,6 +445,7 @@ impl Pane for LocalPane {
         if self.tmux_domain.lock().is_some() {
             Ok(())
         } else {
+            crate::recording::record_input(self.pane_id, text.as_bytes());
             self.terminal.lock().send_paste(text)
         }
     }
@@ -477,6 +485,10 @@ impl Pane for LocalPane {
         }
     }
 
+    fn scroll_viewport_into_scrollback(&self) {
+        self.terminal.lock().scroll_viewport_into_scrollback();
+    }
+
     fn focus_changed(&self, focused: bool) {
         self.terminal.lock().focus_changed(focused);
     }
@@ -485,6 +497,10 @@ impl Pane for LocalPane {
         self.terminal.lock().has_unseen_output()
     }
 
+    fn get_progress(&self) -> Progress {
+        self.terminal.lock().get_progress()
+    }
+
     fn is_mouse_grabbed(&self) -> bool {
         if self.tmux_domain.lock().is_some() {
             false
@@ -635,6 +651,10 @@ impl Pane for LocalPane {
         term.get_semantic_zones()
     }
 
+    fn get_command_history(&self) -> Vec<CommandRecord> {
+        self.terminal.lock().get_command_history()
+    }
+
     async fn search(
         &self,
         pattern: Pattern,
@@ -740,13 +760,6 @@ impl Pane for LocalPane {
             true
         });
 
-        #[derive(Copy, Clone, Debug)]
-        struct Coord {
-            byte_idx: usize,
-            grapheme_idx: usize,
-            stable_row: StableRowIndex,
-        }
-
         fn found_match(
             text: &str,
             byte_idx: usize,
@@ -770,7 +783,7 @@ impl Pane for LocalPane {
                 }
             };
             let (start_x, start_y) = haystack_idx_to_coord(byte_idx, coords);
-            let (end_x, end_y) = haystack_idx_to_coord(byte_idx + text.len(), coords);
+            let (end_x, end_y) = haystack_end_idx_to_coord(byte_idx + text.len(), coords);
             results.push(SearchResult {
                 start_x,
                 start_y,
@@ -780,41 +793,70 @@ impl Pane for LocalPane {
             });
         }
 
-        fn make_coords(lines: &[&Line], stable_row: StableRowIndex) -> Vec<Coord> {
-            let mut byte_idx = 0;
-            let mut coords = vec![];
-
-            for (row_idx, line) in lines.iter().enumerate() {
-                for cell in line.visible_cells() {
-                    coords.push(Coord {
-                        byte_idx,
-                        grapheme_idx: cell.cell_index(),
-                        stable_row: stable_row + row_idx as StableRowIndex,
-                    });
-                    byte_idx += cell.str().len();
-                }
-            }
+        Ok(results)
+    }
+}
 
-            coords
-        }
+/// The position of the cell that a byte in the search haystack
+/// of a logical line came from
+#[derive(Copy, Clone, Debug)]
+struct Coord {
+    byte_idx: usize,
+    grapheme_idx: usize,
+    width: usize,
+    stable_row: StableRowIndex,
+}
 
-        fn haystack_idx_to_coord(idx: usize, coords: &[Coord]) -> (usize, StableRowIndex) {
-            let c = coords
-                .binary_search_by(|ele| ele.byte_idx.cmp(&idx))
-                .or_else(|i| -> Result<usize, usize> { Ok(i) })
-                .unwrap();
-            let coord = coords.get(c).map(|c| *c).unwrap_or_else(|| {
-                let last = coords.last().unwrap();
-                Coord {
-                    grapheme_idx: last.grapheme_idx + 1,
-                    ..*last
-                }
+fn make_coords(lines: &[&Line], stable_row: StableRowIndex) -> Vec<Coord> {
+    let mut byte_idx = 0;
+    let mut coords = vec![];
+
+    for (row_idx, line) in lines.iter().enumerate() {
+        for cell in line.visible_cells() {
+            coords.push(Coord {
+                byte_idx,
+                grapheme_idx: cell.cell_index(),
+                width: cell.width(),
+                stable_row: stable_row + row_idx as StableRowIndex,
             });
-            (coord.grapheme_idx, coord.stable_row)
+            byte_idx += cell.str().len();
         }
+    }
 
-        Ok(results)
+    coords
+}
+
+fn haystack_idx_to_coord(idx: usize, coords: &[Coord]) -> (usize, StableRowIndex) {
+    let c = coords
+        .binary_search_by(|ele| ele.byte_idx.cmp(&idx))
+        .or_else(|i| -> Result<usize, usize> { Ok(i) })
+        .unwrap();
+    let coord = coords.get(c).map(|c| *c).unwrap_or_else(|| {
+        let last = coords.last().unwrap();
+        Coord {
+            grapheme_idx: last.grapheme_idx + 1,
+            ..*last
+        }
+    });
+    (coord.grapheme_idx, coord.stable_row)
+}
+
+/// Like haystack_idx_to_coord, but for the exclusive end of a match.
+/// A match that ends at the end of a wrapped line ends on that line,
+/// rather than at the start of the line that it wrapped onto
+fn haystack_end_idx_to_coord(idx: usize, coords: &[Coord]) -> (usize, StableRowIndex) {
+    let c = match coords.binary_search_by(|ele| ele.byte_idx.cmp(&idx)) {
+        Ok(i) | Err(i) => i,
+    };
+    if c > 0 {
+        let prior = coords[c - 1];
+        if let Some(next) = coords.get(c) {
+            if next.stable_row != prior.stable_row {
+                return (prior.grapheme_idx + prior.width, prior.stable_row);
+            }
+        }
     }
+    haystack_idx_to_coord(idx, coords)
 }
 
 struct LocalPaneDCSHandler {
@@ -977,6 +1019,18 @@ fn split_child(
 }
 
 impl LocalPane {
+    /// Records the bytes that the key event will send to the pty,
+    /// if the input to this pane is being recorded
+    fn record_key(&self, terminal: &Terminal, key: KeyCode, mods: KeyModifiers, is_down: bool) {
+        if !crate::recording::is_recording_input(self.pane_id) {
+            return;
+        }
+        match terminal.encode_key(key, mods, is_down) {
+            Ok(encoded) => crate::recording::record_input(self.pane_id, encoded.as_bytes()),
+            Err(err) => log::error!("failed to encode key for recording: {:#}", err),
+        }
+    }
+
     pub fn new(
         pane_id: PaneId,
         mut terminal: Terminal,
@@ -1135,3 +1189,31 @@ impl Drop for LocalPane {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use termwiz::cell::CellAttributes;
+    use termwiz::surface::SEQ_ZERO;
+
+    #[test]
+    fn search_coords_across_wrap() {
+        let attrs = CellAttributes::default();
+        let first = Line::from_text_with_wrapped_last_col("hello", &attrs, SEQ_ZERO);
+        let second = Line::from_text("world", &attrs, SEQ_ZERO, None);
+        let coords = make_coords(&[&first, &second], 10);
+
+        // "hello" ends exactly at the wrap boundary, so it ends
+        // on the first row rather than at the start of the second
+        assert_eq!(haystack_idx_to_coord(0, &coords), (0, 10));
+        assert_eq!(haystack_end_idx_to_coord(5, &coords), (5, 10));
+
+        // "lowo" spans the boundary
+        assert_eq!(haystack_idx_to_coord(3, &coords), (3, 10));
+        assert_eq!(haystack_end_idx_to_coord(7, &coords), (2, 11));
+
+        // "world" starts on the second row and ends with the haystack
+        assert_eq!(haystack_idx_to_coord(5, &coords), (0, 11));
+        assert_eq!(haystack_end_idx_to_coord(10, &coords), (5, 11));
+    }
+}
@@ -10,6 +10,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::ops::Range;
 use std::sync::Arc;
+use termwiz::escape::osc::Progress;
 use termwiz::hyperlink::Rule;
 use termwiz::input::KeyboardEncoding;
 use termwiz::surface::{Line, SequenceNo};
@@ -17,8 +18,8 @@ use url::Url;
 use wezterm_dynamic::Value;
 use wezterm_term::color::ColorPalette;
 use wezterm_term::{
-    Clipboard, DownloadHandler, KeyCode, KeyModifiers, MouseEvent, SemanticZone, StableRowIndex,
-    TerminalConfiguration, TerminalSize,
+    Clipboard, CommandRecord, DownloadHandler, KeyCode, KeyModifiers, MouseEvent, SemanticZone,
+    StableRowIndex, TerminalConfiguration, TerminalSize,
 };
 
 static PANE_ID: ::std::sync::atomic::AtomicUsize = ::std::sync::atomic::AtomicUsize::new(0);
@@ -261,6 +262,10 @@ pub trait Pane: Downcast + Send + Sync {
 
     fn erase_scrollback(&self, _erase_mode: ScrollbackEraseMode) {}
 
+    /// Moves the lines above the current prompt into the scrollback,
+    /// so that they survive the application clearing the screen
+    fn scroll_viewport_into_scrollback(&self) {}
+
     /// Called to advise on whether this tab has focus
     fn focus_changed(&self, _focused: bool) {}
 
@@ -272,6 +277,12 @@ pub trait Pane: Downcast + Send + Sync {
         false
     }
 
+    /// Returns the progress most recently reported by the
+    /// application via OSC 9;4
+    fn get_progress(&self) -> Progress {
+        Progress::None
+    }
+
     /// Certain panes are OK to be closed with impunity (no prompts)
     fn can_close_without_prompting(&self, _reason: CloseReason) -> bool {
         false
@@ -301,6 +312,12 @@ pub trait Pane: Downcast + Send + Sync {
         Ok(vec![])
     }
 
+    /// Returns the commands that the shell has reported via OSC 133
+    /// shell integration, oldest first
+    fn get_command_history(&self) -> Vec<CommandRecord> {
+        vec![]
+    }
+
     /// Returns true if the terminal has grabbed the mouse and wants to
     /// give the embedded application a chance to process events.
     /// In practice this controls whether the gui will perform local
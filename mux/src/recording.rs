@@ -0,0 +1,312 @@
+//! Records the output of panes, and optionally the input that is sent
+//! to them, in the asciicast v2 format so that terminal sessions can be
+//! played back with `wezterm replay` or asciinema.
+//! See <https://github.com/asciinema/asciinema/blob/develop/doc/asciicast-v2.md>
+use crate::localpane::LocalPane;
+use crate::pane::{Pane, PaneId};
+use anyhow::Context;
+use config::configuration;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+
+lazy_static::lazy_static! {
+    static ref RECORDINGS: Mutex<HashMap<PaneId, Recording>> = Mutex::new(HashMap::new());
+}
+
+/// The number of active recordings, which allows the pty reader to
+/// skip taking the lock when nothing is being recorded
+static NUM_RECORDINGS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Serialize)]
+struct Header {
+    version: u32,
+    width: usize,
+    height: usize,
+    timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    env: HashMap<String, String>,
+}
+
+/// An event to be appended to a recording
+struct Event {
+    elapsed: f64,
+    code: &'static str,
+    data: String,
+}
+
+struct Recording {
+    path: PathBuf,
+    /// Sends events to the thread that writes them to the file,
+    /// so that the pty reader isn't blocked on disk I/O
+    sender: Sender<Event>,
+    start: Instant,
+    include_input: bool,
+    /// Incomplete utf8 sequences at the end of the most recent output
+    output: Vec<u8>,
+    /// Incomplete utf8 sequences at the end of the most recent input
+    input: Vec<u8>,
+}
+
+impl Recording {
+    fn write_event(&mut self, code: &'static str, data: &str) -> anyhow::Result<()> {
+        self.sender
+            .send(Event {
+                elapsed: self.start.elapsed().as_secs_f64(),
+                code,
+                data: data.to_string(),
+            })
+            .context("the recording writer has stopped")
+    }
+
+    /// Appends data to buffer and emits an event for the portion of it
+    /// that is valid utf8.  JSON strings must be utf8, and the data may
+    /// end part way through a multi-byte sequence that will be completed
+    /// by the next read, so the remainder is kept for next time.
+    fn write_data(
+        &mut self,
+        code: &'static str,
+        is_input: bool,
+        data: &[u8],
+    ) -> anyhow::Result<()> {
+        let mut buffer = std::mem::take(if is_input {
+            &mut self.input
+        } else {
+            &mut self.output
+        });
+        buffer.extend_from_slice(data);
+
+        let result = match std::str::from_utf8(&buffer) {
+            Ok(valid) => {
+                let result = self.write_event(code, valid);
+                buffer.clear();
+                result
+            }
+            Err(error) => {
+                let valid_len = error.valid_up_to();
+                let result = if valid_len > 0 {
+                    self.write_event(code, unsafe {
+                        std::str::from_utf8_unchecked(&buffer[0..valid_len])
+                    })
+                } else {
+                    Ok(())
+                };
+                buffer.drain(0..valid_len);
+                if let Some(invalid_sequence_length) = error.error_len() {
+                    // Invalid sequence: skip it
+                    buffer.drain(0..invalid_sequence_length);
+                }
+                result
+            }
+        };
+
+        if is_input {
+            self.input = buffer;
+        } else {
+            self.output = buffer;
+        }
+        result
+    }
+}
+
+/// Appends the events received from receiver to file, until
+/// the corresponding sender is dropped
+fn write_events(receiver: Receiver<Event>, mut file: BufWriter<File>) -> anyhow::Result<()> {
+    while let Ok(event) = receiver.recv() {
+        writeln!(
+            file,
+            "{}",
+            serde_json::to_string(&(event.elapsed, event.code, event.data))?
+        )?;
+    }
+    file.flush()?;
+    Ok(())
+}
+
+/// Spawns the thread that writes the events of a recording to file
+fn spawn_writer(path: PathBuf, file: BufWriter<File>) -> Sender<Event> {
+    let (sender, receiver) = channel();
+    std::thread::spawn(move || {
+        if let Err(err) = write_events(receiver, file) {
+            log::error!("failed to write to recording {}: {:#}", path.display(), err);
+        }
+    });
+    sender
+}
+
+/// Returns the directory in which new recordings are saved
+pub fn recording_directory() -> PathBuf {
+    configuration()
+        .recording_directory
+        .clone()
+        .unwrap_or_else(|| config::HOME_DIR.clone())
+}
+
+/// Returns the path to use for a new recording of pane_id
+fn recording_path(pane_id: PaneId) -> PathBuf {
+    let dir = recording_directory();
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    dir.join(format!("wezterm-recording-{pane_id}-{now}.cast"))
+}
+
+/// Starts recording pane to path, or to a new file in the
+/// recording_directory if path is None.
+/// Returns the path of the recording.
+pub fn start_recording(pane: &Arc<dyn Pane>, path: Option<PathBuf>) -> anyhow::Result<PathBuf> {
+    let pane_id = pane.pane_id();
+    if pane.downcast_ref::<LocalPane>().is_none() {
+        anyhow::bail!("pane {pane_id} cannot be recorded because it is not a local pane");
+    }
+
+    let mut recordings = RECORDINGS.lock();
+    if let Some(recording) = recordings.get(&pane_id) {
+        anyhow::bail!(
+            "pane {pane_id} is already being recorded to {}",
+            recording.path.display()
+        );
+    }
+
+    let config = configuration();
+    let path = path.unwrap_or_else(|| recording_path(pane_id));
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("creating directory {}", dir.display()))?;
+        }
+    }
+    let mut file = BufWriter::new(
+        File::create(&path).with_context(|| format!("creating {}", path.display()))?,
+    );
+
+    let dims = pane.get_dimensions();
+    let mut env = HashMap::new();
+    env.insert("TERM".to_string(), config.term.to_string());
+    if let Ok(shell) = std::env::var("SHELL") {
+        env.insert("SHELL".to_string(), shell);
+    }
+    let title = pane.get_title();
+    let header = Header {
+        version: 2,
+        width: dims.cols,
+        height: dims.viewport_rows,
+        timestamp: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        title: if title.is_empty() { None } else { Some(title) },
+        env,
+    };
+    writeln!(file, "{}", serde_json::to_string(&header)?)?;
+    file.flush()?;
+
+    log::info!("recording pane {pane_id} to {}", path.display());
+    recordings.insert(
+        pane_id,
+        Recording {
+            path: path.clone(),
+            sender: spawn_writer(path.clone(), file),
+            start: Instant::now(),
+            include_input: config.recording_include_input,
+            output: vec![],
+            input: vec![],
+        },
+    );
+    NUM_RECORDINGS.fetch_add(1, Ordering::Relaxed);
+    Ok(path)
+}
+
+/// Stops recording pane_id.
+/// Returns the path of the recording, or None if it wasn't being recorded.
+/// The file is flushed and closed by the writer thread once it
+/// has written any events that are still pending.
+pub fn stop_recording(pane_id: PaneId) -> Option<PathBuf> {
+    let recording = RECORDINGS.lock().remove(&pane_id)?;
+    NUM_RECORDINGS.fetch_sub(1, Ordering::Relaxed);
+    log::info!(
+        "finished recording pane {pane_id} to {}",
+        recording.path.display()
+    );
+    Some(recording.path)
+}
+
+/// Returns the path that pane_id is being recorded to, if any
+pub fn recording_path_for_pane(pane_id: PaneId) -> Option<PathBuf> {
+    if NUM_RECORDINGS.load(Ordering::Relaxed) == 0 {
+        return None;
+    }
+    RECORDINGS.lock().get(&pane_id).map(|r| r.path.clone())
+}
+
+fn with_recording<F: FnOnce(&mut Recording) -> anyhow::Result<()>>(pane_id: PaneId, func: F) {
+    if NUM_RECORDINGS.load(Ordering::Relaxed) == 0 {
+        return;
+    }
+    let failed = {
+        let mut recordings = RECORDINGS.lock();
+        match recordings.get_mut(&pane_id) {
+            Some(recording) => match func(recording) {
+                Ok(()) => false,
+                Err(err) => {
+                    log::error!(
+                        "failed to write to recording {}: {:#}",
+                        recording.path.display(),
+                        err
+                    );
+                    true
+                }
+            },
+            None => false,
+        }
+    };
+    if failed {
+        stop_recording(pane_id);
+    }
+}
+
+/// Called with data that was read from the pty of pane_id
+pub(crate) fn record_output(pane_id: PaneId, data: &[u8]) {
+    with_recording(pane_id, |recording| recording.write_data("o", false, data));
+}
+
+/// Returns true if the input to pane_id is being recorded
+pub(crate) fn is_recording_input(pane_id: PaneId) -> bool {
+    if NUM_RECORDINGS.load(Ordering::Relaxed) == 0 {
+        return false;
+    }
+    RECORDINGS
+        .lock()
+        .get(&pane_id)
+        .map(|r| r.include_input)
+        .unwrap_or(false)
+}
+
+/// Called with the input that is sent to the pty of pane_id.
+/// The replies that the terminal sends in response to queries
+/// are not input, and are not passed here.
+pub(crate) fn record_input(pane_id: PaneId, data: &[u8]) {
+    with_recording(pane_id, |recording| {
+        if recording.include_input {
+            recording.write_data("i", true, data)
+        } else {
+            Ok(())
+        }
+    });
+}
+
+/// Called when pane_id is resized
+pub(crate) fn record_resize(pane_id: PaneId, cols: usize, rows: usize) {
+    with_recording(pane_id, |recording| {
+        recording.write_event("r", &format!("{cols}x{rows}"))
+    });
+}
@@ -0,0 +1,464 @@
+//! Plays back asciicast recordings, such as those made by
+//! TogglePaneRecording or `wezterm record`.
+//! The recorded output is fed to a LocalPane through a pty shim, in the
+//! same way as for tmux panes, so that it is rendered by the normal
+//! terminal model, and the keys that are typed into the pane control
+//! the playback.
+use crate::domain::WriterWrapper;
+use crate::localpane::LocalPane;
+use crate::pane::{alloc_pane_id, Pane};
+use crate::tab::Tab;
+use crate::window::WindowId;
+use crate::Mux;
+use anyhow::Context;
+use crossbeam::channel::{unbounded, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use filedescriptor::{FileDescriptor, Pipe};
+use parking_lot::Mutex;
+use portable_pty::{Child, ChildKiller, ExitStatus, MasterPty, PtySize};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use wezterm_term::TerminalSize;
+
+/// How far the seek keys move the playback position, in seconds
+const SEEK_STEP: f32 = 5.0;
+/// The slowest and fastest playback speeds
+const MIN_SPEED: f32 = 0.125;
+const MAX_SPEED: f32 = 16.0;
+
+/// The fields of the asciicast header that are needed for playback
+#[derive(Deserialize)]
+struct Header {
+    width: usize,
+    height: usize,
+}
+
+/// An event from an asciicast recording: the time at which it
+/// occurred in seconds since the start of the recording, its
+/// type code and its data
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReplayEvent(pub f32, pub String, pub String);
+
+impl ReplayEvent {
+    /// Returns the bytes to send to the terminal to play back this
+    /// event, or None if it doesn't affect the terminal.
+    /// Resize events are played back as requests to resize the
+    /// window, which are subject to the window_resize_requests option
+    /// when the terminal is wezterm.
+    pub fn terminal_bytes(&self) -> Option<Vec<u8>> {
+        match self.1.as_str() {
+            "o" => Some(self.2.as_bytes().to_vec()),
+            "r" => {
+                let (cols, rows) = self.2.split_once('x')?;
+                let cols: usize = cols.parse().ok()?;
+                let rows: usize = rows.parse().ok()?;
+                Some(resize_request(cols, rows))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the escape sequence that asks the terminal to resize
+/// itself to cols x rows
+pub fn resize_request(cols: usize, rows: usize) -> Vec<u8> {
+    format!("\x1b[8;{rows};{cols}t").into_bytes()
+}
+
+/// Reads the recording at path, returning its initial width and
+/// height and the events that can be played back
+pub fn read_recording(path: &Path) -> anyhow::Result<(usize, usize, Vec<ReplayEvent>)> {
+    let mut cast_file = BufReader::new(
+        std::fs::File::open(path)
+            .with_context(|| format!("reading cast file {}", path.display()))?,
+    );
+    let mut header_line = String::new();
+    cast_file
+        .read_line(&mut header_line)
+        .context("reading Header line")?;
+    let header: Header = serde_json::from_str(&header_line).context("parsing Header")?;
+
+    let mut events = vec![];
+    for line in cast_file.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event: ReplayEvent = serde_json::from_str(&line).context("parsing event")?;
+        if event.1 == "o" || event.1 == "r" {
+            events.push(event);
+        }
+    }
+    Ok((header.width, header.height, events))
+}
+
+/// Keys that control playback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayControl {
+    TogglePause,
+    Faster,
+    Slower,
+    SeekForward,
+    SeekBackward,
+    Restart,
+    Quit,
+}
+
+impl PlayControl {
+    /// Input is only treated as a control when it exactly matches one
+    /// of the keys, so that the responses to any queries made by the
+    /// recording, which also arrive as input, are not mistaken for
+    /// key presses
+    pub fn from_input(data: &[u8]) -> Option<Self> {
+        match data {
+            b" " => Some(Self::TogglePause),
+            b"+" | b"=" => Some(Self::Faster),
+            b"-" => Some(Self::Slower),
+            b"\x1b[C" | b"\x1bOC" | b"l" => Some(Self::SeekForward),
+            b"\x1b[D" | b"\x1bOD" | b"h" => Some(Self::SeekBackward),
+            b"0" => Some(Self::Restart),
+            b"q" | b"\x03" => Some(Self::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Tracks the playback position within the events of a recording
+pub struct Player {
+    events: Vec<ReplayEvent>,
+    /// The index of the next event to be emitted
+    next: usize,
+    /// The playback position, in seconds since the start of the recording
+    position: f32,
+    speed: f32,
+    paused: bool,
+    /// Set when seeking backwards, as the terminal needs to be reset
+    /// before the events up to the new position are emitted again
+    reset: bool,
+}
+
+impl Player {
+    pub fn new(events: Vec<ReplayEvent>) -> Self {
+        Self {
+            events,
+            next: 0,
+            position: 0.,
+            speed: 1.,
+            paused: false,
+            reset: false,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.events.len()
+    }
+
+    fn duration(&self) -> f32 {
+        self.events.last().map(|event| event.0).unwrap_or(0.)
+    }
+
+    /// Returns how long to wait before the next event is due,
+    /// or None if playback is paused
+    pub fn time_to_next_event(&self) -> Option<Duration> {
+        if self.paused {
+            return None;
+        }
+        let event = self.events.get(self.next)?;
+        Some(Duration::from_secs_f32(
+            ((event.0 - self.position) / self.speed).max(0.),
+        ))
+    }
+
+    pub fn advance(&mut self, elapsed: Duration) {
+        if !self.paused {
+            self.position += elapsed.as_secs_f32() * self.speed;
+        }
+    }
+
+    fn seek(&mut self, position: f32) {
+        let position = position.max(0.).min(self.duration());
+        if position < self.position {
+            self.next = 0;
+            self.reset = true;
+        }
+        self.position = position;
+    }
+
+    pub fn control(&mut self, control: PlayControl) {
+        match control {
+            PlayControl::TogglePause => self.paused = !self.paused,
+            PlayControl::Faster => self.speed = (self.speed * 2.).min(MAX_SPEED),
+            PlayControl::Slower => self.speed = (self.speed / 2.).max(MIN_SPEED),
+            PlayControl::SeekForward => self.seek(self.position + SEEK_STEP),
+            PlayControl::SeekBackward => self.seek(self.position - SEEK_STEP),
+            PlayControl::Restart => self.seek(0.),
+            PlayControl::Quit => {}
+        }
+    }
+
+    /// Returns true, once, after seeking backwards
+    pub fn take_reset(&mut self) -> bool {
+        std::mem::take(&mut self.reset)
+    }
+
+    /// Returns the next event if the playback position has reached it
+    pub fn next_due_event(&mut self) -> Option<&ReplayEvent> {
+        let event = self.events.get(self.next)?;
+        if event.0 > self.position {
+            return None;
+        }
+        self.next += 1;
+        Some(event)
+    }
+}
+
+/// Messages sent to the playback thread
+#[derive(Debug)]
+enum Control {
+    /// Input that was written to the pane
+    Input(Vec<u8>),
+    /// The pane was killed
+    Quit,
+}
+
+/// Stands in for the pty of a replay pane.  Output is read from the
+/// pipe that the playback thread writes to, and input is forwarded to
+/// the playback thread.
+struct ReplayPty {
+    reader: FileDescriptor,
+    control: Sender<Control>,
+    size: Mutex<PtySize>,
+}
+
+struct ReplayPtyWriter {
+    control: Sender<Control>,
+}
+
+impl Write for ReplayPtyWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // If playback has finished there is nobody to receive
+        // the input, which is fine
+        self.control.send(Control::Input(buf.to_vec())).ok();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl MasterPty for ReplayPty {
+    fn resize(&self, size: PtySize) -> anyhow::Result<()> {
+        *self.size.lock() = size;
+        Ok(())
+    }
+
+    fn get_size(&self) -> anyhow::Result<PtySize> {
+        Ok(*self.size.lock())
+    }
+
+    fn try_clone_reader(&self) -> anyhow::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(self.reader.try_clone()?))
+    }
+
+    fn take_writer(&self) -> anyhow::Result<Box<dyn Write + Send>> {
+        Ok(Box::new(ReplayPtyWriter {
+            control: self.control.clone(),
+        }))
+    }
+
+    #[cfg(unix)]
+    fn process_group_leader(&self) -> Option<libc::pid_t> {
+        None
+    }
+
+    #[cfg(unix)]
+    fn as_raw_fd(&self) -> Option<std::os::fd::RawFd> {
+        None
+    }
+
+    #[cfg(unix)]
+    fn tty_name(&self) -> Option<std::path::PathBuf> {
+        None
+    }
+}
+
+/// Stands in for the process of a replay pane; it exits when
+/// playback finishes
+#[derive(Debug)]
+struct ReplayChild {
+    /// Disconnected by the playback thread when it finishes
+    finished: Receiver<()>,
+    control: Sender<Control>,
+}
+
+impl Child for ReplayChild {
+    fn try_wait(&mut self) -> std::io::Result<Option<ExitStatus>> {
+        match self.finished.try_recv() {
+            Err(TryRecvError::Disconnected) => Ok(Some(ExitStatus::with_exit_code(0))),
+            _ => Ok(None),
+        }
+    }
+
+    fn wait(&mut self) -> std::io::Result<ExitStatus> {
+        // Nothing is sent; this returns when the sender is dropped
+        self.finished.recv().ok();
+        Ok(ExitStatus::with_exit_code(0))
+    }
+
+    fn process_id(&self) -> Option<u32> {
+        None
+    }
+
+    #[cfg(windows)]
+    fn as_raw_handle(&self) -> Option<std::os::windows::io::RawHandle> {
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ReplayChildKiller {
+    control: Sender<Control>,
+}
+
+impl ChildKiller for ReplayChildKiller {
+    fn kill(&mut self) -> std::io::Result<()> {
+        self.control.send(Control::Quit).ok();
+        Ok(())
+    }
+
+    fn clone_killer(&self) -> Box<dyn ChildKiller + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+impl ChildKiller for ReplayChild {
+    fn kill(&mut self) -> std::io::Result<()> {
+        self.control.send(Control::Quit).ok();
+        Ok(())
+    }
+
+    fn clone_killer(&self) -> Box<dyn ChildKiller + Send + Sync> {
+        Box::new(ReplayChildKiller {
+            control: self.control.clone(),
+        })
+    }
+}
+
+/// Plays back the recording at path, writing its output to output
+/// and taking the playback controls from control
+fn play(path: &Path, mut output: FileDescriptor, control: Receiver<Control>) -> anyhow::Result<()> {
+    let (width, height, events) = read_recording(path)?;
+    let mut player = Player::new(events);
+
+    output.write_all(&resize_request(width, height))?;
+
+    let mut last_tick = Instant::now();
+    while !player.is_finished() {
+        let result = match player.time_to_next_event() {
+            Some(timeout) => control.recv_timeout(timeout),
+            None => control.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        let now = Instant::now();
+        player.advance(now - last_tick);
+        last_tick = now;
+
+        match result {
+            Ok(Control::Input(data)) => match PlayControl::from_input(&data) {
+                Some(PlayControl::Quit) => break,
+                Some(control) => player.control(control),
+                None => {}
+            },
+            Ok(Control::Quit) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+
+        if player.take_reset() {
+            // Seeking backwards: start over from a clean slate
+            // and quickly catch up to the new position
+            output.write_all(b"\x1bc")?;
+        }
+        while let Some(event) = player.next_due_event() {
+            if let Some(bytes) = event.terminal_bytes() {
+                output.write_all(&bytes)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Opens a new tab in window_id that plays back the recording at path
+pub fn spawn_replay_tab(path: PathBuf, window_id: WindowId) -> anyhow::Result<Arc<dyn Pane>> {
+    let mux = Mux::get();
+    let size = mux
+        .get_active_tab_for_window(window_id)
+        .map(|tab| tab.get_size())
+        .unwrap_or_else(TerminalSize::default);
+
+    let pane_id = alloc_pane_id();
+    let pipe = Pipe::new()?;
+    let (control_tx, control_rx) = unbounded();
+    let (finished_tx, finished_rx) = unbounded::<()>();
+
+    let pty = ReplayPty {
+        reader: pipe.read,
+        control: control_tx.clone(),
+        size: Mutex::new(PtySize {
+            rows: size.rows as u16,
+            cols: size.cols as u16,
+            pixel_width: size.pixel_width as u16,
+            pixel_height: size.pixel_height as u16,
+        }),
+    };
+    let child = ReplayChild {
+        finished: finished_rx,
+        control: control_tx,
+    };
+
+    let output = pipe.write;
+    let replay_path = path.clone();
+    std::thread::spawn(move || {
+        if let Err(err) = play(&replay_path, output, control_rx) {
+            log::error!("replaying {}: {:#}", replay_path.display(), err);
+        }
+        // Closing the output ends the pane's reader, and dropping
+        // finished_tx lets ReplayChild::wait return
+        drop(finished_tx);
+    });
+
+    let writer = WriterWrapper::new(pane_id, pty.take_writer()?);
+    let terminal = wezterm_term::Terminal::new(
+        size,
+        Arc::new(config::TermConfig::new()),
+        "WezTerm",
+        config::wezterm_version(),
+        Box::new(writer.without_input_recording()),
+    );
+
+    let pane: Arc<dyn Pane> = Arc::new(LocalPane::new(
+        pane_id,
+        terminal,
+        Box::new(child),
+        Box::new(pty),
+        Box::new(writer),
+        mux.default_domain().domain_id(),
+        format!("replay {}", path.display()),
+    ));
+
+    let tab = Arc::new(Tab::new(&size));
+    tab.assign_pane(&pane);
+    mux.add_tab_and_active_pane(&tab)?;
+    mux.add_tab_to_window(&tab, window_id)?;
+
+    if let Some(mut window) = mux.get_window_mut(window_id) {
+        if let Some(idx) = window.idx_by_id(tab.tab_id()) {
+            window.save_and_then_set_active(idx);
+        }
+    }
+
+    Ok(pane)
+}
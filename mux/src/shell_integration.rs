@@ -0,0 +1,146 @@
+//! The shell integration scripts that are embedded in wezterm, along
+//! with the ability to automatically load them into the shells that
+//! are spawned by the local domain.
+use anyhow::Context;
+use config::RUNTIME_DIR;
+use portable_pty::CommandBuilder;
+use std::path::{Path, PathBuf};
+
+/// Shell integration for bash and zsh
+pub const BASH_ZSH_SCRIPT: &str = include_str!("../../assets/shell-integration/wezterm.sh");
+
+/// Shell integration for fish
+pub const FISH_SCRIPT: &str = include_str!("../../assets/shell-integration/wezterm.fish");
+
+/// zsh reads its startup files from $ZDOTDIR, so we point that at a
+/// directory containing this file, which puts back the original ZDOTDIR
+/// so that the rest of the user's startup files are loaded as normal.
+const ZSH_ZSHENV: &str = r#"# Generated by wezterm to load its shell integration; do not edit.
+if [[ -n "${WEZTERM_ORIG_ZDOTDIR+x}" ]] ; then
+  ZDOTDIR="$WEZTERM_ORIG_ZDOTDIR"
+  unset WEZTERM_ORIG_ZDOTDIR
+else
+  unset ZDOTDIR
+fi
+if [[ -f "${ZDOTDIR:-$HOME}/.zshenv" ]] ; then
+  source "${ZDOTDIR:-$HOME}/.zshenv"
+fi
+if [[ -o interactive && -f "$WEZTERM_SHELL_INTEGRATION_SCRIPT" ]] ; then
+  source "$WEZTERM_SHELL_INTEGRATION_SCRIPT"
+fi
+unset WEZTERM_SHELL_INTEGRATION_SCRIPT
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegratedShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl IntegratedShell {
+    /// Determines the shell from the path to its executable
+    pub fn from_program(program: &str) -> Option<Self> {
+        let name = Path::new(program).file_name()?.to_str()?;
+        match name.trim_start_matches('-') {
+            "bash" => Some(Self::Bash),
+            "zsh" => Some(Self::Zsh),
+            "fish" => Some(Self::Fish),
+            _ => None,
+        }
+    }
+
+    pub fn script(self) -> &'static str {
+        match self {
+            Self::Bash | Self::Zsh => BASH_ZSH_SCRIPT,
+            Self::Fish => FISH_SCRIPT,
+        }
+    }
+}
+
+fn write_if_changed(path: &Path, content: &str) -> anyhow::Result<()> {
+    if std::fs::read_to_string(path).ok().as_deref() == Some(content) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        config::create_user_owned_dirs(parent)?;
+    }
+    std::fs::write(path, content).with_context(|| format!("writing {}", path.display()))
+}
+
+fn injection_dir() -> PathBuf {
+    RUNTIME_DIR.join("shell-integration")
+}
+
+/// Arranges for the shell that `cmd` will run to load the shell
+/// integration, by adjusting the environment in a way that is
+/// specific to that shell.  Only zsh and fish can be injected
+/// this way; other shells are left untouched.
+pub fn inject_shell_integration(cmd: &mut CommandBuilder) -> anyhow::Result<()> {
+    let program = if cmd.is_default_prog() {
+        cmd.get_shell()
+    } else {
+        match cmd.get_argv().first().and_then(|arg| arg.to_str()) {
+            Some(program) => program.to_string(),
+            None => return Ok(()),
+        }
+    };
+
+    match IntegratedShell::from_program(&program) {
+        Some(IntegratedShell::Zsh) => {
+            let dir = injection_dir().join("zsh");
+            let script = injection_dir().join("wezterm.sh");
+            write_if_changed(&script, BASH_ZSH_SCRIPT)?;
+            write_if_changed(&dir.join(".zshenv"), ZSH_ZSHENV)?;
+
+            if let Some(zdotdir) = cmd.get_env("ZDOTDIR").map(|d| d.to_os_string()) {
+                cmd.env("WEZTERM_ORIG_ZDOTDIR", zdotdir);
+            }
+            cmd.env("ZDOTDIR", dir);
+            cmd.env("WEZTERM_SHELL_INTEGRATION_SCRIPT", script);
+        }
+        Some(IntegratedShell::Fish) => {
+            // fish loads the files in $XDG_DATA_DIRS/fish/vendor_conf.d
+            let dir = injection_dir().join("fish-data");
+            write_if_changed(
+                &dir.join("fish").join("vendor_conf.d").join("wezterm.fish"),
+                FISH_SCRIPT,
+            )?;
+
+            let dir = dir
+                .to_str()
+                .context("shell integration directory is not UTF-8")?;
+            let data_dirs = match cmd.get_env("XDG_DATA_DIRS").and_then(|d| d.to_str()) {
+                Some(dirs) if !dirs.is_empty() => format!("{dir}:{dirs}"),
+                // This is the default value specified by the XDG spec
+                _ => format!("{dir}:/usr/local/share:/usr/share"),
+            };
+            cmd.env("XDG_DATA_DIRS", data_dirs);
+            cmd.env("WEZTERM_SHELL_INTEGRATION_XDG_DIR", dir);
+        }
+        Some(IntegratedShell::Bash) | None => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shell_from_program() {
+        assert_eq!(
+            IntegratedShell::from_program("/bin/zsh"),
+            Some(IntegratedShell::Zsh)
+        );
+        assert_eq!(
+            IntegratedShell::from_program("-bash"),
+            Some(IntegratedShell::Bash)
+        );
+        assert_eq!(
+            IntegratedShell::from_program("/usr/local/bin/fish"),
+            Some(IntegratedShell::Fish)
+        );
+        assert_eq!(IntegratedShell::from_program("/bin/sh"), None);
+    }
+}
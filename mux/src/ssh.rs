@@ -251,7 +251,7 @@ impl RemoteSshDomain {
         command_dir: Option<String>,
     ) -> anyhow::Result<(Option<String>, HashMap<String, String>)> {
         let config = config::configuration();
-        let cmd = match command {
+        let cmd = match config.resolve_domain_default_prog(&self.dom.name, command) {
             Some(mut cmd) => {
                 config.apply_cmd_defaults(&mut cmd, None);
                 cmd
@@ -275,7 +275,10 @@ impl RemoteSshDomain {
             env: &HashMap<String, String>,
         ) -> anyhow::Result<String> {
             // "Soft" chdir: if it doesn't exist then it doesn't matter
-            let cd_cmd = if let Some(dir) = dir {
+            let cd_cmd = if dir.as_deref() == Some("~") {
+                // Quoting would prevent the shell from expanding this
+                "cd ~;".to_string()
+            } else if let Some(dir) = dir {
                 format!("cd {};", shell_words::quote(&dir))
             } else if let Some(dir) = cmd.get_cwd() {
                 let dir = dir.to_str().context("converting cwd to string")?;
@@ -757,14 +760,14 @@ impl Domain for RemoteSshDomain {
         // eg: tmux integration to be tunnelled via the remote
         // session without duplicating a lot of logic over here.
 
-        let writer = WriterWrapper::new(writer);
+        let writer = WriterWrapper::new(pane_id, writer);
 
         let terminal = wezterm_term::Terminal::new(
             size,
             std::sync::Arc::new(config::TermConfig::new()),
             "WezTerm",
             config::wezterm_version(),
-            Box::new(writer.clone()),
+            Box::new(writer.without_input_recording()),
         );
 
         let pane: Arc<dyn Pane> = Arc::new(LocalPane::new(
@@ -790,6 +793,15 @@ impl Domain for RemoteSshDomain {
         &self.name
     }
 
+    fn home_dir_cwd(&self) -> Option<String> {
+        // build_env_command leaves this for the remote shell to expand
+        Some("~".to_string())
+    }
+
+    fn local_echo_threshold(&self) -> Option<Duration> {
+        self.dom.local_echo_threshold_ms.map(Duration::from_millis)
+    }
+
     async fn attach(&self, _window_id: Option<crate::WindowId>) -> anyhow::Result<()> {
         Ok(())
     }
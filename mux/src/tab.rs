@@ -4,9 +4,9 @@ use crate::renderable::StableCursorPosition;
 use crate::{Mux, MuxNotification, WindowId};
 use bintree::PathBranch;
 use config::configuration;
-use config::keyassignment::PaneDirection;
+use config::keyassignment::{PaneArrangement, PaneDirection};
 use parking_lot::Mutex;
-use rangeset::intersects_range;
+use rangeset::range_intersection;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::convert::TryInto;
@@ -506,6 +506,112 @@ fn cell_dimensions(size: &TerminalSize) -> TerminalSize {
     }
 }
 
+/// Returns the sizes of the two children of a split of size in
+/// direction, where the first child is first cells wide (or tall)
+fn split_sizes(
+    size: &TerminalSize,
+    direction: SplitDirection,
+    first: usize,
+    cell_dimensions: &TerminalSize,
+) -> (TerminalSize, TerminalSize) {
+    let mut first_size = *size;
+    let mut second_size = *size;
+    match direction {
+        SplitDirection::Horizontal => {
+            first_size.cols = first;
+            second_size.cols = size.cols.saturating_sub(first + 1);
+        }
+        SplitDirection::Vertical => {
+            first_size.rows = first;
+            second_size.rows = size.rows.saturating_sub(first + 1);
+        }
+    }
+    for size in [&mut first_size, &mut second_size] {
+        size.pixel_width = size.cols.saturating_mul(cell_dimensions.pixel_width);
+        size.pixel_height = size.rows.saturating_mul(cell_dimensions.pixel_height);
+    }
+    (first_size, second_size)
+}
+
+/// Builds a tree in which the given subtrees are placed one after
+/// the other along direction.  The split sizes are placeholders
+/// that need to be computed afterwards, eg: by balance_splits.
+fn chain_trees(trees: Vec<Tree>, direction: SplitDirection) -> Tree {
+    let mut chain = Tree::Empty;
+    for tree in trees.into_iter().rev() {
+        chain = match chain {
+            Tree::Empty => tree,
+            chain => Tree::Node {
+                left: Box::new(tree),
+                right: Box::new(chain),
+                data: Some(SplitDirectionAndSize {
+                    direction,
+                    first: TerminalSize::default(),
+                    second: TerminalSize::default(),
+                }),
+            },
+        };
+    }
+    chain
+}
+
+/// Returns the number of panes that share the space along `direction`
+/// in this portion of the tree.  Nested splits in the same direction
+/// are counted together, so that balancing gives each of their panes
+/// the same size, rather than halving the space at each level.
+fn balance_weight(tree: &Tree, direction: SplitDirection) -> usize {
+    match tree {
+        Tree::Node {
+            left,
+            right,
+            data: Some(data),
+        } if data.direction == direction => {
+            balance_weight(&*left, direction) + balance_weight(&*right, direction)
+        }
+        _ => 1,
+    }
+}
+
+/// Recomputes the split sizes of tree, which occupies size, so that
+/// the panes arranged along axis are given equal space.
+/// If axis is None, both axes are balanced.
+fn balance_splits(
+    tree: &mut Tree,
+    size: &TerminalSize,
+    axis: Option<SplitDirection>,
+    cell_dimensions: &TerminalSize,
+) {
+    if let Tree::Node {
+        left,
+        right,
+        data: Some(data),
+    } = tree
+    {
+        let direction = data.direction;
+        let (total, mut first) = match direction {
+            SplitDirection::Horizontal => (size.cols, data.first.cols),
+            SplitDirection::Vertical => (size.rows, data.first.rows),
+        };
+
+        if axis.map_or(true, |axis| axis == direction) {
+            let first_weight = balance_weight(&*left, direction);
+            let weight = first_weight + balance_weight(&*right, direction);
+            // Each pane is separated from the next by a single cell
+            let available = total.saturating_sub(weight - 1);
+            if available >= weight {
+                first = available * first_weight / weight + first_weight - 1;
+            }
+        }
+
+        let (first_size, second_size) = split_sizes(size, direction, first, cell_dimensions);
+        data.first = first_size;
+        data.second = second_size;
+
+        balance_splits(&mut *left, &first_size, axis, cell_dimensions);
+        balance_splits(&mut *right, &second_size, axis, cell_dimensions);
+    }
+}
+
 impl Tab {
     pub fn new(size: &TerminalSize) -> Self {
         let inner = TabInner::new(size);
@@ -646,6 +752,23 @@ impl Tab {
         self.inner.lock().adjust_pane_size(direction, amount)
     }
 
+    /// Resizes the splits so that panes that are arranged side by side
+    /// (for Horizontal) or one above the other (for Vertical) are given
+    /// equal space.  If axis is None, both axes are balanced.
+    pub fn balance_panes(&self, axis: Option<SplitDirection>) {
+        self.inner.lock().balance_panes(axis)
+    }
+
+    /// Rebuilds the splits of this tab so that its panes are placed
+    /// according to arrangement.  For the Main arrangements, the active
+    /// pane becomes the main pane and is given main_pane_percent of the
+    /// width or height of the tab.
+    pub fn arrange_panes(&self, arrangement: PaneArrangement, main_pane_percent: u8) {
+        self.inner
+            .lock()
+            .arrange_panes(arrangement, main_pane_percent)
+    }
+
     /// Activate an adjacent pane in the specified direction.
     /// In cases where there are multiple adjacent panes in the
     /// intended direction, we take the pane that has the largest
@@ -1436,6 +1559,94 @@ impl TabInner {
         }
     }
 
+    fn balance_panes(&mut self, axis: Option<SplitDirection>) {
+        if self.zoomed.is_some() {
+            return;
+        }
+        let size = self.size;
+        let cell_dimensions = self.cell_dimensions();
+        if let Some(tree) = self.pane.as_mut() {
+            balance_splits(tree, &size, axis, &cell_dimensions);
+            apply_sizes_from_splits(tree, &size);
+        }
+        Mux::try_get().map(|mux| mux.notify(MuxNotification::TabResized(self.id)));
+    }
+
+    fn arrange_panes(&mut self, arrangement: PaneArrangement, main_pane_percent: u8) {
+        if self.zoomed.is_some() {
+            return;
+        }
+        let mut panes: Vec<Arc<dyn Pane>> = self
+            .iter_panes_ignoring_zoom()
+            .into_iter()
+            .map(|pos| pos.pane)
+            .collect();
+        if panes.len() < 2 || self.active >= panes.len() {
+            return;
+        }
+
+        let size = self.size;
+        let cell_dimensions = self.cell_dimensions();
+
+        let tree = match arrangement {
+            PaneArrangement::Tiled => {
+                let mut cols = 1;
+                while cols * cols < panes.len() {
+                    cols += 1;
+                }
+                let rows = panes
+                    .chunks(cols)
+                    .map(|row| {
+                        let row = row.iter().map(|pane| Tree::Leaf(Arc::clone(pane)));
+                        chain_trees(row.collect(), SplitDirection::Horizontal)
+                    })
+                    .collect();
+                let mut tree = chain_trees(rows, SplitDirection::Vertical);
+                balance_splits(&mut tree, &size, None, &cell_dimensions);
+                tree
+            }
+            PaneArrangement::MainVertical | PaneArrangement::MainHorizontal => {
+                let (direction, others_direction, total) = match arrangement {
+                    PaneArrangement::MainVertical => (
+                        SplitDirection::Horizontal,
+                        SplitDirection::Vertical,
+                        size.cols,
+                    ),
+                    _ => (
+                        SplitDirection::Vertical,
+                        SplitDirection::Horizontal,
+                        size.rows,
+                    ),
+                };
+                let first = (total * main_pane_percent as usize / 100)
+                    .clamp(1, total.saturating_sub(2).max(1));
+                let (first_size, second_size) =
+                    split_sizes(&size, direction, first, &cell_dimensions);
+
+                let main = panes.remove(self.active);
+                self.active = 0;
+
+                let others = panes.into_iter().map(Tree::Leaf).collect();
+                let mut others = chain_trees(others, others_direction);
+                balance_splits(&mut others, &second_size, None, &cell_dimensions);
+
+                Tree::Node {
+                    left: Box::new(Tree::Leaf(main)),
+                    right: Box::new(others),
+                    data: Some(SplitDirectionAndSize {
+                        direction,
+                        first: first_size,
+                        second: second_size,
+                    }),
+                }
+            }
+        };
+
+        apply_sizes_from_splits(&tree, &size);
+        self.pane.replace(tree);
+        Mux::try_get().map(|mux| mux.notify(MuxNotification::TabResized(self.id)));
+    }
+
     fn activate_pane_direction(&mut self, direction: PaneDirection) {
         if self.zoomed.is_some() {
             if !configuration().unzoom_on_switch_pane {
@@ -1489,60 +1700,47 @@ impl TabInner {
 
         let recency = &self.recency;
 
-        fn edge_intersects(
+        /// Returns the length of the shared portion of the edges
+        /// of the active and candidate panes
+        fn edge_overlap(
             active_start: usize,
             active_size: usize,
             current_start: usize,
             current_size: usize,
-        ) -> bool {
-            intersects_range(
+        ) -> usize {
+            range_intersection(
                 &(active_start..active_start + active_size),
                 &(current_start..current_start + current_size),
             )
+            .map(|r| r.end - r.start)
+            .unwrap_or(0)
         }
 
         for pane in &panes {
-            let score = match direction {
-                PaneDirection::Right => {
-                    if pane.left == active.left + active.width + 1
-                        && edge_intersects(active.top, active.height, pane.top, pane.height)
-                    {
-                        1 + recency.score(pane.index)
-                    } else {
-                        0
-                    }
+            let overlap = match direction {
+                PaneDirection::Right if pane.left == active.left + active.width + 1 => {
+                    edge_overlap(active.top, active.height, pane.top, pane.height)
                 }
-                PaneDirection::Left => {
-                    if pane.left + pane.width + 1 == active.left
-                        && edge_intersects(active.top, active.height, pane.top, pane.height)
-                    {
-                        1 + recency.score(pane.index)
-                    } else {
-                        0
-                    }
+                PaneDirection::Left if pane.left + pane.width + 1 == active.left => {
+                    edge_overlap(active.top, active.height, pane.top, pane.height)
                 }
-                PaneDirection::Up => {
-                    if pane.top + pane.height + 1 == active.top
-                        && edge_intersects(active.left, active.width, pane.left, pane.width)
-                    {
-                        1 + recency.score(pane.index)
-                    } else {
-                        0
-                    }
+                PaneDirection::Up if pane.top + pane.height + 1 == active.top => {
+                    edge_overlap(active.left, active.width, pane.left, pane.width)
                 }
-                PaneDirection::Down => {
-                    if active.top + active.height + 1 == pane.top
-                        && edge_intersects(active.left, active.width, pane.left, pane.width)
-                    {
-                        1 + recency.score(pane.index)
-                    } else {
-                        0
-                    }
+                PaneDirection::Down if active.top + active.height + 1 == pane.top => {
+                    edge_overlap(active.left, active.width, pane.left, pane.width)
                 }
+                PaneDirection::Right
+                | PaneDirection::Left
+                | PaneDirection::Up
+                | PaneDirection::Down => 0,
                 PaneDirection::Next | PaneDirection::Prev => unreachable!(),
             };
 
-            if score > 0 {
+            if overlap > 0 {
+                // Prefer the pane that shares the most of its edge with
+                // the active pane, and then the most recently active
+                let score = (overlap, recency.score(pane.index));
                 let target = match best.take() {
                     Some((best_score, best_pane)) if best_score > score => (best_score, best_pane),
                     _ => (score, pane),
@@ -2512,6 +2710,208 @@ mod test {
         assert_eq!(600, panes[2].pixel_height);
     }
 
+    #[test]
+    fn pane_direction() {
+        let size = TerminalSize {
+            rows: 25,
+            cols: 80,
+            pixel_width: 800,
+            pixel_height: 625,
+            dpi: 96,
+        };
+
+        // Split into a left pane and a right column that is itself
+        // split into top and bottom panes, with the bottom pane being
+        // `bottom_rows` tall and being the most recently active
+        let make_tab = |bottom_rows: usize| {
+            let tab = Tab::new(&size);
+            tab.assign_pane(&FakePane::new(1, size));
+
+            let horz = SplitRequest {
+                direction: SplitDirection::Horizontal,
+                ..Default::default()
+            };
+            let horz_size = tab.compute_split_size(0, horz).unwrap();
+            tab.split_and_insert(0, horz, FakePane::new(2, horz_size.second))
+                .unwrap();
+
+            let vert = SplitRequest {
+                direction: SplitDirection::Vertical,
+                size: SplitSize::Cells(bottom_rows),
+                ..Default::default()
+            };
+            let vert_size = tab.compute_split_size(1, vert).unwrap();
+            tab.split_and_insert(1, vert, FakePane::new(3, vert_size.second))
+                .unwrap();
+
+            tab.inner.lock().active = 0;
+            tab
+        };
+
+        // The top pane shares more of the edge, despite being
+        // less recently active
+        let tab = make_tab(6);
+        assert_eq!(tab.get_pane_direction(PaneDirection::Right, false), Some(1));
+
+        // With equal overlap, the most recently active pane wins
+        let tab = make_tab(12);
+        assert_eq!(tab.get_pane_direction(PaneDirection::Right, false), Some(2));
+        tab.inner.lock().recency.tag(1);
+        assert_eq!(tab.get_pane_direction(PaneDirection::Right, false), Some(1));
+
+        // Moving back from either of the right hand panes
+        // lands on the left pane
+        tab.inner.lock().active = 2;
+        assert_eq!(tab.get_pane_direction(PaneDirection::Left, false), Some(0));
+        assert_eq!(tab.get_pane_direction(PaneDirection::Up, false), Some(1));
+        assert_eq!(tab.get_pane_direction(PaneDirection::Down, false), None);
+    }
+
+    #[test]
+    fn balance_panes() {
+        let size = TerminalSize {
+            rows: 24,
+            cols: 80,
+            pixel_width: 800,
+            pixel_height: 600,
+            dpi: 96,
+        };
+
+        let tab = Tab::new(&size);
+        tab.assign_pane(&FakePane::new(1, size));
+
+        // Three columns, with the leftmost one split into rows,
+        // none of which are the same size
+        let splits = [
+            (0, SplitDirection::Horizontal, SplitSize::Percent(50)),
+            (1, SplitDirection::Horizontal, SplitSize::Cells(10)),
+            (0, SplitDirection::Vertical, SplitSize::Cells(5)),
+        ];
+        for (id, &(pane_index, direction, split_size)) in splits.iter().enumerate() {
+            let request = SplitRequest {
+                direction,
+                size: split_size,
+                ..Default::default()
+            };
+            let split_size = tab.compute_split_size(pane_index, request).unwrap();
+            tab.split_and_insert(
+                pane_index,
+                request,
+                FakePane::new(id + 2, split_size.second),
+            )
+            .unwrap();
+        }
+
+        let sizes = |tab: &Tab| {
+            tab.iter_panes()
+                .iter()
+                .map(|p| (p.left, p.top, p.width, p.height))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sizes(&tab),
+            vec![
+                (0, 0, 39, 18),
+                (0, 19, 39, 5),
+                (40, 0, 29, 24),
+                (70, 0, 10, 24)
+            ]
+        );
+
+        // The three columns get an equal share of the width,
+        // without changing the heights
+        tab.balance_panes(Some(SplitDirection::Horizontal));
+        assert_eq!(
+            sizes(&tab),
+            vec![
+                (0, 0, 26, 18),
+                (0, 19, 26, 5),
+                (27, 0, 26, 24),
+                (54, 0, 26, 24)
+            ]
+        );
+
+        tab.balance_panes(None);
+        assert_eq!(
+            sizes(&tab),
+            vec![
+                (0, 0, 26, 11),
+                (0, 12, 26, 12),
+                (27, 0, 26, 24),
+                (54, 0, 26, 24)
+            ]
+        );
+    }
+
+    #[test]
+    fn arrange_panes() {
+        let size = TerminalSize {
+            rows: 24,
+            cols: 80,
+            pixel_width: 800,
+            pixel_height: 600,
+            dpi: 96,
+        };
+
+        let tab = Tab::new(&size);
+        tab.assign_pane(&FakePane::new(1, size));
+        for id in 2..=4 {
+            let request = SplitRequest {
+                direction: SplitDirection::Horizontal,
+                ..Default::default()
+            };
+            let split_size = tab.compute_split_size(0, request).unwrap();
+            tab.split_and_insert(0, request, FakePane::new(id, split_size.second))
+                .unwrap();
+        }
+        tab.inner.lock().active = 2;
+        let active_pane_id = tab.get_active_pane().unwrap().pane_id();
+
+        let sizes = |tab: &Tab| {
+            tab.iter_panes()
+                .iter()
+                .map(|p| (p.left, p.top, p.width, p.height))
+                .collect::<Vec<_>>()
+        };
+
+        tab.arrange_panes(PaneArrangement::MainVertical, 60);
+        assert_eq!(tab.get_active_idx(), 0);
+        assert_eq!(tab.get_active_pane().unwrap().pane_id(), active_pane_id);
+        assert_eq!(
+            sizes(&tab),
+            vec![
+                (0, 0, 48, 24),
+                (49, 0, 31, 7),
+                (49, 8, 31, 7),
+                (49, 16, 31, 8)
+            ]
+        );
+
+        tab.arrange_panes(PaneArrangement::MainHorizontal, 50);
+        assert_eq!(tab.get_active_pane().unwrap().pane_id(), active_pane_id);
+        assert_eq!(
+            sizes(&tab),
+            vec![
+                (0, 0, 80, 12),
+                (0, 13, 26, 11),
+                (27, 13, 26, 11),
+                (54, 13, 26, 11)
+            ]
+        );
+
+        tab.arrange_panes(PaneArrangement::Tiled, 50);
+        assert_eq!(tab.get_active_idx(), 0);
+        assert_eq!(
+            sizes(&tab),
+            vec![
+                (0, 0, 39, 11),
+                (40, 0, 40, 11),
+                (0, 12, 39, 12),
+                (40, 12, 40, 12)
+            ]
+        );
+    }
+
     fn is_send_and_sync<T: Send + Sync>() -> bool {
         true
     }
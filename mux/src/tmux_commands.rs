@@ -128,7 +128,7 @@ impl TmuxDomainState {
                 cmd_queue: self.cmd_queue.clone(),
                 master_pane: ref_pane,
             };
-            let writer = WriterWrapper::new(pane_pty.take_writer()?);
+            let writer = WriterWrapper::new(local_pane_id, pane_pty.take_writer()?);
             let mux = Mux::get();
             let size = TerminalSize {
                 rows: pane.pane_height as usize,
@@ -147,7 +147,7 @@ impl TmuxDomainState {
                 std::sync::Arc::new(config::TermConfig::new()),
                 "WezTerm",
                 config::wezterm_version(),
-                Box::new(writer.clone()),
+                Box::new(writer.without_input_recording()),
             );
 
             let local_pane: Arc<dyn Pane> = Arc::new(LocalPane::new(
@@ -14,6 +14,7 @@ pub struct Window {
     workspace: String,
     title: String,
     initial_position: Option<GuiPosition>,
+    initial_class: Option<String>,
 }
 
 impl Window {
@@ -26,6 +27,7 @@ impl Window {
             title: String::new(),
             workspace: workspace.unwrap_or_else(|| Mux::get().active_workspace()),
             initial_position,
+            initial_class: None,
         }
     }
 
@@ -33,6 +35,16 @@ impl Window {
         &self.initial_position
     }
 
+    /// Returns the windowing system class to use for the gui window,
+    /// if it should differ from the default class of the gui
+    pub fn get_initial_class(&self) -> Option<&str> {
+        self.initial_class.as_deref()
+    }
+
+    pub fn set_initial_class(&mut self, class: Option<String>) {
+        self.initial_class = class;
+    }
+
     pub fn get_workspace(&self) -> &str {
         &self.workspace
     }
@@ -91,6 +91,33 @@ lazy_static::lazy_static! {
     static ref DEFAULT_PALETTE: ColorPalette = ColorPalette::compute_default();
 }
 
+impl ColorPalette {
+    /// Returns a palette in which each color is part way between
+    /// the corresponding colors of self and other, where k is in
+    /// the range 0.0 (self) to 1.0 (other)
+    pub fn interpolate(&self, other: &Self, k: f64) -> Self {
+        let mix = |a: SrgbaTuple, b: SrgbaTuple| a.interpolate(b, k);
+        Self {
+            colors: self
+                .colors
+                .0
+                .iter()
+                .zip(other.colors.0.iter())
+                .map(|(&a, &b)| mix(a, b))
+                .collect(),
+            foreground: mix(self.foreground, other.foreground),
+            background: mix(self.background, other.background),
+            cursor_fg: mix(self.cursor_fg, other.cursor_fg),
+            cursor_bg: mix(self.cursor_bg, other.cursor_bg),
+            cursor_border: mix(self.cursor_border, other.cursor_border),
+            selection_fg: mix(self.selection_fg, other.selection_fg),
+            selection_bg: mix(self.selection_bg, other.selection_bg),
+            scrollbar_thumb: mix(self.scrollbar_thumb, other.scrollbar_thumb),
+            split: mix(self.split, other.split),
+        }
+    }
+}
+
 impl Default for ColorPalette {
     /// Construct a default color palette
     fn default() -> ColorPalette {
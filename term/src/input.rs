@@ -57,8 +57,8 @@ pub struct ClickPosition {
 /// which is the number of successive clicks of the same mouse button
 /// within the `CLICK_INTERVAL`.  The streak is reset to 1 each time
 /// the mouse button differs from the last click, or when the elapsed
-/// time exceeds `CLICK_INTERVAL`, or when the cursor position
-/// changes to a different character cell.
+/// time exceeds `CLICK_INTERVAL` (or the interval passed to `add_within`),
+/// or when the cursor position changes to a different character cell.
 #[derive(Debug, Clone)]
 pub struct LastMouseClick {
     pub button: MouseButton,
@@ -81,10 +81,21 @@ impl LastMouseClick {
     }
 
     pub fn add(&self, button: MouseButton, position: ClickPosition) -> Self {
+        self.add_within(button, position, Duration::from_millis(CLICK_INTERVAL))
+    }
+
+    /// Like `add`, but successive clicks must be within `interval`
+    /// rather than the default multi-click interval
+    pub fn add_within(
+        &self,
+        button: MouseButton,
+        position: ClickPosition,
+        interval: Duration,
+    ) -> Self {
         let now = Instant::now();
         let streak = if button == self.button
             && position == self.position
-            && now.duration_since(self.time) <= Duration::from_millis(CLICK_INTERVAL)
+            && now.duration_since(self.time) <= interval
         {
             self.streak + 1
         } else {
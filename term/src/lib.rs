@@ -20,6 +20,7 @@ use anyhow::Error;
 use serde::{Deserialize, Serialize};
 use std::ops::{Deref, DerefMut, Range};
 use std::str;
+use std::time::{Duration, SystemTime};
 use termwiz::surface::SequenceNo;
 use wezterm_dynamic::{FromDynamic, ToDynamic};
 
@@ -123,6 +124,33 @@ pub struct SemanticZone {
     pub semantic_type: SemanticType,
 }
 
+/// Describes a command that was run by a shell that uses the OSC 133
+/// semantic prompt escapes to mark where the output of each command
+/// begins, and to report its exit status when it completes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandRecord {
+    /// The row on which the output of the command begins
+    pub output_start_y: StableRowIndex,
+    /// The last row of output produced by the command, or the row on
+    /// which the command was entered if it produced no output.
+    /// None while the command is still running.
+    pub end_y: Option<StableRowIndex>,
+    /// When the command started running
+    pub started: SystemTime,
+    /// When the command finished running
+    pub finished: Option<SystemTime>,
+    /// The exit status of the command, if it was reported by the shell
+    pub exit_status: Option<i32>,
+}
+
+impl CommandRecord {
+    /// Returns how long the command ran for, if it has finished
+    pub fn duration(&self) -> Option<Duration> {
+        let finished = self.finished?;
+        Some(finished.duration_since(self.started).unwrap_or_default())
+    }
+}
+
 pub mod color;
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
 use super::*;
 use crate::terminalstate::performer::Performer;
 use std::sync::Arc;
+use termwiz::escape::osc::Progress;
 use termwiz::escape::parser::Parser;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -59,6 +60,32 @@ pub enum Alert {
     /// When something bumps the seqno in the terminal model and
     /// the terminal is not focused
     OutputSinceFocusLost,
+    /// The application has asked for the window to be resized
+    WindowResizeRequested(WindowResizeRequest),
+    /// The application has reported the progress of a long
+    /// running operation via `OSC 9;4`
+    Progress(Progress),
+}
+
+/// A request from the application to resize the window,
+/// made via `CSI 4 t`, `CSI 8 t` or `CSI 9 t`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+pub enum WindowResizeRequest {
+    /// Resize the pane to the specified number of cells.
+    /// None means that the size in that dimension is unchanged.
+    Cells {
+        width: Option<usize>,
+        height: Option<usize>,
+    },
+    /// Resize the pane to the specified number of pixels.
+    /// None means that the size in that dimension is unchanged.
+    Pixels {
+        width: Option<usize>,
+        height: Option<usize>,
+    },
+    Maximize,
+    RestoreMaximized,
 }
 
 pub trait AlertHandler: Send + Sync {
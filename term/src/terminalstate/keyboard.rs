@@ -18,28 +18,37 @@ impl TerminalState {
         }
     }
 
-    /// Processes a key event generated by the gui/render layer
-    /// that is embedding the Terminal.  This method translates the
-    /// keycode into a sequence of bytes to send to the slave end
-    /// of the pty via the `Write`-able object provided by the caller.
-    fn key_up_down(
-        &mut self,
+    /// Returns the sequence of bytes that key_down (or key_up, if
+    /// is_down is false) would send for key in the current modes
+    pub fn encode_key(
+        &self,
         key: KeyCode,
         mods: KeyModifiers,
         is_down: bool,
-    ) -> anyhow::Result<()> {
-        let encoding = self.effective_keyboard_encoding();
-
-        let to_send = key.encode(
+    ) -> anyhow::Result<String> {
+        key.encode(
             mods,
             KeyCodeEncodeModes {
-                encoding,
+                encoding: self.effective_keyboard_encoding(),
                 newline_mode: self.newline_mode,
                 application_cursor_keys: self.application_cursor_keys,
                 modify_other_keys: self.modify_other_keys,
             },
             is_down,
-        )?;
+        )
+    }
+
+    /// Processes a key event generated by the gui/render layer
+    /// that is embedding the Terminal.  This method translates the
+    /// keycode into a sequence of bytes to send to the slave end
+    /// of the pty via the `Write`-able object provided by the caller.
+    fn key_up_down(
+        &mut self,
+        key: KeyCode,
+        mods: KeyModifiers,
+        is_down: bool,
+    ) -> anyhow::Result<()> {
+        let to_send = self.encode_key(key, mods, is_down)?;
 
         if to_send.is_empty() {
             return Ok(());
@@ -6,11 +6,13 @@ use crate::color::{ColorPalette, RgbColor};
 use crate::config::{BidiMode, NewlineCanon};
 use log::debug;
 use num_traits::ToPrimitive;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
 use std::io::{BufWriter, Write};
 use std::num::NonZeroUsize;
 use std::sync::mpsc::{channel, Sender};
 use std::sync::Arc;
+use std::time::SystemTime;
 use terminfo::{Database, Value};
 use termwiz::cell::UnicodeVersion;
 use termwiz::escape::csi::{
@@ -18,6 +20,7 @@ use termwiz::escape::csi::{
     EraseInLine, Mode, Sgr, TabulationClear, TerminalMode, TerminalModeCode, Window, XtSmGraphics,
     XtSmGraphicsAction, XtSmGraphicsItem, XtSmGraphicsStatus, XtermKeyModifierResource,
 };
+use termwiz::escape::osc::Progress;
 use termwiz::escape::{OneBased, OperatingSystemCommand, CSI};
 use termwiz::image::ImageData;
 use termwiz::input::KeyboardEncoding;
@@ -247,6 +250,11 @@ impl ScreenOrAlt {
     }
 }
 
+/// Bounds the number of commands retained from OSC 133 shell integration
+const MAX_COMMAND_HISTORY: usize = 1000;
+/// The number of palettes that XTPUSHCOLORS can save; matches xterm
+const MAX_PALETTE_STACK: usize = 10;
+
 /// Manages the state for the terminal
 pub struct TerminalState {
     config: Arc<dyn TerminalConfiguration>,
@@ -265,6 +273,9 @@ pub struct TerminalState {
 
     clear_semantic_attribute_on_newline: bool,
 
+    /// The most recent commands reported via OSC 133, oldest first
+    command_history: VecDeque<CommandRecord>,
+
     /// If true, writing a character inserts a new cell
     insert: bool,
 
@@ -277,6 +288,10 @@ pub struct TerminalState {
     /// Reverse video mode
     reverse_video_mode: bool,
 
+    /// Synchronized output (DEC 2026).  The batching itself is performed
+    /// by wezterm's mux; we only track it so that it can be queried.
+    synchronized_output: bool,
+
     /// https://vt100.net/docs/vt510-rm/DECOM.html
     /// When OriginMode is enabled, cursor is constrained to the
     /// scroll region and its position is relative to the scroll
@@ -341,6 +356,9 @@ pub struct TerminalState {
     icon_title: Option<String>,
 
     palette: Option<ColorPalette>,
+    /// Palettes saved by XTPUSHCOLORS.  `None` entries record that
+    /// the configured palette was in effect at the time of the push.
+    palette_stack: Vec<Option<ColorPalette>>,
 
     pixel_width: usize,
     pixel_height: usize,
@@ -385,6 +403,9 @@ pub struct TerminalState {
     lost_focus_alerted_seqno: SequenceNo,
     focused: bool,
 
+    /// The progress most recently reported via OSC 9;4
+    progress: Progress,
+
     /// True if lines should be marked as bidi-enabled, and thus
     /// have the renderer apply the bidi algorithm.
     /// true is equivalent to "implicit" bidi mode as described in
@@ -403,6 +424,12 @@ struct UnicodeVersionStackEntry {
     label: Option<String>,
 }
 
+/// Omitted and zero parameters in window resize requests
+/// leave that dimension unchanged
+fn resize_param(n: Option<i64>) -> Option<usize> {
+    n.and_then(|n| usize::try_from(n).ok()).filter(|&n| n > 0)
+}
+
 fn default_color_map() -> HashMap<u16, RgbColor> {
     let mut color_map = HashMap::new();
     // Match colors to the VT340 color table:
@@ -525,11 +552,13 @@ impl TerminalState {
             left_and_right_margin_mode: false,
             wrap_next: false,
             clear_semantic_attribute_on_newline: false,
+            command_history: VecDeque::new(),
             // We default auto wrap to true even though the default for
             // a dec terminal is false, because it is more useful this way.
             dec_auto_wrap: true,
             reverse_wraparound_mode: false,
             reverse_video_mode: false,
+            synchronized_output: false,
             dec_origin_mode: false,
             insert: false,
             application_cursor_keys: false,
@@ -558,6 +587,7 @@ impl TerminalState {
             title: "wezterm".to_string(),
             icon_title: None,
             palette: None,
+            palette_stack: vec![],
             pixel_height: size.pixel_height,
             pixel_width: size.pixel_width,
             dpi: size.dpi,
@@ -581,6 +611,7 @@ impl TerminalState {
             lost_focus_seqno: seqno,
             lost_focus_alerted_seqno: seqno,
             focused: true,
+            progress: Progress::None,
             bidi_enabled: None,
             bidi_hint: None,
         }
@@ -742,6 +773,53 @@ impl TerminalState {
         self.screen_mut().erase_scrollback();
     }
 
+    /// Scrolls the lines above the current prompt up into the
+    /// scrollback, so that they are retained when the application
+    /// subsequently clears the display.
+    /// Without shell integration to mark up the prompt, everything
+    /// above the cursor line is scrolled into the scrollback.
+    /// Does nothing while the alternate screen is active, as it has
+    /// no scrollback.
+    pub fn scroll_viewport_into_scrollback(&mut self) {
+        if self.screen.is_alt_screen_active() {
+            return;
+        }
+
+        let cursor_row = self.screen().visible_row_to_stable_row(self.cursor.y);
+        let mut top_row = cursor_row;
+        for zone in self.get_semantic_zones().unwrap_or_default().iter().rev() {
+            if zone.start_y > cursor_row {
+                continue;
+            }
+            match zone.semantic_type {
+                SemanticType::Prompt | SemanticType::Input => top_row = zone.start_y,
+                SemanticType::Output => break,
+            }
+        }
+
+        let first_visible_row = self.screen().visible_row_to_stable_row(0);
+        let num_rows = (top_row - first_visible_row).max(0) as usize;
+        if num_rows == 0 {
+            return;
+        }
+
+        // Since we may be called outside of perform_actions,
+        // we need to ensure that we increment the seqno in
+        // order to correctly invalidate the display
+        self.increment_seqno();
+        let seqno = self.seqno;
+        let rows = self.screen().physical_rows as VisibleRowIndex;
+        let bidi_mode = self.get_bidi_mode();
+        self.screen_mut().scroll_up(
+            &(0..rows),
+            num_rows,
+            seqno,
+            CellAttributes::blank(),
+            bidi_mode,
+        );
+        self.cursor.y -= num_rows as VisibleRowIndex;
+    }
+
     /// Returns true if the associated application has enabled any of the
     /// supported mouse reporting modes.
     /// This is useful for the hosting GUI application to decide how best
@@ -798,6 +876,28 @@ impl TerminalState {
         !self.focused && self.seqno > self.lost_focus_seqno
     }
 
+    /// Returns the progress most recently reported by the
+    /// application via OSC 9;4
+    pub fn get_progress(&self) -> Progress {
+        self.progress
+    }
+
+    /// Updates the progress and notifies the alert handler
+    pub fn set_progress(&mut self, progress: Progress) {
+        self.progress = progress;
+        if let Some(handler) = self.alert_handler.as_mut() {
+            handler.alert(Alert::Progress(progress));
+        }
+    }
+
+    /// Clears any progress left behind by a program that has
+    /// since exited without clearing it itself
+    pub fn reset_progress(&mut self) {
+        if self.progress != Progress::None {
+            self.set_progress(Progress::None);
+        }
+    }
+
     pub(crate) fn trigger_unseen_output_notif(&mut self) {
         if self.has_unseen_output() {
             // We want to avoid over-notifying about output events,
@@ -927,6 +1027,12 @@ impl TerminalState {
         }
     }
 
+    fn request_window_resize(&mut self, request: WindowResizeRequest) {
+        if let Some(handler) = self.alert_handler.as_mut() {
+            handler.alert(Alert::WindowResizeRequested(request));
+        }
+    }
+
     /// When dealing with selection, mark a range of lines as dirty
     pub fn make_all_lines_dirty(&mut self) {
         let seqno = self.seqno;
@@ -992,7 +1098,11 @@ impl TerminalState {
                     } as i64
                         - 1,
                 )
-                .max(0),
+                .max(if self.dec_origin_mode {
+                    self.left_and_right_margins.start as i64
+                } else {
+                    0
+                }),
             Position::Absolute(x) => (x + if self.dec_origin_mode {
                 self.left_and_right_margins.start
             } else {
@@ -1022,7 +1132,11 @@ impl TerminalState {
                         self.screen().physical_rows as i64
                     } - 1,
                 )
-                .max(0),
+                .max(if self.dec_origin_mode {
+                    self.top_and_bottom_margins.start
+                } else {
+                    0
+                }),
             Position::Absolute(y) => (y + if self.dec_origin_mode {
                 self.top_and_bottom_margins.start
             } else {
@@ -1274,6 +1388,7 @@ impl TerminalState {
 
                 self.reverse_wraparound_mode = false;
                 self.reverse_video_mode = false;
+                self.synchronized_output = false;
                 self.bidi_enabled.take();
                 self.bidi_hint.take();
 
@@ -1568,19 +1683,19 @@ impl TerminalState {
             Mode::SetDecPrivateMode(DecPrivateMode::Code(
                 DecPrivateModeCode::SynchronizedOutput,
             )) => {
-                // This is handled in wezterm's mux
+                // The batching is handled in wezterm's mux
+                self.synchronized_output = true;
             }
             Mode::ResetDecPrivateMode(DecPrivateMode::Code(
                 DecPrivateModeCode::SynchronizedOutput,
             )) => {
-                // This is handled in wezterm's mux
+                // The batching is handled in wezterm's mux
+                self.synchronized_output = false;
             }
             Mode::QueryDecPrivateMode(DecPrivateMode::Code(
                 DecPrivateModeCode::SynchronizedOutput,
             )) => {
-                // This is handled in wezterm's mux; if we get here, then it isn't enabled,
-                // so we always report false
-                self.decqrm_response(mode, true, false);
+                self.decqrm_response(mode, true, self.synchronized_output);
             }
 
             Mode::SetDecPrivateMode(DecPrivateMode::Code(DecPrivateModeCode::SmoothScroll))
@@ -2015,6 +2130,52 @@ impl TerminalState {
         }
     }
 
+    /// XTPUSHCOLORS.  Without a slot, the palette is pushed onto
+    /// the stack.  With a slot, it is stored in that position and
+    /// the stack is truncated to that depth, as xterm does.
+    fn push_palette(&mut self, slot: Option<i64>) {
+        let palette = self.palette.clone();
+        match slot {
+            None => {
+                if self.palette_stack.len() < MAX_PALETTE_STACK {
+                    self.palette_stack.push(palette);
+                }
+            }
+            Some(slot) if slot >= 1 && slot as usize <= MAX_PALETTE_STACK => {
+                let slot = slot as usize;
+                self.palette_stack.resize(slot, None);
+                self.palette_stack[slot - 1] = palette;
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// XTPOPCOLORS.  Without a slot, the most recently pushed
+    /// palette is restored.  With a slot, the palette in that
+    /// position is restored and the stack is truncated above it.
+    fn pop_palette(&mut self, slot: Option<i64>) {
+        let palette = match slot {
+            None => match self.palette_stack.pop() {
+                Some(palette) => palette,
+                None => return,
+            },
+            Some(slot) if slot >= 1 && slot as usize <= self.palette_stack.len() => {
+                let slot = slot as usize;
+                self.palette_stack.truncate(slot);
+                match self.palette_stack.pop() {
+                    Some(palette) => palette,
+                    None => return,
+                }
+            }
+            Some(_) => return,
+        };
+        if palette != self.palette {
+            self.palette = palette;
+            self.implicit_palette_reset_if_same_as_configured();
+            self.palette_did_change();
+        }
+    }
+
     fn perform_csi_window(&mut self, window: Window) {
         match window {
             Window::ReportTextAreaSizeCells => {
@@ -2060,6 +2221,19 @@ impl TerminalState {
                 }
             }
 
+            Window::ReportIconLabel => {
+                if self.config.enable_title_reporting() {
+                    let label = self.get_title().to_string();
+                    write!(
+                        self.writer,
+                        "{}",
+                        OperatingSystemCommand::SetIconNameSun(label)
+                    )
+                    .ok();
+                    self.writer.flush().ok();
+                }
+            }
+
             Window::ChecksumRectangularArea {
                 request_id,
                 top,
@@ -2077,9 +2251,25 @@ impl TerminalState {
                 write!(self.writer, "\x1bP{}!~{:04x}\x1b\\", request_id, checksum).ok();
                 self.writer.flush().ok();
             }
-            Window::ResizeWindowCells { .. } => {
-                // We don't allow the application to change the window size; that's
-                // up to the user!
+            // Whether the application is allowed to change the window
+            // size is decided by the gui, according to the user's config
+            Window::ResizeWindowCells { width, height } => {
+                self.request_window_resize(WindowResizeRequest::Cells {
+                    width: resize_param(width),
+                    height: resize_param(height),
+                });
+            }
+            Window::ResizeWindowPixels { width, height } => {
+                self.request_window_resize(WindowResizeRequest::Pixels {
+                    width: resize_param(width),
+                    height: resize_param(height),
+                });
+            }
+            Window::MaximizeWindow => {
+                self.request_window_resize(WindowResizeRequest::Maximize);
+            }
+            Window::RestoreMaximizedWindow => {
+                self.request_window_resize(WindowResizeRequest::RestoreMaximized);
             }
             Window::Iconify | Window::DeIconify => {}
             Window::PopIconAndWindowTitle
@@ -2088,6 +2278,8 @@ impl TerminalState {
             | Window::PushIconAndWindowTitle
             | Window::PushIconTitle
             | Window::PushWindowTitle => {}
+            Window::PushColors { slot } => self.push_palette(slot),
+            Window::PopColors { slot } => self.pop_palette(slot),
 
             _ => {
                 if self.config.log_unknown_escape_sequences() {
@@ -2647,9 +2839,11 @@ impl TerminalState {
         match sgr {
             Sgr::Reset => {
                 let link = self.pen.hyperlink().map(Arc::clone);
+                let style_class = self.pen.style_class().map(Arc::clone);
                 let semantic_type = self.pen.semantic_type();
                 self.pen = CellAttributes::default();
                 self.pen.set_hyperlink(link);
+                self.pen.set_style_class(style_class);
                 self.pen.set_semantic_type(semantic_type);
             }
             Sgr::Intensity(intensity) => {
@@ -2744,6 +2938,48 @@ impl TerminalState {
         Ok(zones)
     }
 
+    /// Returns the commands that have been reported by the shell via
+    /// OSC 133, oldest first.  The most recent command may still be
+    /// running.
+    pub fn get_command_history(&self) -> Vec<CommandRecord> {
+        self.command_history.iter().cloned().collect()
+    }
+
+    /// Called when the shell marks the start of the output of a command
+    fn start_command(&mut self) {
+        if self.command_history.len() >= MAX_COMMAND_HISTORY {
+            self.command_history.pop_front();
+        }
+        let output_start_y = self.screen().visible_row_to_stable_row(self.cursor.y);
+        self.command_history.push_back(CommandRecord {
+            output_start_y,
+            end_y: None,
+            started: SystemTime::now(),
+            finished: None,
+            exit_status: None,
+        });
+    }
+
+    /// Called when the shell marks the end of the current command
+    fn finish_command(&mut self, exit_status: Option<i32>) {
+        let cursor_y = self.screen().visible_row_to_stable_row(self.cursor.y);
+        // If the cursor is at the start of a line, then the output
+        // ended on the line above
+        let end_y = if self.cursor.x == 0 {
+            cursor_y - 1
+        } else {
+            cursor_y
+        };
+        if let Some(command) = self.command_history.back_mut() {
+            if command.finished.is_none() {
+                command.end_y.replace(end_y);
+                command.finished.replace(SystemTime::now());
+                command.exit_status = exit_status;
+            }
+        }
+        self.reset_progress();
+    }
+
     #[inline]
     pub fn get_reverse_video(&self) -> bool {
         self.reverse_video_mode
@@ -2757,3 +2993,17 @@ impl TerminalState {
             .unwrap_or(self.keyboard_encoding)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resize_param() {
+        assert_eq!(resize_param(None), None);
+        assert_eq!(resize_param(Some(0)), None);
+        assert_eq!(resize_param(Some(-1)), None);
+        assert_eq!(resize_param(Some(1)), Some(1));
+        assert_eq!(resize_param(Some(80)), Some(80));
+    }
+}
@@ -10,6 +10,7 @@ use ordered_float::NotNan;
 use std::fmt::Write;
 use std::io::Write as _;
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 use termwiz::cell::{grapheme_column_width, Cell, CellAttributes, SemanticType};
 use termwiz::escape::csi::{
     CharacterPath, EraseInDisplay, Keyboard, KittyKeyboardFlags, KittyKeyboardMode,
@@ -177,7 +178,7 @@ impl<'a> Performer<'a> {
 
             let x = self.cursor.x;
             let y = self.cursor.y;
-            let width = self.left_and_right_margins.end;
+            let width = self.right_margin_for_row(y);
 
             let pen = self.pen.clone();
 
@@ -216,6 +217,30 @@ impl<'a> Performer<'a> {
         self.print.clear();
     }
 
+    /// Returns the right margin that applies when printing on row y.
+    /// Double width and double height lines only have room for
+    /// half as many cells as there are columns.
+    fn right_margin_for_row(&mut self, y: VisibleRowIndex) -> usize {
+        let margin = self.left_and_right_margins.end;
+        let screen = self.screen_mut();
+        let idx = screen.phys_row(y);
+        if screen.line_mut(idx).is_single_width() {
+            margin
+        } else {
+            margin.min((screen.physical_cols / 2).max(1))
+        }
+    }
+
+    /// Moves the cursor back inside the displayable portion of
+    /// the current line after it has been made double width
+    fn clamp_cursor_to_double_width_line(&mut self) {
+        let limit = (self.screen().physical_cols / 2).max(1) - 1;
+        if self.cursor.x > limit {
+            self.cursor.x = limit;
+            self.wrap_next = false;
+        }
+    }
+
     /// ConPTY, at the time of writing, does something horrible to rewrite
     /// `ESC k TITLE ST` into something completely different and out-of-order,
     /// and critically, removes the ST.
@@ -616,14 +641,17 @@ impl<'a> Performer<'a> {
             Esc::Code(EscCode::DecDoubleHeightTopHalfLine) => {
                 let idx = self.screen.phys_row(self.cursor.y);
                 self.screen.line_mut(idx).set_double_height_top(seqno);
+                self.clamp_cursor_to_double_width_line();
             }
             Esc::Code(EscCode::DecDoubleHeightBottomHalfLine) => {
                 let idx = self.screen.phys_row(self.cursor.y);
                 self.screen.line_mut(idx).set_double_height_bottom(seqno);
+                self.clamp_cursor_to_double_width_line();
             }
             Esc::Code(EscCode::DecDoubleWidthLine) => {
                 let idx = self.screen.phys_row(self.cursor.y);
                 self.screen.line_mut(idx).set_double_width(seqno);
+                self.clamp_cursor_to_double_width_line();
             }
             Esc::Code(EscCode::DecSingleWidthLine) => {
                 let idx = self.screen.phys_row(self.cursor.y);
@@ -667,6 +695,7 @@ impl<'a> Performer<'a> {
                 self.dec_auto_wrap = true;
                 self.reverse_wraparound_mode = false;
                 self.reverse_video_mode = false;
+                self.synchronized_output = false;
                 self.dec_origin_mode = false;
                 self.use_private_color_registers_for_each_graphic = false;
                 self.color_map = default_color_map();
@@ -690,12 +719,14 @@ impl<'a> Performer<'a> {
                 self.newline_mode = false;
                 self.tabs = TabStop::new(self.screen().physical_cols, 8);
                 self.palette.take();
+                self.palette_stack.clear();
                 self.top_and_bottom_margins = 0..self.screen().physical_rows as VisibleRowIndex;
                 self.left_and_right_margins = 0..self.screen().physical_cols;
                 self.unicode_version = self.config.unicode_version();
                 self.unicode_version_stack.clear();
                 self.suppress_initial_title_change = false;
                 self.accumulating_title.take();
+                self.reset_progress();
 
                 self.screen.full_reset();
                 self.screen.activate_alt_screen(seqno);
@@ -749,6 +780,9 @@ impl<'a> Performer<'a> {
             OperatingSystemCommand::SetHyperlink(link) => {
                 self.set_hyperlink(link);
             }
+            OperatingSystemCommand::SetStyleClass(class) => {
+                self.pen.set_style_class(class.map(Arc::from));
+            }
             OperatingSystemCommand::Unspecified(unspec) => {
                 if self.config.log_unknown_escape_sequences() {
                     let mut output = String::new();
@@ -857,6 +891,7 @@ impl<'a> Performer<'a> {
             OperatingSystemCommand::FinalTermSemanticPrompt(
                 FinalTermSemanticPrompt::MarkEndOfCommandWithFreshLine { .. },
             ) => {
+                self.finish_command(None);
                 self.fresh_line();
                 self.pen.set_semantic_type(SemanticType::Prompt);
             }
@@ -875,11 +910,14 @@ impl<'a> Performer<'a> {
                 FinalTermSemanticPrompt::MarkEndOfInputAndStartOfOutput { .. },
             ) => {
                 self.pen.set_semantic_type(SemanticType::Output);
+                self.start_command();
             }
 
             OperatingSystemCommand::FinalTermSemanticPrompt(
-                FinalTermSemanticPrompt::CommandStatus { .. },
-            ) => {}
+                FinalTermSemanticPrompt::CommandStatus { status, .. },
+            ) => {
+                self.finish_command(Some(status));
+            }
 
             OperatingSystemCommand::SystemNotification(message) => {
                 if let Some(handler) = self.alert_handler.as_mut() {
@@ -892,6 +930,9 @@ impl<'a> Performer<'a> {
                     log::info!("Application sends SystemNotification: {}", message);
                 }
             }
+            OperatingSystemCommand::ConEmuProgress(progress) => {
+                self.set_progress(progress);
+            }
             OperatingSystemCommand::RxvtExtension(params) => {
                 if let Some("notify") = params.get(0).map(String::as_str) {
                     let title = params.get(1);
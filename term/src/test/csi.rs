@@ -401,3 +401,63 @@ fn test_ed_erase_scrollback() {
     term.print("b");
     assert_all_contents(&term, file!(), line!(), &["111", "222", "ab"]);
 }
+
+#[test]
+fn test_push_pop_colors() {
+    let mut term = TestTerm::new(3, 3, 0);
+    let configured = term.palette();
+
+    term.print("\x1b]4;1;rgb:ff/00/00\x1b\\");
+    let red = term.palette();
+    assert_ne!(red, configured);
+
+    term.print("\x1b[#P");
+    term.print("\x1b]4;1;rgb:00/ff/00\x1b\\");
+    assert_ne!(term.palette(), red);
+    term.print("\x1b[#Q");
+    assert_eq!(term.palette(), red);
+
+    // Popping an empty stack leaves the palette alone
+    term.print("\x1b[#Q");
+    assert_eq!(term.palette(), red);
+
+    // Restoring a slot that holds the configured palette
+    term.print("\x1b]104\x1b\\");
+    term.print("\x1b[2#P");
+    term.print("\x1b]4;1;rgb:00/00/ff\x1b\\");
+    term.print("\x1b[2#Q");
+    assert_eq!(term.palette(), configured);
+}
+
+#[test]
+fn test_report_icon_label() {
+    let icon_label = |label: &str| {
+        format!(
+            "{}",
+            OperatingSystemCommand::SetIconNameSun(label.to_string())
+        )
+    };
+
+    // Title reporting is disabled by default
+    let mut term = TestTerm::new(3, 10, 0);
+    term.print("\x1b]1;icon\x1b\\");
+    term.print("\x1b[20t");
+    assert_eq!(term.take_output(), "");
+
+    let mut term = TestTerm::with_config(
+        3,
+        10,
+        TestTermConfig {
+            title_reporting: true,
+            ..Default::default()
+        },
+    );
+    // Without an icon name, the window title is reported
+    term.print("\x1b]2;title\x1b\\");
+    term.print("\x1b[20t");
+    assert_eq!(term.take_output(), icon_label("title"));
+
+    term.print("\x1b]1;icon\x1b\\");
+    term.print("\x1b[20t");
+    assert_eq!(term.take_output(), icon_label("icon"));
+}
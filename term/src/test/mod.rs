@@ -38,13 +38,32 @@ impl Clipboard for LocalClip {
     }
 }
 
+/// Collects the responses that the terminal sends to the application
+#[derive(Clone, Default)]
+struct LocalWriter {
+    buf: Arc<Mutex<Vec<u8>>>,
+}
+
+impl std::io::Write for LocalWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buf.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 struct TestTerm {
     term: Terminal,
+    output: LocalWriter,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct TestTermConfig {
     scrollback: usize,
+    title_reporting: bool,
 }
 impl TerminalConfiguration for TestTermConfig {
     fn scrollback_size(&self) -> usize {
@@ -54,15 +73,31 @@ impl TerminalConfiguration for TestTermConfig {
     fn color_palette(&self) -> ColorPalette {
         ColorPalette::default()
     }
+
+    fn enable_title_reporting(&self) -> bool {
+        self.title_reporting
+    }
 }
 
 impl TestTerm {
     fn new(height: usize, width: usize, scrollback: usize) -> Self {
+        Self::with_config(
+            height,
+            width,
+            TestTermConfig {
+                scrollback,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn with_config(height: usize, width: usize, config: TestTermConfig) -> Self {
         let _ = env_logger::Builder::new()
             .is_test(true)
             .filter_level(log::LevelFilter::Trace)
             .try_init();
 
+        let output = LocalWriter::default();
         let mut term = Terminal::new(
             TerminalSize {
                 rows: height,
@@ -71,21 +106,28 @@ impl TestTerm {
                 pixel_height: height * 16,
                 dpi: 0,
             },
-            Arc::new(TestTermConfig { scrollback }),
+            Arc::new(config),
             "WezTerm",
             "O_o",
-            Box::new(Vec::new()),
+            Box::new(output.clone()),
         );
         let clip: Arc<dyn Clipboard> = Arc::new(LocalClip::new());
         term.set_clipboard(&clip);
 
-        let mut term = Self { term };
+        let mut term = Self { term, output };
 
         term.set_auto_wrap(true);
 
         term
     }
 
+    /// Returns what the terminal has sent to the application since
+    /// this was last called
+    fn take_output(&self) -> String {
+        let buf = std::mem::take(&mut *self.output.buf.lock().unwrap());
+        String::from_utf8(buf).unwrap()
+    }
+
     fn print<B: AsRef<[u8]>>(&mut self, bytes: B) {
         self.term.advance_bytes(bytes);
     }
@@ -346,6 +388,49 @@ fn test_semantic_1539() {
     );
 }
 
+#[test]
+fn test_scroll_viewport_into_scrollback() {
+    let mut term = TestTerm::new(4, 10, 10);
+    term.print("one\r\ntwo\r\nthree");
+    term.scroll_viewport_into_scrollback();
+    assert_visible_contents(&term, file!(), line!(), &["three", "", "", ""]);
+    assert_eq!(term.cursor_pos().y, 0);
+
+    // With shell integration, the whole of the current prompt is kept
+    let mut term = TestTerm::new(4, 10, 10);
+    term.print("one\r\ntwo\r\n\x1b]133;A\x1b\\p1\r\n$ \x1b]133;B\x1b\\ls");
+    term.scroll_viewport_into_scrollback();
+    assert_visible_contents(&term, file!(), line!(), &["p1", "$ ls", "", ""]);
+    assert_eq!(term.cursor_pos().y, 1);
+
+    // Clearing the display no longer discards the earlier output
+    term.print("\x1b[H\x1b[2J");
+    assert_all_contents(&term, file!(), line!(), &["one", "two", "", "", "", ""]);
+}
+
+#[test]
+fn test_progress() {
+    use termwiz::escape::osc::Progress;
+    let mut term = TestTerm::new(4, 10, 0);
+    assert_eq!(term.get_progress(), Progress::None);
+    term.print("\x1b]9;4;1;42\x1b\\");
+    assert_eq!(term.get_progress(), Progress::SetPercentage(42));
+    term.print("\x1b]9;4;3\x1b\\");
+    assert_eq!(term.get_progress(), Progress::Indeterminate);
+    term.print("\x1b]9;4;0\x1b\\");
+    assert_eq!(term.get_progress(), Progress::None);
+
+    // The end of the command clears progress that it left behind
+    term.print("\x1b]9;4;1;42\x1b\\");
+    term.print("\x1b]133;D;0\x1b\\");
+    assert_eq!(term.get_progress(), Progress::None);
+
+    // as does a full reset
+    term.print("\x1b]9;4;2;10\x1b\\");
+    term.print("\x1bc");
+    assert_eq!(term.get_progress(), Progress::None);
+}
+
 #[test]
 fn test_semantic() {
     use termwiz::escape::osc::FinalTermSemanticPrompt;
@@ -479,6 +564,48 @@ fn test_semantic() {
     );
 }
 
+#[test]
+fn test_command_history() {
+    use termwiz::escape::osc::FinalTermSemanticPrompt;
+    let mut term = TestTerm::new(5, 10, 0);
+
+    let start_output = format!(
+        "{}",
+        OperatingSystemCommand::FinalTermSemanticPrompt(
+            FinalTermSemanticPrompt::MarkEndOfInputAndStartOfOutput { aid: None }
+        )
+    );
+    let status = |status| {
+        format!(
+            "{}",
+            OperatingSystemCommand::FinalTermSemanticPrompt(
+                FinalTermSemanticPrompt::CommandStatus { status, aid: None }
+            )
+        )
+    };
+
+    term.print(format!("> ls\r\n{start_output}a\r\nb\r\n"));
+    let history = term.get_command_history();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].output_start_y, 1);
+    assert_eq!(history[0].end_y, None);
+    assert_eq!(history[0].duration(), None);
+
+    term.print(status(0));
+    term.print(format!("> false\r\n{start_output}"));
+    term.print(status(1));
+
+    let history = term.get_command_history();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].end_y, Some(2));
+    assert_eq!(history[0].exit_status, Some(0));
+    assert!(history[0].duration().is_some());
+    // No output, so it ends on the line where it was entered
+    assert_eq!(history[1].output_start_y, 4);
+    assert_eq!(history[1].end_y, Some(3));
+    assert_eq!(history[1].exit_status, Some(1));
+}
+
 #[test]
 fn issue_1161() {
     let mut term = TestTerm::new(1, 5, 0);
@@ -755,6 +882,23 @@ fn test_dec_double_width() {
     assert!(lines[3].is_single_width());
 }
 
+/// Double width lines only have room for half as many cells
+#[test]
+fn test_dec_double_width_wrap() {
+    let mut term = TestTerm::new(3, 10, 0);
+
+    term.print("\u{1b}#6abcdefgh\r\nsingle");
+    assert_visible_contents(&term, file!(), line!(), &["abcde", "fgh", "single"]);
+    assert!(term.screen().visible_lines()[0].is_double_width());
+
+    // Making a line double width pulls the cursor back into
+    // the half of the line that is displayed
+    term.cup(8, 2);
+    term.print("\u{1b}#6X");
+    term.assert_cursor_pos(4, 2, None, None);
+    assert_visible_contents(&term, file!(), line!(), &["abcde", "fgh", "singXe"]);
+}
+
 /// This test skips over an edge case with cursor positioning,
 /// while sizing down, but tries to trip over the same edge
 /// case while sizing back up again
@@ -1144,6 +1288,22 @@ fn test_scroll_margins() {
     assert_all_contents(&term, file!(), line!(), &["1", "2", "z", "a", "W", "", ""]);
 }
 
+#[test]
+fn test_origin_mode_relative_movement() {
+    let mut term = TestTerm::new(6, 10, 0);
+    term.set_scroll_region(1, 4);
+    term.set_mode("?69", true);
+    term.set_left_and_right_margins(2, 7);
+    term.set_mode("?6", true);
+    term.assert_cursor_pos(2, 1, None, None);
+
+    // VPB, HPB, VPR and HPR cannot leave the margins while in origin mode
+    term.print("\x1b[5k\x1b[5j");
+    term.assert_cursor_pos(2, 1, None, None);
+    term.print("\x1b[10e\x1b[10a");
+    term.assert_cursor_pos(7, 4, None, None);
+}
+
 #[test]
 fn test_emoji_with_modifier() {
     let waving_hand = "\u{1f44b}";
@@ -87,6 +87,9 @@ struct FatAttributes {
     underline_color: ColorAttribute,
     foreground: ColorAttribute,
     background: ColorAttribute,
+    /// A user defined class name, which can be used to select
+    /// the font for the cell
+    style_class: Option<Arc<str>>,
 }
 
 impl FatAttributes {
@@ -100,6 +103,7 @@ impl FatAttributes {
         self.underline_color.hash(hasher);
         self.foreground.hash(hasher);
         self.background.hash(hasher);
+        self.style_class.hash(hasher);
     }
 }
 
@@ -383,6 +387,7 @@ impl CellAttributes {
                 underline_color: ColorAttribute::Default,
                 foreground: ColorAttribute::Default,
                 background: ColorAttribute::Default,
+                style_class: None,
             }));
         }
     }
@@ -397,6 +402,7 @@ impl CellAttributes {
                     && fat.underline_color == ColorAttribute::Default
                     && fat.foreground == ColorAttribute::Default
                     && fat.background == ColorAttribute::Default
+                    && fat.style_class.is_none()
             })
             .unwrap_or(false);
         if deallocate {
@@ -415,6 +421,19 @@ impl CellAttributes {
         }
     }
 
+    /// Assign a user defined style class; font_rules can
+    /// select a different font for cells with a given class.
+    pub fn set_style_class(&mut self, class: Option<Arc<str>>) -> &mut Self {
+        if class.is_none() && self.fat.is_none() {
+            self
+        } else {
+            self.allocate_fat_attributes();
+            self.fat.as_mut().unwrap().style_class = class;
+            self.deallocate_fat_attributes_if_none();
+            self
+        }
+    }
+
     /// Assign a single image to a cell.
     pub fn set_image(&mut self, image: Box<ImageCell>) -> &mut Self {
         self.allocate_fat_attributes();
@@ -515,6 +534,10 @@ impl CellAttributes {
         self.fat.as_ref().and_then(|fat| fat.hyperlink.as_ref())
     }
 
+    pub fn style_class(&self) -> Option<&Arc<str>> {
+        self.fat.as_ref().and_then(|fat| fat.style_class.as_ref())
+    }
+
     /// Returns the list of attached images in z-index order.
     /// Returns None if there are no attached images; will
     /// never return Some(vec![]).
@@ -40,6 +40,69 @@ impl CellCluster {
         }
     }
 
+    /// Split this cluster so that the cell at `cell_idx` is in a
+    /// cluster of its own, so that it is shaped separately from its
+    /// neighbors.  The resulting clusters are in visual order.
+    /// If `cell_idx` is not part of this cluster, or is the only
+    /// cell in it, the cluster is returned unchanged.
+    pub fn isolate_cell(self, cell_idx: usize) -> Vec<CellCluster> {
+        if cell_idx < self.first_cell_idx
+            || cell_idx >= self.first_cell_idx + self.width
+            || self.text.chars().nth(1).is_none()
+        {
+            return vec![self];
+        }
+
+        // Before the isolated cell, the cell itself, or after it
+        let region = |idx: usize| idx.cmp(&cell_idx);
+
+        let mut pieces: Vec<CellCluster> = vec![];
+        for (byte_idx, c) in self.text.char_indices() {
+            let this_cell = self.byte_to_cell_idx(byte_idx);
+            let cell_width = self.byte_to_cell_width(byte_idx);
+
+            let start_new = match pieces.last() {
+                Some(last) => region(last.first_cell_idx) != region(this_cell),
+                None => true,
+            };
+            if start_new {
+                pieces.push(CellCluster {
+                    attrs: self.attrs.clone(),
+                    text: String::new(),
+                    width: 0,
+                    presentation: self.presentation,
+                    direction: self.direction,
+                    byte_to_cell_idx: vec![],
+                    byte_to_cell_width: vec![],
+                    first_cell_idx: this_cell,
+                });
+            }
+
+            let piece = pieces.last_mut().expect("just pushed");
+            // Only count the width once per cell; a cell can hold
+            // multiple codepoints
+            if piece.text.is_empty() || piece.byte_to_cell_idx(piece.text.len() - 1) != this_cell {
+                piece.width += cell_width as usize;
+            }
+            if !self.byte_to_cell_idx.is_empty() {
+                for _ in 0..c.len_utf8() {
+                    piece.byte_to_cell_idx.push(this_cell);
+                }
+            }
+            if !self.byte_to_cell_width.is_empty() {
+                for _ in 0..c.len_utf8() {
+                    piece.byte_to_cell_width.push(cell_width);
+                }
+            }
+            piece.text.push(c);
+        }
+
+        if self.direction == Direction::RightToLeft {
+            pieces.reverse();
+        }
+        pieces
+    }
+
     /// Compute the list of CellClusters from a set of visible cells.
     /// The input is typically the result of calling `Line::visible_cells()`.
     pub fn make_cluster<'a>(
@@ -303,3 +366,55 @@ impl CellCluster {
         self.text.push_str(text);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::surface::line::Line;
+    use crate::surface::SEQ_ZERO;
+
+    fn isolate(text: &str, cell_idx: usize) -> Vec<(String, usize, usize)> {
+        let line = Line::from_text(text, &CellAttributes::default(), SEQ_ZERO, None);
+        let clusters = line.cluster(None);
+        assert_eq!(clusters.len(), 1);
+        clusters
+            .into_iter()
+            .next()
+            .unwrap()
+            .isolate_cell(cell_idx)
+            .into_iter()
+            .map(|c| (c.text, c.first_cell_idx, c.width))
+            .collect()
+    }
+
+    #[test]
+    fn isolate_cell() {
+        assert_eq!(
+            isolate("a->b", 2),
+            vec![
+                ("a-".to_string(), 0, 2),
+                (">".to_string(), 2, 1),
+                ("b".to_string(), 3, 1),
+            ]
+        );
+        assert_eq!(
+            isolate("->", 0),
+            vec![("-".to_string(), 0, 1), (">".to_string(), 1, 1)]
+        );
+        assert_eq!(isolate("->", 5), vec![("->".to_string(), 0, 2)]);
+        assert_eq!(isolate("x", 0), vec![("x".to_string(), 0, 1)]);
+    }
+
+    #[test]
+    fn isolate_cell_wide() {
+        // The double width cell occupies cells 1 and 2
+        assert_eq!(
+            isolate("a\u{4E2D}=>", 3),
+            vec![
+                ("a\u{4E2D}".to_string(), 0, 3),
+                ("=".to_string(), 3, 1),
+                (">".to_string(), 4, 1),
+            ]
+        );
+    }
+}
@@ -476,6 +476,16 @@ pub enum Window {
     PopIconAndWindowTitle,
     PopIconTitle,
     PopWindowTitle,
+    /// XTPUSHCOLORS; save the current palette on the color stack,
+    /// optionally into a specific (1-based) slot
+    PushColors {
+        slot: Option<i64>,
+    },
+    /// XTPOPCOLORS; restore the palette from the color stack,
+    /// optionally from a specific (1-based) slot
+    PopColors {
+        slot: Option<i64>,
+    },
     /// DECRQCRA; used by esctest
     ChecksumRectangularArea {
         request_id: i64,
@@ -545,6 +555,8 @@ impl Display for Window {
             Window::PopIconAndWindowTitle => write!(f, "23;0t"),
             Window::PopIconTitle => write!(f, "23;1t"),
             Window::PopWindowTitle => write!(f, "23;2t"),
+            Window::PushColors { slot } => write!(f, "{}#P", numstr_or_empty(slot)),
+            Window::PopColors { slot } => write!(f, "{}#Q", numstr_or_empty(slot)),
             Window::ChecksumRectangularArea {
                 request_id,
                 page_number,
@@ -1769,6 +1781,22 @@ impl<'a> CSIParser<'a> {
             ('m', [CsiParam::P(b'>'), ..]) => self.xterm_key_modifier(params),
 
             ('p', [CsiParam::P(b'!')]) => Ok(CSI::Device(Box::new(Device::SoftReset))),
+            ('P', [CsiParam::P(b'#')]) => {
+                Ok(CSI::Window(Box::new(Window::PushColors { slot: None })))
+            }
+            ('P', [CsiParam::Integer(slot), CsiParam::P(b'#')]) => {
+                Ok(CSI::Window(Box::new(Window::PushColors {
+                    slot: Some(*slot),
+                })))
+            }
+            ('Q', [CsiParam::P(b'#')]) => {
+                Ok(CSI::Window(Box::new(Window::PopColors { slot: None })))
+            }
+            ('Q', [CsiParam::Integer(slot), CsiParam::P(b'#')]) => {
+                Ok(CSI::Window(Box::new(Window::PopColors {
+                    slot: Some(*slot),
+                })))
+            }
             ('u', [CsiParam::P(b'='), CsiParam::Integer(flags)]) => {
                 Ok(CSI::Keyboard(Keyboard::SetKittyState {
                     flags: KittyKeyboardFlags::from_bits_truncate(
@@ -3086,6 +3114,24 @@ mod test {
         assert_eq!(res, vec![CSI::Device(Box::new(Device::SoftReset))],);
     }
 
+    #[test]
+    fn push_pop_colors() {
+        let res: Vec<_> = CSI::parse(&[CsiParam::P(b'#')], false, 'P').collect();
+        assert_eq!(encode(&res), "\x1b[#P");
+        assert_eq!(
+            res,
+            vec![CSI::Window(Box::new(Window::PushColors { slot: None }))]
+        );
+
+        let res: Vec<_> =
+            CSI::parse(&[CsiParam::Integer(3), CsiParam::P(b'#')], false, 'Q').collect();
+        assert_eq!(encode(&res), "\x1b[3#Q");
+        assert_eq!(
+            res,
+            vec![CSI::Window(Box::new(Window::PopColors { slot: Some(3) }))]
+        );
+    }
+
     #[test]
     fn device_attr() {
         let res: Vec<_> = CSI::parse(
@@ -6,6 +6,8 @@ use bitflags::bitflags;
 use num_derive::*;
 use num_traits::FromPrimitive;
 use ordered_float::NotNan;
+#[cfg(feature = "use_serde")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::{Display, Error as FmtError, Formatter, Result as FmtResult};
 use std::str;
@@ -38,6 +40,8 @@ pub enum OperatingSystemCommand {
     QuerySelection(Selection),
     SetSelection(Selection, String),
     SystemNotification(String),
+    /// ConEmu's `OSC 9;4` progress report
+    ConEmuProgress(Progress),
     ITermProprietary(ITermProprietary),
     FinalTermSemanticPrompt(FinalTermSemanticPrompt),
     ChangeColorNumber(Vec<ChangeColorPair>),
@@ -46,10 +50,66 @@ pub enum OperatingSystemCommand {
     CurrentWorkingDirectory(String),
     ResetColors(Vec<u8>),
     RxvtExtension(Vec<String>),
+    SetStyleClass(Option<String>),
 
     Unspecified(Vec<Vec<u8>>),
 }
 
+/// The state of a long running operation, as reported by an
+/// application using ConEmu's `OSC 9;4;st;pr` sequence.
+/// Percentages are clamped to the range 0-100.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+pub enum Progress {
+    /// `st=0`: remove the progress indicator
+    None,
+    /// `st=1`: the operation is this far along
+    SetPercentage(u8),
+    /// `st=2`: the operation has failed at this point
+    SetError(u8),
+    /// `st=3`: the operation is ongoing but its progress is unknown
+    Indeterminate,
+    /// `st=4`: the operation is paused at this point
+    Paused(u8),
+}
+
+impl Progress {
+    fn parse(osc: &[&[u8]]) -> Result<Self> {
+        fn param(osc: &[&[u8]], idx: usize) -> Result<u8> {
+            match osc.get(idx) {
+                None => Ok(0),
+                Some(p) if p.is_empty() => Ok(0),
+                Some(p) => {
+                    let value: u32 = str::from_utf8(p)?.parse()?;
+                    Ok(value.min(100) as u8)
+                }
+            }
+        }
+
+        ensure!(osc.len() <= 4, "wrong param count");
+        Ok(match param(osc, 2)? {
+            0 => Self::None,
+            1 => Self::SetPercentage(param(osc, 3)?),
+            2 => Self::SetError(param(osc, 3)?),
+            3 => Self::Indeterminate,
+            4 => Self::Paused(param(osc, 3)?),
+            st => bail!("invalid progress state {}", st),
+        })
+    }
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::None => write!(f, "9;4;0"),
+            Self::SetPercentage(pr) => write!(f, "9;4;1;{}", pr),
+            Self::SetError(pr) => write!(f, "9;4;2;{}", pr),
+            Self::Indeterminate => write!(f, "9;4;3"),
+            Self::Paused(pr) => write!(f, "9;4;4;{}", pr),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
 #[repr(u8)]
 pub enum DynamicColorNumber {
@@ -311,6 +371,9 @@ impl OperatingSystemCommand {
             )),
             SetHyperlink => Ok(OperatingSystemCommand::SetHyperlink(Hyperlink::parse(osc)?)),
             ManipulateSelectionData => Self::parse_selection(osc),
+            SystemNotification if osc.len() > 2 && osc[1] == b"4" => {
+                Progress::parse(osc).map(OperatingSystemCommand::ConEmuProgress)
+            }
             SystemNotification => single_string!(SystemNotification),
             SetCurrentWorkingDirectory => single_string!(CurrentWorkingDirectory),
             ITermProprietary => {
@@ -323,6 +386,17 @@ impl OperatingSystemCommand {
                 }
                 Ok(OperatingSystemCommand::RxvtExtension(vec))
             }
+            SetStyleClass => {
+                if osc.len() != 2 {
+                    bail!("wrong param count");
+                }
+                let class = String::from_utf8(osc[1].to_vec())?;
+                Ok(OperatingSystemCommand::SetStyleClass(if class.is_empty() {
+                    None
+                } else {
+                    Some(class)
+                }))
+            }
             FinalTermSemanticPrompt => self::FinalTermSemanticPrompt::parse(osc)
                 .map(OperatingSystemCommand::FinalTermSemanticPrompt),
             ChangeColorNumber => Self::parse_change_color_number(osc),
@@ -438,6 +512,9 @@ osc_entries!(
     RxvtProprietary = "777",
     FinalTermSemanticPrompt = "133",
     ITermProprietary = "1337",
+    /// WezTerm specific; assigns a class name to subsequently
+    /// printed text that font_rules can match
+    SetStyleClass = "1339",
     /// Here the "Sun" suffix comes from the table in
     /// <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h3-Miscellaneous>
     /// that lays out various window related escape sequences.
@@ -497,6 +574,8 @@ impl Display for OperatingSystemCommand {
             SetHyperlink(Some(link)) => link.fmt(f)?,
             SetHyperlink(None) => write!(f, "8;;")?,
             RxvtExtension(params) => write!(f, "777;{}", params.join(";"))?,
+            SetStyleClass(Some(class)) => write!(f, "1339;{}", class)?,
+            SetStyleClass(None) => write!(f, "1339;")?,
             Unspecified(v) => {
                 for (idx, item) in v.iter().enumerate() {
                     if idx > 0 {
@@ -509,6 +588,7 @@ impl Display for OperatingSystemCommand {
             QuerySelection(s) => write!(f, "52;{};?", s)?,
             SetSelection(s, val) => write!(f, "52;{};{}", s, base64_encode(val))?,
             SystemNotification(s) => write!(f, "9;{}", s)?,
+            ConEmuProgress(p) => p.fmt(f)?,
             ITermProprietary(i) => i.fmt(f)?,
             FinalTermSemanticPrompt(i) => i.fmt(f)?,
             ResetColors(colors) => {
@@ -1365,6 +1445,36 @@ mod test {
         );
     }
 
+    #[test]
+    fn conemu_progress() {
+        assert_eq!(
+            parse(&["9", "4", "1", "42"], "\x1b]9;4;1;42\x1b\\"),
+            OperatingSystemCommand::ConEmuProgress(Progress::SetPercentage(42))
+        );
+        assert_eq!(
+            parse(&["9", "4", "2", "250"], "\x1b]9;4;2;100\x1b\\"),
+            OperatingSystemCommand::ConEmuProgress(Progress::SetError(100))
+        );
+        assert_eq!(
+            parse(&["9", "4", "3"], "\x1b]9;4;3\x1b\\"),
+            OperatingSystemCommand::ConEmuProgress(Progress::Indeterminate)
+        );
+        assert_eq!(
+            parse(&["9", "4", "4", ""], "\x1b]9;4;4;0\x1b\\"),
+            OperatingSystemCommand::ConEmuProgress(Progress::Paused(0))
+        );
+        assert_eq!(
+            parse(&["9", "4", "0"], "\x1b]9;4;0\x1b\\"),
+            OperatingSystemCommand::ConEmuProgress(Progress::None)
+        );
+
+        // A plain notification that happens to start with 4
+        assert_eq!(
+            parse(&["9", "4"], "\x1b]9;4\x1b\\"),
+            OperatingSystemCommand::SystemNotification("4".into())
+        );
+    }
+
     #[test]
     fn hyperlink() {
         assert_eq!(
@@ -1611,6 +1721,18 @@ mod test {
         )
     }
 
+    #[test]
+    fn style_class() {
+        assert_eq!(
+            parse(&["1339", "journal"], "\x1b]1339;journal\x1b\\"),
+            OperatingSystemCommand::SetStyleClass(Some("journal".into()))
+        );
+        assert_eq!(
+            parse(&["1339", ""], "\x1b]1339;\x1b\\"),
+            OperatingSystemCommand::SetStyleClass(None)
+        );
+    }
+
     #[test]
     fn iterm() {
         assert_eq!(
@@ -1383,10 +1383,16 @@ impl Client {
     rpc!(set_window_title, WindowTitleChanged, UnitResponse);
     rpc!(rename_workspace, RenameWorkspace, UnitResponse);
     rpc!(erase_scrollback, EraseScrollbackRequest, UnitResponse);
+    rpc!(
+        scroll_viewport_into_scrollback,
+        ScrollViewportIntoScrollback,
+        UnitResponse
+    );
     rpc!(
         get_pane_direction,
         GetPaneDirection,
         GetPaneDirectionResponse
     );
     rpc!(adjust_pane_size, AdjustPaneSize, UnitResponse);
+    rpc!(record_pane, RecordPane, RecordPaneResponse);
 }
@@ -4,7 +4,7 @@ use anyhow::{anyhow, bail};
 use async_trait::async_trait;
 use codec::{ListPanesResponse, SpawnV2, SplitPane};
 use config::keyassignment::SpawnTabDomain;
-use config::{SshDomain, TlsDomainClient, UnixDomain};
+use config::{configuration, SshDomain, TlsDomainClient, UnixDomain};
 use mux::connui::{ConnectionUI, ConnectionUIParams};
 use mux::domain::{alloc_domain_id, Domain, DomainId, DomainState, SplitSource};
 use mux::pane::{Pane, PaneId};
@@ -416,6 +416,17 @@ impl ClientDomain {
         self.config.connect_automatically()
     }
 
+    /// Returns true if this domain connects to a mux server
+    /// on another host.  Unix domains are local unless they
+    /// use a proxy_command, which is typically used to reach
+    /// the socket of a server on another host via ssh.
+    pub fn is_remote(&self) -> bool {
+        match &self.config {
+            ClientDomainConfig::Unix(unix) => unix.proxy_command.is_some(),
+            _ => true,
+        }
+    }
+
     pub fn perform_detach(&self) {
         log::info!("detached domain {}", self.local_domain_id);
         self.inner.lock().unwrap().take();
@@ -747,6 +758,11 @@ impl Domain for ClientDomain {
         self.config.name()
     }
 
+    fn home_dir_cwd(&self) -> Option<String> {
+        // The server resolves this in the domain that it spawns into
+        Some("~".to_string())
+    }
+
     async fn domain_label(&self) -> String {
         self.label.to_string()
     }
@@ -826,6 +842,8 @@ impl Domain for ClientDomain {
             .ok_or_else(|| anyhow!("domain is not attached"))?;
 
         let workspace = Mux::get().active_workspace();
+        // Resolve this here, as the server only knows its own domains
+        let command = configuration().resolve_domain_default_prog(self.domain_name(), command);
 
         let result = inner
             .client
@@ -836,6 +854,7 @@ impl Domain for ClientDomain {
                 command,
                 command_dir,
                 workspace,
+                class: None,
             })
             .await?;
 
@@ -887,7 +906,11 @@ impl Domain for ClientDomain {
             SplitSource::Spawn {
                 command,
                 command_dir,
-            } => (command, command_dir, None),
+            } => (
+                configuration().resolve_domain_default_prog(self.domain_name(), command),
+                command_dir,
+                None,
+            ),
             SplitSource::MovePane(move_pane_id) => (None, None, Some(move_pane_id)),
         };
 
@@ -21,6 +21,7 @@ use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap};
 use std::ops::Range;
 use std::sync::Arc;
+use termwiz::escape::osc::Progress;
 use termwiz::input::KeyEvent;
 use termwiz::surface::SequenceNo;
 use url::Url;
@@ -44,10 +45,12 @@ pub struct ClientPane {
     mouse: Arc<Mutex<MouseState>>,
     clipboard: Mutex<Option<Arc<dyn Clipboard>>>,
     mouse_grabbed: Mutex<bool>,
+    alt_screen_active: Mutex<bool>,
     ignore_next_kill: Mutex<bool>,
     user_vars: Mutex<HashMap<String, String>>,
     config: Mutex<Option<Arc<dyn TerminalConfiguration>>>,
     unseen_output: Mutex<bool>,
+    progress: Mutex<Progress>,
 }
 
 impl ClientPane {
@@ -125,8 +128,10 @@ impl ClientPane {
             palette: Mutex::new(palette),
             clipboard: Mutex::new(None),
             mouse_grabbed: Mutex::new(false),
+            alt_screen_active: Mutex::new(false),
             ignore_next_kill: Mutex::new(false),
             unseen_output: Mutex::new(false),
+            progress: Mutex::new(Progress::None),
             user_vars: Mutex::new(HashMap::new()),
             config: Mutex::new(None),
         }
@@ -136,6 +141,7 @@ impl ClientPane {
         match pdu {
             Pdu::GetPaneRenderChangesResponse(mut delta) => {
                 *self.mouse_grabbed.lock() = delta.mouse_grabbed;
+                *self.alt_screen_active.lock() = delta.alt_screen_active;
 
                 let bonus_lines = std::mem::take(&mut delta.bonus_lines);
                 let client = { Arc::clone(&self.renderable.lock().inner.borrow().client) };
@@ -183,6 +189,9 @@ impl ClientPane {
                     Alert::SetUserVar { name, value } => {
                         self.user_vars.lock().insert(name.clone(), value.clone());
                     }
+                    Alert::Progress(progress) => {
+                        *self.progress.lock() = *progress;
+                    }
                     Alert::OutputSinceFocusLost => {
                         *self.unseen_output.lock() = true;
                         mux.notify(MuxNotification::Alert {
@@ -319,11 +328,13 @@ impl Pane for ClientPane {
     fn send_paste(&self, text: &str) -> anyhow::Result<()> {
         let client = Arc::clone(&self.client);
         let remote_pane_id = self.remote_pane_id;
-        self.renderable
-            .lock()
-            .inner
-            .borrow_mut()
-            .predict_from_paste(text);
+        if !self.is_alt_screen_active() {
+            self.renderable
+                .lock()
+                .inner
+                .borrow_mut()
+                .predict_from_paste(text);
+        }
 
         let data = text.to_owned();
         promise::spawn::spawn(async move {
@@ -441,7 +452,12 @@ impl Pane for ClientPane {
             let mut inner = renderable.inner.borrow_mut();
             inner.input_serial = InputSerial::now();
             input_serial = inner.input_serial;
-            inner.predict_from_key_event(key, mods);
+            // Applications that use the alternate screen are typically
+            // full screen editors and the like, where keys are commands
+            // rather than text to be echoed
+            if !self.is_alt_screen_active() {
+                inner.predict_from_key_event(key, mods);
+            }
         }
         let client = Arc::clone(&self.client);
         let remote_pane_id = self.remote_pane_id;
@@ -533,8 +549,7 @@ impl Pane for ClientPane {
     }
 
     fn is_alt_screen_active(&self) -> bool {
-        // FIXME: retrieve this from the remote
-        false
+        *self.alt_screen_active.lock()
     }
 
     fn get_current_working_dir(&self, _policy: CachePolicy) -> Option<Url> {
@@ -563,6 +578,20 @@ impl Pane for ClientPane {
         .detach();
     }
 
+    fn scroll_viewport_into_scrollback(&self) {
+        let client = Arc::clone(&self.client);
+        let remote_pane_id = self.remote_pane_id;
+        promise::spawn::spawn(async move {
+            client
+                .client
+                .scroll_viewport_into_scrollback(ScrollViewportIntoScrollback {
+                    pane_id: remote_pane_id,
+                })
+                .await
+        })
+        .detach();
+    }
+
     fn advise_focus(&self) {
         let mut focused_pane = self.client.focused_remote_pane_id.lock().unwrap();
         if *focused_pane != Some(self.remote_pane_id) {
@@ -585,6 +614,10 @@ impl Pane for ClientPane {
         *self.unseen_output.lock()
     }
 
+    fn get_progress(&self) -> Progress {
+        *self.progress.lock()
+    }
+
     fn can_close_without_prompting(&self, reason: CloseReason) -> bool {
         match reason {
             CloseReason::Window => true,
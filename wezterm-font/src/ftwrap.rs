@@ -4,7 +4,7 @@ use crate::locator::{FontDataHandle, FontDataSource};
 use crate::parser::ParsedFont;
 use crate::rasterizer::colr::DrawOp;
 use anyhow::{anyhow, Context};
-use config::{configuration, FreeTypeLoadFlags, FreeTypeLoadTarget};
+use config::{configuration, FreeTypeLcdFilter, FreeTypeLoadFlags, FreeTypeLoadTarget};
 pub use freetype::*;
 use memmap2::{Mmap, MmapOptions};
 use rangeset::RangeSet;
@@ -1190,7 +1190,13 @@ impl Library {
         // own copy of freetype, it is likewise disabled by default for
         // us too.  As a result, this call will generally fail.
         // Freetype is still able to render a decent result without it!
-        lib.set_lcd_filter(FT_LcdFilter::FT_LCD_FILTER_DEFAULT).ok();
+        let filter = match config.freetype_lcd_filter {
+            FreeTypeLcdFilter::Default => FT_LcdFilter::FT_LCD_FILTER_DEFAULT,
+            FreeTypeLcdFilter::Light => FT_LcdFilter::FT_LCD_FILTER_LIGHT,
+            FreeTypeLcdFilter::Legacy => FT_LcdFilter::FT_LCD_FILTER_LEGACY,
+            FreeTypeLcdFilter::None => FT_LcdFilter::FT_LCD_FILTER_NONE,
+        };
+        lib.set_lcd_filter(filter).ok();
 
         Ok(lib)
     }
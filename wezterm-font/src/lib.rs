@@ -48,8 +48,21 @@ pub fn alloc_font_id() -> LoadedFontId {
     FONT_ID.fetch_add(1, ::std::sync::atomic::Ordering::Relaxed)
 }
 
+type MissingGlyphCallback = fn(Vec<char>);
+
 lazy_static::lazy_static! {
     static ref LAST_WARNING: Mutex<Option<(Instant, usize)>> = Mutex::new(None);
+    /// Codepoints for which no font could be found that have
+    /// already been reported
+    static ref REPORTED_MISSING: Mutex<HashSet<char>> = Mutex::new(HashSet::new());
+    static ref MISSING_GLYPH_CALLBACK: Mutex<Option<MissingGlyphCallback>> = Mutex::new(None);
+}
+
+/// Assigns a function that will be called with the codepoints for
+/// which no fallback font could be found.  Each codepoint is passed
+/// to it only once.
+pub fn assign_missing_glyph_callback(cb: MissingGlyphCallback) {
+    MISSING_GLYPH_CALLBACK.lock().unwrap().replace(cb);
 }
 
 pub struct LoadedFont {
@@ -67,6 +80,7 @@ pub struct LoadedFont {
     /// Glyphs for which no font was found and for which we should
     /// stop searching
     tried_glyphs: RefCell<HashSet<char>>,
+    shaper_spec: RefCell<Option<Arc<ShaperSpec>>>,
 }
 
 impl std::fmt::Debug for LoadedFont {
@@ -110,6 +124,7 @@ impl LoadedFont {
             }
         }
         if loaded {
+            self.shaper_spec.borrow_mut().take();
             if let Some(font_config) = self.font_config.upgrade() {
                 *self.shaper.borrow_mut() =
                     new_shaper(&*font_config.config.borrow(), &self.handles.borrow())?;
@@ -291,6 +306,72 @@ impl LoadedFont {
     pub fn clone_handles(&self) -> Vec<ParsedFont> {
         self.handles.borrow().clone()
     }
+
+    /// Returns a description of this font that can be used to shape
+    /// text on another thread.  The same instance is returned until
+    /// the set of fallback fonts changes.
+    pub fn shaper_spec(&self) -> Option<Arc<ShaperSpec>> {
+        let mut spec = self.shaper_spec.borrow_mut();
+        if spec.is_none() {
+            let font_config = self.font_config.upgrade()?;
+            let config = font_config.config.borrow().clone();
+            spec.replace(Arc::new(ShaperSpec {
+                font_id: self.id,
+                config,
+                handles: self.clone_handles(),
+                font_size: self.font_size,
+                dpi: self.dpi,
+            }));
+        }
+        spec.clone()
+    }
+}
+
+/// Describes the fonts used by a LoadedFont in enough detail
+/// to construct an equivalent shaper on a different thread.
+pub struct ShaperSpec {
+    font_id: LoadedFontId,
+    config: ConfigHandle,
+    handles: Vec<ParsedFont>,
+    font_size: f64,
+    dpi: u32,
+}
+
+impl ShaperSpec {
+    pub fn font_id(&self) -> LoadedFontId {
+        self.font_id
+    }
+
+    pub fn new_shaper(&self) -> anyhow::Result<Box<dyn FontShaper>> {
+        new_shaper(&self.config, &self.handles)
+    }
+
+    /// Shape text using a shaper that was produced by `new_shaper`.
+    /// Returns the shaped glyphs along with the list of codepoints
+    /// for which no glyph could be found.  Unlike `LoadedFont::shape`,
+    /// no attempt is made to resolve fallback fonts for those codepoints.
+    pub fn shape(
+        &self,
+        shaper: &dyn FontShaper,
+        text: &str,
+        presentation: Option<Presentation>,
+        direction: Direction,
+        presentation_width: Option<&PresentationWidth>,
+    ) -> anyhow::Result<(Vec<GlyphInfo>, Vec<char>)> {
+        let mut no_glyphs = vec![];
+        let glyphs = shaper.shape(
+            text,
+            self.font_size,
+            self.dpi,
+            &mut no_glyphs,
+            presentation,
+            direction,
+            None,
+            presentation_width,
+        )?;
+        no_glyphs.retain(|&c| c != '\u{FE0F}' && c != '\u{FE0E}');
+        Ok((glyphs, no_glyphs))
+    }
 }
 
 struct FallbackResolveInfo {
@@ -399,6 +480,26 @@ impl FallbackResolveInfo {
                 .map(|c| std::char::from_u32(c).unwrap_or(' '))
                 .collect::<String>();
 
+            let newly_missing: Vec<char> = {
+                let mut reported = REPORTED_MISSING.lock().unwrap();
+                fallback_str
+                    .chars()
+                    .filter(|&c| reported.insert(c))
+                    .collect()
+            };
+            for c in &newly_missing {
+                log::info!(
+                    "No fonts contain a glyph for {} (U+{:04X})",
+                    c.escape_unicode(),
+                    *c as u32
+                );
+            }
+            if !newly_missing.is_empty() {
+                if let Some(cb) = *MISSING_GLYPH_CALLBACK.lock().unwrap() {
+                    cb(newly_missing);
+                }
+            }
+
             let current_gen = self.config.generation();
             let show_warning = self.config.warn_about_missing_glyphs
                 && LAST_WARNING
@@ -624,6 +725,7 @@ impl FontConfigInner {
             text_style: text_style.clone(),
             id: alloc_font_id(),
             tried_glyphs: RefCell::new(HashSet::new()),
+            shaper_spec: RefCell::new(None),
             pixel_geometry: config.display_pixel_geometry,
         });
 
@@ -924,6 +1026,7 @@ impl FontConfigInner {
             text_style: style.clone(),
             id: alloc_font_id(),
             tried_glyphs: RefCell::new(HashSet::new()),
+            shaper_spec: RefCell::new(None),
             pixel_geometry: config.display_pixel_geometry,
         });
 
@@ -1025,6 +1128,12 @@ impl FontConfigInner {
             attr_match!(reverse, &rule);
             attr_match!(strikethrough, &rule);
             attr_match!(invisible, &rule);
+            if let Some(class) = &rule.style_class {
+                if attrs.style_class().map(|c| &**c) != Some(class.as_str()) {
+                    // Rule does not match
+                    continue;
+                }
+            }
 
             // If we get here, then none of the rules didn't match,
             // so we therefore assume that it did match overall.
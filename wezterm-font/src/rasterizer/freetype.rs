@@ -35,6 +35,29 @@ pub struct FreeTypeRasterizer {
     display_pixel_geometry: DisplayPixelGeometry,
     scale: f64,
     hb_raster: HarfbuzzRasterizer,
+    /// Maps the coverage produced by freetype to the adjusted
+    /// value, when freetype_gamma or freetype_contrast are set
+    coverage_table: Option<Box<[u8; 256]>>,
+}
+
+/// Builds a table that applies gamma and contrast adjustments
+/// to linear coverage values.  Returns None when neither would
+/// change the coverage.
+fn compute_coverage_table(gamma: f32, contrast: f32) -> Option<Box<[u8; 256]>> {
+    if gamma == 1.0 && contrast == 1.0 {
+        return None;
+    }
+    let gamma = gamma.max(0.01);
+    let contrast = contrast.max(0.);
+    let mut table = Box::new([0u8; 256]);
+    for (idx, value) in table.iter_mut().enumerate() {
+        let coverage = (idx as f32 / 255.).powf(1. / gamma);
+        let coverage = ((coverage - 0.5) * contrast + 0.5).max(0.).min(1.);
+        *value = (coverage * 255.).round() as u8;
+    }
+    // Don't let the adjustments turn blank pixels into visible ones
+    table[0] = 0;
+    Some(table)
 }
 
 impl FontRasterizer for FreeTypeRasterizer {
@@ -168,6 +191,13 @@ impl FreeTypeRasterizer {
         }
     }
 
+    fn adjust_coverage(&self, coverage: u8) -> u8 {
+        match &self.coverage_table {
+            Some(table) => table[coverage as usize],
+            None => coverage,
+        }
+    }
+
     fn rasterize_gray(
         &self,
         pitch: usize,
@@ -183,7 +213,7 @@ impl FreeTypeRasterizer {
             let src_offset = y * pitch;
             let dest_offset = y * width * 4;
             for x in 0..width {
-                let linear_gray = data[src_offset + x];
+                let linear_gray = self.adjust_coverage(data[src_offset + x]);
                 let gray = linear_u8_to_srgb8(linear_gray);
 
                 // Texture is SRGBA, which in OpenGL means
@@ -223,9 +253,9 @@ impl FreeTypeRasterizer {
             let src_offset = y * pitch as usize;
             let dest_offset = y * width * 4;
             for x in 0..width {
-                let red = data[src_offset + (x * 3)];
-                let green = data[src_offset + (x * 3) + 1];
-                let blue = data[src_offset + (x * 3) + 2];
+                let red = self.adjust_coverage(data[src_offset + (x * 3)]);
+                let green = self.adjust_coverage(data[src_offset + (x * 3) + 1]);
+                let blue = self.adjust_coverage(data[src_offset + (x * 3) + 2]);
 
                 let linear_alpha = red.max(green).max(blue);
 
@@ -276,9 +306,9 @@ impl FreeTypeRasterizer {
             let src_offset = y * pitch * 3;
             let dest_offset = y * width * 4;
             for x in 0..width {
-                let red = data[src_offset + x];
-                let green = data[src_offset + x + pitch];
-                let blue = data[src_offset + x + 2 * pitch];
+                let red = self.adjust_coverage(data[src_offset + x]);
+                let green = self.adjust_coverage(data[src_offset + x + pitch]);
+                let blue = self.adjust_coverage(data[src_offset + x + 2 * pitch]);
 
                 let linear_alpha = red.max(green).max(blue);
 
@@ -382,6 +412,7 @@ impl FreeTypeRasterizer {
         display_pixel_geometry: DisplayPixelGeometry,
     ) -> anyhow::Result<Self> {
         log::trace!("Rasterizier wants {:?}", parsed);
+        let config = config::configuration();
         let lib = ftwrap::Library::new()?;
         let mut face = lib.face_from_locator(&parsed.handle)?;
         let has_color = unsafe {
@@ -408,6 +439,7 @@ impl FreeTypeRasterizer {
             display_pixel_geometry,
             scale: parsed.scale.unwrap_or(1.),
             hb_raster: HarfbuzzRasterizer::from_locator(&parsed)?,
+            coverage_table: compute_coverage_table(config.freetype_gamma, config.freetype_contrast),
         })
     }
 
@@ -908,3 +940,27 @@ fn record_to_cairo_surface(
 
     Ok((surface, has_color))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn coverage_table() {
+        assert!(compute_coverage_table(1.0, 1.0).is_none());
+
+        let heavier = compute_coverage_table(1.5, 1.0).unwrap();
+        assert_eq!(heavier[0], 0);
+        assert_eq!(heavier[255], 255);
+        assert!(heavier[128] > 128);
+
+        let lighter = compute_coverage_table(0.7, 1.0).unwrap();
+        assert!(lighter[128] < 128);
+
+        let sharper = compute_coverage_table(1.0, 2.0).unwrap();
+        assert_eq!(sharper[0], 0);
+        assert!(sharper[64] < 64);
+        assert!(sharper[192] > 192);
+        assert_eq!(sharper[255], 255);
+    }
+}
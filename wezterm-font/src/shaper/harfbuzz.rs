@@ -625,6 +625,15 @@ impl FontShaper for HarfbuzzShaper {
 
         let selected_size = pair.face.set_font_size(size * scale, dpi)?;
         let y_scale = unsafe { (*(*pair.face.face).size).metrics.y_scale.to_num::<f64>() };
+        let (strikethrough_thickness, strikethrough_position) = match pair.face.get_os2_table() {
+            Some(os2) if os2.yStrikeoutSize > 0 => (
+                Some(PixelLength::new(os2.yStrikeoutSize as f64 * y_scale / 64.)),
+                Some(PixelLength::new(
+                    os2.yStrikeoutPosition as f64 * y_scale / 64.,
+                )),
+            ),
+            _ => (None, None),
+        };
         let mut metrics = FontMetrics {
             cell_height: PixelLength::new(selected_size.height),
             cell_width: PixelLength::new(selected_size.width),
@@ -639,6 +648,8 @@ impl FontShaper for HarfbuzzShaper {
             underline_position: PixelLength::new(
                 unsafe { (*pair.face.face).underline_position as f64 } * y_scale / 64.,
             ),
+            strikethrough_thickness,
+            strikethrough_position,
             cap_height_ratio: selected_size.cap_height_to_height_ratio,
             cap_height: selected_size.cap_height.map(PixelLength::new),
             is_scaled: selected_size.is_scaled,
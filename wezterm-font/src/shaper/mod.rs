@@ -63,6 +63,15 @@ pub struct FontMetrics {
     /// values are below the descender.
     pub underline_position: PixelLength,
 
+    /// Vertical size of strikethrough in pixels, if specified
+    /// by the OS/2 table of the font
+    pub strikethrough_thickness: Option<PixelLength>,
+
+    /// Position of the top of the strikethrough relative to the
+    /// baseline, if specified by the OS/2 table of the font.
+    /// Positive values are above the baseline.
+    pub strikethrough_position: Option<PixelLength>,
+
     /// Fraction of the EM square occupied by the cap height
     pub cap_height_ratio: Option<f64>,
     pub cap_height: Option<PixelLength>,
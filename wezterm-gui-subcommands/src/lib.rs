@@ -1,6 +1,6 @@
 use clap::builder::ValueParser;
 use clap::{Parser, ValueHint};
-use config::{GuiPosition, SshParameters};
+use config::{GuiPosition, GuiSize, SshParameters};
 use std::ffi::OsString;
 use std::path::PathBuf;
 
@@ -88,6 +88,13 @@ pub struct StartCommand {
     #[arg(long, verbatim_doc_comment)]
     pub position: Option<GuiPosition>,
 
+    /// Override the size of the initial window launched by this process,
+    /// expressed as COLSxROWS; for example: --size 120x40.
+    /// This takes precedence over the initial_cols and initial_rows
+    /// settings in the configuration.
+    #[arg(long, value_name = "COLSxROWS")]
+    pub size: Option<GuiSize>,
+
     /// Name of the multiplexer domain section from the configuration
     /// to which you'd like to connect. If omitted, the default domain
     /// will be used.
@@ -204,8 +211,11 @@ pub struct SerialCommand {
 #[command(trailing_var_arg = true)]
 pub struct ConnectCommand {
     /// Name of the multiplexer domain section from the configuration
-    /// to which you'd like to connect
-    pub domain_name: String,
+    /// to which you'd like to connect.
+    /// If omitted, a window is opened that lists the configured
+    /// domains, along with whether they are currently attached,
+    /// so that you can pick the domain to connect to.
+    pub domain_name: Option<String>,
 
     /// When spawning into an existing GUI instance, spawn a new
     /// tab into the active window rather than spawn a new window.
@@ -271,3 +281,39 @@ pub struct ShowKeysCommand {
     #[arg(long)]
     pub key_table: Option<String>,
 }
+
+#[derive(Debug, Parser, Clone)]
+pub struct CheckConfigCommand {
+    /// Treat warnings, such as unknown configuration options,
+    /// as errors
+    #[arg(long)]
+    pub strict: bool,
+}
+
+#[derive(Debug, Parser, Clone)]
+pub struct ScreenshotCommand {
+    /// Specify the target pane.
+    /// The default is to use the current pane based on the
+    /// environment variable WEZTERM_PANE.
+    #[arg(long)]
+    pub pane_id: Option<usize>,
+
+    /// Where to save the PNG image.
+    /// The default is wezterm-screenshot-PANE_ID.png in the
+    /// current directory.
+    #[arg(long, short, value_hint=ValueHint::FilePath)]
+    pub output: Option<PathBuf>,
+
+    /// Prefer connecting to a background mux server.
+    /// The default is to prefer connecting to a running
+    /// wezterm gui instance
+    #[arg(long = "prefer-mux")]
+    pub prefer_mux: bool,
+
+    /// When connecting to a gui instance, if you started the
+    /// gui with `--class SOMETHING`, you should also pass
+    /// that same value here in order for the client to find
+    /// the correct gui instance.
+    #[arg(long = "class")]
+    pub class: Option<String>,
+}
@@ -223,6 +223,20 @@ impl CommandDef {
             });
         }
 
+        for layout in &config.pane_layouts {
+            let action = KeyAssignment::ApplyPaneLayout(layout.name.clone());
+            if let Some(cmd) = derive_command_from_key_assignment(&action) {
+                result.push(ExpandedCommand {
+                    brief: cmd.brief.into(),
+                    doc: cmd.doc.into(),
+                    keys: vec![],
+                    action,
+                    menubar: cmd.menubar,
+                    icon: cmd.icon.map(Cow::Borrowed),
+                });
+            }
+        }
+
         // Generate some stuff based on the mux state
         if let Some(mux) = Mux::try_get() {
             let mut domains = mux.iter_domains();
@@ -761,6 +775,17 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["Shell"],
             icon: Some("cod_empty_window"),
         },
+        ViewScrollback(args) => CommandDef {
+            brief: match args.split {
+                Some(direction) => format!("View scrollback in a split {direction:?}").into(),
+                None => "View scrollback in a new tab".into(),
+            },
+            doc: "Opens the scrollback of the current pane in an external viewer".into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Edit"],
+            icon: Some("md_text_box_search_outline"),
+        },
         ClearScrollback(ScrollbackEraseMode::ScrollbackOnly) => CommandDef {
             brief: "Clear scrollback".into(),
             doc: "Clears any text that has scrolled out of the \
@@ -779,6 +804,16 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["Edit"],
             icon: Some("cod_clear_all"),
         },
+        ClearScreenPreservingScrollback => CommandDef {
+            brief: "Clear the screen, keeping its content in the scrollback".into(),
+            doc: "Moves the content above the current prompt into the \
+              scrollback and then sends CTRL-L to the application"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Edit"],
+            icon: Some("cod_clear_all"),
+        },
         Search(Pattern::CurrentSelectionOrEmptyString) => CommandDef {
             brief: "Search pane output".into(),
             doc: "Enters the search mode UI for the current pane".into(),
@@ -795,6 +830,14 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &[],
             icon: Some("oct_search"),
         },
+        SearchAllTabs(_) => CommandDef {
+            brief: "Search all tabs".into(),
+            doc: "Searches the output of all of the panes in all of the tabs in the window".into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["Edit"],
+            icon: Some("oct_search"),
+        },
         ShowDebugOverlay => CommandDef {
             brief: "Show debug overlay".into(),
             doc: "Activates the debug overlay and Lua REPL".into(),
@@ -835,6 +878,16 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &[],
             icon: None,
         },
+        LinkHints => CommandDef {
+            brief: "Enter link hint mode".into(),
+            doc: "Labels the hyperlinks in the viewport of the current pane \
+                  so that one of them can be opened using the keyboard"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Edit"],
+            icon: None,
+        },
         CharSelect(_) => CommandDef {
             brief: "Enter Emoji / Character selection mode".into(),
             doc: "Activates the character selection UI for the current pane".into(),
@@ -859,7 +912,10 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             ..
         }) => CommandDef {
             brief: "Swap a pane with the active pane".into(),
-            doc: "Activates the pane selection UI".into(),
+            doc: "Activates the pane selection UI; the selected pane and the \
+                  active pane exchange positions, and the selected pane \
+                  becomes active"
+                .into(),
             keys: vec![], // FIXME: find a new assignment
             args: &[ArgType::ActivePane],
             menubar: &["Window"],
@@ -870,7 +926,10 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             ..
         }) => CommandDef {
             brief: "Swap a pane with the active pane, keeping focus".into(),
-            doc: "Activates the pane selection UI".into(),
+            doc: "Activates the pane selection UI; the selected pane and the \
+                  active pane exchange positions, and the active pane \
+                  retains focus"
+                .into(),
             keys: vec![], // FIXME: find a new assignment
             args: &[ArgType::ActivePane],
             menubar: &["Window"],
@@ -1595,6 +1654,65 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["Window"],
             icon: Some("md_fullscreen"),
         },
+        TogglePaneMonitor(PaneMonitor::Activity) => CommandDef {
+            brief: "Toggle Pane Activity Monitor".into(),
+            doc: "Notifies you when the current pane produces output \
+                  while it is not visible"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Window"],
+            icon: Some("md_bell_ring"),
+        },
+        TogglePaneMonitor(PaneMonitor::Silence) => CommandDef {
+            brief: "Toggle Pane Silence Monitor".into(),
+            doc: "Notifies you when the current pane has not produced \
+                  any output for monitor_silence_seconds while it is not visible"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Window"],
+            icon: Some("md_bell_sleep"),
+        },
+        TogglePaneReadOnly => CommandDef {
+            brief: "Toggle Pane Read-Only".into(),
+            doc: "Toggles whether keyboard input and pastes to the \
+                  current pane are discarded"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Window"],
+            icon: Some("md_pencil_off"),
+        },
+        TogglePaneDiffHighlight => CommandDef {
+            brief: "Toggle Pane Diff Highlight".into(),
+            doc: "Toggles highlighting the parts of the output of the most \
+                  recent command that differ from the output of the \
+                  command before it"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["View"],
+            icon: Some("md_compare"),
+        },
+        TogglePaneRecording => CommandDef {
+            brief: "Toggle Pane Recording".into(),
+            doc: "Starts or stops recording the current pane as an \
+                  asciicast in the recording_directory"
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Window"],
+            icon: Some("md_record_rec"),
+        },
+        ReplayRecording(path) => CommandDef {
+            brief: format!("Replay recording {path}").into(),
+            doc: "Plays back an asciicast recording in a new tab".into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &[],
+            icon: Some("md_play"),
+        },
         ActivateLastTab => CommandDef {
             brief: "Activate the last active tab".into(),
             doc: "If there was no prior active tab, has no effect.".into(),
@@ -1977,6 +2095,39 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
                 RotationDirection::CounterClockwise => "md_rotate_left",
             }),
         },
+        BalancePanes { axis } => CommandDef {
+            brief: match axis {
+                Some(PaneAxis::Horizontal) => "Balance the widths of the panes",
+                Some(PaneAxis::Vertical) => "Balance the heights of the panes",
+                None => "Balance the sizes of the panes",
+            }
+            .into(),
+            doc: match axis {
+                Some(PaneAxis::Horizontal) => {
+                    "Resizes the panes in the current tab so that panes that \
+                     are side by side have the same width"
+                }
+                Some(PaneAxis::Vertical) => {
+                    "Resizes the panes in the current tab so that panes that \
+                     are stacked vertically have the same height"
+                }
+                None => "Resizes the panes in the current tab so that they share the space equally",
+            }
+            .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Window", "Resize Pane"],
+            icon: Some("md_view_grid"),
+        },
+        ApplyPaneLayout(name) => CommandDef {
+            brief: format!("Apply pane layout {name}").into(),
+            doc: format!("Rearranges the panes in the current tab using the {name} layout")
+                .into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["Window", "Pane Layout"],
+            icon: Some("md_view_dashboard"),
+        },
         SplitPane(split) => {
             let direction = split.direction;
             CommandDef {
@@ -2000,6 +2151,42 @@ pub fn derive_command_from_key_assignment(action: &KeyAssignment) -> Option<Comm
             menubar: &["Shell"],
             icon: None,
         },
+        CaptureScreenshot => CommandDef {
+            brief: "Save a screenshot of the window".into(),
+            doc: "Saves the contents of the window as a PNG image".into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &["View"],
+            icon: Some("md_camera"),
+        },
+        AdjustWindowHsb(adjust) => CommandDef {
+            brief: format!(
+                "Adjust window colors (hue {}, saturation {}, brightness {})",
+                adjust.hue, adjust.saturation, adjust.brightness
+            )
+            .into(),
+            doc: "Multiplies the hue, saturation and brightness of the window contents".into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["View"],
+            icon: None,
+        },
+        ResetWindowHsb => CommandDef {
+            brief: "Reset window colors".into(),
+            doc: "Undoes any adjustments made by AdjustWindowHsb".into(),
+            keys: vec![],
+            args: &[ArgType::ActiveWindow],
+            menubar: &["View"],
+            icon: None,
+        },
+        ShowOverlay(_) => CommandDef {
+            brief: "Show a custom overlay".into(),
+            doc: "Activates an overlay whose content is produced by a lua function".into(),
+            keys: vec![],
+            args: &[ArgType::ActivePane],
+            menubar: &[],
+            icon: None,
+        },
         ActivateCommandPalette => CommandDef {
             brief: "Activate Command Palette".into(),
             doc: "Shows the command palette modal".into(),
@@ -2046,7 +2233,10 @@ fn compute_default_actions() -> Vec<KeyAssignment> {
         PasteFrom(ClipboardPasteSource::Clipboard),
         ClearScrollback(ScrollbackEraseMode::ScrollbackOnly),
         ClearScrollback(ScrollbackEraseMode::ScrollbackAndViewport),
+        ClearScreenPreservingScrollback,
+        ViewScrollback(ViewScrollbackArguments::default()),
         QuickSelect,
+        LinkHints,
         CharSelect(CharSelectArguments::default()),
         ActivateCopyMode,
         ClearKeyTableStack,
@@ -2060,6 +2250,7 @@ fn compute_default_actions() -> Vec<KeyAssignment> {
         ScrollByPage(NotNan::new(1.0).unwrap()),
         ScrollToTop,
         ScrollToBottom,
+        CaptureScreenshot,
         // ----------------- Window
         ToggleFullScreen,
         ToggleAlwaysOnTop,
@@ -2069,6 +2260,7 @@ fn compute_default_actions() -> Vec<KeyAssignment> {
         SetWindowLevel(WindowLevel::AlwaysOnTop),
         Hide,
         Search(Pattern::CurrentSelectionOrEmptyString),
+        SearchAllTabs(Pattern::CurrentSelectionOrEmptyString),
         PaneSelect(PaneSelectArguments {
             alphabet: String::new(),
             mode: PaneSelectMode::Activate,
@@ -2096,6 +2288,7 @@ fn compute_default_actions() -> Vec<KeyAssignment> {
         }),
         RotatePanes(RotationDirection::Clockwise),
         RotatePanes(RotationDirection::CounterClockwise),
+        BalancePanes { axis: None },
         ActivateTab(0),
         ActivateTab(1),
         ActivateTab(2),
@@ -2130,6 +2323,11 @@ fn compute_default_actions() -> Vec<KeyAssignment> {
         ActivatePaneDirection(PaneDirection::Up),
         ActivatePaneDirection(PaneDirection::Down),
         TogglePaneZoomState,
+        TogglePaneMonitor(PaneMonitor::Activity),
+        TogglePaneMonitor(PaneMonitor::Silence),
+        TogglePaneReadOnly,
+        TogglePaneDiffHighlight,
+        TogglePaneRecording,
         ActivateLastTab,
         ShowLauncher,
         ShowTabNavigator,
@@ -4674,11 +4674,25 @@ impl GlyphCache {
         }
 
         let mut metrics = metrics.scale_cell_width(width as f64);
-        if let Some(d) = &self.fonts.config().cursor_thickness {
+        let config = self.fonts.config();
+        let (thickness, pixel_cell) = match shape {
+            Some(CursorShape::BlinkingBar | CursorShape::SteadyBar) => (
+                config.cursor_bar_width.or(config.cursor_thickness),
+                metrics.cell_size.width,
+            ),
+            Some(CursorShape::BlinkingUnderline | CursorShape::SteadyUnderline) => (
+                config
+                    .cursor_underline_thickness
+                    .or(config.cursor_thickness),
+                metrics.cell_size.height,
+            ),
+            _ => (config.cursor_thickness, metrics.cell_size.height),
+        };
+        if let Some(d) = thickness {
             metrics.underline_height = d.evaluate_as_pixels(DimensionContext {
                 dpi: self.fonts.get_dpi() as f32,
                 pixel_max: metrics.underline_height as f32,
-                pixel_cell: metrics.cell_size.height as f32,
+                pixel_cell: pixel_cell as f32,
             }) as isize;
         }
 
@@ -4766,6 +4780,8 @@ impl GlyphCache {
                 descender_plus_two: 0,
                 underline_height: *underline_height,
                 strike_row: 0,
+                strike_height: *underline_height,
+                overline_row: 0,
                 cell_size: cell_size.clone(),
             },
             _ => render_metrics.clone(),
@@ -11,7 +11,7 @@ use mux::window::WindowId as MuxWindowId;
 use mux::{Mux, MuxNotification};
 use promise::{Future, Promise};
 use std::cell::RefCell;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::rc::Rc;
 use std::sync::Arc;
 use wezterm_term::{Alert, ClipboardSelection};
@@ -22,6 +22,9 @@ pub struct GuiFrontEnd {
     switching_workspaces: RefCell<bool>,
     spawned_mux_window: RefCell<HashSet<MuxWindowId>>,
     known_windows: RefCell<BTreeMap<Window, MuxWindowId>>,
+    /// Assignments to perform in mux windows whose gui
+    /// windows have not been created yet
+    pending_assignments: RefCell<HashMap<MuxWindowId, Vec<KeyAssignment>>>,
     client_id: Arc<ClientId>,
     config_subscription: RefCell<Option<ConfigSubscription>>,
 }
@@ -45,6 +48,7 @@ impl GuiFrontEnd {
             switching_workspaces: RefCell::new(false),
             spawned_mux_window: RefCell::new(HashSet::new()),
             known_windows: RefCell::new(BTreeMap::new()),
+            pending_assignments: RefCell::new(HashMap::new()),
             client_id: client_id.clone(),
             config_subscription: RefCell::new(None),
         });
@@ -91,6 +95,7 @@ impl GuiFrontEnd {
                 MuxNotification::TabResized(_) => {}
                 MuxNotification::TabAddedToWindow { .. } => {}
                 MuxNotification::PaneRemoved(_) => {}
+                MuxNotification::PaneExited(_) => {}
                 MuxNotification::WindowInvalidated(_) => {}
                 MuxNotification::PaneOutput(_) => {}
                 MuxNotification::PaneAdded(_) => {}
@@ -147,7 +152,9 @@ impl GuiFrontEnd {
                         | Alert::WindowTitleChanged(_)
                         | Alert::TabTitleChanged(_)
                         | Alert::IconTitleChanged(_)
-                        | Alert::SetUserVar { .. },
+                        | Alert::SetUserVar { .. }
+                        | Alert::WindowResizeRequested(_)
+                        | Alert::Progress(_),
                 } => {}
                 MuxNotification::Empty => {
                     if config::configuration().quit_when_all_windows_are_closed {
@@ -256,6 +263,7 @@ impl GuiFrontEnd {
                             pane_id,
                             workspace,
                             None, // optional position
+                            None, // optional class
                         )
                         .await
                     {
@@ -455,12 +463,45 @@ impl GuiFrontEnd {
     pub fn record_known_window(&self, window: Window, mux_window_id: MuxWindowId) {
         self.known_windows
             .borrow_mut()
-            .insert(window, mux_window_id);
+            .insert(window.clone(), mux_window_id);
+        let pending = self
+            .pending_assignments
+            .borrow_mut()
+            .remove(&mux_window_id)
+            .unwrap_or_default();
+        for assignment in pending {
+            Self::perform_in_window(&window, assignment);
+        }
         if !self.is_switching_workspace() {
             self.reconcile_workspace();
         }
     }
 
+    /// Performs assignment against the active pane of mux_window_id.
+    /// If the gui window for it hasn't been created yet, the assignment
+    /// is performed as soon as it has been.
+    pub fn queue_assignment(&self, mux_window_id: MuxWindowId, assignment: KeyAssignment) {
+        match self.gui_window_for_mux_window(mux_window_id) {
+            Some(gui_window) => Self::perform_in_window(&gui_window.window, assignment),
+            None => self
+                .pending_assignments
+                .borrow_mut()
+                .entry(mux_window_id)
+                .or_default()
+                .push(assignment),
+        }
+    }
+
+    fn perform_in_window(window: &Window, assignment: KeyAssignment) {
+        window.notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+            if let Some(pane) = term_window.get_active_pane_or_overlay() {
+                if let Err(err) = term_window.perform_key_assignment(&pane, &assignment) {
+                    log::error!("performing {:?}: {:#}", assignment, err);
+                }
+            }
+        })));
+    }
+
     pub fn forget_known_window(&self, window: &Window) {
         self.known_windows.borrow_mut().remove(window);
         if !self.is_switching_workspace() {
@@ -545,5 +586,31 @@ pub fn try_new() -> Result<Rc<GuiFrontEnd>, Error> {
         .borrow_mut()
         .replace(config_subscription);
 
+    wezterm_font::assign_missing_glyph_callback(emit_missing_glyphs);
+
     Ok(front_end)
 }
+
+async fn trigger_missing_glyph(lua: Option<Rc<mlua::Lua>>, c: char) -> anyhow::Result<()> {
+    if let Some(lua) = lua {
+        let args = lua.pack_multi((c.to_string(), c as u32))?;
+        config::lua::emit_event(&lua, ("missing-glyph".to_string(), args)).await?;
+    }
+    Ok(())
+}
+
+/// Called from the font fallback resolver when no font could be
+/// found for some codepoints; emits a missing-glyph event for each
+fn emit_missing_glyphs(codepoints: Vec<char>) {
+    promise::spawn::spawn_into_main_thread(async move {
+        for c in codepoints {
+            if let Err(err) =
+                config::with_lua_config_on_main_thread(move |lua| trigger_missing_glyph(lua, c))
+                    .await
+            {
+                log::error!("while processing missing-glyph event: {:#}", err);
+            }
+        }
+    })
+    .detach();
+}
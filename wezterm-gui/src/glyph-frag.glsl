@@ -17,10 +17,21 @@ layout(location=0, index=0) out vec4 color;
 layout(location=0, index=1) out vec4 colorMask;
 
 uniform vec3 foreground_text_hsb;
+uniform vec3 window_hsb;
 uniform sampler2D atlas_nearest_sampler;
 uniform sampler2D atlas_linear_sampler;
 uniform bool subpixel_aa;
 uniform uint milliseconds;
+// The size of the window, in pixels
+uniform vec2 resolution;
+// Set while drawing the background quads of the main layer
+uniform bool background_pass;
+
+#ifdef HAS_BACKGROUND_SHADER
+// Defined by the user's window_background_shader, which is
+// appended to this file
+vec4 background_effect(vec4 color, vec2 coord);
+#endif
 
 struct ColorEase {
   vec4 in_function;
@@ -153,7 +164,13 @@ void main() {
     color = apply_hsv(color, foreground_text_hsb);
   }
 
-  color = apply_hsv(color, o_hsv);
+#ifdef HAS_BACKGROUND_SHADER
+  if (background_pass && (o_has_color == 2.0 || o_has_color == 3.0)) {
+    color = background_effect(color, gl_FragCoord.xy);
+  }
+#endif
+
+  color = apply_hsv(color, o_hsv * window_hsb);
 
   // We MUST output SRGB and tell glium that we do that (outputs_srgb),
   // otherwise something in glium over-gamma-corrects depending on the gl setup.
@@ -850,6 +850,21 @@ impl GlyphCache {
             let y_offset = info.y_offset * scale * metrics_only_scale;
             let x_advance = info.x_advance * scale * metrics_only_scale;
 
+            // Resize color glyphs about their center, without
+            // changing the advance, so that they stay in their cells
+            let emoji_scale = self.fonts.config().emoji_scale;
+            let (bearing_x, bearing_y, scale) =
+                if glyph.has_color && emoji_scale > 0. && emoji_scale != 1.0 {
+                    let adjust = (1.0 - emoji_scale) * scale / 2.;
+                    (
+                        bearing_x + PixelLength::new(glyph.width as f64 * adjust),
+                        bearing_y - PixelLength::new(glyph.height as f64 * adjust),
+                        scale * emoji_scale,
+                    )
+                } else {
+                    (bearing_x, bearing_y, scale)
+                };
+
             log::trace!(
                 "bearing_x={bearing_x:?} bearing_y={bearing_y:?} \
                  x_offset={x_offset:?} y_offset={y_offset:?} x_advance={x_advance:?}"
@@ -1295,7 +1310,7 @@ impl GlyphCache {
         };
 
         let draw_strike = |buffer: &mut Image| {
-            for row in 0..metrics.underline_height {
+            for row in 0..metrics.strike_height {
                 buffer.draw_line(
                     Point::new(
                         cell_rect.origin.x,
@@ -1313,10 +1328,13 @@ impl GlyphCache {
         let draw_overline = |buffer: &mut Image| {
             for row in 0..metrics.underline_height {
                 buffer.draw_line(
-                    Point::new(cell_rect.origin.x, cell_rect.origin.y + row),
+                    Point::new(
+                        cell_rect.origin.x,
+                        cell_rect.origin.y + metrics.overline_row + row,
+                    ),
                     Point::new(
                         cell_rect.origin.x + metrics.cell_size.width,
-                        cell_rect.origin.y + row,
+                        cell_rect.origin.y + metrics.overline_row + row,
                     ),
                     white,
                 );
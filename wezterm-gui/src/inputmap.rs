@@ -1,7 +1,7 @@
 use crate::commands::CommandDef;
 use config::keyassignment::{
-    ClipboardCopyDestination, ClipboardPasteSource, KeyAssignment, KeyTableEntry, KeyTables,
-    MouseEventTrigger, SelectionMode,
+    ClipboardCopyDestination, KeyAssignment, KeyTableEntry, KeyTables, MouseEventTrigger,
+    SelectionMode,
 };
 use config::{ConfigHandle, MouseEventAltScreen, MouseEventTriggerMods};
 use std::collections::{BTreeMap, HashMap};
@@ -87,6 +87,8 @@ impl InputMap {
         }
 
         if !config.disable_default_mouse_bindings {
+            let copy_dest = config.selection_copy_destination;
+            let paste_source = config.middle_click_paste_source;
             m!(
                 [
                     MouseEventTriggerMods {
@@ -182,9 +184,7 @@ impl InputMap {
                         streak: 1,
                         button: MouseButton::Left
                     },
-                    CompleteSelectionOrOpenLinkAtMouseCursor(
-                        ClipboardCopyDestination::ClipboardAndPrimarySelection
-                    )
+                    CompleteSelectionOrOpenLinkAtMouseCursor(copy_dest)
                 ],
                 [
                     MouseEventTriggerMods {
@@ -196,9 +196,7 @@ impl InputMap {
                         streak: 1,
                         button: MouseButton::Left
                     },
-                    CompleteSelectionOrOpenLinkAtMouseCursor(
-                        ClipboardCopyDestination::ClipboardAndPrimarySelection
-                    )
+                    CompleteSelectionOrOpenLinkAtMouseCursor(copy_dest)
                 ],
                 [
                     MouseEventTriggerMods {
@@ -210,7 +208,7 @@ impl InputMap {
                         streak: 1,
                         button: MouseButton::Left
                     },
-                    CompleteSelection(ClipboardCopyDestination::ClipboardAndPrimarySelection)
+                    CompleteSelection(copy_dest)
                 ],
                 [
                     MouseEventTriggerMods {
@@ -248,7 +246,7 @@ impl InputMap {
                         streak: 2,
                         button: MouseButton::Left
                     },
-                    CompleteSelection(ClipboardCopyDestination::ClipboardAndPrimarySelection)
+                    CompleteSelection(copy_dest)
                 ],
                 [
                     MouseEventTriggerMods {
@@ -260,7 +258,7 @@ impl InputMap {
                         streak: 3,
                         button: MouseButton::Left
                     },
-                    CompleteSelection(ClipboardCopyDestination::ClipboardAndPrimarySelection)
+                    CompleteSelection(copy_dest)
                 ],
                 [
                     MouseEventTriggerMods {
@@ -320,7 +318,7 @@ impl InputMap {
                         streak: 1,
                         button: MouseButton::Middle
                     },
-                    PasteFrom(ClipboardPasteSource::PrimarySelection)
+                    PasteFrom(paste_source)
                 ],
                 [
                     MouseEventTriggerMods {
@@ -347,6 +345,22 @@ impl InputMap {
                     StartWindowDrag
                 ],
             );
+
+            let link_mods = config.hyperlink_click_modifiers;
+            if link_mods != Modifiers::NONE {
+                m!([
+                    MouseEventTriggerMods {
+                        mods: link_mods,
+                        mouse_reporting: false,
+                        alt_screen: MouseEventAltScreen::Any,
+                    },
+                    MouseEventTrigger::Up {
+                        streak: 1,
+                        button: MouseButton::Left
+                    },
+                    CompleteSelectionOrOpenLinkAtMouseCursor(copy_dest)
+                ]);
+            }
         }
 
         keys.default
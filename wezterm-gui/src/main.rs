@@ -8,7 +8,9 @@ use ::window::*;
 use anyhow::{anyhow, Context};
 use clap::builder::ValueParser;
 use clap::{Parser, ValueHint};
-use config::keyassignment::{SpawnCommand, SpawnTabDomain};
+use config::keyassignment::{
+    KeyAssignment, LauncherActionArgs, LauncherFlags, SpawnCommand, SpawnTabDomain,
+};
 use config::{ConfigHandle, SerialDomain, SshDomain, SshMultiplexing};
 use mux::activity::Activity;
 use mux::domain::{Domain, LocalDomain};
@@ -45,10 +47,12 @@ mod overlay;
 mod quad;
 mod renderstate;
 mod resize_increment_calculator;
+mod screenshot;
 mod scripting;
 mod scrollbar;
 mod selection;
 mod shapecache;
+mod shapeworker;
 mod spawn;
 mod stats;
 mod tabbar;
@@ -129,8 +133,20 @@ enum SubCommand {
     #[command(name = "ls-fonts", about = "Display information about fonts")]
     LsFonts(LsFontsCommand),
 
+    #[command(
+        name = "screenshot",
+        about = "Render a pane to a PNG image without a window"
+    )]
+    Screenshot(ScreenshotCommand),
+
     #[command(name = "show-keys", about = "Show key assignments")]
     ShowKeys(ShowKeysCommand),
+
+    #[command(
+        name = "check-config",
+        about = "Load and validate the configuration without starting the GUI"
+    )]
+    CheckConfig(CheckConfigCommand),
 }
 
 async fn async_run_ssh(opts: SshCommand) -> anyhow::Result<()> {
@@ -452,12 +468,15 @@ async fn async_run_terminal_gui(
         None
     };
 
-    if !opts.attach {
+    // `wezterm connect` without a domain name starts up as normal
+    // and then shows a picker for the domain to connect to
+    let pick_domain = opts.attach && domain.is_none();
+    let is_connecting = opts.attach && !pick_domain;
+
+    if !is_connecting {
         trigger_and_log_gui_startup(spawn_command).await;
     }
 
-    let is_connecting = opts.attach;
-
     if let Some(domain) = &domain {
         if !opts.attach {
             let window_id = {
@@ -489,7 +508,108 @@ async fn async_run_terminal_gui(
             trigger_and_log_gui_attached(MuxDomain(domain.domain_id())).await;
         }
     }
-    spawn_tab_in_domain_if_mux_is_empty(cmd, is_connecting, domain, opts.workspace).await
+    if pick_domain {
+        show_domain_picker(opts.workspace).await?;
+    } else {
+        spawn_tab_in_domain_if_mux_is_empty(cmd, is_connecting, domain, opts.workspace).await?;
+    }
+    Ok(())
+}
+
+/// Returns the launcher, restricted to domains, that is used to pick
+/// the domain to connect to
+fn domain_picker_launcher() -> KeyAssignment {
+    KeyAssignment::ShowLauncherArgs(LauncherActionArgs {
+        flags: LauncherFlags::FUZZY | LauncherFlags::DOMAINS,
+        title: Some("Connect to domain".to_string()),
+    })
+}
+
+/// Used by `wezterm connect` when no domain name was given.
+/// Rather than spawning the default program, opens a window holding
+/// a placeholder tab and shows the domain picker over it once the gui
+/// window has been created.  The placeholder is removed as soon as
+/// another tab, such as one from the chosen domain, is added to the window.
+async fn show_domain_picker(workspace: Option<String>) -> anyhow::Result<()> {
+    let mux = Mux::get();
+    let window_id = *mux.new_empty_window(workspace, None);
+
+    let config = config::configuration();
+    let dpi = config.dpi.unwrap_or_else(|| ::window::default_dpi());
+    let size = config.initial_size(dpi as u32, Some(cell_pixel_dims(&config, dpi)?));
+    let (term, pane) = mux::termwiztermtab::allocate(size, Arc::new(config::TermConfig::new()));
+    let tab = Arc::new(mux::tab::Tab::new(&size));
+    tab.assign_pane(&pane);
+    mux.add_tab_and_active_pane(&tab)?;
+    mux.add_tab_to_window(&tab, window_id)?;
+
+    let placeholder_tab_id = tab.tab_id();
+    let placeholder_pane_id = pane.pane_id();
+    mux.subscribe(move |n| match n {
+        mux::MuxNotification::TabAddedToWindow {
+            tab_id,
+            window_id: added_to,
+        } if added_to == window_id && tab_id != placeholder_tab_id => {
+            promise::spawn::spawn_into_main_thread(async move {
+                Mux::get().remove_pane(placeholder_pane_id);
+            })
+            .detach();
+            false
+        }
+        mux::MuxNotification::WindowRemoved(removed) if removed == window_id => false,
+        _ => true,
+    });
+
+    std::thread::spawn(move || {
+        if let Err(err) = run_domain_picker_placeholder(term, window_id) {
+            // This is expected once the placeholder has been removed
+            log::debug!("domain picker placeholder: {:#}", err);
+        }
+    });
+
+    crate::frontend::front_end().queue_assignment(window_id, domain_picker_launcher());
+    Ok(())
+}
+
+/// Runs the placeholder tab of the domain picker, from which the
+/// picker can be shown again if it was dismissed
+fn run_domain_picker_placeholder(
+    mut term: mux::termwiztermtab::TermWizTerminal,
+    window_id: mux::window::WindowId,
+) -> anyhow::Result<()> {
+    use termwiz::input::{InputEvent, KeyCode, KeyEvent};
+    use termwiz::surface::Change;
+    use termwiz::terminal::Terminal;
+
+    term.render(&[
+        Change::Title("Connect to domain".to_string()),
+        Change::Text(
+            "Press Enter to choose a domain to connect to, or Escape to close this window.\r\n"
+                .to_string(),
+        ),
+    ])?;
+    term.flush()?;
+
+    while let Some(event) = term.poll_input(None)? {
+        match event {
+            InputEvent::Key(KeyEvent {
+                key: KeyCode::Enter,
+                ..
+            }) => {
+                promise::spawn::spawn_into_main_thread(async move {
+                    crate::frontend::front_end()
+                        .queue_assignment(window_id, domain_picker_launcher());
+                })
+                .detach();
+            }
+            InputEvent::Key(KeyEvent {
+                key: KeyCode::Escape,
+                ..
+            }) => break,
+            _ => {}
+        }
+    }
+    Ok(())
 }
 
 #[derive(Debug)]
@@ -612,6 +732,7 @@ impl Publish {
                                         .as_deref()
                                         .unwrap_or(mux::DEFAULT_WORKSPACE)
                                 ).to_string(),
+                                class: None,
                             })
                             .await
                     }));
@@ -858,6 +979,75 @@ fn run_show_keys(config: config::ConfigHandle, cmd: &ShowKeysCommand) -> anyhow:
     Ok(())
 }
 
+lazy_static::lazy_static! {
+    static ref CHECK_CONFIG_ERRORS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(vec![]);
+}
+
+/// Loads the configuration and exercises the parts of it that are
+/// otherwise only evaluated lazily by the GUI, printing a report of
+/// any problems.  Exits with a non-zero status if there were errors.
+fn run_check_config(config: config::ConfigHandle, cmd: &CheckConfigCommand) -> anyhow::Result<()> {
+    // Collect errors rather than showing them in the config error window
+    config::assign_error_callback(|err| {
+        CHECK_CONFIG_ERRORS.lock().unwrap().push(err.to_string());
+    });
+
+    let mut errors = vec![];
+    let mut warnings = config::configuration_warnings_and_errors();
+    if let Err(err) = config::configuration_result() {
+        // The error is also the first item of the warnings
+        if !warnings.is_empty() {
+            warnings.remove(0);
+        }
+        errors.push(format!("{err:#}"));
+    } else {
+        let _ = crate::inputmap::InputMap::new(&config);
+
+        match FontConfiguration::new(
+            Some(config.clone()),
+            config.dpi.unwrap_or_else(|| ::window::default_dpi()) as usize,
+        ) {
+            Ok(font_config) => {
+                let mut styles = vec![config.font.clone()];
+                styles.extend(config.font_rules.iter().map(|rule| rule.font.clone()));
+                for style in &styles {
+                    if let Err(err) = font_config.resolve_font(style) {
+                        errors.push(format!("{err:#}"));
+                    }
+                }
+                if let Err(err) = font_config.title_font() {
+                    errors.push(format!("{err:#}"));
+                }
+            }
+            Err(err) => errors.push(format!("{err:#}")),
+        }
+        errors.append(&mut CHECK_CONFIG_ERRORS.lock().unwrap());
+    }
+
+    if cmd.strict {
+        errors.append(&mut warnings);
+    }
+
+    match std::env::var_os("WEZTERM_CONFIG_FILE") {
+        Some(path) => println!("Checked {}", PathBuf::from(path).display()),
+        None if errors.is_empty() => println!("No configuration file was found"),
+        None => {}
+    }
+    for warning in &warnings {
+        println!("warning: {warning}");
+    }
+    for error in &errors {
+        println!("error: {error}");
+    }
+
+    if errors.is_empty() {
+        println!("The configuration is OK");
+        Ok(())
+    } else {
+        std::process::exit(1);
+    }
+}
+
 pub fn run_ls_fonts(config: config::ConfigHandle, cmd: &LsFontsCommand) -> anyhow::Result<()> {
     use wezterm_font::parser::ParsedFont;
 
@@ -1221,6 +1411,7 @@ fn run() -> anyhow::Result<()> {
     if let Some(value) = &config.default_ssh_auth_sock {
         std::env::set_var("SSH_AUTH_SOCK", value);
     }
+    window::apply_gpu_preference_env();
 
     let sub = match opts.cmd.as_ref().cloned() {
         Some(SubCommand::BlockingStart(start)) => {
@@ -1249,6 +1440,20 @@ fn run() -> anyhow::Result<()> {
         }
     };
 
+    if let SubCommand::Start(StartCommand {
+        size: Some(size), ..
+    }) = &sub
+    {
+        // Apply the size in the same way as --config initial_cols=...
+        // so that it is used both when we start a new gui and when we
+        // ask an existing gui to spawn a new window
+        let mut overrides = opts.config_override.clone();
+        overrides.push(("initial_cols".to_string(), size.cols.to_string()));
+        overrides.push(("initial_rows".to_string(), size.rows.to_string()));
+        config::set_config_overrides(&overrides).context("applying --size")?;
+        config::reload();
+    }
+
     match sub {
         SubCommand::Start(start) => {
             log::trace!("Using configuration: {:#?}\nopts: {:#?}", config, opts);
@@ -1261,21 +1466,26 @@ fn run() -> anyhow::Result<()> {
         SubCommand::Serial(serial) => run_serial(config, serial),
         SubCommand::Connect(connect) => run_terminal_gui(
             StartCommand {
-                domain: Some(connect.domain_name.clone()),
+                domain: connect.domain_name.clone(),
                 class: connect.class,
                 workspace: connect.workspace,
                 position: connect.position,
+                size: None,
                 prog: connect.prog,
                 new_tab: connect.new_tab,
                 always_new_process: true,
+                // Without a domain, this combination (which clap doesn't
+                // allow for `wezterm start`) shows the domain picker
                 attach: true,
                 _cmd: false,
                 no_auto_connect: false,
                 cwd: None,
             },
-            Some(connect.domain_name),
+            connect.domain_name,
         ),
         SubCommand::LsFonts(cmd) => run_ls_fonts(config, &cmd),
+        SubCommand::Screenshot(cmd) => screenshot::run_screenshot(config, &cmd),
         SubCommand::ShowKeys(cmd) => run_show_keys(config, &cmd),
+        SubCommand::CheckConfig(cmd) => run_check_config(config, &cmd),
     }
 }
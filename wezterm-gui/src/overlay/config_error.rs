@@ -0,0 +1,90 @@
+use mux::termwiztermtab::TermWizTerminal;
+use regex::Regex;
+use termwiz::cell::{AttributeChange, Intensity};
+use termwiz::color::{AnsiColor, ColorAttribute};
+use termwiz::input::{InputEvent, KeyCode, KeyEvent};
+use termwiz::surface::{Change, CursorVisibility, Position};
+use termwiz::terminal::Terminal;
+
+/// Extracts the file and line number from a lua error message.
+/// The main config file is loaded as a named chunk, so it is reported
+/// as `[string "/path/wezterm.lua"]:12:`, whereas modules that it
+/// requires are reported as `/path/module.lua:12:`.
+fn error_location(error: &str) -> Option<(String, usize)> {
+    lazy_static::lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r#"(?:\[string "([^"]+)"\]|([^\s:"]+\.lua)):(\d+):"#).unwrap();
+    }
+    let caps = RE.captures(error)?;
+    let file = caps.get(1).or_else(|| caps.get(2))?.as_str().to_string();
+    let line = caps.get(3)?.as_str().parse().ok()?;
+    Some((file, line))
+}
+
+pub fn show_config_error_overlay(mut term: TermWizTerminal, error: String) -> anyhow::Result<()> {
+    term.set_raw_mode()?;
+    let size = term.get_screen_size()?;
+    let width = size.cols.saturating_sub(2).max(20);
+
+    let mut changes = vec![
+        Change::ClearScreen(ColorAttribute::Default),
+        Change::CursorVisibility(CursorVisibility::Hidden),
+        AttributeChange::Intensity(Intensity::Bold).into(),
+        AttributeChange::Foreground(AnsiColor::Red.into()).into(),
+        "Configuration Error\r\n".into(),
+        AttributeChange::Intensity(Intensity::Normal).into(),
+        AttributeChange::Foreground(ColorAttribute::Default).into(),
+        "wezterm is still using the last configuration that loaded successfully.\r\n".into(),
+    ];
+
+    if let Some((file, line)) = error_location(&error) {
+        changes.push(format!("File: {file}, line {line}\r\n").into());
+    }
+    changes.push("\r\n".into());
+
+    for line in textwrap::fill(&error, width).lines() {
+        changes.push(format!("{line}\r\n").into());
+    }
+
+    changes.push(Change::CursorPosition {
+        x: Position::Absolute(0),
+        y: Position::Absolute(size.rows.saturating_sub(1)),
+    });
+    changes.push(AttributeChange::Intensity(Intensity::Half).into());
+    changes.push("Press Escape or Enter to dismiss".into());
+
+    term.render(&changes)?;
+    term.flush()?;
+
+    while let Ok(Some(event)) = term.poll_input(None) {
+        match event {
+            InputEvent::Key(KeyEvent {
+                key: KeyCode::Escape | KeyCode::Enter | KeyCode::Char('q'),
+                ..
+            }) => break,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn location() {
+        assert_eq!(
+            error_location(
+                r#"runtime error: [string "/home/me/.wezterm.lua"]:12: attempt to index a nil value"#
+            ),
+            Some(("/home/me/.wezterm.lua".to_string(), 12))
+        );
+        assert_eq!(
+            error_location("/home/me/.config/wezterm/keys.lua:3: unexpected symbol near '}'"),
+            Some(("/home/me/.config/wezterm/keys.lua".to_string(), 3))
+        );
+        assert_eq!(error_location("something went wrong"), None);
+    }
+}
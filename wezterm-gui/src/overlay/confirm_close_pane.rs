@@ -1,3 +1,4 @@
+use crate::termwindow::TermWindowNotif;
 use crate::TermWindow;
 use mux::pane::PaneId;
 use mux::tab::TabId;
@@ -9,6 +10,7 @@ use termwiz::color::ColorAttribute;
 use termwiz::input::{InputEvent, KeyCode, KeyEvent, MouseButtons, MouseEvent};
 use termwiz::surface::{Change, CursorVisibility, Position};
 use termwiz::terminal::Terminal;
+use wezterm_term::WindowResizeRequest;
 
 fn run_confirmation_app(message: &str, term: &mut TermWizTerminal) -> anyhow::Result<bool> {
     term.set_raw_mode()?;
@@ -231,6 +233,58 @@ pub fn confirm_close_window(
     Ok(())
 }
 
+pub fn confirm_window_resize(
+    pane_id: PaneId,
+    mut term: TermWizTerminal,
+    window: ::window::Window,
+    request: WindowResizeRequest,
+) -> anyhow::Result<()> {
+    if run_confirmation_app("📐 Allow this pane to resize the window?", &mut term)? {
+        window.notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+            term_window.apply_window_resize_request(pane_id, request);
+        })));
+    }
+    TermWindow::schedule_cancel_overlay_for_pane(window, pane_id);
+
+    Ok(())
+}
+
+/// Describes a size in bytes in the most readable unit
+fn format_byte_size(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MiB", bytes as f64 / (1024. * 1024.))
+    } else if bytes >= 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.)
+    } else {
+        format!("{} bytes", bytes)
+    }
+}
+
+pub fn confirm_large_paste(
+    pane_id: PaneId,
+    mut term: TermWizTerminal,
+    window: ::window::Window,
+    text: String,
+) -> anyhow::Result<()> {
+    let lines = text.lines().count();
+    let message = format!(
+        "📋 Paste {} ({} line{}) into this remote pane?",
+        format_byte_size(text.len()),
+        lines,
+        if lines == 1 { "" } else { "s" }
+    );
+    if run_confirmation_app(&message, &mut term)? {
+        window.notify(TermWindowNotif::Apply(Box::new(move |_term_window| {
+            if let Some(pane) = Mux::get().get_pane(pane_id) {
+                pane.send_paste(&text).ok();
+            }
+        })));
+    }
+    TermWindow::schedule_cancel_overlay_for_pane(window, pane_id);
+
+    Ok(())
+}
+
 pub fn confirm_quit_program(
     mut term: TermWizTerminal,
     window: ::window::Window,
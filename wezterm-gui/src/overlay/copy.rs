@@ -2,8 +2,8 @@ use crate::selection::{SelectionCoordinate, SelectionRange, SelectionX};
 use crate::termwindow::keyevent::KeyTableArgs;
 use crate::termwindow::{TermWindow, TermWindowNotif};
 use config::keyassignment::{
-    ClipboardCopyDestination, CopyModeAssignment, KeyAssignment, KeyTable, KeyTableEntry,
-    ScrollbackEraseMode, SelectionMode,
+    ClipboardCopyDestination, CopyModeAssignment, CopyModeReplacement, KeyAssignment, KeyTable,
+    KeyTableEntry, ScrollbackEraseMode, SelectionMode,
 };
 use mux::domain::DomainId;
 use mux::pane::{
@@ -12,6 +12,7 @@ use mux::pane::{
 };
 use mux::renderable::*;
 use mux::tab::TabId;
+use mux::window::WindowId;
 use ordered_float::NotNan;
 use parking_lot::{MappedMutexGuard, Mutex, MutexGuard};
 use rangeset::RangeSet;
@@ -20,7 +21,7 @@ use std::ops::Range;
 use std::sync::Arc;
 use std::time::Duration;
 use termwiz::cell::{Cell, CellAttributes};
-use termwiz::color::AnsiColor;
+use termwiz::color::{AnsiColor, ColorAttribute};
 use termwiz::lineedit::{LineEditBuffer, Movement};
 use termwiz::surface::{CursorVisibility, SequenceNo, SEQ_ZERO};
 use unicode_segmentation::*;
@@ -34,6 +35,19 @@ use window::{KeyCode as WKeyCode, Modifiers, WindowOps};
 
 lazy_static::lazy_static! {
     static ref SAVED_PATTERN: Mutex<HashMap<TabId, Pattern>> = Mutex::new(HashMap::new());
+    static ref LAST_WINDOW_PATTERN: Mutex<HashMap<WindowId, Pattern>> = Mutex::new(HashMap::new());
+}
+
+/// Returns the most recent non-empty pattern that was searched
+/// for in the specified window
+pub fn last_search_pattern(window_id: WindowId) -> Option<Pattern> {
+    LAST_WINDOW_PATTERN.lock().get(&window_id).cloned()
+}
+
+/// Discards the pattern remembered for the specified window;
+/// called when the window is closed
+pub fn forget_last_search_pattern(window_id: WindowId) {
+    LAST_WINDOW_PATTERN.lock().remove(&window_id);
 }
 
 const SEARCH_CHUNK_SIZE: StableRowIndex = 1000;
@@ -50,6 +64,16 @@ struct PendingJump {
     prev_char: bool,
 }
 
+/// A match to select once the search has found it
+#[derive(Copy, Clone, Debug)]
+enum PendingMatch {
+    /// The top-most match, which is known only once the
+    /// search has completed
+    First,
+    /// The match starting at the specified position
+    At { x: usize, y: StableRowIndex },
+}
+
 #[derive(Copy, Clone, Debug)]
 struct Jump {
     forward: bool,
@@ -80,11 +104,14 @@ struct CopyRenderable {
     editing_search: bool,
     result_pos: Option<usize>,
     tab_id: TabId,
+    window_id: WindowId,
     /// Used to debounce queries while the user is typing
     typing_cookie: usize,
     searching: Option<Searching>,
     pending_jump: Option<PendingJump>,
     last_jump: Option<Jump>,
+    all_panes: bool,
+    pending_match: Option<PendingMatch>,
 }
 
 struct Searching {
@@ -107,6 +134,9 @@ struct Dimensions {
 pub struct CopyModeParams {
     pub pattern: Pattern,
     pub editing_search: bool,
+    /// Move on to the other panes in the tab when the matches
+    /// in this pane have been exhausted
+    pub all_panes: bool,
 }
 
 impl CopyOverlay {
@@ -119,7 +149,7 @@ impl CopyOverlay {
         cursor.shape = termwiz::surface::CursorShape::SteadyBlock;
         cursor.visibility = CursorVisibility::Visible;
 
-        let (_domain, _window, tab_id) = mux::Mux::get()
+        let (_domain, window_id, tab_id) = mux::Mux::get()
             .resolve_pane_id(pane.pane_id())
             .ok_or_else(|| anyhow::anyhow!("no tab contains the current pane"))?;
 
@@ -153,6 +183,7 @@ impl CopyOverlay {
             last_result_seqno: SEQ_ZERO,
             last_bar_pos: None,
             tab_id,
+            window_id,
             pattern_type: PatternType::from(&pattern),
             search_line,
             editing_search: params.editing_search,
@@ -162,6 +193,8 @@ impl CopyOverlay {
             searching: None,
             pending_jump: None,
             last_jump: None,
+            all_panes: params.all_panes,
+            pending_match: None,
         };
 
         let search_row = render.compute_search_row();
@@ -185,12 +218,14 @@ impl CopyOverlay {
         CopyModeParams {
             pattern: render.get_pattern(),
             editing_search: render.editing_search,
+            all_panes: render.all_panes,
         }
     }
 
     pub fn apply_params(&self, params: CopyModeParams) {
         let mut render = self.render.lock();
         render.editing_search = params.editing_search;
+        render.all_panes = params.all_panes;
         if render.get_pattern() != params.pattern {
             render.pattern_type = PatternType::from(&params.pattern);
             render
@@ -202,6 +237,18 @@ impl CopyOverlay {
         render.dirty_results.add(search_row);
     }
 
+    /// Arranges for the top-most match to be selected once the
+    /// search has completed, rather than the bottom-most match
+    pub fn select_first_match_when_done(&self) {
+        self.render.lock().pending_match = Some(PendingMatch::First);
+    }
+
+    /// Arranges for the match that starts at the specified position
+    /// to be selected once the search has found it
+    pub fn select_match_at(&self, x: usize, y: StableRowIndex) {
+        self.render.lock().pending_match = Some(PendingMatch::At { x, y });
+    }
+
     pub fn viewport_changed(&self, viewport: Option<StableRowIndex>) {
         let mut render = self.render.lock();
         if render.viewport != viewport {
@@ -317,6 +364,10 @@ impl CopyRenderable {
 
         let pattern = self.get_pattern();
         if !pattern.is_empty() {
+            LAST_WINDOW_PATTERN
+                .lock()
+                .insert(self.window_id, pattern.clone());
+
             let pane: Arc<dyn Pane> = self.delegate.clone();
             let window = self.window.clone();
             let dims = pane.get_dimensions();
@@ -379,9 +430,23 @@ impl CopyRenderable {
             }
         }
 
+        if let Some(PendingMatch::At { x, y }) = self.pending_match {
+            if let Some(n) = self
+                .results
+                .iter()
+                .position(|res| res.start_x == x && res.start_y == y)
+            {
+                self.pending_match.take();
+                self.activate_match_number(n);
+            }
+        }
+
         let dims = self.delegate.get_dimensions();
         if range.start == dims.scrollback_top {
             self.searching.take();
+            if let Some(PendingMatch::First) = self.pending_match.take() {
+                self.first_match();
+            }
             return;
         }
 
@@ -586,6 +651,9 @@ impl CopyRenderable {
         if let Some(cur) = self.result_pos.as_ref() {
             let prior = if *cur > 0 {
                 cur - 1
+            } else if self.all_panes {
+                self.move_to_adjacent_pane(true, self.results.len() - 1);
+                return;
             } else {
                 self.results.len() - 1
             };
@@ -597,6 +665,10 @@ impl CopyRenderable {
     fn prior_match(&mut self) {
         if let Some(cur) = self.result_pos.as_ref() {
             let next = if *cur + 1 >= self.results.len() {
+                if self.all_panes {
+                    self.move_to_adjacent_pane(false, 0);
+                    return;
+                }
                 0
             } else {
                 *cur + 1
@@ -605,6 +677,104 @@ impl CopyRenderable {
         }
     }
 
+    /// Continue the search in the next (or prior) pane in the tab that
+    /// has a match for the pattern, activating that pane.
+    /// If no other pane matches, wraps around to match number `wrap`
+    /// in this pane.
+    fn move_to_adjacent_pane(&mut self, forward: bool, wrap: usize) {
+        let mux = mux::Mux::get();
+        let panes: Vec<Arc<dyn Pane>> = match mux.get_tab(self.tab_id) {
+            Some(tab) => tab
+                .iter_panes_ignoring_zoom()
+                .into_iter()
+                .map(|p| p.pane)
+                .collect(),
+            None => return,
+        };
+        let pane_id = self.delegate.pane_id();
+        let idx = match panes.iter().position(|p| p.pane_id() == pane_id) {
+            Some(idx) => idx,
+            None => return,
+        };
+        let num_panes = panes.len();
+        let candidates: Vec<Arc<dyn Pane>> = (1..num_panes)
+            .map(|i| {
+                if forward {
+                    (idx + i) % num_panes
+                } else {
+                    (idx + num_panes - i) % num_panes
+                }
+            })
+            .map(|i| Arc::clone(&panes[i]))
+            .collect();
+
+        let params = CopyModeParams {
+            pattern: self.get_pattern(),
+            editing_search: self.editing_search,
+            all_panes: true,
+        };
+        let window = self.window.clone();
+
+        promise::spawn::spawn(async move {
+            for pane in candidates {
+                let dims = pane.get_dimensions();
+                let range = dims.scrollback_top
+                    ..dims.scrollback_top + dims.scrollback_rows as StableRowIndex;
+                let results = pane.search(params.pattern.clone(), range, Some(1)).await?;
+                if !results.is_empty() {
+                    window.notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                        match term_window.move_search_to_pane(pane_id, &pane, params) {
+                            Ok(overlay) => {
+                                if forward {
+                                    if let Some(copy_overlay) =
+                                        overlay.downcast_ref::<CopyOverlay>()
+                                    {
+                                        copy_overlay.select_first_match_when_done();
+                                    }
+                                }
+                            }
+                            Err(err) => log::error!("move_search_to_pane: {:#}", err),
+                        }
+                    })));
+                    return Ok(());
+                }
+            }
+
+            // No other pane matched; wrap around in this one
+            window.notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                let state = term_window.pane_state(pane_id);
+                if let Some(overlay) = state.overlay.as_ref() {
+                    if let Some(copy_overlay) = overlay.pane.downcast_ref::<CopyOverlay>() {
+                        let mut r = copy_overlay.render.lock();
+                        if wrap < r.results.len() {
+                            r.activate_match_number(wrap);
+                        }
+                    }
+                }
+            })));
+            anyhow::Result::<()>::Ok(())
+        })
+        .detach();
+    }
+
+    fn toggle_search_all_panes(&mut self) {
+        self.all_panes = !self.all_panes;
+        let search_row = self.compute_search_row();
+        self.dirty_results.add(search_row);
+        self.window.invalidate();
+    }
+
+    /// Replaces this overlay with a list of the matches from every
+    /// pane in the tab
+    fn list_all_panes_matches(&mut self) {
+        let pane_id = self.delegate.pane_id();
+        let pattern = self.get_pattern();
+        self.window
+            .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                term_window.list_matches_in_tab(pane_id, pattern);
+            })));
+    }
+
     /// Skip this page of matches and move down to the first match from
     /// the next page.
     fn next_match_page(&mut self) {
@@ -640,6 +810,31 @@ impl CopyRenderable {
         }
     }
 
+    /// Returns the palette with which to dim text that doesn't match
+    /// the search pattern, if that has been enabled.
+    fn unmatched_text_palette(&self) -> Option<ColorPalette> {
+        let brightness = config::configuration().search_unmatched_text_brightness;
+        if brightness >= 1.0 || self.get_pattern().is_empty() {
+            None
+        } else {
+            Some(self.delegate.palette())
+        }
+    }
+
+    /// Move to the match nearest the top of the scrollback
+    fn first_match(&mut self) {
+        if !self.results.is_empty() {
+            self.activate_match_number(self.results.len() - 1);
+        }
+    }
+
+    /// Move to the match nearest the bottom of the scrollback
+    fn last_match(&mut self) {
+        if !self.results.is_empty() {
+            self.activate_match_number(0);
+        }
+    }
+
     fn get_pattern(&self) -> Pattern {
         let pattern = self.search_line.get_line().to_string();
         match self.pattern_type {
@@ -1098,6 +1293,35 @@ impl CopyRenderable {
         self.start.take();
         self.clear_selection();
     }
+
+    /// Copies the selected text to destination, after applying
+    /// the copy_mode_replacements preset with the given name
+    fn copy_with_replacement(&self, preset: &str, destination: ClipboardCopyDestination) {
+        let pane_id = self.delegate.pane_id();
+        let preset = preset.to_string();
+        self.window
+            .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                let pane = match mux::Mux::get().get_pane(pane_id) {
+                    Some(pane) => pane,
+                    None => return,
+                };
+                let replacements = match term_window.config.copy_mode_replacements.get(&preset) {
+                    Some(replacements) => replacements.clone(),
+                    None => {
+                        log::error!("copy_mode_replacements has no preset named {preset:?}");
+                        return;
+                    }
+                };
+                let text = term_window.selection_text(&pane);
+                if text.is_empty() {
+                    return;
+                }
+                match apply_replacements(&replacements, &text) {
+                    Ok(text) => term_window.copy_to_clipboard(destination, text),
+                    Err(err) => log::error!("copy_mode_replacements {preset:?}: {err:#}"),
+                }
+            })));
+    }
 }
 
 impl Pane for CopyOverlay {
@@ -1275,6 +1499,10 @@ impl Pane for CopyOverlay {
                     NextMatch => render.next_match(),
                     PriorMatchPage => render.prior_match_page(),
                     NextMatchPage => render.next_match_page(),
+                    FirstMatch => render.first_match(),
+                    LastMatch => render.last_match(),
+                    ToggleSearchAllPanes => render.toggle_search_all_panes(),
+                    ListAllPanesMatches => render.list_all_panes_matches(),
                     CycleMatchType => render.cycle_match_type(),
                     ClearPattern => render.clear_pattern(),
                     EditPattern => render.edit_pattern(),
@@ -1289,6 +1517,10 @@ impl Pane for CopyOverlay {
                     JumpBackward { prev_char } => render.jump(false, *prev_char),
                     JumpAgain => render.jump_again(false),
                     JumpReverse => render.jump_again(true),
+                    CopyWithReplacement {
+                        preset,
+                        destination,
+                    } => render.copy_with_replacement(preset, *destination),
                 }
                 PerformAssignmentResult::Handled
             }
@@ -1309,7 +1541,19 @@ impl Pane for CopyOverlay {
     }
 
     fn palette(&self) -> ColorPalette {
-        self.delegate.palette()
+        let mut palette = self.delegate.palette();
+        let config = config::configuration();
+        let colors = &config.resolved_palette;
+        if let Some(fg) = colors.copy_mode_cursor_fg {
+            palette.cursor_fg = fg.into();
+        }
+        if let Some(bg) = colors.copy_mode_cursor_bg {
+            palette.cursor_bg = bg.into();
+        }
+        if let Some(border) = colors.copy_mode_cursor_border {
+            palette.cursor_border = border.into();
+        }
+        palette
     }
 
     fn domain_id(&self) -> DomainId {
@@ -1394,12 +1638,14 @@ impl Pane for CopyOverlay {
         renderer.check_for_resize();
         let dims = self.get_dimensions();
         let search_row = renderer.compute_search_row();
+        let unmatched_palette = renderer.unmatched_text_palette();
 
         struct OverlayLines<'a> {
             with_lines: &'a mut dyn WithPaneLines,
             dims: RenderableDimensions,
             search_row: StableRowIndex,
             renderer: &'a mut CopyRenderable,
+            unmatched_palette: Option<ColorPalette>,
         }
 
         self.delegate.with_lines_mut(
@@ -1409,6 +1655,7 @@ impl Pane for CopyOverlay {
                 dims,
                 search_row,
                 renderer: &mut *renderer,
+                unmatched_palette,
             },
         );
 
@@ -1442,11 +1689,16 @@ impl Pane for CopyOverlay {
                             }
                             None => String::new(),
                         };
+                        let scope = if self.renderer.all_panes {
+                            ", all panes"
+                        } else {
+                            ""
+                        };
 
                         line.overlay_text_with_attribute(
                             0,
                             &format!(
-                                "Search: {} ({}/{} matches. {}{remain})",
+                                "Search: {} ({}/{} matches. {}{scope}{remain})",
                                 *pattern,
                                 self.renderer.result_pos.map(|x| x + 1).unwrap_or(0),
                                 self.renderer.results.len(),
@@ -1457,8 +1709,13 @@ impl Pane for CopyOverlay {
                         );
                         self.renderer.last_bar_pos = Some(self.search_row);
                         line.clear_appdata();
-                    } else if let Some(matches) = self.renderer.by_line.get(&stable_idx) {
-                        for m in matches {
+                    } else {
+                        let matches = self.renderer.by_line.get(&stable_idx);
+                        if let Some(palette) = &self.unmatched_palette {
+                            dim_unmatched_cells(&mut line, matches, palette);
+                            line.clear_appdata();
+                        }
+                        for m in matches.into_iter().flatten() {
                             // highlight
                             for cell_idx in m.range.clone() {
                                 if let Some(cell) =
@@ -1494,7 +1751,9 @@ impl Pane for CopyOverlay {
                                 }
                             }
                         }
-                        line.clear_appdata();
+                        if matches.is_some() {
+                            line.clear_appdata();
+                        }
                     }
                     overlay_lines.push(line);
                 }
@@ -1523,6 +1782,7 @@ impl Pane for CopyOverlay {
         // the search UI.
         // For rows with search results, we want to highlight the matching ranges
         let search_row = renderer.compute_search_row();
+        let unmatched_palette = renderer.unmatched_text_palette();
         for (idx, line) in lines.iter_mut().enumerate() {
             let stable_idx = idx as StableRowIndex + top;
             renderer.dirty_results.remove(stable_idx);
@@ -1536,10 +1796,15 @@ impl Pane for CopyOverlay {
                     Pattern::CaseInSensitiveString(_) => "ignore-case",
                     Pattern::Regex(_) => "regex",
                 };
+                let scope = if renderer.all_panes {
+                    ", all panes"
+                } else {
+                    ""
+                };
                 line.overlay_text_with_attribute(
                     0,
                     &format!(
-                        "Search: {} ({}/{} matches. {})",
+                        "Search: {} ({}/{} matches. {}{scope})",
                         *pattern,
                         renderer.result_pos.map(|x| x + 1).unwrap_or(0),
                         renderer.results.len(),
@@ -1549,8 +1814,12 @@ impl Pane for CopyOverlay {
                     SEQ_ZERO,
                 );
                 renderer.last_bar_pos = Some(search_row);
-            } else if let Some(matches) = renderer.by_line.get(&stable_idx) {
-                for m in matches {
+            } else {
+                let matches = renderer.by_line.get(&stable_idx);
+                if let Some(palette) = &unmatched_palette {
+                    dim_unmatched_cells(line, matches, palette);
+                }
+                for m in matches.into_iter().flatten() {
                     // highlight
                     for cell_idx in m.range.clone() {
                         if let Some(cell) = line.cells_mut_for_attr_changes_only().get_mut(cell_idx)
@@ -1616,6 +1885,16 @@ impl std::io::Write for SearchOverlayPatternWriter {
     }
 }
 
+/// Applies each of the replacements to text, in order
+fn apply_replacements(replacements: &[CopyModeReplacement], text: &str) -> anyhow::Result<String> {
+    let mut text = text.to_string();
+    for r in replacements {
+        let re = regex::Regex::new(&r.regex)?;
+        text = re.replace_all(&text, r.replacement.as_str()).into_owned();
+    }
+    Ok(text)
+}
+
 fn is_whitespace_word(word: &str) -> bool {
     if let Some(c) = word.chars().next() {
         c.is_whitespace()
@@ -1624,6 +1903,51 @@ fn is_whitespace_word(word: &str) -> bool {
     }
 }
 
+/// Scale the foreground color of the cells in line that are not part
+/// of any of the matches towards their background color
+fn dim_unmatched_cells(
+    line: &mut Line,
+    matches: Option<&Vec<MatchResult>>,
+    palette: &ColorPalette,
+) {
+    let brightness = config::configuration()
+        .search_unmatched_text_brightness
+        .clamp(0.0, 1.0) as f64;
+    for (cell_idx, cell) in line
+        .cells_mut_for_attr_changes_only()
+        .iter_mut()
+        .enumerate()
+    {
+        if matches
+            .map(|m| m.iter().any(|m| m.range.contains(&cell_idx)))
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        let attrs = cell.attrs_mut();
+        let (fg, bg, bg_attr) = if attrs.reverse() {
+            let bg = palette.resolve_fg(attrs.foreground());
+            (
+                palette.resolve_bg(attrs.background()),
+                bg,
+                ColorAttribute::TrueColorWithDefaultFallback(bg),
+            )
+        } else {
+            (
+                palette.resolve_fg(attrs.foreground()),
+                palette.resolve_bg(attrs.background()),
+                attrs.background(),
+            )
+        };
+        attrs
+            .set_foreground(ColorAttribute::TrueColorWithDefaultFallback(
+                bg.interpolate(fg, brightness),
+            ))
+            .set_background(bg_attr)
+            .set_reverse(false);
+    }
+}
+
 pub fn search_key_table() -> KeyTable {
     let mut table = KeyTable::default();
     for (key, mods, action) in [
@@ -1657,6 +1981,16 @@ pub fn search_key_table() -> KeyTable {
             Modifiers::NONE,
             KeyAssignment::CopyMode(CopyModeAssignment::NextMatchPage),
         ),
+        (
+            WKeyCode::Home,
+            Modifiers::CTRL,
+            KeyAssignment::CopyMode(CopyModeAssignment::FirstMatch),
+        ),
+        (
+            WKeyCode::End,
+            Modifiers::CTRL,
+            KeyAssignment::CopyMode(CopyModeAssignment::LastMatch),
+        ),
         (
             WKeyCode::Char('n'),
             Modifiers::CTRL,
@@ -1677,6 +2011,16 @@ pub fn search_key_table() -> KeyTable {
             Modifiers::CTRL,
             KeyAssignment::CopyMode(CopyModeAssignment::ClearPattern),
         ),
+        (
+            WKeyCode::Char('t'),
+            Modifiers::CTRL,
+            KeyAssignment::CopyMode(CopyModeAssignment::ToggleSearchAllPanes),
+        ),
+        (
+            WKeyCode::Char('l'),
+            Modifiers::CTRL,
+            KeyAssignment::CopyMode(CopyModeAssignment::ListAllPanesMatches),
+        ),
     ] {
         table.insert((key, mods), KeyTableEntry { action });
     }
@@ -2021,3 +2365,32 @@ pub fn copy_key_table() -> KeyTable {
     }
     table
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replacements() {
+        let replacements = vec![
+            CopyModeReplacement {
+                regex: r"(?m)^\$ ".to_string(),
+                replacement: String::new(),
+            },
+            CopyModeReplacement {
+                regex: r"[ \t]+".to_string(),
+                replacement: " ".to_string(),
+            },
+        ];
+        assert_eq!(
+            apply_replacements(&replacements, "$ echo   hello\nhello\n$ ls\t\t-l").unwrap(),
+            "echo hello\nhello\nls -l"
+        );
+
+        let bad = vec![CopyModeReplacement {
+            regex: "(".to_string(),
+            replacement: String::new(),
+        }];
+        assert!(apply_replacements(&bad, "text").is_err());
+    }
+}
@@ -0,0 +1,500 @@
+//! Searches the scrollback of several panes and presents the matches
+//! as a list, grouped under a heading for the pane in which they were
+//! found.  Selecting a match activates its tab and pane and opens
+//! search mode there with the match selected.
+use crate::overlay::{CopyModeParams, CopyOverlay};
+use crate::termwindow::TermWindowNotif;
+use mux::pane::{PaneId, Pattern};
+use mux::tab::TabId;
+use mux::termwiztermtab::TermWizTerminal;
+use mux::window::WindowId;
+use mux::Mux;
+use termwiz::cell::{AttributeChange, Intensity};
+use termwiz::color::ColorAttribute;
+use termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent};
+use termwiz::surface::{Change, Position};
+use termwiz::terminal::Terminal;
+use termwiz_funcs::truncate_right;
+use wezterm_term::StableRowIndex;
+use window::WindowOps;
+
+const ROW_OVERHEAD: usize = 3;
+
+/// Bound the work done for panes with very large scrollback
+const MAX_HITS_PER_PANE: u32 = 1000;
+
+/// The panes whose scrollback is searched
+#[derive(Debug, Clone, Copy)]
+pub enum SearchScope {
+    /// Every pane in the tab
+    Tab(TabId),
+    /// Every pane in every tab of the window
+    Window(WindowId),
+}
+
+impl SearchScope {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Tab(_) => "Search all panes",
+            Self::Window(_) => "Search all tabs",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GlobalSearchHit {
+    /// The index of the tab within the window
+    tab_idx: usize,
+    pane_id: PaneId,
+    /// The index of the pane within its tab
+    pane_idx: usize,
+    pane_title: String,
+    start_x: usize,
+    start_y: StableRowIndex,
+    text: String,
+}
+
+/// A row in the list of matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListRow {
+    /// The heading for the pane that contains this hit and the
+    /// hits that follow it
+    Pane(usize),
+    Hit(usize),
+}
+
+/// Groups the hits, which are ordered by pane, under a heading
+/// for each pane
+fn list_rows(hits: &[GlobalSearchHit]) -> Vec<ListRow> {
+    let mut rows = vec![];
+    let mut prior_pane = None;
+    for (idx, hit) in hits.iter().enumerate() {
+        if prior_pane != Some(hit.pane_id) {
+            rows.push(ListRow::Pane(idx));
+            prior_pane = Some(hit.pane_id);
+        }
+        rows.push(ListRow::Hit(idx));
+    }
+    rows
+}
+
+/// Must be called on the Mux thread!
+async fn search_panes(scope: SearchScope, pattern: Pattern) -> Vec<GlobalSearchHit> {
+    let mux = Mux::get();
+    let tabs: Vec<_> = match scope {
+        SearchScope::Tab(tab_id) => match mux.get_tab(tab_id) {
+            Some(tab) => vec![(0, tab)],
+            None => return vec![],
+        },
+        SearchScope::Window(mux_window_id) => match mux.get_window(mux_window_id) {
+            Some(window) => window.iter().cloned().enumerate().collect(),
+            None => return vec![],
+        },
+    };
+
+    let mut hits = vec![];
+    for (tab_idx, tab) in tabs {
+        for pos in tab.iter_panes_ignoring_zoom() {
+            let pane_idx = pos.index;
+            let pane = pos.pane;
+            let dims = pane.get_dimensions();
+            let range =
+                dims.scrollback_top..dims.scrollback_top + dims.scrollback_rows as StableRowIndex;
+            let mut results = match pane
+                .search(pattern.clone(), range, Some(MAX_HITS_PER_PANE))
+                .await
+            {
+                Ok(results) => results,
+                Err(err) => {
+                    log::error!("searching pane {}: {:#}", pane.pane_id(), err);
+                    continue;
+                }
+            };
+            results.sort();
+
+            let pane_title = pane.get_title();
+            for result in results {
+                // Use the logical line so that a match that spans a wrap
+                // boundary is shown in its entirety
+                let text = pane
+                    .get_logical_lines(result.start_y..result.start_y + 1)
+                    .first()
+                    .map(|line| line.logical.as_str().trim().to_string())
+                    .unwrap_or_default();
+                hits.push(GlobalSearchHit {
+                    tab_idx,
+                    pane_id: pane.pane_id(),
+                    pane_idx,
+                    pane_title: pane_title.clone(),
+                    start_x: result.start_x,
+                    start_y: result.start_y,
+                    text,
+                });
+            }
+        }
+    }
+    hits
+}
+
+// spawn_into_main_thread wants the overall future to be Send,
+// but the pane search futures are not, so we spawn the search
+// separately and use a channel to funnel the results back.
+fn search_trampoline(
+    scope: SearchScope,
+    pattern: Pattern,
+) -> smol::channel::Receiver<Vec<GlobalSearchHit>> {
+    let (tx, rx) = smol::channel::bounded(1);
+    promise::spawn::spawn(async move {
+        let hits = search_panes(scope, pattern).await;
+        tx.send(hits).await.ok();
+    })
+    .detach();
+    rx
+}
+
+struct GlobalSearchState {
+    pattern: Pattern,
+    /// The pattern that produced the current set of hits
+    searched: Option<Pattern>,
+    hits: Vec<GlobalSearchHit>,
+    rows: Vec<ListRow>,
+    /// The index of the selected hit
+    active_idx: usize,
+    max_items: usize,
+    top_row: usize,
+    scope: SearchScope,
+    window: ::window::Window,
+}
+
+impl GlobalSearchState {
+    fn search(&mut self) {
+        let pattern = self.pattern.clone();
+        let scope = self.scope;
+        self.hits = if pattern.is_empty() {
+            vec![]
+        } else {
+            let pattern = self.pattern.clone();
+            smol::block_on(promise::spawn::spawn_into_main_thread(async move {
+                search_trampoline(scope, pattern)
+                    .recv()
+                    .await
+                    .unwrap_or_default()
+            }))
+        };
+        self.rows = list_rows(&self.hits);
+        self.searched.replace(pattern);
+        self.active_idx = 0;
+        self.top_row = 0;
+    }
+
+    fn cycle_match_type(&mut self) {
+        let text = self.pattern.to_string();
+        self.pattern = match &self.pattern {
+            Pattern::CaseSensitiveString(_) => Pattern::CaseInSensitiveString(text),
+            Pattern::CaseInSensitiveString(_) => Pattern::Regex(text),
+            Pattern::Regex(_) => Pattern::CaseSensitiveString(text),
+        };
+    }
+
+    /// Returns the text of the heading for the pane that contains `hit`
+    fn heading(&self, hit: &GlobalSearchHit) -> String {
+        match self.scope {
+            SearchScope::Tab(_) => format!("Pane {}: {}", hit.pane_idx + 1, hit.pane_title),
+            SearchScope::Window(_) => format!(
+                "Tab {}, pane {}: {}",
+                hit.tab_idx + 1,
+                hit.pane_idx + 1,
+                hit.pane_title
+            ),
+        }
+    }
+
+    fn render(&mut self, term: &mut TermWizTerminal) -> termwiz::Result<()> {
+        let size = term.get_screen_size()?;
+        let max_width = size.cols.saturating_sub(6);
+
+        let mode = match &self.pattern {
+            Pattern::CaseSensitiveString(_) => "case-sensitive",
+            Pattern::CaseInSensitiveString(_) => "ignore-case",
+            Pattern::Regex(_) => "regex",
+        };
+        let status = match &self.searched {
+            Some(searched) if *searched == self.pattern => {
+                let num_panes = self.rows.len() - self.hits.len();
+                format!("{} matches in {} panes", self.hits.len(), num_panes)
+            }
+            _ => "Enter=search".to_string(),
+        };
+
+        let mut changes = vec![
+            Change::ClearScreen(ColorAttribute::Default),
+            Change::CursorPosition {
+                x: Position::Absolute(0),
+                y: Position::Absolute(0),
+            },
+            Change::Text(format!(
+                "{}\r\n",
+                truncate_right(
+                    &format!(
+                        "{}: {} ({status}. {mode})  \
+                         Enter=go to match  CTRL-R=match type  Esc=cancel",
+                        self.scope.title(),
+                        *self.pattern
+                    ),
+                    max_width
+                )
+            )),
+        ];
+
+        for (row_num, row) in self.rows.iter().skip(self.top_row).enumerate() {
+            if row_num > self.max_items {
+                break;
+            }
+
+            match *row {
+                ListRow::Pane(hit_idx) => {
+                    changes.push(AttributeChange::Intensity(Intensity::Bold).into());
+                    changes.push(Change::Text(format!(
+                        "{}\r\n",
+                        truncate_right(&self.heading(&self.hits[hit_idx]), max_width)
+                    )));
+                    changes.push(AttributeChange::Intensity(Intensity::Normal).into());
+                }
+                ListRow::Hit(hit_idx) => {
+                    if hit_idx == self.active_idx {
+                        changes.push(AttributeChange::Reverse(true).into());
+                    }
+
+                    changes.push(Change::Text(format!(
+                        "  {} \r\n",
+                        truncate_right(&self.hits[hit_idx].text, max_width)
+                    )));
+
+                    if hit_idx == self.active_idx {
+                        changes.push(AttributeChange::Reverse(false).into());
+                    }
+                }
+            }
+        }
+
+        term.render(&changes)
+    }
+
+    /// Activates the pane containing the hit and starts search
+    /// mode there, with the hit selected
+    fn go_to_hit(&self, hit_idx: usize) -> bool {
+        let hit = match self.hits.get(hit_idx) {
+            Some(hit) => hit.clone(),
+            None => return false,
+        };
+        let pattern = self.pattern.clone();
+        // Searching a tab continues in that mode in the pane
+        let all_panes = matches!(self.scope, SearchScope::Tab(_));
+
+        self.window
+            .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                let pane = match Mux::get().get_pane(hit.pane_id) {
+                    Some(pane) => pane,
+                    None => return,
+                };
+                let params = CopyModeParams {
+                    pattern,
+                    editing_search: false,
+                    all_panes,
+                };
+                match term_window.move_search_to_pane(hit.pane_id, &pane, params) {
+                    Ok(overlay) => {
+                        if let Some(copy_overlay) = overlay.downcast_ref::<CopyOverlay>() {
+                            copy_overlay.select_match_at(hit.start_x, hit.start_y);
+                        }
+                    }
+                    Err(err) => log::error!("move_search_to_pane: {:#}", err),
+                }
+            })));
+        true
+    }
+
+    /// Returns the index of the hit that is displayed on row `y`
+    /// of the overlay, taking the scroll position into account.
+    /// Returns None for pane headings.
+    fn hit_at_row(&self, y: u16) -> Option<usize> {
+        let row = self.top_row + (y as usize).checked_sub(1)?;
+        match self.rows.get(row)? {
+            ListRow::Hit(idx) => Some(*idx),
+            ListRow::Pane(_) => None,
+        }
+    }
+
+    /// Scrolls so that the selected hit is visible, along with
+    /// the heading of its pane if it is the first hit in that pane
+    fn scroll_to_active(&mut self) {
+        let row = match self
+            .rows
+            .iter()
+            .position(|row| *row == ListRow::Hit(self.active_idx))
+        {
+            Some(row) => row,
+            None => return,
+        };
+        let first = match row.checked_sub(1).map(|prior| self.rows[prior]) {
+            Some(ListRow::Pane(_)) => row - 1,
+            _ => row,
+        };
+        if first < self.top_row {
+            self.top_row = first;
+        } else if row > self.top_row + self.max_items {
+            self.top_row = row.saturating_sub(self.max_items);
+        }
+    }
+
+    fn move_up(&mut self) {
+        self.active_idx = self.active_idx.saturating_sub(1);
+        self.scroll_to_active();
+    }
+
+    fn move_down(&mut self) {
+        self.active_idx = (self.active_idx + 1).min(self.hits.len().saturating_sub(1));
+        self.scroll_to_active();
+    }
+
+    fn run_loop(&mut self, term: &mut TermWizTerminal) -> anyhow::Result<()> {
+        while let Ok(Some(event)) = term.poll_input(None) {
+            match event {
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('G') | KeyCode::Char('['),
+                    modifiers: Modifiers::CTRL,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::Escape,
+                    ..
+                }) => {
+                    break;
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('R'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.cycle_match_type();
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('U'),
+                    modifiers: Modifiers::CTRL,
+                }) => {
+                    self.pattern.clear();
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('P'),
+                    modifiers: Modifiers::CTRL,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::UpArrow,
+                    ..
+                }) => {
+                    self.move_up();
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char('N'),
+                    modifiers: Modifiers::CTRL,
+                })
+                | InputEvent::Key(KeyEvent {
+                    key: KeyCode::DownArrow,
+                    ..
+                }) => {
+                    self.move_down();
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Backspace,
+                    ..
+                }) => {
+                    self.pattern.pop();
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Char(c),
+                    modifiers: Modifiers::NONE | Modifiers::SHIFT,
+                }) => {
+                    self.pattern.push(c);
+                }
+                InputEvent::Key(KeyEvent {
+                    key: KeyCode::Enter,
+                    ..
+                }) => {
+                    if self.searched.as_ref() != Some(&self.pattern) {
+                        self.search();
+                    } else if self.go_to_hit(self.active_idx) {
+                        break;
+                    }
+                }
+                InputEvent::Mouse(MouseEvent {
+                    y, mouse_buttons, ..
+                }) if mouse_buttons.contains(MouseButtons::VERT_WHEEL) => {
+                    if mouse_buttons.contains(MouseButtons::WHEEL_POSITIVE) {
+                        self.top_row = self.top_row.saturating_sub(1);
+                    } else {
+                        self.top_row += 1;
+                        self.top_row = self.top_row.min(
+                            self.rows
+                                .len()
+                                .saturating_sub(self.max_items)
+                                .saturating_sub(1),
+                        );
+                    }
+                    if let Some(idx) = self.hit_at_row(y) {
+                        self.active_idx = idx;
+                    }
+                }
+                InputEvent::Mouse(MouseEvent {
+                    y, mouse_buttons, ..
+                }) => {
+                    if let Some(idx) = self.hit_at_row(y) {
+                        self.active_idx = idx;
+
+                        if mouse_buttons == MouseButtons::LEFT {
+                            if self.go_to_hit(self.active_idx) {
+                                break;
+                            }
+                        }
+                    }
+                    if mouse_buttons != MouseButtons::NONE {
+                        // Treat any other mouse button as cancel
+                        break;
+                    }
+                }
+                InputEvent::Resized { rows, .. } => {
+                    self.max_items = rows.saturating_sub(ROW_OVERHEAD);
+                }
+                _ => {}
+            }
+            self.render(term)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn global_search(
+    mut term: TermWizTerminal,
+    window: ::window::Window,
+    scope: SearchScope,
+    pattern: Pattern,
+) -> anyhow::Result<()> {
+    let size = term.get_screen_size()?;
+    let mut state = GlobalSearchState {
+        pattern,
+        searched: None,
+        hits: vec![],
+        rows: vec![],
+        active_idx: 0,
+        max_items: size.rows.saturating_sub(ROW_OVERHEAD),
+        top_row: 0,
+        scope,
+        window,
+    };
+
+    term.set_raw_mode()?;
+    term.render(&[Change::Title(scope.title().to_string())])?;
+    if !state.pattern.is_empty() {
+        state.search();
+    }
+    state.render(&mut term)?;
+    state.run_loop(&mut term)
+}
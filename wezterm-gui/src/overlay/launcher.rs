@@ -18,6 +18,7 @@ use mux::window::WindowId;
 use mux::Mux;
 use rayon::prelude::*;
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 use termwiz::cell::{AttributeChange, CellAttributes};
 use termwiz::color::ColorAttribute;
 use termwiz::input::{InputEvent, KeyCode, KeyEvent, Modifiers, MouseButtons, MouseEvent};
@@ -57,6 +58,40 @@ pub struct LauncherArgs {
     title: String,
     active_workspace: String,
     workspaces: Vec<String>,
+    recordings: Vec<PathBuf>,
+}
+
+/// The maximum number of recordings to list in the launcher
+const MAX_RECORDINGS: usize = 20;
+
+/// Returns the most recently modified asciicast files
+/// in the recording_directory, newest first
+fn recent_recordings() -> Vec<PathBuf> {
+    let dir = mux::recording::recording_directory();
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::debug!("reading recordings from {}: {:#}", dir.display(), err);
+            return vec![];
+        }
+    };
+    let mut recordings: Vec<_> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let path = entry.path();
+            if path.extension()? != "cast" {
+                return None;
+            }
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, path))
+        })
+        .collect();
+    recordings.sort_by(|a, b| b.0.cmp(&a.0));
+    recordings
+        .into_iter()
+        .take(MAX_RECORDINGS)
+        .map(|(_modified, path)| path)
+        .collect()
 }
 
 impl LauncherArgs {
@@ -146,6 +181,16 @@ impl LauncherArgs {
             vec![]
         };
 
+        let recordings = if flags.contains(LauncherFlags::RECORDINGS) {
+            // The recording_directory may be slow to read, for example
+            // when it is on a network filesystem, so avoid blocking here
+            promise::spawn::spawn_into_new_thread(|| Ok(recent_recordings()))
+                .await
+                .unwrap_or_default()
+        } else {
+            vec![]
+        };
+
         Self {
             flags,
             domains,
@@ -155,6 +200,7 @@ impl LauncherArgs {
             title: title.to_string(),
             workspaces,
             active_workspace,
+            recordings,
         }
     }
 }
@@ -213,27 +259,41 @@ impl LauncherState {
 
     fn build_entries(&mut self, args: LauncherArgs) {
         let config = configuration();
+
+        // Launch menu items that spawn into one of the listed domains
+        // are grouped beneath the entry for that domain
+        let mut items_by_domain: BTreeMap<&str, Vec<Entry>> = BTreeMap::new();
+
         // Pull in the user defined entries from the launch_menu
         // section of the configuration.
         if args.flags.contains(LauncherFlags::LAUNCH_MENU_ITEMS) {
             for item in &config.launch_menu {
-                self.entries.push(Entry {
-                    label: match item.label.as_ref() {
-                        Some(label) => label.to_string(),
-                        None => match item.args.as_ref() {
-                            Some(args) => args.join(" "),
-                            None => "(default shell)".to_string(),
-                        },
+                let label = match item.label.as_ref() {
+                    Some(label) => label.to_string(),
+                    None => match item.args.as_ref() {
+                        Some(args) => args.join(" "),
+                        None => "(default shell)".to_string(),
                     },
-                    action: KeyAssignment::SpawnCommandInNewTab(item.clone()),
-                });
+                };
+                let action = KeyAssignment::SpawnCommandInNewTab(item.clone());
+                match &item.domain {
+                    SpawnTabDomain::DomainName(name)
+                        if args.domains.iter().any(|dom| dom.name == *name) =>
+                    {
+                        items_by_domain.entry(name).or_default().push(Entry {
+                            label: format!("  {label}"),
+                            action,
+                        });
+                    }
+                    _ => self.entries.push(Entry { label, action }),
+                }
             }
         }
 
         for domain in &args.domains {
             let entry = if domain.state == DomainState::Attached {
                 Entry {
-                    label: format!("New Tab ({})", domain.label),
+                    label: format!("New Tab ({}) [attached]", domain.label),
                     action: KeyAssignment::SpawnCommandInNewTab(SpawnCommand {
                         domain: SpawnTabDomain::DomainName(domain.name.to_string()),
                         ..SpawnCommand::default()
@@ -241,7 +301,7 @@ impl LauncherState {
                 }
             } else {
                 Entry {
-                    label: format!("Attach {}", domain.label),
+                    label: format!("Attach {} [detached]", domain.label),
                     action: KeyAssignment::AttachDomain(domain.name.to_string()),
                 }
             };
@@ -253,6 +313,9 @@ impl LauncherState {
                 self.active_idx = self.entries.len();
             }
             self.entries.push(entry);
+            if let Some(items) = items_by_domain.remove(domain.name.as_str()) {
+                self.entries.extend(items);
+            }
         }
 
         if args.flags.contains(LauncherFlags::WORKSPACES) {
@@ -279,6 +342,17 @@ impl LauncherState {
             });
         }
 
+        for path in &args.recordings {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string());
+            self.entries.push(Entry {
+                label: format!("Replay recording `{}`", name),
+                action: KeyAssignment::ReplayRecording(path.to_string_lossy().to_string()),
+            });
+        }
+
         for tab in &args.tabs {
             self.entries.push(Entry {
                 label: match tab.pane_count {
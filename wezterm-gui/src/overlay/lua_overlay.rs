@@ -0,0 +1,99 @@
+//! Implements the ShowOverlay key assignment, which shows an
+//! overlay whose content is produced by a lua function, and
+//! passes each line of input entered by the user back to it.
+use crate::overlay::prompt::PromptHost;
+use crate::scripting::guiwin::GuiWin;
+use config::keyassignment::{KeyAssignment, ShowOverlay};
+use mux::termwiztermtab::TermWizTerminal;
+use mux_lua::MuxPane;
+use termwiz::lineedit::*;
+use termwiz::surface::{Change, CursorVisibility};
+use termwiz::terminal::Terminal;
+
+pub fn show_lua_overlay(
+    mut term: TermWizTerminal,
+    args: ShowOverlay,
+    window: GuiWin,
+    pane: MuxPane,
+) -> anyhow::Result<()> {
+    let name = match *args.action {
+        KeyAssignment::EmitEvent(id) => id,
+        _ => anyhow::bail!("ShowOverlay requires action to be defined by wezterm.action_callback"),
+    };
+
+    term.no_grab_mouse_in_raw_mode();
+    if !args.title.is_empty() {
+        term.render(&[Change::Title(args.title.clone())])?;
+    }
+
+    let mut host = PromptHost::new();
+    let mut line = None;
+    loop {
+        let text = match call_overlay_function(&name, &window, &pane, line.take())? {
+            Some(text) => text,
+            None => return Ok(()),
+        };
+
+        term.render(&[
+            Change::ClearScreen(Default::default()),
+            Change::CursorVisibility(CursorVisibility::Visible),
+            Change::Text(text.replace("\r\n", "\n").replace("\n", "\r\n")),
+            Change::Text("\r\n".to_string()),
+        ])?;
+
+        let mut editor = LineEditor::new(&mut term);
+        editor.set_prompt("> ");
+        match editor.read_line(&mut host)? {
+            Some(entered) => {
+                host.history().add(&entered);
+                line.replace(entered);
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Calls the lua function on the gui thread and waits for the
+/// text that it returns
+fn call_overlay_function(
+    name: &str,
+    window: &GuiWin,
+    pane: &MuxPane,
+    line: Option<String>,
+) -> anyhow::Result<Option<String>> {
+    let name = name.to_string();
+    let window = window.clone();
+    let pane = *pane;
+    smol::block_on(promise::spawn::spawn_into_main_thread(async move {
+        trampoline(name, window, pane, line)
+            .recv()
+            .await
+            .map_err(|err| anyhow::anyhow!("{:#}", err))?
+    }))
+}
+
+// mlua values are not Send, so the evaluation is spawned
+// separately and its result is funneled back via a channel.
+fn trampoline(
+    name: String,
+    window: GuiWin,
+    pane: MuxPane,
+    line: Option<String>,
+) -> smol::channel::Receiver<anyhow::Result<Option<String>>> {
+    let (tx, rx) = smol::channel::bounded(1);
+    promise::spawn::spawn(async move {
+        let result = config::with_lua_config_on_main_thread(move |lua| async move {
+            let lua = lua.ok_or_else(|| anyhow::anyhow!("missing lua context"))?;
+            let value =
+                config::lua::emit_async_callback(&*lua, (name.clone(), (window, pane, line)))
+                    .await?;
+            let text: Option<String> = luahelper::from_lua_value_dynamic(value)
+                .map_err(|err| anyhow::anyhow!("ShowOverlay {}: {:#}", name, err))?;
+            Ok(text)
+        })
+        .await;
+        tx.send(result).await.ok();
+    })
+    .detach();
+    rx
+}
@@ -6,16 +6,20 @@ use std::pin::Pin;
 use std::sync::Arc;
 use wezterm_term::{TerminalConfiguration, TerminalSize};
 
+pub mod config_error;
 pub mod confirm_close_pane;
 pub mod copy;
 pub mod debug;
+pub mod global_search;
 pub mod launcher;
+pub mod lua_overlay;
 pub mod prompt;
 pub mod quickselect;
 pub mod selector;
 
 pub use confirm_close_pane::{
-    confirm_close_pane, confirm_close_tab, confirm_close_window, confirm_quit_program,
+    confirm_close_pane, confirm_close_tab, confirm_close_window, confirm_large_paste,
+    confirm_quit_program, confirm_window_resize,
 };
 pub use copy::{CopyModeParams, CopyOverlay};
 pub use debug::show_debug_overlay;
@@ -8,12 +8,12 @@ use termwiz::lineedit::*;
 use termwiz::surface::Change;
 use termwiz::terminal::Terminal;
 
-struct PromptHost {
+pub(crate) struct PromptHost {
     history: BasicHistory,
 }
 
 impl PromptHost {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             history: BasicHistory::default(),
         }
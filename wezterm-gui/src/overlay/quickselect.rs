@@ -15,6 +15,7 @@ use std::ops::Range;
 use std::sync::Arc;
 use termwiz::cell::{Cell, CellAttributes};
 use termwiz::color::AnsiColor;
+use termwiz::hyperlink::Hyperlink;
 use termwiz::surface::{SequenceNo, SEQ_ZERO};
 use url::Url;
 use wezterm_term::color::ColorPalette;
@@ -23,7 +24,7 @@ use wezterm_term::{
 };
 use window::WindowOps;
 
-const PATTERNS: [&str; 14] = [
+const PATTERNS: [&str; 15] = [
     // markdown_url
     r"\[[^]]*\]\(([^)]+)\)",
     // url
@@ -34,6 +35,8 @@ const PATTERNS: [&str; 14] = [
     r"\+\+\+ b/(\S+)",
     // docker
     r"sha256:([0-9a-f]{64})",
+    // path with line number, eg: compiler error locations
+    r"[.\w\-@~/]*[\w\-@]\.\w+:\d+(?::\d+)?",
     // path
     r"(?:[.\w\-@~]+)?(?:/+[.\w\-@]+)+",
     // color
@@ -227,6 +230,10 @@ struct QuickSelectRenderable {
 
     config: ConfigHandle,
     args: QuickSelectArguments,
+
+    /// When operating in link hint mode, holds the distinct hyperlinks
+    /// in the viewport, indexed by the match_id of the results
+    links: Option<Vec<Arc<Hyperlink>>>,
 }
 
 impl QuickSelectOverlay {
@@ -235,10 +242,7 @@ impl QuickSelectOverlay {
         pane: &Arc<dyn Pane>,
         args: &QuickSelectArguments,
     ) -> Arc<dyn Pane> {
-        let viewport = term_window.get_viewport(pane.pane_id());
-        let dims = pane.get_dimensions();
-
-        let config = term_window.config.clone();
+        let config = &term_window.config;
 
         let mut pattern = "(?m)(".to_string();
         let mut have_patterns = false;
@@ -271,7 +275,35 @@ impl QuickSelectOverlay {
         }
         pattern.push(')');
 
-        let pattern = Pattern::Regex(pattern);
+        Self::new(term_window, pane, args, Pattern::Regex(pattern), None)
+    }
+
+    /// Creates an overlay that labels only the hyperlinks in the viewport,
+    /// and that opens the link whose label is typed in
+    pub fn with_links(term_window: &TermWindow, pane: &Arc<dyn Pane>) -> Arc<dyn Pane> {
+        let args = QuickSelectArguments {
+            label: "open".to_string(),
+            ..Default::default()
+        };
+        Self::new(
+            term_window,
+            pane,
+            &args,
+            Pattern::Regex(String::new()),
+            Some(vec![]),
+        )
+    }
+
+    fn new(
+        term_window: &TermWindow,
+        pane: &Arc<dyn Pane>,
+        args: &QuickSelectArguments,
+        pattern: Pattern,
+        links: Option<Vec<Arc<Hyperlink>>>,
+    ) -> Arc<dyn Pane> {
+        let viewport = term_window.get_viewport(pane.pane_id());
+        let dims = pane.get_dimensions();
+        let config = term_window.config.clone();
 
         let window = term_window.window.clone().unwrap();
         let mut renderer = QuickSelectRenderable {
@@ -290,6 +322,7 @@ impl QuickSelectOverlay {
             height: dims.viewport_rows,
             config,
             args: args.clone(),
+            links,
         };
 
         let search_row = renderer.compute_search_row();
@@ -312,6 +345,10 @@ impl QuickSelectOverlay {
                 render.dirty_results.add(*pos);
             }
             render.viewport = viewport;
+            if render.links.is_some() {
+                // Only the links in the viewport are labelled
+                render.update_search(false);
+            }
         }
     }
 }
@@ -418,7 +455,11 @@ impl Pane for QuickSelectOverlay {
                 let lowered = r.selection.to_lowercase();
                 let paste = lowered != r.selection;
                 if let Some(result_index) = r.by_label.get(&lowered).cloned() {
-                    r.select_and_copy_match_number(result_index, paste);
+                    if r.links.is_some() {
+                        r.open_link_number(result_index);
+                    } else {
+                        r.select_and_copy_match_number(result_index, paste);
+                    }
                     r.close();
                 }
             }
@@ -561,15 +602,7 @@ impl Pane for QuickSelectOverlay {
                         line.fill_range(0..self.dims.cols, &Cell::new(' ', rev.clone()), SEQ_ZERO);
                         line.overlay_text_with_attribute(
                             0,
-                            &format!(
-                                "Select: {}  (type highlighted prefix to {}, uppercase pastes, ESC to cancel)",
-                                self.renderer.selection,
-                                if self.renderer.args.label.is_empty() {
-                                    "copy"
-                                } else {
-                                    &self.renderer.args.label
-                                },
-                            ),
+                            &self.renderer.search_bar_text(),
                             rev,
                             SEQ_ZERO,
                         );
@@ -645,20 +678,7 @@ impl Pane for QuickSelectOverlay {
                 // Replace with search UI
                 let rev = CellAttributes::default().set_reverse(true).clone();
                 line.fill_range(0..dims.cols, &Cell::new(' ', rev.clone()), SEQ_ZERO);
-                line.overlay_text_with_attribute(
-                    0,
-                    &format!(
-                        "Select: {}  (type highlighted prefix to {}, uppercase pastes, ESC to cancel)",
-                        renderer.selection,
-                        if renderer.args.label.is_empty() {
-                            "copy"
-                        } else {
-                            &renderer.args.label
-                        },
-                    ),
-                    rev,
-                    SEQ_ZERO,
-                );
+                line.overlay_text_with_attribute(0, &renderer.search_bar_text(), rev, SEQ_ZERO);
                 renderer.last_bar_pos = Some(search_row);
             } else if let Some(matches) = renderer.by_line.get(&stable_idx) {
                 for m in matches {
@@ -711,6 +731,26 @@ impl Pane for QuickSelectOverlay {
 }
 
 impl QuickSelectRenderable {
+    /// Returns the text shown in the search row
+    fn search_bar_text(&self) -> String {
+        if self.links.is_some() {
+            format!(
+                "Open: {}  (type highlighted prefix to open link, ESC to cancel)",
+                self.selection,
+            )
+        } else {
+            format!(
+                "Select: {}  (type highlighted prefix to {}, uppercase pastes, ESC to cancel)",
+                self.selection,
+                if self.args.label.is_empty() {
+                    "copy"
+                } else {
+                    &self.args.label
+                },
+            )
+        }
+    }
+
     fn compute_search_row(&self) -> StableRowIndex {
         let dims = self.delegate.get_dimensions();
         let top = self.viewport.unwrap_or_else(|| dims.physical_top);
@@ -838,7 +878,13 @@ impl QuickSelectRenderable {
         let bar_pos = self.compute_search_row();
         self.dirty_results.add(bar_pos);
 
-        if !self.pattern.is_empty() {
+        if self.links.is_some() {
+            self.find_links();
+            self.recompute_results();
+            if self.results.is_empty() {
+                self.clear_selection();
+            }
+        } else if !self.pattern.is_empty() {
             let pane: Arc<dyn Pane> = self.delegate.clone();
             let window = self.window.clone();
             let pattern = self.pattern.clone();
@@ -954,6 +1000,85 @@ impl QuickSelectRenderable {
             })));
     }
 
+    /// Collects the hyperlinks in the viewport, both the explicit ones
+    /// and those produced by the hyperlink_rules, into the results
+    fn find_links(&mut self) {
+        fn add_result(
+            links: &mut Vec<Arc<Hyperlink>>,
+            results: &mut Vec<SearchResult>,
+            y: StableRowIndex,
+            (link, start_x, end_x): (Arc<Hyperlink>, usize, usize),
+        ) {
+            let match_id = match links.iter().position(|l| **l == *link) {
+                Some(idx) => idx,
+                None => {
+                    links.push(link);
+                    links.len() - 1
+                }
+            };
+            results.push(SearchResult {
+                start_y: y,
+                start_x,
+                end_y: y,
+                end_x,
+                match_id,
+            });
+        }
+
+        let dims = self.delegate.get_dimensions();
+        let top = self.viewport.unwrap_or(dims.physical_top);
+        // The bottom row of the viewport is occupied by our prompt
+        let range = top..self.compute_search_row();
+
+        self.delegate
+            .apply_hyperlinks(range.clone(), &self.config.hyperlink_rules);
+        let (first_row, lines) = self.delegate.get_lines(range);
+
+        let mut links = vec![];
+        let mut results = vec![];
+        for (idx, line) in lines.iter().enumerate() {
+            let y = first_row + idx as StableRowIndex;
+            let mut current: Option<(Arc<Hyperlink>, usize, usize)> = None;
+            for cell in line.visible_cells() {
+                let link = cell.attrs().hyperlink();
+                if let (Some((cur, _, end)), Some(link)) = (current.as_mut(), link) {
+                    if cur == link {
+                        *end = cell.cell_index() + cell.width();
+                        continue;
+                    }
+                }
+                if let Some(run) = current.take() {
+                    add_result(&mut links, &mut results, y, run);
+                }
+                if let Some(link) = link {
+                    let start = cell.cell_index();
+                    current.replace((Arc::clone(link), start, start + cell.width()));
+                }
+            }
+            if let Some(run) = current.take() {
+                add_result(&mut links, &mut results, y, run);
+            }
+        }
+
+        self.results = results;
+        self.links.replace(links);
+    }
+
+    fn open_link_number(&mut self, n: usize) {
+        let link = match (self.links.as_ref(), self.results.get(n)) {
+            (Some(links), Some(result)) => Arc::clone(&links[result.match_id]),
+            _ => return,
+        };
+        let pane_id = self.delegate.pane_id();
+        self.window
+            .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                let mux = mux::Mux::get();
+                if let Some(pane) = mux.get_pane(pane_id) {
+                    term_window.open_link(&pane, &link);
+                }
+            })));
+    }
+
     fn activate_match_number(&mut self, n: usize) {
         self.result_pos.replace(n);
         let result = self.results[n].clone();
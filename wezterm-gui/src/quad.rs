@@ -222,7 +222,7 @@ pub trait TripleLayerQuadAllocatorTrait {
 /// and the total amount of contiguous memory is in the MB range,
 /// which is a bit gnarly to reallocate, and can waste several MB
 /// in unused capacity
-#[derive(Default)]
+#[derive(Default, Clone, Copy)]
 pub struct BoxedQuad {
     position: (f32, f32, f32, f32),
     fg_color: [f32; 4],
@@ -279,6 +279,28 @@ impl BoxedQuad {
         }
     }
 
+    /// Returns the portion of this quad that lies between `top` and `bottom`,
+    /// or None if it lies entirely outside of that range
+    fn clip_y(&self, top: f32, bottom: f32) -> Option<Self> {
+        let (left, quad_top, right, quad_bottom) = self.position;
+        if quad_top >= top && quad_bottom <= bottom {
+            return Some(*self);
+        }
+        let clipped_top = quad_top.max(top);
+        let clipped_bottom = quad_bottom.min(bottom);
+        if clipped_top >= clipped_bottom {
+            return None;
+        }
+
+        let (x1, x2, y1, y2) = self.tex;
+        let tex_y = |y: f32| y1 + (y2 - y1) * (y - quad_top) / (quad_bottom - quad_top);
+        Some(Self {
+            position: (left, clipped_top, right, clipped_bottom),
+            tex: (x1, x2, tex_y(clipped_top), tex_y(clipped_bottom)),
+            ..*self
+        })
+    }
+
     fn to_vertices(&self) -> [Vertex; VERTICES_PER_CELL] {
         let mut vert: [Vertex; VERTICES_PER_CELL] = Default::default();
         let mut quad = Quad { vert: &mut vert };
@@ -332,6 +354,25 @@ impl HeapQuadAllocator {
         metrics::histogram!("quad_buffer_apply").record(start.elapsed());
         Ok(())
     }
+
+    /// Like apply_to, but only the portions of the quads that lie
+    /// vertically between `top` and `bottom` are applied; the texture
+    /// coordinates of quads that straddle either edge are adjusted to match
+    pub fn apply_clipped_to(
+        &self,
+        other: &mut TripleLayerQuadAllocator,
+        top: f32,
+        bottom: f32,
+    ) -> anyhow::Result<()> {
+        for (layer_num, quads) in [(0, &self.layer0), (1, &self.layer1), (2, &self.layer2)] {
+            for quad in quads {
+                if let Some(clipped) = quad.clip_y(top, bottom) {
+                    other.extend_with(layer_num, &clipped.to_vertices());
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl TripleLayerQuadAllocatorTrait for HeapQuadAllocator {
@@ -401,3 +442,28 @@ fn size() {
     assert_eq!(std::mem::size_of::<Vertex>() * VERTICES_PER_CELL, 272);
     assert_eq!(std::mem::size_of::<BoxedQuad>(), 84);
 }
+
+#[cfg(test)]
+#[test]
+fn clip_y() {
+    let quad = BoxedQuad {
+        position: (0., 10., 8., 30.),
+        tex: (0., 1., 0., 1.),
+        ..BoxedQuad::default()
+    };
+
+    let inside = quad.clip_y(0., 40.).unwrap();
+    assert_eq!(inside.position, quad.position);
+    assert_eq!(inside.tex, quad.tex);
+
+    let top = quad.clip_y(15., 40.).unwrap();
+    assert_eq!(top.position, (0., 15., 8., 30.));
+    assert_eq!(top.tex, (0., 1., 0.25, 1.));
+
+    let bottom = quad.clip_y(0., 25.).unwrap();
+    assert_eq!(bottom.position, (0., 10., 8., 25.));
+    assert_eq!(bottom.tex, (0., 1., 0., 0.75));
+
+    assert!(quad.clip_y(30., 40.).is_none());
+    assert!(quad.clip_y(0., 10.).is_none());
+}
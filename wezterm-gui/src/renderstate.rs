@@ -109,7 +109,12 @@ impl RenderContext {
     pub fn renderer_info(&self) -> String {
         match self {
             Self::Glium(ctx) => format!(
-                "OpenGL: {} {}",
+                "{}: {} {} {}",
+                match ctx.get_opengl_version().0 {
+                    ::window::glium::Api::GlEs => "OpenGL ES",
+                    ::window::glium::Api::Gl => "OpenGL",
+                },
+                ctx.get_opengl_vendor_string(),
                 ctx.get_opengl_renderer_string(),
                 ctx.get_opengl_version_string()
             ),
@@ -497,6 +502,10 @@ impl RenderLayer {
         self.vb.borrow()[vb_idx].need_more_quads()
     }
 
+    pub fn quad_capacity(&self, vb_idx: usize) -> usize {
+        self.vb.borrow()[vb_idx].capacity
+    }
+
     pub fn reallocate_quads(&self, idx: usize, num_quads: usize) -> anyhow::Result<()> {
         let vb = Self::compute_vertices(&self.context, num_quads)?;
         self.vb.borrow_mut()[idx] = vb;
@@ -568,11 +577,21 @@ impl TripleLayerQuadAllocatorTrait for BorrowedLayers {
     }
 }
 
+/// Returns true if the shader source refers to the `milliseconds` uniform
+fn shader_uses_time(source: &str) -> bool {
+    source
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .any(|word| word == "milliseconds")
+}
+
 pub struct RenderState {
     pub context: RenderContext,
     pub glyph_cache: RefCell<GlyphCache>,
     pub util_sprites: UtilSprites,
     pub glyph_prog: Option<glium::Program>,
+    /// The source of the window_background_shader that
+    /// glyph_prog was compiled with
+    background_shader: Option<String>,
     pub layers: RefCell<Vec<Rc<RenderLayer>>>,
 }
 
@@ -590,7 +609,9 @@ impl RenderState {
                 Ok(util_sprites) => {
                     let glyph_prog = match &context {
                         RenderContext::Glium(context) => {
-                            Some(Self::compile_prog(&context, Self::glyph_shader)?)
+                            Some(Self::compile_prog(&context, |version| {
+                                Self::glyph_shader(version, None)
+                            })?)
                         }
                         RenderContext::WebGpu(_) => None,
                     };
@@ -602,6 +623,7 @@ impl RenderState {
                         glyph_cache,
                         util_sprites,
                         glyph_prog,
+                        background_shader: None,
                         layers: RefCell::new(vec![main_layer]),
                     });
                 }
@@ -649,9 +671,11 @@ impl RenderState {
         for layer in self.layers.borrow().iter() {
             for vb_idx in 0..3 {
                 if let Some(need_quads) = layer.need_more_quads(vb_idx) {
-                    // Round up to next multiple of 128 that is >=
-                    // the number of needed quads for this frame
-                    let num_quads = (need_quads + 127) & !127;
+                    // Leave some headroom so that a frame that is only
+                    // slightly busier than this one doesn't require
+                    // another allocation and render pass, and round up
+                    // to the next multiple of 128
+                    let num_quads = (need_quads + need_quads / 4 + 127) & !127;
                     layer.reallocate_quads(vb_idx, num_quads).with_context(|| {
                         format!(
                             "Failed to allocate {} quads (needed {})",
@@ -667,9 +691,65 @@ impl RenderState {
         Ok(allocated)
     }
 
+    /// Ensures that the background and glyph vertex buffers of the
+    /// main layer can hold `num_quads` quads without needing to be
+    /// grown mid-frame.  This is called with an estimate derived from
+    /// the size of the cell grid, so that growing the window doesn't
+    /// cost an extra render pass to discover that we need more quads.
+    pub fn reserve_quads(&self, num_quads: usize) -> anyhow::Result<()> {
+        let layer = self.layer_for_zindex(0)?;
+        for vb_idx in 0..2 {
+            if layer.quad_capacity(vb_idx) < num_quads {
+                let num_quads = (num_quads + 127) & !127;
+                layer
+                    .reallocate_quads(vb_idx, num_quads)
+                    .with_context(|| format!("Failed to reserve {} quads", num_quads))?;
+                log::trace!("Reserved {} quads for layer {}", num_quads, vb_idx);
+            }
+        }
+        Ok(())
+    }
+
+    /// Recompiles glyph_prog with the window_background_shader source
+    /// spliced in, or without it if source is None.
+    /// If the shader fails to compile, the current program is retained.
+    pub fn set_background_shader(&mut self, source: Option<String>) -> anyhow::Result<()> {
+        if source == self.background_shader {
+            return Ok(());
+        }
+        let context = match &self.context {
+            RenderContext::Glium(context) => context,
+            RenderContext::WebGpu(_) => {
+                if source.is_some() {
+                    log::warn!(
+                        "window_background_shader is not supported with front_end=\"WebGpu\""
+                    );
+                }
+                return Ok(());
+            }
+        };
+        let prog = Self::compile_prog(context, |version| {
+            Self::glyph_shader(version, source.as_deref())
+        })
+        .context("compiling window_background_shader")?;
+        self.glyph_prog.replace(prog);
+        self.background_shader = source;
+        Ok(())
+    }
+
+    /// Returns true if glyph_prog includes a window_background_shader
+    /// that reads the `milliseconds` uniform, and so needs the window
+    /// to be repainted continuously in order to animate
+    pub fn has_animated_background_shader(&self) -> bool {
+        self.background_shader
+            .as_deref()
+            .map(shader_uses_time)
+            .unwrap_or(false)
+    }
+
     fn compile_prog(
         context: &Rc<GliumContext>,
-        fragment_shader: fn(&str) -> (String, String),
+        fragment_shader: impl Fn(&str) -> (String, String),
     ) -> anyhow::Result<glium::Program> {
         let mut errors = vec![];
 
@@ -699,14 +779,22 @@ impl RenderState {
         anyhow::bail!("Failed to compile shaders: {}", errors.join("\n"))
     }
 
-    fn glyph_shader(version: &str) -> (String, String) {
+    fn glyph_shader(version: &str, background_shader: Option<&str>) -> (String, String) {
         (
             format!(
                 "#version {}\n{}",
                 version,
                 include_str!("glyph-vertex.glsl")
             ),
-            format!("#version {}\n{}", version, include_str!("glyph-frag.glsl")),
+            match background_shader {
+                Some(background_shader) => format!(
+                    "#version {}\n#define HAS_BACKGROUND_SHADER\n{}\n{}",
+                    version,
+                    include_str!("glyph-frag.glsl"),
+                    background_shader
+                ),
+                None => format!("#version {}\n{}", version, include_str!("glyph-frag.glsl")),
+            },
         )
     }
 
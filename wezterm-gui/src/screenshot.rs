@@ -0,0 +1,385 @@
+//! Saving screenshots of panes as PNG images.
+//! The GUI captures the rendered frame from the GPU; see
+//! termwindow/screenshot.rs. The headless `wezterm screenshot`
+//! command has no frame to capture, so it composites the glyphs
+//! from the same fonts and glyph cache that the GUI uses.
+use crate::customglyph::BlockKey;
+use crate::glyphcache::GlyphCache;
+use crate::utilsprites::RenderMetrics;
+use ::window::bitmaps::atlas::Sprite;
+use ::window::bitmaps::ImageTexture;
+use anyhow::Context;
+use config::ConfigHandle;
+use image::{ImageFormat, RgbaImage};
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::rc::Rc;
+use termwiz::cell::{CellAttributes, Underline};
+use termwiz::surface::Line;
+use tiny_skia::{Color, Paint, Pixmap, PixmapPaint, PremultipliedColorU8, Rect, Transform};
+use wezterm_font::shaper::PresentationWidth;
+use wezterm_font::FontConfiguration;
+use wezterm_gui_subcommands::{ScreenshotCommand, DEFAULT_WINDOW_CLASS};
+use wezterm_term::color::{ColorPalette, SrgbaTuple};
+use wezterm_term::StableRowIndex;
+
+/// Size of the in-memory glyph atlas used for software rendering
+const ATLAS_SIZE: usize = 2048;
+
+/// Returns the path to use for a new screenshot
+pub fn screenshot_path(config: &ConfigHandle) -> PathBuf {
+    let dir = config
+        .screenshot_directory
+        .clone()
+        .or_else(dirs_next::picture_dir)
+        .or_else(dirs_next::home_dir)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let now = chrono::Local::now();
+    dir.join(format!(
+        "wezterm-screenshot-{}.png",
+        now.format("%Y%m%d-%H%M%S")
+    ))
+}
+
+/// Encodes image as a PNG
+pub fn encode_png(image: &RgbaImage) -> anyhow::Result<Vec<u8>> {
+    let mut png = vec![];
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .context("encoding PNG")?;
+    Ok(png)
+}
+
+fn to_color(color: SrgbaTuple) -> Color {
+    let (r, g, b, a) = color.to_srgb_u8();
+    Color::from_rgba8(r, g, b, a)
+}
+
+fn fill_rect(pixmap: &mut Pixmap, x: usize, y: usize, width: usize, height: usize, color: Color) {
+    if let Some(rect) = Rect::from_xywh(x as f32, y as f32, width as f32, height as f32) {
+        let mut paint = Paint::default();
+        paint.set_color(color);
+        pixmap.fill_rect(rect, &paint, Transform::identity(), None);
+    }
+}
+
+/// Resolves the foreground and background colors of attrs,
+/// taking reverse video into account
+fn cell_colors(attrs: &CellAttributes, palette: &ColorPalette) -> (Color, Color) {
+    let fg = palette.resolve_fg(attrs.foreground());
+    let bg = palette.resolve_bg(attrs.background());
+    if attrs.reverse() {
+        (to_color(bg), to_color(fg))
+    } else {
+        (to_color(fg), to_color(bg))
+    }
+}
+
+/// Copies the glyph in sprite out of the atlas.
+/// Color glyphs keep their own colors, while other glyphs
+/// are used as a coverage mask for fg.
+fn sprite_pixmap(sprite: &Sprite, has_color: bool, fg: Color) -> Option<Pixmap> {
+    let tex = sprite.texture.downcast_ref::<ImageTexture>()?;
+    let tex = tex.image.borrow();
+    let mut pixmap = Pixmap::new(sprite.coords.width() as u32, sprite.coords.height() as u32)?;
+    let fg = fg.premultiply().to_color_u8();
+
+    let min_x = sprite.coords.min_x() as usize;
+    let max_x = sprite.coords.max_x() as usize;
+    let width = pixmap.width() as usize;
+    for (y, row) in pixmap.pixels_mut().chunks_mut(width).enumerate() {
+        let src = tex.horizontal_pixel_range(min_x, max_x, sprite.coords.min_y() as usize + y);
+        for (dest, &px) in row.iter_mut().zip(src.iter()) {
+            // The atlas holds premultiplied RGBA, just like tiny-skia
+            let [r, g, b, a] = px.to_ne_bytes();
+            let scale = |c: u8| ((c as u16 * a as u16) / 255) as u8;
+            let px = if has_color {
+                PremultipliedColorU8::from_rgba(r, g, b, a)
+            } else {
+                PremultipliedColorU8::from_rgba(
+                    scale(fg.red()),
+                    scale(fg.green()),
+                    scale(fg.blue()),
+                    scale(fg.alpha()),
+                )
+            };
+            if let Some(px) = px {
+                *dest = px;
+            }
+        }
+    }
+    Some(pixmap)
+}
+
+/// Composites the glyph in sprite onto pixmap with its top left
+/// corner at (left, top), scaling it by scale
+fn draw_sprite(
+    pixmap: &mut Pixmap,
+    sprite: &Sprite,
+    left: isize,
+    top: isize,
+    scale: f64,
+    has_color: bool,
+    fg: Color,
+) {
+    if let Some(glyph) = sprite_pixmap(sprite, has_color, fg) {
+        pixmap.draw_pixmap(
+            0,
+            0,
+            glyph.as_ref(),
+            &PixmapPaint::default(),
+            Transform::from_row(scale as f32, 0., 0., scale as f32, left as f32, top as f32),
+            None,
+        );
+    }
+}
+
+/// Renders lines to images in software, keeping its glyph cache
+/// between calls so that repeated renders don't rasterize the
+/// same glyphs again
+pub struct SoftwareRenderer {
+    config: ConfigHandle,
+    fonts: Rc<FontConfiguration>,
+    render_metrics: RenderMetrics,
+    glyph_cache: GlyphCache,
+}
+
+impl SoftwareRenderer {
+    pub fn new(config: &ConfigHandle, fonts: &Rc<FontConfiguration>) -> anyhow::Result<Self> {
+        Ok(Self {
+            config: config.clone(),
+            fonts: Rc::clone(fonts),
+            render_metrics: RenderMetrics::new(fonts)?,
+            glyph_cache: GlyphCache::new_in_memory(fonts, ATLAS_SIZE)?,
+        })
+    }
+
+    /// Renders lines, which are cols cells wide, to an image
+    pub fn render(
+        &mut self,
+        lines: &[Line],
+        cols: usize,
+        palette: &ColorPalette,
+    ) -> anyhow::Result<RgbaImage> {
+        let config = &self.config;
+        let fonts = &self.fonts;
+        let render_metrics = &self.render_metrics;
+        let glyph_cache = &mut self.glyph_cache;
+
+        let cell_width = render_metrics.cell_size.width as usize;
+        let cell_height = render_metrics.cell_size.height as usize;
+        let mut pixmap = Pixmap::new(
+            (cols * cell_width).max(1) as u32,
+            (lines.len() * cell_height).max(1) as u32,
+        )
+        .context("allocating image")?;
+        pixmap.fill(to_color(palette.background));
+
+        let bidi_hint = if config.bidi_enabled {
+            Some(config.bidi_direction)
+        } else {
+            None
+        };
+
+        for (row, line) in lines.iter().enumerate() {
+            let row_top = row * cell_height;
+
+            for cell in line.visible_cells() {
+                let (_fg, bg) = cell_colors(cell.attrs(), palette);
+                fill_rect(
+                    &mut pixmap,
+                    cell.cell_index() * cell_width,
+                    row_top,
+                    cell.width() * cell_width,
+                    cell_height,
+                    bg,
+                );
+            }
+
+            for cluster in line.cluster(bidi_hint) {
+                let attrs = &cluster.attrs;
+                let (fg, _bg) = cell_colors(attrs, palette);
+                let cluster_left = cluster.first_cell_idx * cell_width;
+
+                if attrs.underline() != Underline::None {
+                    fill_rect(
+                        &mut pixmap,
+                        cluster_left,
+                        row_top + render_metrics.descender_row.max(0) as usize,
+                        cluster.width * cell_width,
+                        render_metrics.underline_height as usize,
+                        fg,
+                    );
+                }
+                if attrs.strikethrough() {
+                    fill_rect(
+                        &mut pixmap,
+                        cluster_left,
+                        row_top + render_metrics.strike_row.max(0) as usize,
+                        cluster.width * cell_width,
+                        render_metrics.underline_height as usize,
+                        fg,
+                    );
+                }
+                if attrs.invisible() {
+                    continue;
+                }
+
+                let style = fonts.match_style(config, attrs);
+                let font = fonts.resolve_font(style)?;
+                let presentation_width = PresentationWidth::with_cluster(&cluster);
+                let infos = font
+                    .blocking_shape(
+                        &cluster.text,
+                        Some(cluster.presentation),
+                        cluster.direction,
+                        None,
+                        Some(&presentation_width),
+                    )
+                    .with_context(|| format!("shaping {:?}", cluster.text))?;
+
+                for info in &infos {
+                    let cell_idx = cluster.byte_to_cell_idx(info.cluster as usize);
+                    let cell_left = (cell_idx * cell_width) as isize;
+                    let followed_by_space = match line.get_cell(cell_idx + 1) {
+                        Some(cell) => cell.str() == " ",
+                        None => false,
+                    };
+
+                    if config.custom_block_glyphs {
+                        if let Some(block) = info.only_char.and_then(BlockKey::from_char) {
+                            let sprite = glyph_cache.cached_block(block, render_metrics)?;
+                            draw_sprite(
+                                &mut pixmap,
+                                &sprite,
+                                cell_left,
+                                row_top as isize,
+                                1.0,
+                                false,
+                                fg,
+                            );
+                            continue;
+                        }
+                    }
+
+                    let glyph = glyph_cache.cached_glyph(
+                        info,
+                        style,
+                        followed_by_space,
+                        &font,
+                        render_metrics,
+                        info.num_cells,
+                    )?;
+                    if let Some(sprite) = &glyph.texture {
+                        let left = cell_left + (glyph.x_offset + glyph.bearing_x).get() as isize;
+                        let top = row_top as isize
+                            + cell_height as isize
+                            + (render_metrics.descender - (glyph.y_offset + glyph.bearing_y)).get()
+                                as isize;
+                        draw_sprite(
+                            &mut pixmap,
+                            sprite,
+                            left,
+                            top,
+                            glyph.scale,
+                            glyph.has_color,
+                            fg,
+                        );
+                    }
+                }
+            }
+        }
+
+        let pixels = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|px| {
+                let px = px.demultiply();
+                [px.red(), px.green(), px.blue(), px.alpha()]
+            })
+            .collect();
+        RgbaImage::from_raw(pixmap.width(), pixmap.height(), pixels)
+            .context("image data doesn't match its dimensions")
+    }
+}
+
+/// Renders lines, which are cols cells wide, to an image
+pub fn render_lines(
+    config: &ConfigHandle,
+    fonts: &Rc<FontConfiguration>,
+    lines: &[Line],
+    cols: usize,
+    palette: &ColorPalette,
+) -> anyhow::Result<RgbaImage> {
+    SoftwareRenderer::new(config, fonts)?.render(lines, cols, palette)
+}
+
+/// Implements `wezterm screenshot`, which fetches the visible lines
+/// of a pane from the mux and renders them without a window
+pub fn run_screenshot(config: ConfigHandle, cmd: &ScreenshotCommand) -> anyhow::Result<()> {
+    use wezterm_client::client::Client;
+
+    if let Err(err) = config::configuration_result() {
+        log::error!("{}", err);
+        return Ok(());
+    }
+
+    // Disable the normal config error UI window, as we don't have
+    // a fully baked GUI environment running
+    config::assign_error_callback(|err| eprintln!("{}", err));
+
+    let executor = promise::spawn::ScopedExecutor::new();
+    let (pane_id, cols, lines) = promise::spawn::block_on(executor.run(async move {
+        let mut ui = mux::connui::ConnectionUI::new_headless();
+        let client = Client::new_default_unix_domain(
+            true,
+            &mut ui,
+            true,
+            cmd.prefer_mux,
+            cmd.class.as_deref().unwrap_or(DEFAULT_WINDOW_CLASS),
+        )?;
+
+        let pane_id = client.resolve_pane_id(cmd.pane_id).await?;
+        let info = client
+            .get_dimensions(codec::GetPaneRenderableDimensions { pane_id })
+            .await?;
+        let top = info.dimensions.physical_top;
+        let bottom = top + info.dimensions.viewport_rows as StableRowIndex;
+
+        let lines = client
+            .get_lines(codec::GetLines {
+                pane_id,
+                lines: vec![top..bottom],
+            })
+            .await?;
+        let lines: Vec<Line> = lines
+            .lines
+            .extract_data()
+            .0
+            .into_iter()
+            .map(|(_idx, line)| line)
+            .collect();
+
+        anyhow::Result::<_>::Ok((pane_id, info.dimensions.cols, lines))
+    }))?;
+
+    let font_config = Rc::new(FontConfiguration::new(
+        Some(config.clone()),
+        config.dpi.unwrap_or_else(|| ::window::default_dpi()) as usize,
+    )?);
+
+    // The pane palette is only sent to the gui, so use the
+    // palette from the config
+    let palette: ColorPalette = config.resolved_palette.clone().into();
+    let image = render_lines(&config, &font_config, &lines, cols, &palette)?;
+
+    let path = cmd
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("wezterm-screenshot-{pane_id}.png")));
+    image
+        .save(&path)
+        .with_context(|| format!("saving screenshot to {}", path.display()))?;
+    println!("{}", path.display());
+    Ok(())
+}
@@ -2,17 +2,19 @@
 use super::luaerr;
 use crate::termwindow::TermWindowNotif;
 use crate::TermWindow;
-use config::keyassignment::{ClipboardCopyDestination, KeyAssignment};
+use config::keyassignment::{ClipboardCopyDestination, ClipboardPasteSource, KeyAssignment};
+use config::window::WindowProgress;
 use luahelper::*;
 use mlua::{UserData, UserDataMethods, UserDataRef};
 use mux::pane::PaneId;
 use mux::window::WindowId as MuxWindowId;
 use mux::Mux;
 use mux_lua::MuxPane;
+use std::path::PathBuf;
 use termwiz_funcs::lines_to_escapes;
 use wezterm_dynamic::{FromDynamic, ToDynamic};
 use wezterm_toast_notification::ToastNotification;
-use window::{Connection, ConnectionOps, DeadKeyStatus, WindowOps, WindowState};
+use window::{Clipboard, Connection, ConnectionOps, DeadKeyStatus, WindowOps, WindowState};
 
 #[derive(Clone)]
 pub struct GuiWin {
@@ -93,6 +95,29 @@ impl UserData for GuiWin {
                 Ok(())
             },
         );
+        methods.add_method(
+            "set_progress",
+            |_, this, progress: Option<WindowProgress>| {
+                let progress = progress.unwrap_or_default();
+                this.window
+                    .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                        term_window.set_window_progress(progress, None);
+                    })));
+                Ok(())
+            },
+        );
+        methods.add_method("set_badge", |_, this, badge: Option<String>| {
+            this.window.set_badge(badge);
+            Ok(())
+        });
+        methods.add_method("set_icon", |_, this, path: Option<String>| {
+            let path = path.map(PathBuf::from);
+            this.window
+                .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                    term_window.set_icon(path);
+                })));
+            Ok(())
+        });
         methods.add_method("get_appearance", |_, _, _: ()| {
             Ok(Connection::get().unwrap().get_appearance().to_string())
         });
@@ -198,6 +223,12 @@ impl UserData for GuiWin {
                 .notify(TermWindowNotif::SetConfigOverrides(value));
             Ok(())
         });
+        methods.add_method("merge_config_overrides", |_, this, value: mlua::Value| {
+            let value = lua_value_to_dynamic(value)?;
+            this.window
+                .notify(TermWindowNotif::MergeConfigOverrides(value));
+            Ok(())
+        });
         methods.add_async_method("is_focused", |_, this, _: ()| async move {
             let (tx, rx) = smol::channel::bounded(1);
             this.window
@@ -273,6 +304,20 @@ impl UserData for GuiWin {
 
             Ok((mods.to_string(), leds.to_string()))
         });
+        methods.add_async_method("keyboard_layout", |_, this, _: ()| async move {
+            let (tx, rx) = smol::channel::bounded(1);
+            this.window
+                .notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                    tx.try_send(term_window.keyboard_layout()).ok();
+                })));
+            let result = rx
+                .recv()
+                .await
+                .map_err(|e| anyhow::anyhow!("{:#}", e))
+                .map_err(luaerr)?;
+
+            Ok(result)
+        });
         methods.add_async_method("active_pane", |_, this, _: ()| async move {
             let (tx, rx) = smol::channel::bounded(1);
             this.window
@@ -309,6 +354,17 @@ impl UserData for GuiWin {
                 Ok(())
             },
         );
+        methods.add_async_method(
+            "get_clipboard",
+            |_, this, clipboard: Option<ClipboardPasteSource>| async move {
+                let clipboard = match clipboard.unwrap_or_default() {
+                    ClipboardPasteSource::Clipboard => Clipboard::Clipboard,
+                    ClipboardPasteSource::PrimarySelection => Clipboard::PrimarySelection,
+                };
+                let text = this.window.get_clipboard(clipboard).await.map_err(luaerr)?;
+                Ok(text)
+            },
+        );
         methods.add_async_method(
             "get_selection_escapes_for_pane",
             |_, this, pane: UserDataRef<MuxPane>| async move {
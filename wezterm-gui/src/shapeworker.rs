@@ -0,0 +1,210 @@
+use crate::customglyph::BlockKey;
+use crate::shapecache::{ShapeCacheKey, ShapeCacheKeyTrait};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use termwiz::cellcluster::CellCluster;
+use wezterm_font::shaper::{FontShaper, PresentationWidth};
+use wezterm_font::{GlyphInfo, ShaperSpec};
+
+/// The number of shapers that each worker keeps around
+const MAX_SHAPERS_PER_WORKER: usize = 8;
+
+struct ShapeJob {
+    key: ShapeCacheKey,
+    spec: Arc<ShaperSpec>,
+    cluster: CellCluster,
+    notify: Box<dyn FnOnce() + Send>,
+}
+
+/// The outcome of shaping a cluster on a worker thread
+pub struct ShapeResult {
+    /// The font that was used to shape the text.  If the font has since
+    /// picked up additional fallbacks, the result is stale and should
+    /// be discarded.
+    pub spec: Arc<ShaperSpec>,
+    /// None if shaping failed, or if some of the text requires fallback
+    /// fonts to be resolved, in which case it needs to be shaped on
+    /// the GUI thread.
+    pub glyphs: Option<Vec<GlyphInfo>>,
+}
+
+/// A small pool of threads that shape text on behalf of a window,
+/// so that very large panes don't stall the GUI thread.
+/// The threads are spawned on first use and exit when this is dropped.
+#[derive(Default)]
+pub struct ShapeWorkers {
+    jobs: RefCell<Option<Sender<ShapeJob>>>,
+    completed: Arc<Mutex<Vec<(ShapeCacheKey, ShapeResult)>>>,
+    pending: RefCell<HashSet<ShapeCacheKey>>,
+    /// Completed results, along with the frame in which they were collected
+    ready: RefCell<HashMap<ShapeCacheKey, (usize, ShapeResult)>>,
+    frame: Cell<usize>,
+}
+
+impl ShapeWorkers {
+    /// Queue a cluster to be shaped.
+    /// `notify` is called when results are available to be collected
+    /// via `take_ready`.
+    pub fn submit<F: FnOnce() + Send + 'static>(
+        &self,
+        key: ShapeCacheKey,
+        spec: Arc<ShaperSpec>,
+        cluster: &CellCluster,
+        notify: F,
+    ) {
+        let mut jobs = self.jobs.borrow_mut();
+        if jobs.is_none() {
+            jobs.replace(self.spawn_workers());
+        }
+        let job = ShapeJob {
+            key: key.clone(),
+            spec,
+            cluster: cluster.clone(),
+            notify: Box::new(notify),
+        };
+        if jobs.as_ref().unwrap().send(job).is_ok() {
+            self.pending.borrow_mut().insert(key);
+        }
+    }
+
+    /// Returns true if the text for key has been submitted but
+    /// its result has not yet been collected
+    pub fn is_pending(&self, key: &dyn ShapeCacheKeyTrait) -> bool {
+        self.collect_completed();
+        self.pending.borrow().contains(key)
+    }
+
+    /// Returns the completed result for key, if any
+    pub fn take_ready(&self, key: &dyn ShapeCacheKeyTrait) -> Option<ShapeResult> {
+        self.collect_completed();
+        self.ready
+            .borrow_mut()
+            .remove(key)
+            .map(|(_, result)| result)
+    }
+
+    /// Called at the start of each frame.  The arrival of results
+    /// triggers a repaint, so a result that wasn't taken by the end
+    /// of the following frame is for text that is no longer visible;
+    /// those are discarded so that they don't accumulate.
+    pub fn start_frame(&self) {
+        let frame = self.frame.get() + 1;
+        self.frame.set(frame);
+        self.ready
+            .borrow_mut()
+            .retain(|_, (collected, _)| *collected + 1 >= frame);
+    }
+
+    fn collect_completed(&self) {
+        let completed = std::mem::take(&mut *self.completed.lock().unwrap());
+        if completed.is_empty() {
+            return;
+        }
+        let mut pending = self.pending.borrow_mut();
+        let mut ready = self.ready.borrow_mut();
+        let frame = self.frame.get();
+        for (key, result) in completed {
+            pending.remove(&key);
+            ready.insert(key, (frame, result));
+        }
+    }
+
+    fn spawn_workers(&self) -> Sender<ShapeJob> {
+        let (tx, rx) = channel();
+        let rx = Arc::new(Mutex::new(rx));
+        let num_workers = std::thread::available_parallelism()
+            .map(|n| n.get() / 2)
+            .unwrap_or(1)
+            .clamp(1, 4);
+        for idx in 0..num_workers {
+            let rx = Arc::clone(&rx);
+            let completed = Arc::clone(&self.completed);
+            if let Err(err) = std::thread::Builder::new()
+                .name(format!("shaper-{idx}"))
+                .spawn(move || worker(rx, completed))
+            {
+                log::error!("failed to spawn shaper thread: {:#}", err);
+            }
+        }
+        tx
+    }
+}
+
+fn worker(
+    jobs: Arc<Mutex<Receiver<ShapeJob>>>,
+    completed: Arc<Mutex<Vec<(ShapeCacheKey, ShapeResult)>>>,
+) {
+    let mut shapers: Vec<(Arc<ShaperSpec>, Box<dyn FontShaper>)> = vec![];
+
+    loop {
+        let job = match jobs.lock().unwrap().recv() {
+            Ok(job) => job,
+            Err(_) => break,
+        };
+
+        let glyphs = shape(&mut shapers, &job.spec, &job.cluster);
+
+        let was_empty = {
+            let mut completed = completed.lock().unwrap();
+            let was_empty = completed.is_empty();
+            completed.push((
+                job.key,
+                ShapeResult {
+                    spec: job.spec,
+                    glyphs,
+                },
+            ));
+            was_empty
+        };
+        // Only wake up the window for the first of a batch of results;
+        // it will collect the rest at the same time
+        if was_empty {
+            (job.notify)();
+        }
+    }
+}
+
+fn shape(
+    shapers: &mut Vec<(Arc<ShaperSpec>, Box<dyn FontShaper>)>,
+    spec: &Arc<ShaperSpec>,
+    cluster: &CellCluster,
+) -> Option<Vec<GlyphInfo>> {
+    let idx = match shapers.iter().position(|(s, _)| Arc::ptr_eq(s, spec)) {
+        Some(idx) => idx,
+        None => {
+            let shaper = match spec.new_shaper() {
+                Ok(shaper) => shaper,
+                Err(err) => {
+                    log::error!("failed to create shaper: {:#}", err);
+                    return None;
+                }
+            };
+            if shapers.len() >= MAX_SHAPERS_PER_WORKER {
+                shapers.remove(0);
+            }
+            shapers.push((Arc::clone(spec), shaper));
+            shapers.len() - 1
+        }
+    };
+
+    let presentation_width = PresentationWidth::with_cluster(cluster);
+    match spec.shape(
+        &*shapers[idx].1,
+        &cluster.text,
+        Some(cluster.presentation),
+        cluster.direction,
+        Some(&presentation_width),
+    ) {
+        Ok((glyphs, mut no_glyphs)) => {
+            BlockKey::filter_out_synthetic(&mut no_glyphs);
+            if no_glyphs.is_empty() {
+                Some(glyphs)
+            } else {
+                None
+            }
+        }
+        Err(_) => None,
+    }
+}
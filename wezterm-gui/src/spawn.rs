@@ -1,12 +1,14 @@
 use anyhow::{anyhow, bail, Context};
-use config::keyassignment::SpawnCommand;
+use config::keyassignment::{SpawnCommand, SpawnCwdStrategy};
 use config::TermConfig;
 use mux::activity::Activity;
 use mux::domain::SplitSource;
+use mux::pane::{CachePolicy, Pane};
 use mux::tab::SplitRequest;
 use mux::window::WindowId as MuxWindowId;
 use mux::Mux;
 use portable_pty::CommandBuilder;
+use std::collections::HashMap;
 use std::sync::Arc;
 use wezterm_term::TerminalSize;
 
@@ -42,7 +44,7 @@ pub async fn spawn_command_internal(
     size: TerminalSize,
     src_window_id: Option<MuxWindowId>,
     term_config: Arc<TermConfig>,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Arc<dyn Pane>> {
     let mux = Mux::get();
     let activity = Activity::new();
 
@@ -57,7 +59,13 @@ pub async fn spawn_command_internal(
         None => None,
     };
 
-    let cwd = if let Some(cwd) = spawn.cwd.as_ref() {
+    let current_pane = current_pane_id.and_then(|pane_id| mux.get_pane(pane_id));
+    let pane_cwd_url = current_pane
+        .as_ref()
+        .and_then(|pane| pane.get_current_working_dir(CachePolicy::AllowStale));
+    let pane_cwd = pane_cwd_url.as_ref().and_then(mux::cwd_url_to_path);
+
+    let fixed_cwd = if let Some(cwd) = spawn.cwd.as_ref() {
         Some(cwd.to_str().map(|s| s.to_owned()).ok_or_else(|| {
             anyhow!(
                 "Domain::spawn requires that the cwd be unicode in {:?}",
@@ -68,22 +76,60 @@ pub async fn spawn_command_internal(
         None
     };
 
-    let cmd_builder = if let Some(args) = spawn.args {
-        let mut builder = CommandBuilder::from_argv(args.iter().map(Into::into).collect());
-        for (k, v) in spawn.set_environment_variables.iter() {
+    let domain = mux.resolve_spawn_tab_domain(current_pane_id, &spawn.domain)?;
+
+    let cwd = match spawn.cwd_strategy {
+        SpawnCwdStrategy::Default => fixed_cwd,
+        SpawnCwdStrategy::Fixed => match fixed_cwd {
+            Some(cwd) => Some(cwd),
+            None => bail!("cwd_strategy=\"Fixed\" requires that cwd also be set"),
+        },
+        SpawnCwdStrategy::Home => match domain.home_dir_cwd() {
+            Some(home) => Some(home),
+            None => bail!(
+                "cwd_strategy=\"Home\" is not supported by domain {}",
+                domain.domain_name()
+            ),
+        },
+        SpawnCwdStrategy::Inherit => pane_cwd.clone(),
+    };
+
+    let env = if spawn.set_environment_variables.is_empty() {
+        HashMap::new()
+    } else {
+        // Prefer the host reported by the shell via OSC 7, as that
+        // reflects the remote host when the pane is running ssh
+        let hostname = pane_cwd_url
+            .as_ref()
+            .and_then(|url| url.host_str())
+            .filter(|host| !host.is_empty());
+        spawn.expand_environment_variables(pane_cwd.as_deref(), domain.domain_name(), hostname)
+    };
+
+    // When only the environment is specified, a command builder for the
+    // default program carries it through to the domain
+    let cmd_builder = match spawn.args {
+        Some(args) => Some(CommandBuilder::from_argv(
+            args.iter().map(Into::into).collect(),
+        )),
+        None if !env.is_empty() => Some(CommandBuilder::new_default_prog()),
+        None => None,
+    };
+    let cmd_builder = cmd_builder.map(|mut builder| {
+        for (k, v) in env.iter() {
             builder.env(k, v);
         }
-        if let Some(cwd) = spawn.cwd {
+        if let (Some(cwd), SpawnCwdStrategy::Default | SpawnCwdStrategy::Fixed) =
+            (&cwd, spawn.cwd_strategy)
+        {
             builder.cwd(cwd);
         }
-        Some(builder)
-    } else {
-        None
-    };
+        builder
+    });
 
     let workspace = mux.active_workspace().clone();
 
-    match spawn_where {
+    let pane = match spawn_where {
         SpawnWhere::SplitPane(direction) => {
             let src_window_id = match src_window_id {
                 Some(id) => id,
@@ -109,6 +155,7 @@ pub async fn spawn_command_internal(
                     .await
                     .context("split_pane")?;
                 pane.set_config(term_config);
+                pane
             } else {
                 bail!("there is no active tab while splitting pane!?");
             }
@@ -127,6 +174,7 @@ pub async fn spawn_command_internal(
                     current_pane_id,
                     workspace,
                     spawn.position,
+                    spawn.class,
                 )
                 .await
                 .context("spawn_tab_or_window")?;
@@ -137,10 +185,11 @@ pub async fn spawn_command_internal(
             if Some(window_id) == src_window_id {
                 pane.set_config(term_config);
             }
+            pane
         }
     };
 
     drop(activity);
 
-    Ok(())
+    Ok(pane)
 }
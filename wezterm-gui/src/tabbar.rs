@@ -1,4 +1,6 @@
 use crate::termwindow::{PaneInformation, TabInformation, UIItem, UIItemType};
+use config::keyassignment::PaneMonitor;
+use config::window::WindowProgress;
 use config::{ConfigHandle, TabBarColors};
 use finl_unicode::grapheme_clusters::Graphemes;
 use mlua::FromLua;
@@ -7,6 +9,7 @@ use termwiz::color::ColorSpec;
 use termwiz::escape::csi::Sgr;
 use termwiz::escape::parser::Parser;
 use termwiz::escape::{Action, ControlCode, CSI};
+use termwiz::nerdfonts::NERD_FONTS;
 use termwiz::surface::SEQ_ZERO;
 use termwiz_funcs::{format_as_escapes, FormatItem};
 use wezterm_term::Line;
@@ -103,6 +106,32 @@ fn call_format_tab_title(
     }
 }
 
+/// Returns a glyph that depicts progress, with the percentage
+/// rounded up to the nearest eighth of a circle
+fn progress_glyph(progress: WindowProgress) -> Option<char> {
+    const SLICES: [&str; 8] = [
+        "md_circle_slice_1",
+        "md_circle_slice_2",
+        "md_circle_slice_3",
+        "md_circle_slice_4",
+        "md_circle_slice_5",
+        "md_circle_slice_6",
+        "md_circle_slice_7",
+        "md_circle_slice_8",
+    ];
+    let name = match progress {
+        WindowProgress::None => return None,
+        WindowProgress::Normal(pct) => {
+            let eighths = (pct.min(100) as usize * 8 + 99) / 100;
+            SLICES[eighths.max(1) - 1]
+        }
+        WindowProgress::Error(_) => "md_progress_alert",
+        WindowProgress::Indeterminate => "md_progress_clock",
+        WindowProgress::Paused(_) => "md_pause_circle",
+    };
+    NERD_FONTS.get(name).copied()
+}
+
 fn compute_tab_title(
     tab: &TabInformation,
     tab_info: &[TabInformation],
@@ -122,6 +151,28 @@ fn compute_tab_title(
                 } else {
                     tab.tab_title.clone()
                 };
+                // Flag tabs with an outstanding TogglePaneMonitor alert,
+                // in the same way that tmux flags windows
+                match tab.monitor_alert {
+                    Some(PaneMonitor::Activity) => title = format!("#{title}"),
+                    Some(PaneMonitor::Silence) => title = format!("~{title}"),
+                    None => {}
+                }
+                if pane.is_read_only {
+                    title = format!("[RO] {title}");
+                }
+                if config.show_progress_in_tab_bar {
+                    if let Some(glyph) = progress_glyph(pane.progress) {
+                        title = format!("{glyph} {title}");
+                    }
+                }
+                // Label tabs from remote domains, so that they can be
+                // told apart from local tabs running the same program
+                if let Some(label) = &config.tab_domain_label {
+                    if !pane.domain_name.is_empty() && pane.domain_name != "local" {
+                        title = format!("{}{title}", label.replace("{domain}", &pane.domain_name));
+                    }
+                }
                 let classic_spacing = if config.use_fancy_tab_bar { "" } else { " " };
                 if config.show_tab_index_in_tab_bar {
                     title = format!(
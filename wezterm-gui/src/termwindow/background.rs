@@ -492,12 +492,14 @@ impl crate::TermWindow {
         let width = match layer.def.width {
             BackgroundSize::Contain => max_aspect_width as f32,
             BackgroundSize::Cover => min_aspect_width as f32,
+            BackgroundSize::Auto => tex_width,
             BackgroundSize::Dimension(n) => n.evaluate_as_pixels(h_context),
         };
 
         let height = match layer.def.height {
             BackgroundSize::Contain => max_aspect_height as f32,
             BackgroundSize::Cover => min_aspect_height as f32,
+            BackgroundSize::Auto => tex_height,
             BackgroundSize::Dimension(n) => n.evaluate_as_pixels(v_context),
         };
 
@@ -1,11 +1,29 @@
+use crate::overlay::{confirm_large_paste, start_overlay_pane};
 use crate::termwindow::TermWindowNotif;
 use crate::TermWindow;
 use config::keyassignment::{ClipboardCopyDestination, ClipboardPasteSource};
 use mux::pane::Pane;
 use mux::Mux;
 use std::sync::Arc;
+use wezterm_client::domain::ClientDomain;
 use window::{Clipboard, WindowOps};
 
+/// Returns true if pane belongs to a domain that sends its
+/// input over the network to another host
+fn is_remote_pane(pane: &Arc<dyn Pane>) -> bool {
+    let mux = Mux::get();
+    match mux.get_domain(pane.domain_id()) {
+        Some(domain) => {
+            domain.downcast_ref::<mux::ssh::RemoteSshDomain>().is_some()
+                || domain
+                    .downcast_ref::<ClientDomain>()
+                    .map(|domain| domain.is_remote())
+                    .unwrap_or(false)
+        }
+        None => false,
+    }
+}
+
 impl TermWindow {
     pub fn copy_to_clipboard(&self, clipboard: ClipboardCopyDestination, text: String) {
         let clipboard = match clipboard {
@@ -36,21 +54,35 @@ impl TermWindow {
             ClipboardPasteSource::PrimarySelection => Clipboard::PrimarySelection,
         };
         let future = window.get_clipboard(clipboard);
+        let sanitize = self.config.sanitize_pasted_text;
+        let threshold = self.config.remote_paste_confirmation_threshold;
         promise::spawn::spawn(async move {
             if let Ok(clip) = future.await {
+                let clip = if sanitize {
+                    sanitize_paste(&clip)
+                } else {
+                    clip
+                };
                 window.notify(TermWindowNotif::Apply(Box::new(move |myself| {
-                    if let Some(pane) = myself
-                        .pane_state(pane_id)
-                        .overlay
-                        .as_ref()
-                        .map(|overlay| overlay.pane.clone())
-                        .or_else(|| {
-                            let mux = Mux::get();
-                            mux.get_pane(pane_id)
-                        })
-                    {
-                        pane.send_paste(&clip).ok();
+                    if let Some(overlay) = myself.pane_state(pane_id).overlay.as_ref() {
+                        overlay.pane.send_paste(&clip).ok();
+                        return;
                     }
+                    let pane = match Mux::get().get_pane(pane_id) {
+                        Some(pane) => pane,
+                        None => return,
+                    };
+                    if threshold > 0 && clip.len() > threshold && is_remote_pane(&pane) {
+                        let window = myself.window.clone().unwrap();
+                        let (overlay, future) =
+                            start_overlay_pane(myself, &pane, move |pane_id, term| {
+                                confirm_large_paste(pane_id, term, window, clip)
+                            });
+                        myself.assign_overlay_for_pane(pane_id, overlay);
+                        promise::spawn::spawn(future).detach();
+                        return;
+                    }
+                    pane.send_paste(&clip).ok();
                 })));
             }
         })
@@ -58,3 +90,34 @@ impl TermWindow {
         self.maybe_scroll_to_bottom_for_input(&pane);
     }
 }
+
+/// Removes ESC and the other C0 and C1 control characters, other than
+/// tab and newlines, from text that is about to be pasted.
+/// Text copied from a web page can embed escape sequences that are
+/// invisible in the browser; if passed through, they could end a
+/// bracketed paste early or otherwise cause the pasted text to be
+/// interpreted as commands.
+fn sanitize_paste(text: &str) -> String {
+    text.chars()
+        .filter(|&c| match c {
+            '\t' | '\n' | '\r' => true,
+            c => !c.is_control(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sanitize() {
+        assert_eq!(sanitize_paste("ls -l\r\n\tfoo"), "ls -l\r\n\tfoo");
+        assert_eq!(
+            sanitize_paste("echo hi\x1b[201~; rm -rf ~\x1b[200~"),
+            "echo hi[201~; rm -rf ~[200~"
+        );
+        assert_eq!(sanitize_paste("a\u{9b}31mb\x07c\x7f"), "a31mbc");
+        assert_eq!(sanitize_paste("café ☕"), "café ☕");
+    }
+}
@@ -0,0 +1,143 @@
+use config::Config;
+
+/// Describes which parts of the window need to be rebuilt after
+/// the configuration was reloaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigChange {
+    /// The fonts, glyph atlas and shaped text need to be rebuilt
+    pub fonts: bool,
+    /// The terminal and window dimensions need to be recomputed
+    pub geometry: bool,
+    /// The key and mouse bindings need to be rebuilt
+    pub input: bool,
+}
+
+impl ConfigChange {
+    pub fn between(old: &Config, new: &Config) -> Self {
+        let mut change = Self {
+            fonts: false,
+            geometry: false,
+            input: false,
+        };
+        for key in old.changed_keys(new) {
+            change.fonts |= is_font_option(&key);
+            change.geometry |= is_geometry_option(&key);
+            change.input |= is_input_option(&key);
+        }
+        // Font metrics determine the size of the cells
+        change.geometry |= change.fonts;
+        change
+    }
+}
+
+/// Options that are used to locate, load, rasterize or shape fonts,
+/// or that influence the metrics used to size the glyph atlas.
+/// Options are assumed to affect the fonts unless they are known not
+/// to, so that forgetting to classify a new option costs a needless
+/// rebuild rather than leaving stale glyphs on screen.
+fn is_font_option(key: &str) -> bool {
+    // The window frame specifies the font used by the fancy tab bar
+    key == "window_frame" || !is_non_font_option(key)
+}
+
+/// Options that are known to have no effect on the fonts
+fn is_non_font_option(key: &str) -> bool {
+    is_geometry_option(key)
+        || is_input_option(key)
+        || key.starts_with("color")
+        || key.starts_with("window_background")
+        || key.starts_with("cursor_blink")
+        || key.starts_with("cursor_animation")
+        || key.starts_with("text_blink")
+        || key.starts_with("selection_")
+        || key.starts_with("quick_select")
+        || key.starts_with("show_")
+        || key.starts_with("default_")
+        || key.starts_with("exit_behavior")
+        || key.starts_with("check_for_updates")
+        || key.starts_with("mux_")
+        || key.ends_with("_domains")
+        || key.ends_with("_hsb")
+        || key.ends_with("_opacity")
+        || key.ends_with("_fg_color")
+        || key.ends_with("_bg_color")
+        || matches!(
+            key,
+            "scrollback_lines"
+                | "animation_fps"
+                | "max_fps"
+                | "visual_bell"
+                | "audible_bell"
+                | "bell_request_attention"
+                | "notification_handling"
+                | "hyperlink_rules"
+                | "launch_menu"
+                | "set_environment_variables"
+                | "automatically_reload_config"
+                | "status_update_interval"
+                | "window_close_confirmation"
+                | "hide_mouse_cursor_when_typing"
+                | "scroll_to_bottom_on_input"
+                | "pane_focus_follows_mouse"
+                | "unzoom_on_switch_pane"
+        )
+}
+
+/// Options that affect the space available to the terminal
+/// within the window
+fn is_geometry_option(key: &str) -> bool {
+    key.contains("tab_bar")
+        || key.starts_with("window_padding")
+        || key.starts_with("integrated_title_button")
+        || matches!(
+            key,
+            "window_decorations"
+                | "window_frame"
+                | "enable_scroll_bar"
+                | "min_scroll_bar_height"
+                | "tab_max_width"
+                | "use_resize_increments"
+        )
+}
+
+/// Options that are used to build the InputMap
+fn is_input_option(key: &str) -> bool {
+    matches!(
+        key,
+        "keys"
+            | "key_tables"
+            | "mouse_bindings"
+            | "leader"
+            | "key_map_preference"
+            | "disable_default_key_bindings"
+            | "disable_default_mouse_bindings"
+            | "hyperlink_click_modifiers"
+            | "selection_copy_destination"
+            | "middle_click_paste_source"
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn classify_options() {
+        assert!(is_font_option("font_size"));
+        assert!(is_font_option("freetype_load_target"));
+        assert!(is_font_option("command_palette_font_size"));
+        assert!(is_font_option("cursor_underline_thickness"));
+        assert!(is_font_option("allow_square_glyphs_to_overflow_width"));
+        assert!(is_font_option("window_frame"));
+        assert!(!is_font_option("colors"));
+        assert!(!is_font_option("window_background_opacity"));
+        assert!(!is_font_option("tab_bar_at_bottom"));
+        assert!(is_geometry_option("hide_tab_bar_if_only_one_tab"));
+        assert!(is_geometry_option("window_padding"));
+        assert!(!is_geometry_option("window_background_opacity"));
+        assert!(is_input_option("keys"));
+        assert!(is_input_option("selection_copy_destination"));
+        assert!(is_input_option("middle_click_paste_source"));
+        assert!(!is_input_option("scrollback_lines"));
+    }
+}
@@ -0,0 +1,225 @@
+//! Immediate local echo of typed characters, for panes whose output
+//! arrives with a high latency, such as those in ssh domains that
+//! don't use the wezterm multiplexer.
+//! The predicted text is drawn at the cursor in the same way as IME
+//! composition text, until the pane next produces output.
+use ::window::Modifiers;
+use mux::pane::{Pane, PaneId};
+use mux::renderable::StableCursorPosition;
+use mux::Mux;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use termwiz::cell::unicode_column_width;
+use termwiz::input::KeyCode;
+use wezterm_dynamic::Value;
+
+#[derive(Default)]
+pub struct EchoState {
+    /// When the earliest key that hasn't yet been followed by
+    /// output from the pane was sent
+    awaiting_output: Option<Instant>,
+    /// How long it took for output to follow the most recent key
+    last_rtt: Option<Duration>,
+    /// The position of the cursor when the prediction started,
+    /// and the predicted text
+    predicted: Option<(StableCursorPosition, String)>,
+}
+
+impl EchoState {
+    /// Called when the pane has produced output; it has either echoed
+    /// what we predicted, or done something else that we should show
+    /// instead of our prediction
+    pub fn output_arrived(&mut self) {
+        if let Some(sent) = self.awaiting_output.take() {
+            self.last_rtt.replace(sent.elapsed());
+        }
+        self.predicted.take();
+    }
+
+    /// Returns the cursor x position at which the predicted text starts,
+    /// along with that text, if it is still valid for the cursor.
+    pub fn prediction(&self, cursor: &StableCursorPosition) -> Option<(usize, String)> {
+        match &self.predicted {
+            Some((origin, text)) if origin.x == cursor.x && origin.y == cursor.y => {
+                Some((origin.x, text.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns true if typed characters should be echoed before the
+/// pane echoes them.  We only do this when the latency is high, as
+/// a wrong prediction is distracting, and not while a full screen
+/// application or a password prompt is likely to be reading the keys.
+pub fn should_predict_echo(
+    threshold: Option<Duration>,
+    last_rtt: Option<Duration>,
+    alt_screen: bool,
+    password_input: bool,
+) -> bool {
+    match (threshold, last_rtt) {
+        (Some(threshold), Some(rtt)) => rtt >= threshold && !alt_screen && !password_input,
+        _ => false,
+    }
+}
+
+/// Returns the text that a key press is expected to be echoed as,
+/// if it is a plain printable character
+pub fn echoed_text(key: &KeyCode, mods: Modifiers) -> Option<String> {
+    match key {
+        KeyCode::Char(c)
+            if !c.is_control()
+                && (mods - Modifiers::SHIFT)
+                    .remove_positional_mods()
+                    .is_empty() =>
+        {
+            Some(c.to_string())
+        }
+        _ => None,
+    }
+}
+
+fn password_input(pane: &Arc<dyn Pane>) -> bool {
+    match pane.get_metadata() {
+        Value::Object(obj) => matches!(
+            obj.get(&Value::String("password_input".to_string())),
+            Some(Value::Bool(true))
+        ),
+        _ => false,
+    }
+}
+
+impl super::TermWindow {
+    /// Called after a key has been sent to the pane.  text is what the
+    /// key is expected to be echoed as, if it is printable.
+    pub fn predict_echo(&mut self, pane: &Arc<dyn Pane>, text: Option<String>) {
+        let threshold = Mux::get()
+            .get_domain(pane.domain_id())
+            .and_then(|domain| domain.local_echo_threshold());
+        if threshold.is_none() {
+            return;
+        }
+
+        let cursor = pane.get_cursor_position();
+        let cols = pane.get_dimensions().cols;
+        let alt_screen = pane.is_alt_screen_active();
+        let password_input = password_input(pane);
+
+        let mut state = self.pane_state(pane.pane_id());
+        let echo = &mut state.echo;
+        if echo.awaiting_output.is_none() {
+            echo.awaiting_output.replace(Instant::now());
+        }
+
+        let text = match text {
+            Some(text)
+                if should_predict_echo(threshold, echo.last_rtt, alt_screen, password_input) =>
+            {
+                text
+            }
+            _ => {
+                // We can't tell what other keys will do, so stop
+                // predicting until the pane catches up
+                echo.predicted.take();
+                return;
+            }
+        };
+
+        // The cursor can't have moved while the prediction is valid,
+        // as that requires output from the pane
+        let mut predicted = echo
+            .prediction(&cursor)
+            .map(|(_, predicted)| predicted)
+            .unwrap_or_default();
+        predicted.push_str(&text);
+        // Don't try to predict how the line will wrap
+        if cursor.x + unicode_column_width(&predicted, None) >= cols {
+            echo.predicted.take();
+            return;
+        }
+        echo.predicted.replace((cursor, predicted));
+    }
+
+    /// Records that the pane produced output, which replaces any prediction
+    pub fn echo_output_arrived(&mut self, pane_id: PaneId) {
+        if let Some(state) = self.pane_state.borrow_mut().get_mut(&pane_id) {
+            state.echo.output_arrived();
+        }
+    }
+
+    /// Returns the cursor x position and the text that is predicted
+    /// to be echoed there, if any
+    pub fn predicted_echo(
+        &self,
+        pane_id: PaneId,
+        cursor: &StableCursorPosition,
+    ) -> Option<(usize, String)> {
+        self.pane_state
+            .borrow()
+            .get(&pane_id)
+            .and_then(|state| state.echo.prediction(cursor))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gating() {
+        let ms = Duration::from_millis;
+        // No threshold configured for the domain
+        assert!(!should_predict_echo(None, Some(ms(500)), false, false));
+
+        let predict = |rtt, alt_screen, password_input| {
+            should_predict_echo(Some(ms(100)), rtt, alt_screen, password_input)
+        };
+        // Nothing measured yet
+        assert!(!predict(None, false, false));
+        // Latency is below the threshold
+        assert!(!predict(Some(ms(20)), false, false));
+        assert!(predict(Some(ms(100)), false, false));
+        assert!(predict(Some(ms(500)), false, false));
+        // Full screen applications and password prompts
+        assert!(!predict(Some(ms(500)), true, false));
+        assert!(!predict(Some(ms(500)), false, true));
+    }
+
+    #[test]
+    fn echoed_keys() {
+        assert_eq!(
+            echoed_text(&KeyCode::Char('a'), Modifiers::NONE),
+            Some("a".to_string())
+        );
+        assert_eq!(
+            echoed_text(&KeyCode::Char('A'), Modifiers::SHIFT),
+            Some("A".to_string())
+        );
+        assert_eq!(echoed_text(&KeyCode::Char('a'), Modifiers::CTRL), None);
+        assert_eq!(echoed_text(&KeyCode::Char('a'), Modifiers::ALT), None);
+        assert_eq!(echoed_text(&KeyCode::Char('\r'), Modifiers::NONE), None);
+        assert_eq!(echoed_text(&KeyCode::Enter, Modifiers::NONE), None);
+    }
+
+    #[test]
+    fn prediction_follows_cursor() {
+        let mut state = EchoState::default();
+        let cursor = StableCursorPosition {
+            x: 4,
+            y: 10,
+            ..Default::default()
+        };
+        state.predicted.replace((cursor, "ls".to_string()));
+        assert_eq!(state.prediction(&cursor), Some((4, "ls".to_string())));
+
+        let moved = StableCursorPosition { x: 5, ..cursor };
+        assert_eq!(state.prediction(&moved), None);
+
+        state.awaiting_output.replace(Instant::now());
+        state.output_arrived();
+        assert_eq!(state.prediction(&cursor), None);
+        assert!(state.awaiting_output.is_none());
+        assert!(state.last_rtt.is_some());
+    }
+}
@@ -0,0 +1,134 @@
+use regex::Regex;
+use std::path::PathBuf;
+use url::Url;
+
+/// A reference to a location in a file, such as `src/main.rs:12:5`,
+/// as produced by the file path hyperlink rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLineLink {
+    pub path: String,
+    pub line: usize,
+    pub column: Option<usize>,
+}
+
+impl FileLineLink {
+    /// Parse a `file:path:line[:column]` uri.
+    /// Returns None for regular `file://` uris that don't carry
+    /// a line number, so that they can be opened normally.
+    pub fn parse(uri: &str) -> Option<Self> {
+        lazy_static::lazy_static! {
+            static ref RE: Regex = Regex::new(r"^file:(.+?):(\d+)(?::(\d+))?$").unwrap();
+        }
+        let caps = RE.captures(uri)?;
+        let path = caps.get(1)?.as_str();
+        if path.starts_with("//") {
+            return None;
+        }
+        Some(Self {
+            path: path.to_string(),
+            line: caps.get(2)?.as_str().parse().ok()?,
+            column: caps.get(3).and_then(|c| c.as_str().parse().ok()),
+        })
+    }
+
+    /// Resolve the path relative to the working directory of the pane
+    /// in which the link was found
+    pub fn resolve(&self, cwd: Option<&Url>) -> PathBuf {
+        if let Some(rest) = self.path.strip_prefix("~/") {
+            return config::HOME_DIR.join(rest);
+        }
+        let path = PathBuf::from(&self.path);
+        if path.is_absolute() {
+            return path;
+        }
+        match cwd.and_then(|url| url.to_file_path().ok()) {
+            Some(dir) => dir.join(path),
+            None => path,
+        }
+    }
+
+    /// Expand `{file}`, `{line}` and `{column}` in the editor command
+    fn expand_editor_command(&self, path: &str, editor: &[String]) -> Vec<String> {
+        let line = self.line.to_string();
+        let column = self.column.unwrap_or(1).to_string();
+        editor
+            .iter()
+            .map(|arg| {
+                arg.replace("{file}", path)
+                    .replace("{line}", &line)
+                    .replace("{column}", &column)
+            })
+            .collect()
+    }
+
+    /// Open the file in the configured editor, or, if there is no
+    /// editor configured, using the system default handler for the file.
+    pub fn open(&self, cwd: Option<&Url>, editor: Option<&[String]>) {
+        let path = self.resolve(cwd);
+        if !path.exists() {
+            log::error!("{} does not exist", path.display());
+            return;
+        }
+        match editor {
+            Some(editor) if !editor.is_empty() => {
+                let argv = self.expand_editor_command(&path.to_string_lossy(), editor);
+                log::info!("opening {} with {:?}", path.display(), argv);
+                let mut cmd = std::process::Command::new(&argv[0]);
+                cmd.args(&argv[1..]);
+                if let Some(dir) = path.parent() {
+                    cmd.current_dir(dir);
+                }
+                if let Err(err) = cmd.spawn() {
+                    log::error!("Failed to spawn {:?}: {:#}", argv, err);
+                }
+            }
+            _ => match Url::from_file_path(&path) {
+                Ok(url) => wezterm_open_url::open_url(url.as_str()),
+                Err(()) => log::error!("cannot make a url from {}", path.display()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_file_line() {
+        assert_eq!(
+            FileLineLink::parse("file:src/main.rs:12:5"),
+            Some(FileLineLink {
+                path: "src/main.rs".to_string(),
+                line: 12,
+                column: Some(5),
+            })
+        );
+        assert_eq!(
+            FileLineLink::parse("file:/tmp/foo.c:3"),
+            Some(FileLineLink {
+                path: "/tmp/foo.c".to_string(),
+                line: 3,
+                column: None,
+            })
+        );
+        assert_eq!(FileLineLink::parse("file:///tmp/foo.c"), None);
+        assert_eq!(FileLineLink::parse("https://example.com:8080"), None);
+    }
+
+    #[test]
+    fn expand_editor() {
+        let link = FileLineLink::parse("file:/tmp/foo.c:3").unwrap();
+        assert_eq!(
+            link.expand_editor_command(
+                "/tmp/foo.c",
+                &[
+                    "code".to_string(),
+                    "-g".to_string(),
+                    "{file}:{line}:{column}".to_string()
+                ]
+            ),
+            vec!["code", "-g", "/tmp/foo.c:3:1"]
+        );
+    }
+}
@@ -1,6 +1,8 @@
+use crate::termwindow::echo::echoed_text;
 use crate::termwindow::InputMap;
 use ::window::{
-    DeadKeyStatus, KeyCode, KeyEvent, KeyboardLedStatus, Modifiers, RawKeyEvent, WindowOps,
+    DeadKeyStatus, KeyCode, KeyEvent, KeyboardLedStatus, Modifiers, PhysKeyCode, RawKeyEvent,
+    WindowOps,
 };
 use anyhow::Context;
 use config::keyassignment::{KeyAssignment, KeyTableEntry};
@@ -358,6 +360,9 @@ impl super::TermWindow {
 
             if bypass_compose {
                 if let Key::Code(term_key) = self.win_key_code_to_termwiz_key_code(keycode) {
+                    if self.is_pane_read_only(&pane) {
+                        return true;
+                    }
                     let tw_raw_modifiers = raw_modifiers;
 
                     let mut did_encode = false;
@@ -427,7 +432,68 @@ impl super::TermWindow {
         false
     }
 
-    pub fn raw_key_event_impl(&mut self, key: RawKeyEvent, context: &dyn WindowOps) {
+    /// Applies swap_alt_and_super and caps_lock_modifier to modifiers
+    fn remap_modifiers(&self, mut modifiers: Modifiers) -> Modifiers {
+        if self.config.swap_alt_and_super {
+            let alt_mask = Modifiers::ALT | Modifiers::LEFT_ALT | Modifiers::RIGHT_ALT;
+            let alt = modifiers.intersects(alt_mask);
+            let sup = modifiers.contains(Modifiers::SUPER);
+            modifiers.remove(alt_mask | Modifiers::SUPER);
+            if alt {
+                modifiers |= Modifiers::SUPER;
+            }
+            if sup {
+                modifiers |= Modifiers::ALT;
+            }
+        }
+        if self.caps_lock_held {
+            if let Some(mods) = self.config.caps_lock_modifier {
+                modifiers |= mods;
+            }
+        }
+        modifiers
+    }
+
+    /// The system still toggles the caps lock state when CapsLock is
+    /// being used as a modifier, which would otherwise leave letters
+    /// in the wrong case half of the time.  Undo that for letters.
+    fn undo_caps_lock(&self, key: &mut KeyEvent) {
+        if self.config.caps_lock_modifier.is_none()
+            || !key.leds.contains(KeyboardLedStatus::CAPS_LOCK)
+        {
+            return;
+        }
+        if let KeyCode::Char(c) = key.key {
+            if !c.is_alphabetic() {
+                return;
+            }
+            let shifted = key
+                .raw
+                .as_ref()
+                .map_or(key.modifiers, |raw| raw.modifiers)
+                .contains(Modifiers::SHIFT);
+            let mut changed = if shifted {
+                c.to_uppercase()
+            } else {
+                c.to_lowercase()
+            };
+            if let (Some(c), None) = (changed.next(), changed.next()) {
+                key.key = KeyCode::Char(c);
+            }
+        }
+    }
+
+    pub fn raw_key_event_impl(&mut self, mut key: RawKeyEvent, context: &dyn WindowOps) {
+        if self.config.caps_lock_modifier.is_some() && key.phys_code == Some(PhysKeyCode::CapsLock)
+        {
+            // CapsLock is acting as a modifier; track its state
+            // rather than treating it as a key press
+            self.caps_lock_held = key.key_is_down;
+            key.set_handled();
+            return;
+        }
+        key.modifiers = self.remap_modifiers(key.modifiers);
+
         // The leader key is a kind of modal modifier key.
         // It is allowed to be active for up to the leader timeout duration,
         // after which it auto-deactivates.
@@ -531,6 +597,10 @@ impl super::TermWindow {
         self.current_modifier_and_leds
     }
 
+    pub fn keyboard_layout(&self) -> Option<String> {
+        self.keyboard_layout.clone()
+    }
+
     pub fn leader_is_active(&self) -> bool {
         match self.leader_is_down.as_ref() {
             Some(expiry) if *expiry > std::time::Instant::now() => {
@@ -596,7 +666,13 @@ impl super::TermWindow {
         }
     }
 
-    pub fn key_event_impl(&mut self, window_key: KeyEvent, context: &dyn WindowOps) {
+    pub fn key_event_impl(&mut self, mut window_key: KeyEvent, context: &dyn WindowOps) {
+        if self.config.caps_lock_modifier.is_some() && window_key.key == KeyCode::CapsLock {
+            return;
+        }
+        window_key.modifiers = self.remap_modifiers(window_key.modifiers);
+        self.undo_caps_lock(&mut window_key);
+
         let pane = match self.get_active_pane_or_overlay() {
             Some(pane) => pane,
             None => return,
@@ -671,6 +747,10 @@ impl super::TermWindow {
                     return;
                 }
 
+                if self.is_pane_read_only(&pane) {
+                    return;
+                }
+
                 let res = if let Some(encoded) = self.encode_win32_input(&pane, &window_key) {
                     if self.config.debug_key_events {
                         log::info!("win32: Encoded input as {:?}", encoded);
@@ -708,6 +788,7 @@ impl super::TermWindow {
                         && self.pane_state(pane.pane_id()).overlay.is_none()
                     {
                         self.maybe_scroll_to_bottom_for_input(&pane);
+                        self.predict_echo(&pane, echoed_text(&key, modifiers));
                     }
                     if window_key.key_is_down
                         && self.config.hide_mouse_cursor_when_typing
@@ -732,11 +813,15 @@ impl super::TermWindow {
                     return;
                 }
                 self.key_table_state.did_process_key();
+                if self.is_pane_read_only(&pane) {
+                    return;
+                }
                 if self.config.debug_key_events {
                     log::info!("send to pane string={:?}", s);
                 }
                 pane.writer().write_all(s.as_bytes()).ok();
                 self.maybe_scroll_to_bottom_for_input(&pane);
+                self.predict_echo(&pane, Some(s));
                 context.invalidate();
             }
             Key::None => {}
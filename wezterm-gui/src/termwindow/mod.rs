@@ -4,6 +4,7 @@ use super::utilsprites::RenderMetrics;
 use crate::colorease::ColorEase;
 use crate::frontend::{front_end, try_front_end};
 use crate::inputmap::InputMap;
+use crate::overlay::global_search::SearchScope;
 use crate::overlay::{
     confirm_close_pane, confirm_close_tab, confirm_close_window, confirm_quit_program, launcher,
     start_overlay, start_overlay_pane, CopyModeParams, CopyOverlay, LauncherArgs, LauncherFlags,
@@ -14,13 +15,17 @@ use crate::scripting::guiwin::GuiWin;
 use crate::scrollbar::*;
 use crate::selection::Selection;
 use crate::shapecache::*;
+use crate::shapeworker::ShapeWorkers;
 use crate::tabbar::{TabBarItem, TabBarState};
 use crate::termwindow::background::{
     load_background_image, reload_background_image, LoadedBackgroundLayer,
 };
+use crate::termwindow::config_change::ConfigChange;
 use crate::termwindow::keyevent::{KeyTableArgs, KeyTableState};
 use crate::termwindow::modal::Modal;
+use crate::termwindow::render::diff_highlight::DiffZoneCache;
 use crate::termwindow::render::paint::AllowImage;
+use crate::termwindow::render::present::PresentedFrame;
 use crate::termwindow::render::{
     CachedLineState, LineQuadCacheKey, LineQuadCacheValue, LineToEleShapeCacheKey,
     LineToElementShapeItem,
@@ -30,13 +35,14 @@ use ::wezterm_term::input::{ClickPosition, MouseButton as TMB};
 use ::window::*;
 use anyhow::{anyhow, ensure, Context};
 use config::keyassignment::{
-    KeyAssignment, PaneDirection, Pattern, PromptInputLine, QuickSelectArguments,
-    RotationDirection, SpawnCommand, SplitSize,
+    KeyAssignment, OverlayScope, PaneAxis, PaneDirection, PaneMonitor, Pattern, PromptInputLine,
+    QuickSelectArguments, RotationDirection, ShowOverlay, SpawnCommand, SplitSize,
 };
-use config::window::WindowLevel;
+use config::window::{WindowLevel, WindowProgress};
 use config::{
     configuration, AudibleBell, ConfigHandle, Dimension, DimensionContext, FrontEndSelection,
-    GeometryOrigin, GuiPosition, TermConfig, WindowCloseConfirmation,
+    GeometryOrigin, GuiPosition, HsbTransform, RequestAttention, TermConfig,
+    WindowCloseConfirmation,
 };
 use lfucache::*;
 use mlua::{FromLua, UserData, UserDataFields};
@@ -53,15 +59,18 @@ use mux::{Mux, MuxNotification};
 use mux_lua::MuxPane;
 use smol::channel::Sender;
 use smol::Timer;
-use std::cell::{RefCell, RefMut};
+use std::cell::{Cell, RefCell, RefMut};
 use std::collections::{HashMap, LinkedList};
 use std::ops::Add;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use termwiz::escape::osc::Progress;
 use termwiz::hyperlink::Hyperlink;
 use termwiz::surface::SequenceNo;
+use url::Url;
 use wezterm_dynamic::Value;
 use wezterm_font::FontConfiguration;
 use wezterm_term::color::ColorPalette;
@@ -72,15 +81,21 @@ pub mod background;
 pub mod box_model;
 pub mod charselect;
 pub mod clipboard;
+mod config_change;
+mod echo;
+mod filelink;
 pub mod keyevent;
 pub mod modal;
+mod monitor;
 mod mouseevent;
 pub mod palette;
 pub mod paneselect;
 mod prevcursor;
 pub mod render;
 pub mod resize;
+mod screenshot;
 mod selection;
+mod smoothscroll;
 pub mod spawn;
 pub mod webgpu;
 use crate::spawn::SpawnWhere;
@@ -88,6 +103,10 @@ use prevcursor::PrevCursorPos;
 
 const ATLAS_SIZE: usize = 128;
 
+/// The minimum interval between status updates for windows that
+/// are not focused
+const UNFOCUSED_STATUS_UPDATE_INTERVAL: Duration = Duration::from_secs(10);
+
 lazy_static::lazy_static! {
     static ref WINDOW_CLASS: Mutex<String> = Mutex::new(wezterm_gui_subcommands::DEFAULT_WINDOW_CLASS.to_owned());
     static ref POSITION: Mutex<Option<GuiPosition>> = Mutex::new(None);
@@ -117,6 +136,7 @@ pub enum MouseCapture {
 /// context of the window-specific event loop
 pub enum TermWindowNotif {
     InvalidateShapeCache,
+    BackgroundShapingComplete,
     PerformAssignment {
         pane_id: PaneId,
         assignment: KeyAssignment,
@@ -136,6 +156,7 @@ pub enum TermWindowNotif {
     },
     GetConfigOverrides(Sender<wezterm_dynamic::Value>),
     SetConfigOverrides(wezterm_dynamic::Value),
+    MergeConfigOverrides(wezterm_dynamic::Value),
     CancelOverlayForPane(PaneId),
     CancelOverlayForTab {
         tab_id: TabId,
@@ -159,6 +180,7 @@ pub enum UIItemType {
     ScrollThumb,
     BelowScrollThumb,
     Split(PositionedSplit),
+    ScrollPositionIndicator,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -204,6 +226,25 @@ pub struct PaneState {
 
     bell_start: Option<Instant>,
     pub mouse_terminal_coords: Option<(ClickPosition, StableRowIndex)>,
+    /// The number of pixels by which the content is shifted down
+    /// from the top of the viewport, when smooth scrolling has
+    /// left it part way between two lines
+    scroll_pixel_offset: f32,
+    monitor: Option<monitor::PaneMonitorState>,
+    /// The palette used to render the most recent frame; cleared
+    /// when the palette of the pane may have changed
+    displayed_palette: Option<Rc<ColorPalette>>,
+    /// Incremented whenever displayed_palette changes, so that
+    /// cached line quads are rebuilt with the new colors
+    palette_generation: usize,
+    /// The palette to fade from, and when the fade started
+    palette_transition: Option<(Rc<ColorPalette>, Instant)>,
+    /// Whether the output of the most recent command is highlighted
+    /// where it differs from that of the command before it
+    diff_highlight: bool,
+    diff_zones: DiffZoneCache,
+    /// Typed characters that we have shown before the pane echoes them
+    echo: echo::EchoState,
 }
 
 /// Data used when synchronously formatting pane and window titles
@@ -215,6 +256,9 @@ pub struct TabInformation {
     pub active_pane: Option<PaneInformation>,
     pub window_id: MuxWindowId,
     pub tab_title: String,
+    /// Set when a pane in the tab that is being monitored via
+    /// TogglePaneMonitor has raised an alert that hasn't been seen
+    pub monitor_alert: Option<PaneMonitor>,
 }
 
 impl UserData for TabInformation {
@@ -243,6 +287,9 @@ impl UserData for TabInformation {
         });
         fields.add_field_method_get("window_id", |_, this| Ok(this.window_id));
         fields.add_field_method_get("tab_title", |_, this| Ok(this.tab_title.clone()));
+        fields.add_field_method_get("monitor_alert", |_, this| {
+            Ok(this.monitor_alert.map(|mode| format!("{mode:?}")))
+        });
         fields.add_field_method_get("window_title", |_, this| {
             let mux = Mux::get();
             let window = mux.get_window(this.window_id).ok_or_else(|| {
@@ -253,6 +300,16 @@ impl UserData for TabInformation {
     }
 }
 
+fn progress_to_window_progress(progress: Progress) -> WindowProgress {
+    match progress {
+        Progress::None => WindowProgress::None,
+        Progress::SetPercentage(pct) => WindowProgress::Normal(pct),
+        Progress::SetError(pct) => WindowProgress::Error(pct),
+        Progress::Indeterminate => WindowProgress::Indeterminate,
+        Progress::Paused(pct) => WindowProgress::Paused(pct),
+    }
+}
+
 /// Data used when synchronously formatting pane and window titles
 #[derive(Debug, Clone)]
 pub struct PaneInformation {
@@ -269,6 +326,9 @@ pub struct PaneInformation {
     pub pixel_height: usize,
     pub title: String,
     pub user_vars: HashMap<String, String>,
+    pub domain_name: String,
+    pub is_read_only: bool,
+    pub progress: WindowProgress,
 }
 
 impl UserData for PaneInformation {
@@ -317,21 +377,9 @@ impl UserData for PaneInformation {
             }
             Ok(None)
         });
-        fields.add_field_method_get("domain_name", |_, this| {
-            let mut name = None;
-            if let Some(mux) = Mux::try_get() {
-                if let Some(pane) = mux.get_pane(this.pane_id) {
-                    let domain_id = pane.domain_id();
-                    name = mux
-                        .get_domain(domain_id)
-                        .map(|dom| dom.domain_name().to_string());
-                }
-            }
-            match name {
-                Some(name) => Ok(name),
-                None => Ok("".to_string()),
-            }
-        });
+        fields.add_field_method_get("domain_name", |_, this| Ok(this.domain_name.clone()));
+        fields.add_field_method_get("is_read_only", |_, this| Ok(this.is_read_only));
+        fields.add_field_method_get("progress", |_, this| Ok(this.progress));
     }
 }
 
@@ -341,6 +389,9 @@ pub struct TabState {
     /// contents, we're overlaying a little internal application
     /// tab.  We'll also route input to it.
     pub overlay: Option<OverlayState>,
+    /// The zoomed pane as of the last time that we checked,
+    /// used to detect changes to the zoom state
+    pub zoomed_pane: Option<PaneId>,
 }
 
 /// Manages the state/queue of lua based event handlers.
@@ -362,6 +413,9 @@ pub struct TermWindow {
     pub window: Option<Window>,
     pub config: ConfigHandle,
     pub config_overrides: wezterm_dynamic::Value,
+    /// The most recent configuration error that was shown in
+    /// this window, so that it isn't shown repeatedly
+    last_config_error: Option<String>,
     os_parameters: Option<parameters::Parameters>,
     /// When we most recently received keyboard focus
     pub focused: Option<Instant>,
@@ -370,8 +424,18 @@ pub struct TermWindow {
     pub dimensions: Dimensions,
     pub window_state: WindowState,
     pub resizes_pending: usize,
+    /// The dimensions, window state and terminal size that were
+    /// current when we last emitted the `window-resized` event
+    last_resized_event: Option<(Dimensions, WindowState, usize, usize)>,
     is_repaint_pending: bool,
     pending_scale_changes: LinkedList<resize::ScaleChange>,
+    /// The font scale chosen by the user via IncreaseFontSize and friends.
+    /// This is tracked separately from the scale of the fonts so that
+    /// it can be reapplied after config reloads and dpi changes.
+    user_font_scale: f64,
+    /// The adjustment to window_hsb made via AdjustWindowHsb;
+    /// like user_font_scale, it is retained across config reloads
+    user_window_hsb: HsbTransform,
     /// Terminal dimensions
     terminal_size: TerminalSize,
     pub mux_window_id: MuxWindowId,
@@ -406,6 +470,9 @@ pub struct TermWindow {
     window_background: Vec<LoadedBackgroundLayer>,
 
     current_modifier_and_leds: (Modifiers, KeyboardLedStatus),
+    keyboard_layout: Option<String>,
+    /// Whether CapsLock is being held while caps_lock_modifier is set
+    caps_lock_held: bool,
     current_mouse_buttons: Vec<MousePress>,
     current_mouse_capture: Option<MouseCapture>,
 
@@ -413,6 +480,22 @@ pub struct TermWindow {
 
     /// Keeps track of double and triple clicks
     last_mouse_click: Option<LastMouseClick>,
+    /// Where the most recent mouse press happened
+    last_mouse_press_coords: Option<Point>,
+    /// Whether the mouse has moved beyond the mouse_drag_threshold
+    /// since the most recent press
+    mouse_drag_started: bool,
+    /// The pane whose selection will be dragged out of the window
+    /// if the mouse is dragged with the button held down
+    selection_drag_pane: Option<PaneId>,
+    /// Touchpad scrolling that didn't yet amount to a whole line
+    pixel_scroll_remainder: isize,
+    kinetic_scroll: Option<smoothscroll::KineticScroll>,
+
+    /// The progress shown in the taskbar entry of the window,
+    /// and the pane that reported it via OSC 9;4, if any
+    window_progress: WindowProgress,
+    progress_pane: Option<PaneId>,
 
     /// The URL over which we are currently hovering
     current_highlight: Option<Arc<Hyperlink>>,
@@ -420,6 +503,10 @@ pub struct TermWindow {
     quad_generation: usize,
     shape_generation: usize,
     shape_cache: RefCell<LfuCache<ShapeCacheKey, anyhow::Result<Rc<Vec<ShapedInfo>>>>>,
+    shape_workers: ShapeWorkers,
+    /// Once this time has passed while painting a frame, any further
+    /// shaping of pane content is handed off to shape_workers
+    shaping_deadline: Cell<Option<Instant>>,
     line_to_ele_shape_cache: RefCell<LfuCache<LineToEleShapeCacheKey, LineToElementShapeItem>>,
 
     line_state_cache: RefCell<LfuCacheU64<Arc<CachedLineState>>>,
@@ -441,11 +528,29 @@ pub struct TermWindow {
 
     event_states: HashMap<String, EventState>,
     pub current_event: Option<Value>,
+    /// The active tab and pane as of the last title update; used
+    /// to detect changes in focus so that we can emit events
+    last_active_tab: Option<TabId>,
+    last_active_pane: Option<PaneId>,
     has_animation: RefCell<Option<Instant>>,
     /// We use this to attempt to do something reasonable
     /// if we run out of texture space
     allow_images: AllowImage,
     scheduled_animation: RefCell<Option<Instant>>,
+    /// Used to skip presenting frames that are identical to the last one
+    presented_frame: Option<PresentedFrame>,
+    /// When the window is hidden, the time at which we next allow it
+    /// to be repainted
+    hidden_repaint_due: Option<Instant>,
+    /// Where to save the next frame that is painted, when a
+    /// screenshot has been requested
+    screenshot_path: Option<PathBuf>,
+    /// The icon set by window:set_icon, which takes precedence
+    /// over the icon from the config
+    icon_override: Option<PathBuf>,
+    /// When the terminal dimensions last changed, so that
+    /// they can be shown for a little while afterwards
+    resize_overlay_shown: Option<Instant>,
 
     created: Instant,
 
@@ -526,9 +631,11 @@ impl TermWindow {
 
         if self.focused.is_none() {
             self.last_mouse_click = None;
+            self.last_mouse_press_coords = None;
             self.current_mouse_buttons.clear();
             self.current_mouse_capture = None;
             self.is_click_to_focus_window = false;
+            self.caps_lock_held = false;
 
             for state in self.pane_state.borrow_mut().values_mut() {
                 state.mouse_terminal_coords.take();
@@ -538,6 +645,14 @@ impl TermWindow {
         // Reset the cursor blink phase
         self.prev_cursor.bump();
 
+        // Periodic status updates are less frequent while we are not
+        // focused; bring the status up to date and resume the usual
+        // interval rather than waiting for the longer one to elapse
+        if focused {
+            self.last_status_call = Instant::now();
+            self.schedule_status_update();
+        }
+
         // force cursor to be repainted
         window.invalidate();
 
@@ -563,9 +678,14 @@ impl TermWindow {
                     config::wezterm_version(),
                 );
                 self.render_state.replace(render_state);
+                self.load_background_shader();
             }
             Err(err) => {
-                log::error!("failed to create RenderState: {}", err);
+                log::error!(
+                    "failed to create RenderState using {}: {:#}",
+                    render_info,
+                    err
+                );
             }
         }
 
@@ -688,6 +808,11 @@ impl TermWindow {
             window_background,
             config: config.clone(),
             config_overrides: wezterm_dynamic::Value::default(),
+            // An error at startup is reported via the configuration
+            // error window rather than in an overlay
+            last_config_error: config::configuration_result()
+                .err()
+                .map(|err| err.to_string()),
             palette: None,
             focused: None,
             mux_window_id,
@@ -697,8 +822,11 @@ impl TermWindow {
             dimensions,
             window_state: WindowState::default(),
             resizes_pending: 0,
+            last_resized_event: None,
             is_repaint_pending: false,
             pending_scale_changes: LinkedList::new(),
+            user_font_scale: 1.0,
+            user_window_hsb: HsbTransform::default(),
             terminal_size,
             render_state,
             input_map: InputMap::new(&config),
@@ -714,6 +842,8 @@ impl TermWindow {
             window_drag_position: None,
             current_mouse_event: None,
             current_modifier_and_leds: Default::default(),
+            keyboard_layout: None,
+            caps_lock_held: false,
             prev_cursor: PrevCursorPos::new(),
             last_scroll_info: RenderableDimensions::default(),
             tab_state: RefCell::new(HashMap::new()),
@@ -721,6 +851,13 @@ impl TermWindow {
             current_mouse_buttons: vec![],
             current_mouse_capture: None,
             last_mouse_click: None,
+            last_mouse_press_coords: None,
+            mouse_drag_started: false,
+            selection_drag_pane: None,
+            pixel_scroll_remainder: 0,
+            kinetic_scroll: None,
+            window_progress: WindowProgress::None,
+            progress_pane: None,
             current_highlight: None,
             quad_generation: 0,
             shape_generation: 0,
@@ -730,6 +867,8 @@ impl TermWindow {
                 |config| config.shape_cache_size,
                 &config,
             )),
+            shape_workers: ShapeWorkers::default(),
+            shaping_deadline: Cell::new(None),
             line_state_cache: RefCell::new(LfuCacheU64::new(
                 "line_state_cache.hit.rate",
                 "line_state_cache.miss.rate",
@@ -773,8 +912,15 @@ impl TermWindow {
             )),
             event_states: HashMap::new(),
             current_event: None,
+            last_active_tab: None,
+            last_active_pane: None,
             has_animation: RefCell::new(None),
             scheduled_animation: RefCell::new(None),
+            presented_frame: None,
+            hidden_repaint_due: None,
+            screenshot_path: None,
+            icon_override: None,
+            resize_overlay_shown: None,
             allow_images: AllowImage::Yes,
             semantic_zones: HashMap::new(),
             ui_items: vec![],
@@ -812,8 +958,13 @@ impl TermWindow {
         };
         log::trace!("{:?}", geometry);
 
+        let class = mux
+            .get_window(mux_window_id)
+            .and_then(|window| window.get_initial_class().map(|class| class.to_string()))
+            .unwrap_or_else(get_window_class);
+
         let window = Window::new_window(
-            &get_window_class(),
+            &class,
             "wezterm",
             geometry,
             Some(&config),
@@ -828,13 +979,16 @@ impl TermWindow {
         .await?;
         tw.borrow_mut().window.replace(window.clone());
 
-        Self::apply_icon(&window)?;
+        tw.borrow().apply_icon();
 
+        // Reload errors are shown in an overlay by show_config_error_if_changed
+        config::set_show_reload_errors(false);
         let config_subscription = config::subscribe_to_config_reload({
             let window = window.clone();
             move || {
                 window.notify(TermWindowNotif::Apply(Box::new(|tw| {
-                    tw.config_was_reloaded()
+                    tw.config_was_reloaded();
+                    tw.show_config_error_if_changed();
                 })));
                 true
             }
@@ -897,6 +1051,17 @@ impl TermWindow {
         window: &Window,
     ) -> anyhow::Result<bool> {
         log::debug!("{event:?}");
+        // Anything other than a repaint request or a notification
+        // (which are handled below) may change what is on screen
+        // in ways that the presented frame fingerprint doesn't track.
+        // That includes Exposed, as the window system has discarded
+        // the frame that we presented.
+        if !matches!(
+            event,
+            WindowEvent::NeedRepaint | WindowEvent::Notification(_)
+        ) {
+            self.invalidate_presented_frame();
+        }
         match event {
             WindowEvent::Destroyed => {
                 // Ensure that we cancel any overlays we had running, so
@@ -973,6 +1138,18 @@ impl TermWindow {
                 window.invalidate();
                 Ok(true)
             }
+            WindowEvent::KeyboardLayoutChanged(layout) => {
+                // The first report is the layout that was already
+                // active when we gained focus, which isn't a change
+                // from the perspective of the user
+                let prior = self.keyboard_layout.replace(layout);
+                if prior.is_some() {
+                    self.dead_key_status = DeadKeyStatus::None;
+                    self.update_title();
+                    self.emit_window_event("keyboard-layout-changed", None);
+                }
+                Ok(true)
+            }
             WindowEvent::RawKeyEvent(event) => {
                 self.raw_key_event_impl(event, window);
                 Ok(true)
@@ -998,6 +1175,8 @@ impl TermWindow {
                 if self.resizes_pending > 0 {
                     self.is_repaint_pending = true;
                     Ok(true)
+                } else if self.defer_hidden_paint(window) {
+                    Ok(true)
                 } else if self.webgpu.is_some() {
                     self.do_paint_webgpu()
                 } else {
@@ -1013,16 +1192,16 @@ impl TermWindow {
             }
             WindowEvent::DroppedString(text) => {
                 let pane = match self.get_active_pane_or_overlay() {
-                    Some(pane) => pane,
-                    None => return Ok(true),
+                    Some(pane) if !self.is_pane_read_only(&pane) => pane,
+                    _ => return Ok(true),
                 };
                 pane.send_paste(text.as_str())?;
                 Ok(true)
             }
             WindowEvent::DroppedUrl(urls) => {
                 let pane = match self.get_active_pane_or_overlay() {
-                    Some(pane) => pane,
-                    None => return Ok(true),
+                    Some(pane) if !self.is_pane_read_only(&pane) => pane,
+                    _ => return Ok(true),
                 };
                 let urls = urls
                     .iter()
@@ -1035,8 +1214,8 @@ impl TermWindow {
             }
             WindowEvent::DroppedFile(paths) => {
                 let pane = match self.get_active_pane_or_overlay() {
-                    Some(pane) => pane,
-                    None => return Ok(true),
+                    Some(pane) if !self.is_pane_read_only(&pane) => pane,
+                    _ => return Ok(true),
                 };
                 let paths = paths
                     .iter()
@@ -1052,6 +1231,7 @@ impl TermWindow {
                 Ok(true)
             }
             WindowEvent::DraggedFile(_) => Ok(true),
+            WindowEvent::Exposed => Ok(true),
         }
     }
 
@@ -1068,6 +1248,11 @@ impl TermWindow {
             return false;
         }
 
+        if self.frame_is_unchanged() {
+            log::trace!("skip presenting unchanged frame");
+            return true;
+        }
+
         let mut frame = glium::Frame::new(
             Rc::clone(&gl),
             (
@@ -1076,10 +1261,15 @@ impl TermWindow {
             ),
         );
         self.paint_impl(&mut RenderFrame::Glium(&mut frame));
+        self.save_screenshot_glium(&frame);
         window.finish_frame(frame).is_ok()
     }
 
     fn do_paint_webgpu(&mut self) -> anyhow::Result<bool> {
+        if self.frame_is_unchanged() {
+            log::trace!("skip presenting unchanged frame");
+            return Ok(true);
+        }
         self.webgpu.as_mut().unwrap().resize(self.dimensions);
         match self.do_paint_webgpu_impl() {
             Ok(ok) => Ok(ok),
@@ -1106,6 +1296,15 @@ impl TermWindow {
             anyhow::anyhow!("{}", e)
         }
 
+        // Pane output is accounted for by the presented frame; anything
+        // else may have changed the tab bar, a modal or other window state
+        if !matches!(
+            notif,
+            TermWindowNotif::MuxNotification(MuxNotification::PaneOutput(_))
+        ) {
+            self.invalidate_presented_frame();
+        }
+
         match notif {
             TermWindowNotif::InvalidateShapeCache => {
                 self.shape_generation += 1;
@@ -1113,6 +1312,9 @@ impl TermWindow {
                 self.invalidate_modal();
                 window.invalidate();
             }
+            TermWindowNotif::BackgroundShapingComplete => {
+                window.invalidate();
+            }
             TermWindowNotif::PerformAssignment {
                 pane_id,
                 assignment,
@@ -1184,6 +1386,24 @@ impl TermWindow {
                     self.config_was_reloaded();
                 }
             }
+            TermWindowNotif::MergeConfigOverrides(value) => {
+                let merged = match (self.config_overrides.clone(), value) {
+                    (
+                        wezterm_dynamic::Value::Object(mut overrides),
+                        wezterm_dynamic::Value::Object(additions),
+                    ) => {
+                        for (key, value) in additions {
+                            overrides.insert(key, value);
+                        }
+                        wezterm_dynamic::Value::Object(overrides)
+                    }
+                    (_, value) => value,
+                };
+                if merged != self.config_overrides {
+                    self.config_overrides = merged;
+                    self.config_was_reloaded();
+                }
+            }
             TermWindowNotif::CancelOverlayForPane(pane_id) => {
                 self.cancel_overlay_for_pane(pane_id);
             }
@@ -1216,6 +1436,7 @@ impl TermWindow {
                     // Shape cache includes color information, so
                     // ensure that we invalidate that as part of
                     // this overall invalidation for the palette
+                    self.invalidate_pane_palette(pane_id);
                     self.dispatch_notif(TermWindowNotif::InvalidateShapeCache, window)?;
                     self.mux_pane_output_event(pane_id);
                 }
@@ -1223,15 +1444,23 @@ impl TermWindow {
                     alert: Alert::Bell,
                     pane_id,
                 } => {
-                    match self.config.audible_bell {
+                    match &self.config.audible_bell {
                         AudibleBell::SystemBeep => {
                             Connection::get().expect("on main thread").beep();
                         }
+                        AudibleBell::SoundFile(path) => {
+                            let conn = Connection::get().expect("on main thread");
+                            if let Err(err) = conn.play_sound_file(Path::new(path)) {
+                                log::error!("audible_bell: {:#}, falling back to SystemBeep", err);
+                                conn.beep();
+                            }
+                        }
                         AudibleBell::Disabled => {}
                     }
 
                     log::trace!("Ding! (this is the bell) in pane {}", pane_id);
                     self.emit_window_event("bell", Some(pane_id));
+                    self.request_attention_for_pane(pane_id, self.config.bell_request_attention);
 
                     let mut per_pane = self.pane_state(pane_id);
                     per_pane.bell_start.replace(Instant::now());
@@ -1239,8 +1468,31 @@ impl TermWindow {
                 }
                 MuxNotification::Alert {
                     alert: Alert::ToastNotification { .. },
-                    ..
-                } => {}
+                    pane_id,
+                } => {
+                    self.request_attention_for_pane(
+                        pane_id,
+                        self.config.notification_request_attention,
+                    );
+                }
+                MuxNotification::Alert {
+                    alert: Alert::WindowResizeRequested(request),
+                    pane_id,
+                } => {
+                    self.window_resize_requested(pane_id, request);
+                }
+                MuxNotification::Alert {
+                    alert: Alert::Progress(progress),
+                    pane_id,
+                } => {
+                    self.pane_progress_changed(pane_id, progress);
+                    self.update_title();
+                }
+                MuxNotification::PaneRemoved(pane_id) => {
+                    if self.progress_pane == Some(pane_id) {
+                        self.set_window_progress(WindowProgress::None, None);
+                    }
+                }
                 MuxNotification::TabAddedToWindow {
                     window_id: _,
                     tab_id,
@@ -1265,7 +1517,7 @@ impl TermWindow {
                             || size.pixel_width != self.terminal_size.pixel_width
                             || size.pixel_height != self.terminal_size.pixel_height
                         {
-                            self.set_window_size(size, window)?;
+                            self.set_window_size(size, self.user_font_scale, window)?;
                         } else if tab_size.dpi == 0 {
                             log::debug!("fixup dpi in newly added tab");
                             tab.resize(self.terminal_size);
@@ -1292,16 +1544,17 @@ impl TermWindow {
                     // Also handled by clientpane
                     self.update_title_post_status();
                 }
-                MuxNotification::TabResized(_) => {
+                MuxNotification::TabResized(tab_id) => {
                     // Also handled by wezterm-client
+                    self.check_for_zoom_change(tab_id);
                     self.update_title_post_status();
                 }
                 MuxNotification::TabTitleChanged { .. } => {
                     self.update_title_post_status();
                 }
                 MuxNotification::PaneAdded(_)
+                | MuxNotification::PaneExited(_)
                 | MuxNotification::WorkspaceRenamed { .. }
-                | MuxNotification::PaneRemoved(_)
                 | MuxNotification::WindowWorkspaceChanged(_)
                 | MuxNotification::ActiveWorkspaceChanged(_)
                 | MuxNotification::Empty
@@ -1384,16 +1637,93 @@ impl TermWindow {
         self.tab_state.borrow_mut().clear();
     }
 
-    fn apply_icon(window: &Window) -> anyhow::Result<()> {
-        let image = image::load_from_memory(ICON_DATA)?.into_rgba8();
-        let (width, height) = image.dimensions();
-        window.set_icon(Image::with_rgba32(
-            width as usize,
-            height as usize,
-            width as usize * 4,
-            image.as_raw(),
-        ));
-        Ok(())
+    /// Returns the path to the icon for this window: the one set by
+    /// window:set_icon, then the one for its class in
+    /// window_icon_by_class, then window_icon.
+    /// None means that the built-in icon is used.
+    fn icon_path(&self) -> Option<PathBuf> {
+        if let Some(path) = &self.icon_override {
+            return Some(path.clone());
+        }
+        let class = Mux::get()
+            .get_window(self.mux_window_id)
+            .and_then(|window| window.get_initial_class().map(|class| class.to_string()))
+            .unwrap_or_else(get_window_class);
+        self.config
+            .window_icon_by_class
+            .get(&class)
+            .or(self.config.window_icon.as_ref())
+            .cloned()
+    }
+
+    /// Sets the icon for this window, taking precedence over the
+    /// icon from the config, or reverts to the icon from the config
+    /// when path is None
+    pub fn set_icon(&mut self, path: Option<PathBuf>) {
+        self.icon_override = path;
+        self.apply_icon();
+    }
+
+    fn apply_icon(&self) {
+        fn load_icon(path: Option<PathBuf>) -> anyhow::Result<image::RgbaImage> {
+            if let Some(path) = path {
+                match image::open(&path) {
+                    Ok(image) => return Ok(image.into_rgba8()),
+                    Err(err) => {
+                        log::error!("Failed to load window icon {}: {:#}", path.display(), err);
+                    }
+                }
+            }
+            Ok(image::load_from_memory(ICON_DATA)?.into_rgba8())
+        }
+
+        let window = match self.window.as_ref() {
+            Some(window) => window,
+            None => return,
+        };
+
+        match load_icon(self.icon_path()) {
+            Ok(image) => {
+                let (width, height) = image.dimensions();
+                window.set_icon(Image::with_rgba32(
+                    width as usize,
+                    height as usize,
+                    width as usize * 4,
+                    image.as_raw(),
+                ));
+            }
+            Err(err) => log::error!("Failed to load window icon: {:#}", err),
+        }
+    }
+
+    /// Shows progress in the taskbar entry of the window.
+    /// pane_id is the pane that reported it, or None if it
+    /// was set by lua.
+    pub fn set_window_progress(&mut self, progress: WindowProgress, pane_id: Option<PaneId>) {
+        self.progress_pane = match progress {
+            WindowProgress::None => None,
+            _ => pane_id,
+        };
+        if progress == self.window_progress {
+            return;
+        }
+        self.window_progress = progress;
+        if let Some(window) = self.window.as_ref() {
+            window.set_progress(progress);
+        }
+    }
+
+    fn pane_progress_changed(&mut self, pane_id: PaneId, progress: Progress) {
+        let mux = Mux::get();
+        match mux.resolve_pane_id(pane_id) {
+            Some((_domain, window_id, _tab_id)) if window_id == self.mux_window_id => {}
+            _ => return,
+        }
+        if progress == Progress::None && self.progress_pane != Some(pane_id) {
+            // Don't let one pane clear the progress of another
+            return;
+        }
+        self.set_window_progress(progress_to_window_progress(progress), Some(pane_id));
     }
 
     fn schedule_status_update(&self) {
@@ -1422,8 +1752,31 @@ impl TermWindow {
         tab.contains_pane(pane_id)
     }
 
+    /// Asks the window system to draw the user's attention to this
+    /// window when pane_id, which belongs to it, has alerted while
+    /// the window isn't focused
+    fn request_attention_for_pane(&self, pane_id: PaneId, attention: RequestAttention) {
+        if self.focused.is_some() {
+            return;
+        }
+        let attention = match attention.attention_type() {
+            Some(attention) => attention,
+            None => return,
+        };
+        let mux = Mux::get();
+        match mux.resolve_pane_id(pane_id) {
+            Some((_domain, window_id, _tab_id)) if window_id == self.mux_window_id => {}
+            _ => return,
+        }
+        if let Some(window) = self.window.as_ref() {
+            window.request_attention(attention);
+        }
+    }
+
     fn mux_pane_output_event(&mut self, pane_id: PaneId) {
         metrics::histogram!("mux.pane_output_event.rate").record(1.);
+        self.pane_monitor_output(pane_id);
+        self.echo_output_arrived(pane_id);
         if self.is_pane_visible(pane_id) {
             if let Some(ref win) = self.window {
                 win.invalidate();
@@ -1452,6 +1805,9 @@ impl TermWindow {
                     | Alert::TabTitleChanged(_)
                     | Alert::IconTitleChanged(_)
                     | Alert::SetUserVar { .. }
+                    | Alert::ToastNotification { .. }
+                    | Alert::WindowResizeRequested(_)
+                    | Alert::Progress(_)
                     | Alert::Bell,
             }
             | MuxNotification::PaneFocused(pane_id)
@@ -1498,12 +1854,9 @@ impl TermWindow {
                     return true;
                 }
             }
-            MuxNotification::Alert {
-                alert: Alert::ToastNotification { .. },
-                ..
-            }
-            | MuxNotification::AssignClipboard { .. }
+            MuxNotification::AssignClipboard { .. }
             | MuxNotification::SaveToDownloads { .. }
+            | MuxNotification::PaneExited(_)
             | MuxNotification::WindowCreated(_)
             | MuxNotification::ActiveWorkspaceChanged(_)
             | MuxNotification::WorkspaceRenamed { .. }
@@ -1703,12 +2056,35 @@ impl TermWindow {
         self.palette.as_ref().unwrap()
     }
 
+    /// Reads window_background_shader and compiles it into the
+    /// glyph program, if it has changed
+    fn load_background_shader(&mut self) {
+        let source = match &self.config.window_background_shader {
+            Some(path) => match std::fs::read_to_string(path) {
+                Ok(source) => Some(source),
+                Err(err) => {
+                    log::error!(
+                        "Unable to read window_background_shader {}: {:#}",
+                        path.display(),
+                        err
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        if let Some(render_state) = self.render_state.as_mut() {
+            if let Err(err) = render_state.set_background_shader(source) {
+                config::show_error(&format!("{:#}", err));
+            }
+        }
+    }
+
     pub fn config_was_reloaded(&mut self) {
         log::debug!(
             "config was reloaded, overrides: {:?}",
             self.config_overrides
         );
-        self.key_table_state.clear_stack();
         self.connection_name = Connection::get().unwrap().name();
         let config = match config::overridden_config(&self.config_overrides) {
             Ok(config) => config,
@@ -1721,14 +2097,34 @@ impl TermWindow {
                 configuration()
             }
         };
+        let change = ConfigChange::between(&self.config, &config);
+        let icon_changed = config.window_icon != self.config.window_icon
+            || config.window_icon_by_class != self.config.window_icon_by_class;
+        self.invalidate_presented_frame();
         self.config = config.clone();
+        if icon_changed {
+            self.apply_icon();
+        }
         self.palette.take();
 
+        // Have each pane look up its palette again, fading from the
+        // colors that it is currently showing to whatever its palette
+        // turns out to be with the new config
+        let now = Instant::now();
+        for state in self.pane_state.borrow_mut().values_mut() {
+            if let Some(palette) = state.displayed_palette.take() {
+                if config.color_scheme_transition_duration_ms > 0 {
+                    state.palette_transition.replace((palette, now));
+                }
+            }
+        }
+
         let mux = Mux::get();
         let window = match mux.get_window(self.mux_window_id) {
             Some(window) => window,
             _ => return,
         };
+        let show_tab_bar = self.show_tab_bar;
         if window.len() == 1 {
             self.show_tab_bar = config.enable_tab_bar && !config.hide_tab_bar_if_only_one_tab;
         } else {
@@ -1761,7 +2157,9 @@ impl TermWindow {
         {
             let mut shape_cache = self.shape_cache.borrow_mut();
             shape_cache.update_config(&config);
-            shape_cache.clear();
+            if change.fonts {
+                shape_cache.clear();
+            }
         }
         self.line_state_cache.borrow_mut().update_config(&config);
         self.line_quad_cache.borrow_mut().update_config(&config);
@@ -1771,14 +2169,23 @@ impl TermWindow {
         self.fancy_tab_bar.take();
         self.invalidate_fancy_tab_bar();
         self.invalidate_modal();
-        self.input_map = InputMap::new(&config);
-        self.leader_is_down = None;
-        self.render_state.as_mut().map(|rs| rs.config_changed());
+        if change.input {
+            self.key_table_state.clear_stack();
+            self.input_map = InputMap::new(&config);
+            self.leader_is_down = None;
+        }
         let dimensions = self.dimensions;
 
-        if let Err(err) = self.fonts.config_changed(&config) {
-            log::error!("Failed to load font configuration: {:#}", err);
+        if change.fonts {
+            // Rebuilding the fonts discards the glyph cache and atlas,
+            // which is expensive, so only do it when something that
+            // affects glyph rendering has actually changed
+            self.render_state.as_mut().map(|rs| rs.config_changed());
+            if let Err(err) = self.fonts.config_changed(&config) {
+                log::error!("Failed to load font configuration: {:#}", err);
+            }
         }
+        self.load_background_shader();
 
         if let Some(window) = mux.get_window(self.mux_window_id) {
             let term_config: Arc<dyn TerminalConfiguration> =
@@ -1802,8 +2209,14 @@ impl TermWindow {
 
         if let Some(window) = self.window.as_ref().map(|w| w.clone()) {
             self.load_os_parameters();
-            self.apply_scale_change(&dimensions, self.fonts.get_font_scale());
-            self.apply_dimensions(&dimensions, None, &window);
+            // Rebuilding the fonts, or a change in the dpi, must not
+            // lose the zoom level that the user has selected
+            if change.fonts || self.fonts.get_font_scale() != self.user_font_scale {
+                self.apply_scale_change(&dimensions, self.user_font_scale);
+            }
+            if change.geometry || show_tab_bar != self.show_tab_bar {
+                self.apply_dimensions(&dimensions, None, &window);
+            }
             window.config_did_change(&config);
             window.invalidate();
         }
@@ -1946,6 +2359,11 @@ impl TermWindow {
         let active_tab = tabs.iter().find(|t| t.is_active).cloned();
         let active_pane = panes.iter().find(|p| p.is_active).cloned();
 
+        self.check_for_focus_change(
+            active_tab.as_ref().map(|tab| tab.tab_id),
+            active_pane.as_ref().map(|pane| pane.pane_id),
+        );
+
         let border = self.get_os_border();
         let tab_bar_height = self.tab_bar_pixel_height().unwrap_or(0.);
         let tab_bar_y = if self.config.tab_bar_at_bottom {
@@ -2072,7 +2490,13 @@ impl TermWindow {
         if let Some(window) = self.window.as_ref() {
             let now = Instant::now();
             if self.last_status_call <= now {
-                let interval = Duration::from_millis(self.config.status_update_interval);
+                let mut interval = Duration::from_millis(self.config.status_update_interval);
+                if self.focused.is_none() {
+                    // Wake up less often on behalf of a window that
+                    // isn't focused; focus_changed brings the status
+                    // up to date when it is focused again
+                    interval = interval.max(UNFOCUSED_STATUS_UPDATE_INTERVAL);
+                }
                 let target = now + interval;
                 self.last_status_call = target;
 
@@ -2299,6 +2723,91 @@ impl TermWindow {
         promise::spawn::spawn(future).detach();
     }
 
+    fn show_lua_overlay(&mut self, pane: &Arc<dyn Pane>, args: &ShowOverlay) {
+        let gui_win = GuiWin::new(self);
+        let mux_pane = MuxPane(pane.pane_id());
+        let args = args.clone();
+
+        match args.scope {
+            OverlayScope::Pane => {
+                let (overlay, future) = start_overlay_pane(self, pane, move |_pane_id, term| {
+                    crate::overlay::lua_overlay::show_lua_overlay(term, args, gui_win, mux_pane)
+                });
+                self.assign_overlay_for_pane(pane.pane_id(), overlay);
+                promise::spawn::spawn(future).detach();
+            }
+            OverlayScope::Tab => {
+                let mux = Mux::get();
+                let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
+                    Some(tab) => tab,
+                    None => return,
+                };
+                let (overlay, future) = start_overlay(self, &tab, move |_tab_id, term| {
+                    crate::overlay::lua_overlay::show_lua_overlay(term, args, gui_win, mux_pane)
+                });
+                self.assign_overlay(tab.tab_id(), overlay);
+                promise::spawn::spawn(future).detach();
+            }
+        }
+    }
+
+    /// Lists the matches for `pattern` from the panes in `scope`,
+    /// grouped by pane, in an overlay over the active tab
+    fn show_search_results_by_pane(&mut self, scope: SearchScope, pattern: MuxPattern) {
+        let mux = Mux::get();
+        let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
+            Some(tab) => tab,
+            None => return,
+        };
+
+        let window = self.window.as_ref().unwrap().clone();
+
+        let (overlay, future) = start_overlay(self, &tab, move |_tab_id, term| {
+            crate::overlay::global_search::global_search(term, window, scope, pattern)
+        });
+        self.assign_overlay(tab.tab_id(), overlay);
+        promise::spawn::spawn(future).detach();
+    }
+
+    fn show_search_all_tabs(&mut self, pattern: &Pattern) {
+        let pane = match self.get_active_pane_or_overlay() {
+            Some(pane) => pane,
+            None => return,
+        };
+
+        let pattern = self.resolve_search_pattern(pattern.clone(), &pane);
+        self.show_search_results_by_pane(SearchScope::Window(self.mux_window_id), pattern);
+    }
+
+    /// Shows an overlay describing the error if the most recent attempt
+    /// to reload the configuration failed with a different error than
+    /// the last one that we showed
+    fn show_config_error_if_changed(&mut self) {
+        let error = config::configuration_result()
+            .err()
+            .map(|err| err.to_string());
+        if error == self.last_config_error {
+            return;
+        }
+        self.last_config_error = error.clone();
+        let error = match error {
+            Some(error) => error,
+            None => return,
+        };
+
+        let mux = Mux::get();
+        let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
+            Some(tab) => tab,
+            None => return,
+        };
+
+        let (overlay, future) = start_overlay(self, &tab, move |_tab_id, term| {
+            crate::overlay::config_error::show_config_error_overlay(term, error)
+        });
+        self.assign_overlay(tab.tab_id(), overlay);
+        promise::spawn::spawn(future).detach();
+    }
+
     fn show_debug_overlay(&mut self) {
         let mux = Mux::get();
         let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
@@ -2518,6 +3027,14 @@ impl TermWindow {
 
         let window = self.window.as_ref().map(|w| w.clone());
 
+        if matches!(
+            assignment,
+            SendString(_) | SendKey(_) | PasteFrom(_) | ClearScreenPreservingScrollback
+        ) && self.is_pane_read_only(pane)
+        {
+            return Ok(PerformAssignmentResult::Handled);
+        }
+
         match assignment {
             ActivateKeyTable {
                 name,
@@ -2767,6 +3284,15 @@ impl TermWindow {
                 let window = self.window.as_ref().unwrap();
                 window.invalidate();
             }
+            ClearScreenPreservingScrollback => {
+                pane.scroll_viewport_into_scrollback();
+                // Have the application clear the screen; for shells,
+                // CTRL-L redraws the prompt at the top of the screen
+                pane.writer().write_all(b"\x0c")?;
+                self.scroll_to_bottom(pane);
+                let window = self.window.as_ref().unwrap();
+                window.invalidate();
+            }
             Search(pattern) => {
                 if let Some(pane) = self.get_active_pane_or_overlay() {
                     let mut replace_current = false;
@@ -2785,6 +3311,7 @@ impl TermWindow {
                             CopyModeParams {
                                 pattern: self.resolve_search_pattern(pattern.clone(), &pane),
                                 editing_search: true,
+                                all_panes: false,
                             },
                         )?;
                         self.assign_overlay_for_pane(pane.pane_id(), search);
@@ -2804,6 +3331,7 @@ impl TermWindow {
                         });
                 }
             }
+            SearchAllTabs(pattern) => self.show_search_all_tabs(pattern),
             QuickSelect => {
                 if let Some(pane) = self.get_active_pane_no_overlay() {
                     let qa = QuickSelectOverlay::with_pane(
@@ -2820,6 +3348,12 @@ impl TermWindow {
                     self.assign_overlay_for_pane(pane.pane_id(), qa);
                 }
             }
+            LinkHints => {
+                if let Some(pane) = self.get_active_pane_no_overlay() {
+                    let qa = QuickSelectOverlay::with_links(self, &pane);
+                    self.assign_overlay_for_pane(pane.pane_id(), qa);
+                }
+            }
             ActivateCopyMode => {
                 if let Some(pane) = self.get_active_pane_or_overlay() {
                     let mut replace_current = false;
@@ -2835,6 +3369,7 @@ impl TermWindow {
                             CopyModeParams {
                                 pattern: MuxPattern::default(),
                                 editing_search: false,
+                                all_panes: false,
                             },
                         )?;
                         self.assign_overlay_for_pane(pane.pane_id(), copy);
@@ -2904,6 +3439,37 @@ impl TermWindow {
                 };
                 tab.toggle_zoom();
             }
+            TogglePaneMonitor(mode) => self.toggle_pane_monitor(pane.pane_id(), *mode),
+            TogglePaneReadOnly => {
+                let mux = Mux::get();
+                let pane_id = pane.pane_id();
+                mux.set_pane_read_only(pane_id, !mux.is_pane_read_only(pane_id));
+                self.update_title();
+            }
+            TogglePaneDiffHighlight => {
+                let mut state = self.pane_state(pane.pane_id());
+                state.diff_highlight = !state.diff_highlight;
+                drop(state);
+                if let Some(window) = self.window.as_ref() {
+                    window.invalidate();
+                }
+            }
+            TogglePaneRecording => {
+                let (title, path) = match mux::recording::stop_recording(pane.pane_id()) {
+                    Some(path) => ("Recording saved", path),
+                    None => (
+                        "Recording started",
+                        mux::recording::start_recording(pane, None)?,
+                    ),
+                };
+                wezterm_toast_notification::ToastNotification {
+                    title: title.to_string(),
+                    message: path.display().to_string(),
+                    url: None,
+                    timeout: Some(std::time::Duration::from_secs(5)),
+                }
+                .show();
+            }
             SetPaneZoomState(zoomed) => {
                 let mux = Mux::get();
                 let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
@@ -2974,33 +3540,41 @@ impl TermWindow {
                 let dpi = self.dimensions.dpi as u32;
 
                 promise::spawn::spawn(async move {
-                    let mux = Mux::get();
-                    let domain = mux
-                        .get_domain_by_name(&domain)
-                        .ok_or_else(|| anyhow!("{} is not a valid domain name", domain))?;
-                    domain.attach(Some(window)).await?;
+                    let result = async {
+                        let mux = Mux::get();
+                        let domain = mux
+                            .get_domain_by_name(&domain)
+                            .ok_or_else(|| anyhow!("{} is not a valid domain name", domain))?;
+                        domain.attach(Some(window)).await?;
+
+                        let have_panes_in_domain = mux
+                            .iter_panes()
+                            .iter()
+                            .any(|p| p.domain_id() == domain.domain_id());
+
+                        if !have_panes_in_domain {
+                            let config = config::configuration();
+                            let _tab = domain
+                                .spawn(
+                                    config.initial_size(
+                                        dpi,
+                                        Some(crate::cell_pixel_dims(&config, dpi as f64)?),
+                                    ),
+                                    None,
+                                    None,
+                                    window,
+                                )
+                                .await?;
+                        }
 
-                    let have_panes_in_domain = mux
-                        .iter_panes()
-                        .iter()
-                        .any(|p| p.domain_id() == domain.domain_id());
-
-                    if !have_panes_in_domain {
-                        let config = config::configuration();
-                        let _tab = domain
-                            .spawn(
-                                config.initial_size(
-                                    dpi,
-                                    Some(crate::cell_pixel_dims(&config, dpi as f64)?),
-                                ),
-                                None,
-                                None,
-                                window,
-                            )
-                            .await?;
+                        Result::<(), anyhow::Error>::Ok(())
+                    }
+                    .await;
+                    // Nothing else observes the result of this future,
+                    // so make sure that failures show up in the debug overlay
+                    if let Err(err) = result {
+                        log::error!("AttachDomain {}: {:#}", domain, err);
                     }
-
-                    Result::<(), anyhow::Error>::Ok(())
                 })
                 .detach();
             }
@@ -3018,6 +3592,53 @@ impl TermWindow {
                     RotationDirection::CounterClockwise => tab.rotate_counter_clockwise(),
                 }
             }
+            BalancePanes { axis } => {
+                let mux = Mux::get();
+                let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
+                    Some(tab) => tab,
+                    None => return Ok(PerformAssignmentResult::Handled),
+                };
+                tab.balance_panes(axis.map(|axis| match axis {
+                    PaneAxis::Horizontal => SplitDirection::Horizontal,
+                    PaneAxis::Vertical => SplitDirection::Vertical,
+                }));
+            }
+            ViewScrollback(args) => self.view_scrollback(pane, args)?,
+            CaptureScreenshot => self.capture_screenshot()?,
+            ReplayRecording(path) => {
+                mux::replay::spawn_replay_tab(PathBuf::from(path), self.mux_window_id)?;
+            }
+            AdjustWindowHsb(adjust) => {
+                self.user_window_hsb = HsbTransform {
+                    hue: self.user_window_hsb.hue * adjust.hue,
+                    saturation: self.user_window_hsb.saturation * adjust.saturation,
+                    brightness: self.user_window_hsb.brightness * adjust.brightness,
+                };
+                if let Some(window) = self.window.as_ref() {
+                    window.invalidate();
+                }
+            }
+            ResetWindowHsb => {
+                self.user_window_hsb = HsbTransform::default();
+                if let Some(window) = self.window.as_ref() {
+                    window.invalidate();
+                }
+            }
+            ShowOverlay(args) => self.show_lua_overlay(pane, args),
+            ApplyPaneLayout(name) => {
+                let layout = self
+                    .config
+                    .pane_layouts
+                    .iter()
+                    .find(|layout| layout.name == *name)
+                    .ok_or_else(|| anyhow!("no pane layout named {name}"))?;
+                let mux = Mux::get();
+                let tab = match mux.get_active_tab_for_window(self.mux_window_id) {
+                    Some(tab) => tab,
+                    None => return Ok(PerformAssignmentResult::Handled),
+                };
+                tab.arrange_panes(layout.arrangement, layout.main_pane_percent);
+            }
             SplitPane(split) => {
                 log::trace!("SplitPane {:?}", split);
                 self.spawn_command(
@@ -3077,47 +3698,72 @@ impl TermWindow {
 
     fn do_open_link_at_mouse_cursor(&self, pane: &Arc<dyn Pane>) {
         // They clicked on a link, so let's open it!
+        let required_mods = self.config.hyperlink_click_modifiers;
+        if required_mods != Modifiers::NONE {
+            let mods = self
+                .current_mouse_event
+                .as_ref()
+                .map(|event| event.modifiers)
+                .unwrap_or(Modifiers::NONE);
+            if !mods.contains(required_mods) {
+                return;
+            }
+        }
+
+        if let Some(link) = self.current_highlight.as_ref() {
+            self.open_link(pane, link);
+        }
+    }
+
+    pub fn open_link(&self, pane: &Arc<dyn Pane>, link: &Hyperlink) {
         // We need to ensure that we spawn the `open` call outside of the context
         // of our window loop; on Windows it can cause a panic due to
         // triggering our WndProc recursively.
         // We get that assurance for free as part of the async dispatch that we
         // perform below; here we allow the user to define an `open-uri` event
         // handler that can bypass the normal `open_url` functionality.
-        if let Some(link) = self.current_highlight.as_ref().cloned() {
-            let window = GuiWin::new(self);
-            let pane = MuxPane(pane.pane_id());
-
-            async fn open_uri(
-                lua: Option<Rc<mlua::Lua>>,
-                window: GuiWin,
-                pane: MuxPane,
-                link: String,
-            ) -> anyhow::Result<()> {
-                let default_click = match lua {
-                    Some(lua) => {
-                        let args = lua.pack_multi((window, pane, link.clone()))?;
-                        config::lua::emit_event(&lua, ("open-uri".to_string(), args))
-                            .await
-                            .map_err(|e| {
-                                log::error!("while processing open-uri event: {:#}", e);
-                                e
-                            })?
-                    }
-                    None => true,
-                };
-                if default_click {
-                    log::info!("clicking {}", link);
-                    wezterm_open_url::open_url(&link);
+        let window = GuiWin::new(self);
+        let cwd = pane.get_current_working_dir(CachePolicy::AllowStale);
+        let editor = self.config.editor_command.clone();
+        let pane = MuxPane(pane.pane_id());
+        let link = link.uri().to_string();
+
+        async fn open_uri(
+            lua: Option<Rc<mlua::Lua>>,
+            window: GuiWin,
+            pane: MuxPane,
+            link: String,
+            cwd: Option<Url>,
+            editor: Option<Vec<String>>,
+        ) -> anyhow::Result<()> {
+            let default_click = match lua {
+                Some(lua) => {
+                    let args = lua.pack_multi((window, pane, link.clone()))?;
+                    config::lua::emit_event(&lua, ("open-uri".to_string(), args))
+                        .await
+                        .map_err(|e| {
+                            log::error!("while processing open-uri event: {:#}", e);
+                            e
+                        })?
+                }
+                None => true,
+            };
+            if default_click {
+                log::info!("clicking {}", link);
+                match filelink::FileLineLink::parse(&link) {
+                    Some(file_link) => file_link.open(cwd.as_ref(), editor.as_deref()),
+                    None => wezterm_open_url::open_url(&link),
                 }
-                Ok(())
             }
-
-            promise::spawn::spawn(config::with_lua_config_on_main_thread(move |lua| {
-                open_uri(lua, window, pane, link.uri().to_string())
-            }))
-            .detach();
+            Ok(())
         }
+
+        promise::spawn::spawn(config::with_lua_config_on_main_thread(move |lua| {
+            open_uri(lua, window, pane, link, cwd, editor)
+        }))
+        .detach();
     }
+
     fn close_current_pane(&mut self, confirm: bool) {
         let mux_window_id = self.mux_window_id;
         let mux = Mux::get();
@@ -3200,6 +3846,33 @@ impl TermWindow {
         })
     }
 
+    /// Emits the `tab-activated` and `pane-focus-changed` events if
+    /// the active tab or pane is different from the last time that
+    /// we looked.  Nothing is emitted for the initial tab and pane.
+    fn check_for_focus_change(&mut self, tab_id: Option<TabId>, pane_id: Option<PaneId>) {
+        let previous_tab = std::mem::replace(&mut self.last_active_tab, tab_id);
+        let previous_pane = std::mem::replace(&mut self.last_active_pane, pane_id);
+        if previous_tab.is_some() && previous_tab != tab_id {
+            self.emit_window_event("tab-activated", pane_id);
+        }
+        if previous_pane.is_some() && previous_pane != pane_id {
+            self.emit_window_event("pane-focus-changed", pane_id);
+        }
+    }
+
+    /// Emits the `pane-zoom-changed` event if the zoomed pane in
+    /// the tab is different from the last time that we looked
+    fn check_for_zoom_change(&mut self, tab_id: TabId) {
+        let zoomed_pane = match Mux::get().get_tab(tab_id) {
+            Some(tab) => tab.get_zoomed_pane().map(|pane| pane.pane_id()),
+            None => return,
+        };
+        let previous = std::mem::replace(&mut self.tab_state(tab_id).zoomed_pane, zoomed_pane);
+        if previous != zoomed_pane {
+            self.emit_window_event("pane-zoom-changed", zoomed_pane.or(previous));
+        }
+    }
+
     pub fn tab_state(&self, tab_id: TabId) -> RefMut<TabState> {
         RefMut::map(self.tab_state.borrow_mut(), |state| {
             state.entry(tab_id).or_insert_with(TabState::default)
@@ -3257,7 +3930,9 @@ impl TermWindow {
             None => None,
         };
 
+        self.kinetic_scroll.take();
         let mut state = self.pane_state(pane_id);
+        state.scroll_pixel_offset = 0.;
         if pos != state.viewport {
             state.viewport = pos;
 
@@ -3274,6 +3949,14 @@ impl TermWindow {
         self.window.as_ref().unwrap().invalidate();
     }
 
+    /// Returns true if input to pane should be discarded because it
+    /// was made read-only.  Overlays such as copy mode share the id
+    /// of the pane that they cover, but remain interactive.
+    fn is_pane_read_only(&mut self, pane: &Arc<dyn Pane>) -> bool {
+        Mux::get().is_pane_read_only(pane.pane_id())
+            && self.pane_state(pane.pane_id()).overlay.is_none()
+    }
+
     fn maybe_scroll_to_bottom_for_input(&mut self, pane: &Arc<dyn Pane>) {
         if self.config.scroll_to_bottom_on_input {
             self.scroll_to_bottom(pane);
@@ -3286,7 +3969,10 @@ impl TermWindow {
     }
 
     fn scroll_to_bottom(&mut self, pane: &Arc<dyn Pane>) {
-        self.pane_state(pane.pane_id()).viewport = None;
+        self.kinetic_scroll.take();
+        let mut state = self.pane_state(pane.pane_id());
+        state.viewport = None;
+        state.scroll_pixel_offset = 0.;
     }
 
     fn get_active_pane_no_overlay(&self) -> Option<Arc<dyn Pane>> {
@@ -3359,6 +4045,12 @@ impl TermWindow {
             pixel_height: pos.pixel_height,
             title: pos.pane.get_title(),
             user_vars: pos.pane.copy_user_vars(),
+            domain_name: Mux::get()
+                .get_domain(pos.pane.domain_id())
+                .map(|dom| dom.domain_name().to_string())
+                .unwrap_or_default(),
+            is_read_only: Mux::get().is_pane_read_only(pos.pane.pane_id()),
+            progress: progress_to_window_progress(pos.pane.get_progress()),
         }
     }
 
@@ -3382,6 +4074,7 @@ impl TermWindow {
                     is_active: tab_index == idx,
                     window_id: self.mux_window_id,
                     tab_title: tab.get_title(),
+                    monitor_alert: self.tab_monitor_alert(tab),
                     active_pane: panes
                         .iter()
                         .find(|p| p.is_active)
@@ -3485,6 +4178,65 @@ impl TermWindow {
         window.notify(TermWindowNotif::CancelOverlayForPane(pane_id));
     }
 
+    /// Closes the search overlay of the `from` pane and lists the
+    /// matches for `pattern` from every pane in its tab instead
+    pub fn list_matches_in_tab(&mut self, from: PaneId, pattern: MuxPattern) {
+        let tab_id = match Mux::get().resolve_pane_id(from) {
+            Some((_domain, _window, tab_id)) => tab_id,
+            None => return,
+        };
+        self.cancel_overlay_for_pane(from);
+        self.show_search_results_by_pane(SearchScope::Tab(tab_id), pattern);
+    }
+
+    /// Closes the search overlay of the `from` pane and starts searching
+    /// in `pane` instead, making it the active pane of its tab.
+    /// Returns the new overlay.
+    pub fn move_search_to_pane(
+        &mut self,
+        from: PaneId,
+        pane: &Arc<dyn Pane>,
+        params: CopyModeParams,
+    ) -> anyhow::Result<Arc<dyn Pane>> {
+        let mux = Mux::get();
+        let (_domain, window_id, tab_id) = mux
+            .resolve_pane_id(pane.pane_id())
+            .ok_or_else(|| anyhow!("pane {} is not in a tab", pane.pane_id()))?;
+        let tab = mux
+            .get_tab(tab_id)
+            .ok_or_else(|| anyhow!("tab {} not found", tab_id))?;
+
+        self.cancel_overlay_for_pane(from);
+        if window_id == self.mux_window_id {
+            let tab_idx = mux
+                .get_window(window_id)
+                .and_then(|window| window.idx_by_id(tab_id));
+            if let Some(tab_idx) = tab_idx {
+                self.activate_tab(tab_idx as isize)?;
+            }
+        }
+        tab.set_active_pane(pane);
+
+        let key_table = if params.editing_search {
+            "search_mode"
+        } else {
+            "copy_mode"
+        };
+        let overlay = CopyOverlay::with_pane(self, pane, params)?;
+        self.assign_overlay_for_pane(pane.pane_id(), Arc::clone(&overlay));
+        if let Some(state) = self.pane_state(pane.pane_id()).overlay.as_mut() {
+            state.key_table_state.activate(KeyTableArgs {
+                name: key_table,
+                timeout_milliseconds: None,
+                replace_current: false,
+                one_shot: false,
+                until_unknown: false,
+                prevent_fallback: false,
+            });
+        }
+        Ok(overlay)
+    }
+
     pub fn assign_overlay_for_pane(&mut self, pane_id: PaneId, pane: Arc<dyn Pane>) {
         self.cancel_overlay_for_pane(pane_id);
         self.pane_state(pane_id).overlay.replace(OverlayState {
@@ -3509,21 +4261,38 @@ impl TermWindow {
             Pattern::CaseInSensitiveString(s) => MuxPattern::CaseInSensitiveString(s),
             Pattern::Regex(s) => MuxPattern::Regex(s),
             Pattern::CurrentSelectionOrEmptyString => {
-                let text = self.selection_text(pane);
-                let first_line = text
-                    .lines()
-                    .next()
-                    .map(|s| s.to_string())
-                    .unwrap_or_default();
-                MuxPattern::CaseSensitiveString(first_line)
+                MuxPattern::CaseSensitiveString(self.selection_first_line(pane))
+            }
+            Pattern::CurrentSelectionText => {
+                let first_line = self.selection_first_line(pane);
+                if first_line.is_empty() {
+                    crate::overlay::copy::last_search_pattern(self.mux_window_id)
+                        .unwrap_or_default()
+                } else {
+                    MuxPattern::CaseSensitiveString(first_line)
+                }
+            }
+            Pattern::LastPattern => {
+                crate::overlay::copy::last_search_pattern(self.mux_window_id).unwrap_or_default()
             }
         }
     }
+
+    /// Returns the first line of the selection text, as searching
+    /// only operates on a single line
+    fn selection_first_line(&self, pane: &Arc<dyn Pane>) -> String {
+        let text = self.selection_text(pane);
+        text.lines()
+            .next()
+            .map(|s| s.to_string())
+            .unwrap_or_default()
+    }
 }
 
 impl Drop for TermWindow {
     fn drop(&mut self) {
         self.clear_all_overlays();
+        crate::overlay::copy::forget_last_search_pattern(self.mux_window_id);
         if let Some(window) = self.window.take() {
             if let Some(fe) = try_front_end() {
                 fe.forget_known_window(&window);
@@ -0,0 +1,196 @@
+use crate::termwindow::TermWindowNotif;
+use config::keyassignment::PaneMonitor;
+use config::NotificationHandling;
+use mux::pane::PaneId;
+use mux::tab::Tab;
+use mux::Mux;
+use smol::Timer;
+use std::time::{Duration, Instant};
+use wezterm_toast_notification::persistent_toast_notification;
+
+/// Tracks a pane that is being watched for activity or silence
+pub struct PaneMonitorState {
+    pub mode: PaneMonitor,
+    last_output: Instant,
+    /// Set when the user has been notified; cleared when the pane is
+    /// next seen, so that we don't repeatedly notify about it
+    pub alerted: bool,
+    silence_check_scheduled: bool,
+}
+
+impl PaneMonitorState {
+    pub fn new(mode: PaneMonitor) -> Self {
+        Self {
+            mode,
+            last_output: Instant::now(),
+            alerted: false,
+            silence_check_scheduled: false,
+        }
+    }
+}
+
+impl super::TermWindow {
+    /// Applies f to the monitor state of the pane, if it is being
+    /// monitored.  Unlike pane_state, this doesn't create state for
+    /// panes that don't have any yet.
+    fn with_pane_monitor<R>(
+        &self,
+        pane_id: PaneId,
+        f: impl FnOnce(&mut PaneMonitorState) -> R,
+    ) -> Option<R> {
+        let mut pane_state = self.pane_state.borrow_mut();
+        pane_state.get_mut(&pane_id)?.monitor.as_mut().map(f)
+    }
+
+    pub fn toggle_pane_monitor(&mut self, pane_id: PaneId, mode: PaneMonitor) {
+        let current = self.with_pane_monitor(pane_id, |monitor| monitor.mode);
+        let enabled = current != Some(mode);
+        if enabled {
+            self.pane_state(pane_id)
+                .monitor
+                .replace(PaneMonitorState::new(mode));
+        } else if let Some(state) = self.pane_state.borrow_mut().get_mut(&pane_id) {
+            state.monitor.take();
+        }
+        log::info!(
+            "{} {:?} monitoring for pane {}",
+            if enabled { "enabled" } else { "disabled" },
+            mode,
+            pane_id
+        );
+        if enabled && mode == PaneMonitor::Silence {
+            self.schedule_silence_check(pane_id);
+        }
+        self.update_title();
+    }
+
+    /// A pane is in the background if the user can't currently see it
+    fn is_pane_in_background(&mut self, pane_id: PaneId) -> bool {
+        self.focused.is_none() || !self.is_pane_visible(pane_id)
+    }
+
+    /// Called when a pane produces output
+    pub fn pane_monitor_output(&mut self, pane_id: PaneId) {
+        let in_background = self.is_pane_in_background(pane_id);
+        let (mode, was_alerted) = match self.with_pane_monitor(pane_id, |monitor| {
+            monitor.last_output = Instant::now();
+            (monitor.mode, monitor.alerted)
+        }) {
+            Some(state) => state,
+            None => return,
+        };
+
+        match mode {
+            PaneMonitor::Activity => {
+                if in_background {
+                    self.alert_pane_monitor(pane_id);
+                }
+            }
+            PaneMonitor::Silence => {
+                // The pane is no longer silent
+                if was_alerted {
+                    self.pane_monitor_seen(pane_id);
+                }
+                self.schedule_silence_check(pane_id);
+            }
+        }
+    }
+
+    /// Clears the alert for panes that the user can now see
+    pub fn pane_monitor_seen(&mut self, pane_id: PaneId) {
+        let was_alerted = self
+            .with_pane_monitor(pane_id, |monitor| std::mem::take(&mut monitor.alerted))
+            .unwrap_or(false);
+        if was_alerted {
+            self.update_title();
+        }
+    }
+
+    /// Returns the monitor mode of any pane in the tab that has an
+    /// outstanding alert
+    pub fn tab_monitor_alert(&self, tab: &Tab) -> Option<PaneMonitor> {
+        tab.iter_panes_ignoring_zoom().into_iter().find_map(|pos| {
+            self.with_pane_monitor(pos.pane.pane_id(), |monitor| {
+                monitor.alerted.then(|| monitor.mode)
+            })
+            .flatten()
+        })
+    }
+
+    fn schedule_silence_check(&mut self, pane_id: PaneId) {
+        let interval = Duration::from_secs(self.config.monitor_silence_seconds);
+        let due = match self.with_pane_monitor(pane_id, |monitor| {
+            if monitor.silence_check_scheduled {
+                // It will reschedule itself based on last_output
+                return None;
+            }
+            monitor.silence_check_scheduled = true;
+            Some(monitor.last_output + interval)
+        }) {
+            Some(Some(due)) => due,
+            _ => return,
+        };
+
+        let window = match self.window.clone() {
+            Some(window) => window,
+            None => return,
+        };
+        promise::spawn::spawn(async move {
+            Timer::at(due).await;
+            window.notify(TermWindowNotif::Apply(Box::new(move |term_window| {
+                term_window.check_pane_silence(pane_id);
+            })));
+        })
+        .detach();
+    }
+
+    fn check_pane_silence(&mut self, pane_id: PaneId) {
+        let interval = Duration::from_secs(self.config.monitor_silence_seconds);
+        let silent = match self.with_pane_monitor(pane_id, |monitor| {
+            if monitor.mode != PaneMonitor::Silence {
+                return None;
+            }
+            monitor.silence_check_scheduled = false;
+            Some(monitor.last_output.elapsed() >= interval)
+        }) {
+            Some(Some(silent)) => silent,
+            _ => return,
+        };
+
+        if !silent {
+            self.schedule_silence_check(pane_id);
+        } else if self.is_pane_in_background(pane_id) {
+            self.alert_pane_monitor(pane_id);
+        }
+    }
+
+    fn alert_pane_monitor(&mut self, pane_id: PaneId) {
+        let mode = match self.with_pane_monitor(pane_id, |monitor| {
+            if monitor.alerted {
+                return None;
+            }
+            monitor.alerted = true;
+            Some(monitor.mode)
+        }) {
+            Some(Some(mode)) => mode,
+            _ => return,
+        };
+
+        let title = Mux::get()
+            .get_pane(pane_id)
+            .map(|pane| pane.get_title())
+            .unwrap_or_default();
+        let message = match mode {
+            PaneMonitor::Activity => "Activity in pane".to_string(),
+            PaneMonitor::Silence => format!(
+                "Pane has been silent for {} seconds",
+                self.config.monitor_silence_seconds
+            ),
+        };
+        // The tab title still shows the alert when notifications are disabled
+        if self.config.notification_handling != NotificationHandling::NeverShow {
+            persistent_toast_notification(&message, &title);
+        }
+        self.update_title();
+    }
+}
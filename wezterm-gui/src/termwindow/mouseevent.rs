@@ -3,7 +3,7 @@ use crate::termwindow::{
     GuiWin, MouseCapture, PositionedSplit, ScrollHit, TermWindowNotif, UIItem, UIItemType, TMB,
 };
 use ::window::{
-    MouseButtons as WMB, MouseCursor, MouseEvent, MouseEventKind as WMEK, MousePress,
+    MouseButtons as WMB, MouseCursor, MouseEvent, MouseEventKind as WMEK, MousePress, Point,
     WindowDecorations, WindowOps, WindowState,
 };
 use config::keyassignment::{KeyAssignment, MouseEventTrigger, SpawnTabDomain};
@@ -43,7 +43,8 @@ impl super::TermWindow {
             | UIItemType::AboveScrollThumb
             | UIItemType::BelowScrollThumb
             | UIItemType::ScrollThumb
-            | UIItemType::Split(_) => {}
+            | UIItemType::Split(_)
+            | UIItemType::ScrollPositionIndicator => {}
         }
     }
 
@@ -54,10 +55,17 @@ impl super::TermWindow {
             | UIItemType::AboveScrollThumb
             | UIItemType::BelowScrollThumb
             | UIItemType::ScrollThumb
-            | UIItemType::Split(_) => {}
+            | UIItemType::Split(_)
+            | UIItemType::ScrollPositionIndicator => {}
         }
     }
 
+    /// Returns true if b is within mouse_drag_threshold pixels of a
+    fn within_drag_threshold(&self, a: &Point, b: &Point) -> bool {
+        let threshold = self.config.mouse_drag_threshold as isize;
+        (a.x - b.x).abs() <= threshold && (a.y - b.y).abs() <= threshold
+    }
+
     pub fn mouse_event_impl(&mut self, event: MouseEvent, context: &dyn WindowOps) {
         log::trace!("{:?}", event);
         let pane = match self.get_active_pane_or_overlay() {
@@ -141,16 +149,34 @@ impl super::TermWindow {
                 // Perform click counting
                 let button = mouse_press_to_tmb(press);
 
-                let click_position = ClickPosition {
+                let mut click_position = ClickPosition {
                     column: x,
                     row: y,
                     x_pixel_offset,
                     y_pixel_offset,
                 };
 
+                // A press that is close enough to the prior one is
+                // considered to be at the same position for the purposes
+                // of counting multiple clicks
+                if let (Some(prior), Some(prior_coords)) = (
+                    self.last_mouse_click.as_ref(),
+                    self.last_mouse_press_coords.as_ref(),
+                ) {
+                    if self.within_drag_threshold(prior_coords, &event.coords) {
+                        click_position = prior.position;
+                    }
+                }
+                self.last_mouse_press_coords.replace(event.coords);
+                self.mouse_drag_started = false;
+
                 let click = match self.last_mouse_click.take() {
                     None => LastMouseClick::new(button, click_position),
-                    Some(click) => click.add(button, click_position),
+                    Some(click) => click.add_within(
+                        button,
+                        click_position,
+                        Duration::from_millis(self.config.double_click_time_ms),
+                    ),
                 };
                 self.last_mouse_click = Some(click);
                 self.current_mouse_buttons.retain(|p| p != press);
@@ -382,9 +408,25 @@ impl super::TermWindow {
             UIItemType::CloseTab(idx) => {
                 self.mouse_event_close_tab(idx, event, context);
             }
+            UIItemType::ScrollPositionIndicator => {
+                self.mouse_event_scroll_position_indicator(pane, event, context);
+            }
         }
     }
 
+    pub fn mouse_event_scroll_position_indicator(
+        &mut self,
+        pane: Arc<dyn Pane>,
+        event: MouseEvent,
+        context: &dyn WindowOps,
+    ) {
+        if let WMEK::Press(MousePress::Left) = event.kind {
+            self.scroll_to_bottom(&pane);
+            context.invalidate();
+        }
+        context.set_cursor(Some(MouseCursor::Arrow));
+    }
+
     pub fn mouse_event_close_tab(
         &mut self,
         idx: usize,
@@ -645,11 +687,76 @@ impl super::TermWindow {
         }
     }
 
+    /// Drags the selected text out of the window when the mouse is
+    /// dragged after pressing the left button and the
+    /// selection_drag_modifiers over the selection.
+    /// Returns true if the event was consumed; presses are left alone
+    /// when the window cannot start a drag.
+    fn drag_selection_out(
+        &mut self,
+        pane: &Arc<dyn Pane>,
+        event: &MouseEvent,
+        column: usize,
+        stable_row: StableRowIndex,
+        context: &dyn WindowOps,
+    ) -> bool {
+        match &event.kind {
+            WMEK::Press(MousePress::Left) => {
+                let mods = self.config.selection_drag_modifiers;
+                if mods.is_empty() || !context.supports_text_drag() {
+                    self.selection_drag_pane.take();
+                    return false;
+                }
+                let over_selection = {
+                    let selection = self.selection(pane.pane_id());
+                    match selection.range {
+                        Some(range) => {
+                            let range = range.normalize();
+                            range.rows().contains(&stable_row)
+                                && range
+                                    .cols_for_row(stable_row, selection.rectangular)
+                                    .contains(&column)
+                        }
+                        None => false,
+                    }
+                };
+                if event.modifiers == mods && over_selection {
+                    self.selection_drag_pane.replace(pane.pane_id());
+                    return true;
+                }
+                self.selection_drag_pane.take();
+                false
+            }
+            WMEK::Move => {
+                if self.selection_drag_pane != Some(pane.pane_id()) {
+                    return false;
+                }
+                let started = match self.last_mouse_press_coords.as_ref() {
+                    Some(press) => !self.within_drag_threshold(press, &event.coords),
+                    None => true,
+                };
+                if started {
+                    self.selection_drag_pane.take();
+                    let text = self.selection_text(pane);
+                    if !text.is_empty() {
+                        context.start_text_drag(text);
+                    }
+                }
+                true
+            }
+            WMEK::Release(MousePress::Left) => {
+                self.selection_drag_pane.take();
+                false
+            }
+            _ => false,
+        }
+    }
+
     fn mouse_event_terminal(
         &mut self,
         mut pane: Arc<dyn Pane>,
         position: ClickPosition,
-        event: MouseEvent,
+        mut event: MouseEvent,
         context: &dyn WindowOps,
         capture_mouse: bool,
     ) {
@@ -695,8 +802,11 @@ impl super::TermWindow {
                                 context.invalidate();
                             }
                         }
-                        WMEK::Release(_) | WMEK::HorzWheel(_) => {}
-                        WMEK::VertWheel(_) => {
+                        WMEK::Release(_) => {}
+                        WMEK::VertWheel(_)
+                        | WMEK::HorzWheel(_)
+                        | WMEK::VertPixelScroll(_)
+                        | WMEK::PixelScrollEnd => {
                             // Let wheel events route to the hovered pane,
                             // even if it doesn't have focus
                             pane = Arc::clone(&pos.pane);
@@ -755,7 +865,13 @@ impl super::TermWindow {
         }
 
         let allow_action = if self.is_click_to_focus_window || !is_focused {
-            matches!(&event.kind, WMEK::VertWheel(_) | WMEK::HorzWheel(_))
+            matches!(
+                &event.kind,
+                WMEK::VertWheel(_)
+                    | WMEK::HorzWheel(_)
+                    | WMEK::VertPixelScroll(_)
+                    | WMEK::PixelScrollEnd
+            )
         } else {
             true
         };
@@ -768,10 +884,19 @@ impl super::TermWindow {
         );
 
         let dims = pane.get_dimensions();
-        let stable_row = self
-            .get_viewport(pane.pane_id())
-            .unwrap_or(dims.physical_top)
-            + row as StableRowIndex;
+        let stable_row = match self.get_viewport(pane.pane_id()) {
+            Some(top) => {
+                // When smooth scrolling has left the content shifted down
+                // by part of a line, the pointer may be over the lower
+                // part of the line above the one at its cell position
+                let cell_height = self.render_metrics.cell_size.height.max(1);
+                let scroll_pixel_offset =
+                    self.pane_state(pane.pane_id()).scroll_pixel_offset as isize;
+                let pixel_y = row as isize * cell_height + y_pixel_offset - scroll_pixel_offset;
+                top + pixel_y.div_euclid(cell_height)
+            }
+            None => dims.physical_top + row as StableRowIndex,
+        };
 
         self.pane_state(pane.pane_id())
             .mouse_terminal_coords
@@ -843,6 +968,38 @@ impl super::TermWindow {
             MouseCursor::Text
         }));
 
+        match event.kind {
+            WMEK::VertPixelScroll(pixels) => {
+                if !allow_action {
+                    return;
+                }
+                match self.pixel_scroll(&pane, pixels, event.modifiers) {
+                    Some(lines) => {
+                        // Process it as though it came from a mouse wheel
+                        event.kind = WMEK::VertWheel(lines);
+                        self.current_mouse_event.replace(event.clone());
+                    }
+                    None => return,
+                }
+            }
+            WMEK::PixelScrollEnd => {
+                if allow_action {
+                    self.end_pixel_scroll();
+                }
+                return;
+            }
+            WMEK::Press(_) => {
+                // Line up the display with the cells that will
+                // be selected or reported to the application
+                self.snap_scroll_pixel_offset(&pane);
+            }
+            _ => {}
+        }
+
+        if allow_action && self.drag_selection_out(&pane, &event, column, stable_row, context) {
+            return;
+        }
+
         let event_trigger_type = match &event.kind {
             WMEK::Press(press) => {
                 let press = mouse_press_to_tmb(press);
@@ -869,7 +1026,13 @@ impl super::TermWindow {
                 }
             }
             WMEK::Move => {
-                if !self.current_mouse_buttons.is_empty() {
+                if !self.current_mouse_buttons.is_empty() && !self.mouse_drag_started {
+                    self.mouse_drag_started = match self.last_mouse_press_coords.as_ref() {
+                        Some(press) => !self.within_drag_threshold(press, &event.coords),
+                        None => true,
+                    };
+                }
+                if !self.current_mouse_buttons.is_empty() && self.mouse_drag_started {
                     if let Some(LastMouseClick { streak, button, .. }) =
                         self.last_mouse_click.as_ref()
                     {
@@ -912,6 +1075,8 @@ impl super::TermWindow {
                     button: MouseButton::WheelRight(-amount as usize),
                 },
             }),
+            // These were handled above
+            WMEK::VertPixelScroll(_) | WMEK::PixelScrollEnd => None,
         };
 
         if allow_action {
@@ -921,12 +1086,13 @@ impl super::TermWindow {
 
                 // Since we use shift to force assessing the mouse bindings, pretend
                 // that shift is not one of the mods when the mouse is grabbed.
+                // An empty set of bypass modifiers disables the bypass, so that
+                // all events are reported to the application.
+                let bypass = self.config.bypass_mouse_reporting_modifiers;
                 let mut mouse_reporting = pane.is_mouse_grabbed();
-                if mouse_reporting {
-                    if modifiers.contains(self.config.bypass_mouse_reporting_modifiers) {
-                        modifiers.remove(self.config.bypass_mouse_reporting_modifiers);
-                        mouse_reporting = false;
-                    }
+                if mouse_reporting && !bypass.is_empty() && modifiers.contains(bypass) {
+                    modifiers.remove(bypass);
+                    mouse_reporting = false;
                 }
 
                 if mouse_reporting {
@@ -993,6 +1159,7 @@ impl super::TermWindow {
                 WMEK::Move => TMEK::Move,
                 WMEK::VertWheel(_) | WMEK::HorzWheel(_) | WMEK::Press(_) => TMEK::Press,
                 WMEK::Release(_) => TMEK::Release,
+                WMEK::VertPixelScroll(_) | WMEK::PixelScrollEnd => return,
             },
             button: match event.kind {
                 WMEK::Release(ref press) | WMEK::Press(ref press) => mouse_press_to_tmb(press),
@@ -1021,6 +1188,7 @@ impl super::TermWindow {
                         TMB::WheelRight((-amount) as usize)
                     }
                 }
+                WMEK::VertPixelScroll(_) | WMEK::PixelScrollEnd => return,
             },
             x: column,
             y: row,
@@ -1,17 +1,22 @@
+use mux::pane::PaneId;
 use mux::renderable::StableCursorPosition;
 use std::time::Instant;
 
 #[derive(Clone)]
 pub struct PrevCursorPos {
+    pane_id: Option<PaneId>,
     pos: StableCursorPosition,
     when: Instant,
+    prior: Option<(StableCursorPosition, Instant)>,
 }
 
 impl PrevCursorPos {
     pub fn new() -> Self {
         PrevCursorPos {
+            pane_id: None,
             pos: StableCursorPosition::default(),
             when: Instant::now(),
+            prior: None,
         }
     }
 
@@ -20,11 +25,22 @@ impl PrevCursorPos {
         self.when = Instant::now();
     }
 
-    /// Update the cursor position if its different
-    pub fn update(&mut self, newpos: &StableCursorPosition) {
-        if &self.pos != newpos {
+    /// Update the cursor position if its different.
+    /// The prior position is only recorded for moves within the
+    /// same pane; it is cleared when the active pane changes.
+    pub fn update(&mut self, pane_id: PaneId, newpos: &StableCursorPosition) {
+        if self.pane_id != Some(pane_id) {
+            self.pane_id.replace(pane_id);
+            self.prior.take();
             self.pos = *newpos;
             self.when = Instant::now();
+        } else if &self.pos != newpos {
+            let now = Instant::now();
+            if self.pos.x != newpos.x || self.pos.y != newpos.y {
+                self.prior.replace((self.pos, now));
+            }
+            self.pos = *newpos;
+            self.when = now;
         }
     }
 
@@ -32,4 +48,15 @@ impl PrevCursorPos {
     pub fn last_cursor_movement(&self) -> Instant {
         self.when
     }
+
+    /// Returns the position that the cursor occupied prior to its
+    /// most recent change of cell in the specified pane, along with
+    /// the time of that change.
+    pub fn prior_position(&self, pane_id: PaneId) -> Option<(StableCursorPosition, Instant)> {
+        if self.pane_id == Some(pane_id) {
+            self.prior
+        } else {
+            None
+        }
+    }
 }
@@ -0,0 +1,95 @@
+use crate::termwindow::box_model::*;
+use crate::termwindow::render::corners::{
+    BOTTOM_LEFT_ROUNDED_CORNER, BOTTOM_RIGHT_ROUNDED_CORNER, TOP_LEFT_ROUNDED_CORNER,
+    TOP_RIGHT_ROUNDED_CORNER,
+};
+use crate::termwindow::DimensionContext;
+use crate::utilsprites::RenderMetrics;
+use config::Dimension;
+use std::rc::Rc;
+use wezterm_font::LoadedFont;
+use wezterm_term::color::ColorPalette;
+
+/// Builds a chip: a short piece of text in a small box with a rounded
+/// border, drawn over the terminal to show some transient status.
+/// `padding_x` and `padding_y` are in cells; `alpha` scales the opacity
+/// of all of its colors so that it can be faded out.
+pub fn chip_element(
+    font: &Rc<LoadedFont>,
+    text: String,
+    palette: &ColorPalette,
+    padding_x: f32,
+    padding_y: f32,
+    alpha: f32,
+) -> Element {
+    let corner = SizedPoly {
+        width: Dimension::Cells(0.25),
+        height: Dimension::Cells(0.25),
+        poly: TOP_LEFT_ROUNDED_CORNER,
+    };
+
+    Element::new(font, ElementContent::Text(text))
+        .colors(ElementColors {
+            border: BorderColor::new(palette.foreground.to_linear().mul_alpha(alpha).into()),
+            bg: palette.background.to_linear().mul_alpha(alpha).into(),
+            text: palette.foreground.to_linear().mul_alpha(alpha).into(),
+        })
+        .padding(BoxDimension {
+            left: Dimension::Cells(padding_x),
+            right: Dimension::Cells(padding_x),
+            top: Dimension::Cells(padding_y),
+            bottom: Dimension::Cells(padding_y),
+        })
+        .border(BoxDimension::new(Dimension::Pixels(1.)))
+        .border_corners(Some(Corners {
+            top_left: corner,
+            top_right: SizedPoly {
+                poly: TOP_RIGHT_ROUNDED_CORNER,
+                ..corner
+            },
+            bottom_left: SizedPoly {
+                poly: BOTTOM_LEFT_ROUNDED_CORNER,
+                ..corner
+            },
+            bottom_right: SizedPoly {
+                poly: BOTTOM_RIGHT_ROUNDED_CORNER,
+                ..corner
+            },
+        }))
+}
+
+impl crate::TermWindow {
+    /// Lays out a chip at the top left of the window; the caller is
+    /// expected to translate it into position before rendering it.
+    /// `metrics` are those of the font that the chip was built with.
+    pub fn compute_chip(
+        &mut self,
+        element: &Element,
+        metrics: &RenderMetrics,
+    ) -> anyhow::Result<ComputedElement> {
+        self.compute_element(
+            &LayoutContext {
+                height: DimensionContext {
+                    dpi: self.dimensions.dpi as f32,
+                    pixel_max: self.dimensions.pixel_height as f32,
+                    pixel_cell: metrics.cell_size.height as f32,
+                },
+                width: DimensionContext {
+                    dpi: self.dimensions.dpi as f32,
+                    pixel_max: self.dimensions.pixel_width as f32,
+                    pixel_cell: metrics.cell_size.width as f32,
+                },
+                bounds: euclid::rect(
+                    0.,
+                    0.,
+                    self.dimensions.pixel_width as f32,
+                    self.dimensions.pixel_height as f32,
+                ),
+                metrics,
+                gl_state: self.render_state.as_ref().unwrap(),
+                zindex: 100,
+            },
+            element,
+        )
+    }
+}
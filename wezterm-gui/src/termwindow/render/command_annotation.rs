@@ -0,0 +1,80 @@
+use chrono::{DateTime, Local};
+use mux::pane::Pane;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+use termwiz::cell::{CellAttributes, Intensity};
+use termwiz::color::AnsiColor;
+use termwiz::surface::SEQ_ZERO;
+use wezterm_term::{CommandRecord, Line, StableRowIndex};
+
+/// Returns the finished commands whose output ends within `range`,
+/// keyed by the row on which they end
+pub fn command_annotations(
+    pane: &Arc<dyn Pane>,
+    range: Range<StableRowIndex>,
+) -> HashMap<StableRowIndex, CommandRecord> {
+    pane.get_command_history()
+        .into_iter()
+        .filter_map(|record| match record.end_y {
+            Some(end_y) if range.contains(&end_y) => Some((end_y, record)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h{}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m{}s", secs / 60, secs % 60)
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
+}
+
+fn annotation_text(record: &CommandRecord) -> String {
+    let mut text = String::new();
+    if let Some(status) = record.exit_status.filter(|&status| status != 0) {
+        text.push_str(&format!("exit {status}  "));
+    }
+    if let Some(duration) = record.duration() {
+        text.push_str(&format_duration(duration));
+    }
+    if let Some(finished) = record.finished {
+        let finished: DateTime<Local> = finished.into();
+        text.push_str(&format!("  {}", finished.format("%H:%M:%S")));
+    }
+    text
+}
+
+/// Returns a copy of `line` with the annotation for `record` right
+/// aligned at the end of it, or None if the annotation would obscure
+/// any of the text in the line
+pub fn annotate_line(line: &Line, record: &CommandRecord, cols: usize) -> Option<Line> {
+    let text = annotation_text(record);
+    let width = termwiz::cell::unicode_column_width(&text, None);
+    // Leave a blank cell either side of the annotation
+    let start = cols.checked_sub(width + 1)?;
+    let occupied = line
+        .visible_cells()
+        .any(|cell| cell.cell_index() + 1 >= start && cell.str() != " ");
+    if occupied {
+        return None;
+    }
+
+    let mut attr = CellAttributes::default();
+    attr.set_intensity(Intensity::Half);
+    if record.exit_status.unwrap_or(0) != 0 {
+        attr.set_foreground(AnsiColor::Maroon);
+    }
+
+    let mut line = line.clone();
+    if line.len() < cols {
+        line.resize(cols, SEQ_ZERO);
+    }
+    line.overlay_text_with_attribute(start, &text, attr, SEQ_ZERO);
+    Some(line)
+}
@@ -0,0 +1,164 @@
+use mux::pane::Pane;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use termwiz::cell::SemanticType;
+use termwiz::color::ColorAttribute;
+use termwiz::surface::SequenceNo;
+use wezterm_term::{Line, SemanticZone, StableRowIndex};
+
+/// Caches the output zones of the two most recent commands in a pane,
+/// so that the semantic zones are only fetched again when the pane
+/// has changed
+#[derive(Default)]
+pub struct DiffZoneCache {
+    seqno: SequenceNo,
+    zones: Option<(SemanticZone, SemanticZone)>,
+}
+
+impl DiffZoneCache {
+    /// Returns the output zone of the most recent command and that
+    /// of the command before it.
+    /// The output of each command is determined by the semantic zones
+    /// reported by shell integration.
+    pub fn output_zones(&mut self, pane: &Arc<dyn Pane>) -> Option<(SemanticZone, SemanticZone)> {
+        let seqno = pane.get_current_seqno();
+        if self.seqno != seqno {
+            let zones = pane.get_semantic_zones().unwrap_or_default();
+            let mut outputs = zones
+                .into_iter()
+                .rev()
+                .filter(|zone| zone.semantic_type == SemanticType::Output);
+            self.zones = match (outputs.next(), outputs.next()) {
+                (Some(current), Some(prior)) => Some((current, prior)),
+                _ => None,
+            };
+            self.seqno = seqno;
+        }
+        self.zones
+    }
+}
+
+/// Returns the cells in the `current` output zone that differ from
+/// the corresponding cells in the `prior` output zone, keyed by row,
+/// for the rows within `range`.
+pub fn diff_highlights(
+    pane: &Arc<dyn Pane>,
+    (current, prior): (SemanticZone, SemanticZone),
+    range: Range<StableRowIndex>,
+) -> HashMap<StableRowIndex, Vec<Range<usize>>> {
+    let mut result = HashMap::new();
+
+    let rows = current.start_y.max(range.start)..(current.end_y + 1).min(range.end);
+    if rows.is_empty() {
+        return result;
+    }
+    let offset = rows.start - current.start_y;
+
+    let (_, lines) = pane.get_lines(rows.clone());
+    let prior_start = prior.start_y + offset;
+    let prior_end = (prior_start + rows.len() as StableRowIndex).min(prior.end_y + 1);
+    let (_, prior_lines) = if prior_start < prior_end {
+        pane.get_lines(prior_start..prior_end)
+    } else {
+        (prior_start, vec![])
+    };
+
+    for (idx, line) in lines.iter().enumerate() {
+        let changed = changed_cells(line, prior_lines.get(idx));
+        if !changed.is_empty() {
+            result.insert(rows.start + idx as StableRowIndex, changed);
+        }
+    }
+
+    result
+}
+
+/// Returns the ranges of cells in `line` whose text differs from
+/// the cell at the same position in `prior`
+fn changed_cells(line: &Line, prior: Option<&Line>) -> Vec<Range<usize>> {
+    let mut changed: Vec<Range<usize>> = vec![];
+    for cell in line.visible_cells() {
+        let prior_text = prior.and_then(|prior| prior.get_cell(cell.cell_index()));
+        let same = match prior_text {
+            Some(prior_cell) => prior_cell.str() == cell.str(),
+            None => cell.str() == " ",
+        };
+        if same {
+            continue;
+        }
+        let idx = cell.cell_index();
+        match changed.last_mut() {
+            Some(last) if last.end == idx => last.end = idx + cell.width(),
+            _ => changed.push(idx..idx + cell.width()),
+        }
+    }
+    changed
+}
+
+/// Returns a copy of `line` with the background of the cells in
+/// `ranges` changed to `bg` to highlight them
+pub fn highlight_line(line: &Line, ranges: &[Range<usize>], bg: ColorAttribute) -> Line {
+    let mut line = line.clone();
+    // The copy shares the cached shaping state of the original line;
+    // discard it so that the highlighted line is shaped afresh
+    line.clear_appdata();
+    let cells = line.cells_mut_for_attr_changes_only();
+    for range in ranges {
+        for idx in range.clone() {
+            if let Some(cell) = cells.get_mut(idx) {
+                cell.attrs_mut().set_background(bg).set_reverse(false);
+            }
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use termwiz::cell::CellAttributes;
+    use termwiz::surface::SEQ_ZERO;
+
+    fn line(s: &str) -> Line {
+        Line::from_text(s, &CellAttributes::default(), SEQ_ZERO, None)
+    }
+
+    #[test]
+    fn unchanged() {
+        assert_eq!(changed_cells(&line("ok 3"), Some(&line("ok 3"))), vec![]);
+    }
+
+    #[test]
+    fn changed_runs() {
+        assert_eq!(
+            changed_cells(
+                &line("passed: 12 failed: 0"),
+                Some(&line("passed: 10 failed: 2"))
+            ),
+            vec![9..10, 19..20]
+        );
+        assert_eq!(
+            changed_cells(&line("abcd"), Some(&line("xyzd"))),
+            vec![0..3]
+        );
+    }
+
+    #[test]
+    fn longer_than_prior() {
+        assert_eq!(changed_cells(&line("ab cd"), Some(&line("ab"))), vec![3..5]);
+    }
+
+    #[test]
+    fn no_prior_line() {
+        assert_eq!(changed_cells(&line("a b"), None), vec![0..1, 2..3]);
+    }
+
+    #[test]
+    fn wide_cells() {
+        assert_eq!(
+            changed_cells(&line("a字b"), Some(&line("a漢b"))),
+            vec![1..3]
+        );
+    }
+}
@@ -10,6 +10,18 @@ use ::window::glium::{BlendingFunction, LinearBlendingFactor, Surface};
 use config::FreeTypeLoadTarget;
 
 impl crate::TermWindow {
+    /// Returns window_hsb with any adjustment made by
+    /// AdjustWindowHsb applied
+    fn window_hsb(&self) -> [f32; 3] {
+        let config = self.config.window_hsb;
+        let user = self.user_window_hsb;
+        [
+            config.hue * user.hue,
+            config.saturation * user.saturation,
+            config.brightness * user.brightness,
+        ]
+    }
+
     pub fn call_draw(&mut self, frame: &mut RenderFrame) -> anyhow::Result<()> {
         match frame {
             RenderFrame::Glium(ref mut frame) => self.call_draw_glium(frame),
@@ -20,7 +32,7 @@ impl crate::TermWindow {
     fn call_draw_webgpu(&mut self) -> anyhow::Result<()> {
         use crate::termwindow::webgpu::WebGpuTexture;
 
-        let webgpu = self.webgpu.as_mut().unwrap();
+        let webgpu = self.webgpu.as_ref().unwrap();
         let render_state = self.render_state.as_ref().unwrap();
 
         let output = webgpu.surface.get_current_texture()?;
@@ -75,6 +87,7 @@ impl crate::TermWindow {
             foreground_text_hsb.saturation,
             foreground_text_hsb.brightness,
         ];
+        let window_hsb = self.window_hsb();
 
         let milliseconds = self.created.elapsed().as_millis() as u32;
         let projection = euclid::Transform3D::<f32, f32, f32>::ortho(
@@ -124,6 +137,8 @@ impl crate::TermWindow {
                         foreground_text_hsb,
                         milliseconds,
                         projection,
+                        window_hsb,
+                        _padding: 0.,
                     });
 
                     render_pass.set_pipeline(&webgpu.render_pipeline);
@@ -144,6 +159,7 @@ impl crate::TermWindow {
 
         // submit will accept anything that implements IntoIter
         webgpu.queue.submit(std::iter::once(encoder.finish()));
+        self.save_screenshot_webgpu(&output.texture);
         output.present();
 
         Ok(())
@@ -227,6 +243,12 @@ impl crate::TermWindow {
             foreground_text_hsb.saturation,
             foreground_text_hsb.brightness,
         );
+        let [hue, saturation, brightness] = self.window_hsb();
+        let window_hsb = (hue, saturation, brightness);
+        let resolution = (
+            self.dimensions.pixel_width as f32,
+            self.dimensions.pixel_height as f32,
+        );
 
         let milliseconds = self.created.elapsed().as_millis() as u32;
 
@@ -248,6 +270,9 @@ impl crate::TermWindow {
                     uniforms.add("atlas_nearest_sampler", &atlas_nearest_sampler);
                     uniforms.add("atlas_linear_sampler", &atlas_linear_sampler);
                     uniforms.add("foreground_text_hsb", &foreground_text_hsb);
+                    uniforms.add("window_hsb", &window_hsb);
+                    uniforms.add("resolution", &resolution);
+                    uniforms.add("background_pass", &(layer.zindex == 0 && idx == 0));
                     uniforms.add("subpixel_aa", &subpixel_aa);
                     uniforms.add("milliseconds", &milliseconds);
                     uniforms.add_struct("cursor_blink", &cursor_blink);
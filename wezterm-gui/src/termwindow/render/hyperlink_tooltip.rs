@@ -0,0 +1,52 @@
+use crate::termwindow::render::chip::chip_element;
+use crate::utilsprites::RenderMetrics;
+
+impl crate::TermWindow {
+    /// Shows the URI of the hyperlink under the mouse cursor in a
+    /// small chip at the bottom left of the window, similar to the
+    /// status bubble shown by web browsers.
+    pub fn paint_hyperlink_tooltip(&mut self) -> anyhow::Result<()> {
+        if !self.config.show_hyperlink_tooltip {
+            return Ok(());
+        }
+        let link = match self.current_highlight.as_ref() {
+            Some(link) => link.clone(),
+            None => return Ok(()),
+        };
+
+        let font = self.fonts.title_font()?;
+        let metrics = RenderMetrics::with_font_metrics(&font.metrics());
+        let palette = self.palette().clone();
+
+        let mut uri = link.uri().to_string();
+        let max_chars = (self.terminal_size.cols as usize).saturating_sub(4).max(8);
+        if uri.chars().count() > max_chars {
+            uri = uri.chars().take(max_chars - 1).collect();
+            uri.push('…');
+        }
+
+        let element = chip_element(&font, uri, &palette, 0.5, 0.1, 1.0);
+
+        let border = self.get_os_border();
+        let (padding_left, _padding_top) = self.padding_left_top();
+        let bottom_bar_height = if self.show_tab_bar && self.config.tab_bar_at_bottom {
+            self.tab_bar_pixel_height()?
+        } else {
+            0.
+        };
+
+        let mut computed = self.compute_chip(&element, &metrics)?;
+
+        computed.translate(euclid::vec2(
+            border.left.get() as f32 + padding_left,
+            self.dimensions.pixel_height as f32
+                - (computed.bounds.height()
+                    + border.bottom.get() as f32
+                    + bottom_bar_height
+                    + metrics.cell_size.height as f32 / 4.),
+        ));
+
+        let gl_state = self.render_state.as_ref().unwrap();
+        self.render_element(&computed, gl_state, None)
+    }
+}
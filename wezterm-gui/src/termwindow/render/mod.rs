@@ -12,7 +12,10 @@ use crate::utilsprites::RenderMetrics;
 use ::window::bitmaps::{TextureCoord, TextureRect, TextureSize};
 use ::window::{DeadKeyStatus, PointF, RectF, SizeF, WindowOps};
 use anyhow::{anyhow, Context};
-use config::{BoldBrightening, ConfigHandle, DimensionContext, TextStyle, VisualBellTarget};
+use config::{
+    BoldBrightening, ConfigHandle, DimensionContext, ReverseVideoSwap, TextStyle,
+    UnfocusedCursorStyle, VisualBellTarget,
+};
 use euclid::num::Zero;
 use mux::pane::{Pane, PaneId};
 use mux::renderable::{RenderableDimensions, StableCursorPosition};
@@ -20,7 +23,7 @@ use ordered_float::NotNan;
 use std::ops::Range;
 use std::rc::Rc;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use termwiz::cellcluster::CellCluster;
 use termwiz::hyperlink::Hyperlink;
 use termwiz::surface::{CursorShape, CursorVisibility, SequenceNo};
@@ -32,15 +35,23 @@ use wezterm_term::{CellAttributes, Line, StableRowIndex};
 use window::color::LinearRgba;
 
 pub mod borders;
+pub mod chip;
+pub mod command_annotation;
 pub mod corners;
+pub mod diff_highlight;
 pub mod draw;
 pub mod fancy_tab_bar;
+pub mod hyperlink_tooltip;
 pub mod paint;
 pub mod pane;
+pub mod present;
+pub mod resize_overlay;
 pub mod screen_line;
+pub mod scroll_indicator;
 pub mod split;
 pub mod tab_bar;
 pub mod window_buttons;
+pub mod zoom_badge;
 
 /// The data that we associate with a line; we use this to cache it shape hash
 #[derive(Debug)]
@@ -55,6 +66,7 @@ pub struct LineQuadCacheKey {
     pub config_generation: usize,
     pub shape_generation: usize,
     pub quad_generation: usize,
+    pub palette_generation: usize,
     /// Only set if cursor.y == stable_row
     pub composing: Option<String>,
     pub selection: Range<usize>,
@@ -93,6 +105,9 @@ pub struct LineToElementParams<'a> {
 pub struct LineToEleShapeCacheKey {
     pub shape_hash: [u8; 16],
     pub composing: Option<(usize, String)>,
+    /// The cursor column, if it is on this line and
+    /// disable_ligatures_at_cursor is enabled
+    pub cursor_x: Option<usize>,
     pub shape_generation: usize,
 }
 
@@ -118,6 +133,9 @@ pub struct LineToElementShape {
 
 pub struct RenderScreenLineResult {
     pub invalidate_on_hover_change: bool,
+    /// Some of the text is still being shaped in the background,
+    /// so the rendered line should not be cached
+    pub shaping_pending: bool,
 }
 
 pub struct RenderScreenLineParams<'a> {
@@ -226,6 +244,57 @@ impl crate::TermWindow {
         }
     }
 
+    /// Returns the palette to use when rendering pane, which is part way
+    /// between the old and new palettes while a color scheme change is
+    /// fading in.
+    /// The palette is cached until the pane reports that it has changed,
+    /// or the configuration is reloaded.
+    pub fn pane_palette(&self, pane: &Arc<dyn Pane>) -> Rc<ColorPalette> {
+        let mut per_pane = self.pane_state(pane.pane_id());
+        if per_pane.palette_transition.is_none() {
+            if let Some(palette) = &per_pane.displayed_palette {
+                return Rc::clone(palette);
+            }
+        }
+
+        let palette = pane.palette();
+        let duration = self.config.color_scheme_transition_duration_ms;
+        let palette = match per_pane.palette_transition.take() {
+            Some((from, start)) if duration > 0 && *from != palette => {
+                let progress = start.elapsed().as_millis() as f32 / duration as f32;
+                if progress >= 1.0 {
+                    palette
+                } else {
+                    let k = self
+                        .config
+                        .color_scheme_transition_function
+                        .evaluate_at_position(progress);
+                    let blended = from.interpolate(&palette, k as f64);
+                    per_pane.palette_transition.replace((from, start));
+                    let frame_interval =
+                        Duration::from_millis(1000 / self.config.max_fps.max(1) as u64);
+                    self.update_next_frame_time(Some(Instant::now() + frame_interval));
+                    blended
+                }
+            }
+            _ => palette,
+        };
+        let palette = Rc::new(palette);
+        if per_pane.displayed_palette.as_deref() != Some(&*palette) {
+            per_pane.palette_generation += 1;
+        }
+        per_pane.displayed_palette.replace(Rc::clone(&palette));
+        palette
+    }
+
+    /// Called when the palette of pane_id may have changed, so that
+    /// pane_palette looks it up again
+    pub fn invalidate_pane_palette(&self, pane_id: PaneId) {
+        if let Some(state) = self.pane_state.borrow_mut().get_mut(&pane_id) {
+            state.displayed_palette.take();
+        }
+    }
+
     fn get_intensity_if_bell_target_ringing(
         &self,
         pane: &Arc<dyn Pane>,
@@ -572,9 +641,10 @@ impl crate::TermWindow {
             cursor_shape,
             visibility,
         ) {
-            // Selected text overrides colors
+            // Selected text overrides colors; a translucent selection_fg
+            // tints the text color rather than replacing it
             (true, _, _, CursorVisibility::Hidden) => (
-                params.selection_fg.when_fully_transparent(params.fg_color),
+                params.selection_fg.blend_over(params.fg_color),
                 params.selection_bg,
                 params.cursor_bg,
             ),
@@ -589,7 +659,12 @@ impl crate::TermWindow {
                     (params.bg_color, params.fg_color, params.fg_color)
                 } else {
                     (
-                        params.cursor_fg.when_fully_transparent(params.fg_color),
+                        ensure_cursor_contrast(
+                            params.cursor_fg.when_fully_transparent(params.fg_color),
+                            params.cursor_bg,
+                            params.bg_color,
+                            self.config.cursor_min_contrast_ratio,
+                        ),
                         params.cursor_bg,
                         params.cursor_bg,
                     )
@@ -663,7 +738,11 @@ impl crate::TermWindow {
                     }
                     // When not focused, convert bar to block to make it more visually
                     // distinct from the focused bar in another pane
-                    _shape if !focused_and_active => Some(CursorShape::SteadyBlock),
+                    shape if !focused_and_active => match self.config.unfocused_cursor_style {
+                        UnfocusedCursorStyle::HollowBlock => Some(CursorShape::SteadyBlock),
+                        UnfocusedCursorStyle::Unchanged => Some(shape),
+                        UnfocusedCursorStyle::Hidden => None,
+                    },
                     shape => Some(shape),
                 }
             } else {
@@ -791,6 +870,80 @@ impl crate::TermWindow {
         Ok(glyph_info)
     }
 
+    /// Like cached_cluster_shape, but once the shaping budget for the
+    /// current frame has been used up, the cluster is handed off to the
+    /// background shaping threads instead of being shaped here.
+    /// Returns None if the shaped result is not yet available; the
+    /// window will be invalidated once it is.
+    fn cached_cluster_shape_or_defer(
+        &self,
+        style: &TextStyle,
+        cluster: &CellCluster,
+        gl_state: &RenderState,
+        metrics: &RenderMetrics,
+    ) -> anyhow::Result<Option<Rc<Vec<ShapedInfo>>>> {
+        let key = BorrowedShapeCacheKey {
+            style,
+            text: &cluster.text,
+        };
+        match self.lookup_cached_shape(&key) {
+            Some(Ok(info)) => return Ok(Some(info)),
+            Some(Err(err)) => return Err(err),
+            None => {}
+        }
+
+        if let Some(result) = self.shape_workers.take_ready(&key) {
+            let font = self.fonts.resolve_font(style)?;
+            let is_current = font
+                .shaper_spec()
+                .map(|spec| Arc::ptr_eq(&spec, &result.spec))
+                .unwrap_or(false);
+            if let (true, Some(info)) = (is_current, result.glyphs) {
+                let glyphs = self.glyph_infos_to_glyphs(
+                    style,
+                    &mut gl_state.glyph_cache.borrow_mut(),
+                    &info,
+                    &font,
+                    metrics,
+                )?;
+                let shaped = Rc::new(ShapedInfo::process(&info, &glyphs));
+                self.shape_cache
+                    .borrow_mut()
+                    .put(key.to_owned(), Ok(Rc::clone(&shaped)));
+                return Ok(Some(shaped));
+            }
+            // The result is stale, or needs fallback fonts to be
+            // resolved; shape it here rather than resubmitting it
+            return self
+                .cached_cluster_shape(style, cluster, gl_state, Some(&font), metrics)
+                .map(Some);
+        }
+
+        if self.shape_workers.is_pending(&key) {
+            return Ok(None);
+        }
+
+        let out_of_time = self
+            .shaping_deadline
+            .get()
+            .map(|deadline| Instant::now() >= deadline)
+            .unwrap_or(false);
+        if out_of_time {
+            let font = self.fonts.resolve_font(style)?;
+            if let Some(spec) = font.shaper_spec() {
+                let window = self.window.as_ref().unwrap().clone();
+                self.shape_workers
+                    .submit(key.to_owned(), spec, cluster, move || {
+                        window.notify(TermWindowNotif::BackgroundShapingComplete)
+                    });
+                return Ok(None);
+            }
+        }
+
+        self.cached_cluster_shape(style, cluster, gl_state, None, metrics)
+            .map(Some)
+    }
+
     fn lookup_cached_shape(
         &self,
         key: &dyn ShapeCacheKeyTrait,
@@ -881,6 +1034,63 @@ fn resolve_fg_color_attr(
     .to_linear()
 }
 
+/// Returns the (fg, bg) colors to use for a cell that is displayed in
+/// reverse video, given its resolved fg_color and bg_color
+fn reverse_colors(
+    attrs: &CellAttributes,
+    fg_color: LinearRgba,
+    bg_color: LinearRgba,
+    palette: &ColorPalette,
+    config: &ConfigHandle,
+    style: &config::TextStyle,
+) -> (LinearRgba, LinearRgba) {
+    match config.reverse_video_swap {
+        ReverseVideoSwap::AfterPalette => (bg_color, fg_color),
+        ReverseVideoSwap::BeforePalette => {
+            let fg = match attrs.background() {
+                ColorAttribute::Default => bg_color,
+                color => resolve_fg_color_attr(attrs, color, palette, config, style),
+            };
+            let bg = match attrs.foreground() {
+                ColorAttribute::Default => fg_color,
+                color => palette.resolve_bg(color).to_linear(),
+            };
+            (fg, bg)
+        }
+    }
+}
+
+/// Returns a color for the text under a block cursor that has at least
+/// `min_ratio` contrast against the cursor background.
+/// The cell background is preferred, as that is equivalent to
+/// reversing the colors, falling back to black or white.
+fn ensure_cursor_contrast(
+    fg: LinearRgba,
+    cursor_bg: LinearRgba,
+    cell_bg: LinearRgba,
+    min_ratio: f32,
+) -> LinearRgba {
+    if min_ratio <= 1.0 {
+        return fg;
+    }
+    let bg = cursor_bg.to_srgb();
+    let ratio = |color: LinearRgba| color.to_srgb().contrast_ratio(&bg) as f32;
+
+    if ratio(fg) >= min_ratio {
+        fg
+    } else if !cell_bg.is_fully_transparent() && ratio(cell_bg) >= min_ratio {
+        cell_bg
+    } else {
+        let black = LinearRgba::with_components(0., 0., 0., 1.);
+        let white = LinearRgba::with_components(1., 1., 1., 1.);
+        if ratio(black) >= ratio(white) {
+            black
+        } else {
+            white
+        }
+    }
+}
+
 fn update_next_frame_time(storage: &mut Option<Instant>, next_due: Option<Instant>) {
     if let Some(next_due) = next_due {
         match storage.take() {
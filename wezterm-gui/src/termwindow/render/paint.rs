@@ -3,6 +3,7 @@ use ::window::bitmaps::atlas::OutOfTextureSpace;
 use ::window::WindowOps;
 use anyhow::Context;
 use smol::Timer;
+use std::rc::Rc;
 use std::time::{Duration, Instant};
 use wezterm_font::ClearShapeCache;
 
@@ -19,6 +20,7 @@ impl crate::TermWindow {
         // If nothing on screen needs animating, then we can avoid
         // invalidating as frequently
         *self.has_animation.borrow_mut() = None;
+        self.advance_kinetic_scroll();
         // Start with the assumption that we should allow images to render
         self.allow_images = AllowImage::Yes;
 
@@ -34,6 +36,25 @@ impl crate::TermWindow {
             }
         }
 
+        // Size the vertex buffers to suit the cell grid up front, rather
+        // than discovering that we need more quads part way through
+        // a render pass and having to redo it
+        let num_cells = self.terminal_size.rows * self.terminal_size.cols;
+        if let Err(err) = self.render_state.as_ref().unwrap().reserve_quads(num_cells) {
+            log::error!("{:#}", err);
+        }
+
+        // Spend no more than around half of the frame interval shaping
+        // text on this thread; anything beyond that is shaped in the
+        // background and filled in on a subsequent frame
+        self.shaping_deadline
+            .set(if self.config.background_shaping {
+                Some(start + Duration::from_millis(500 / self.config.max_fps.max(1) as u64))
+            } else {
+                None
+            });
+        self.shape_workers.start_frame();
+
         'pass: for pass in 0.. {
             match self.paint_pass() {
                 Ok(_) => match self.render_state.as_mut().unwrap().allocated_more_quads() {
@@ -103,6 +124,7 @@ impl crate::TermWindow {
                 }
             }
         }
+        self.shaping_deadline.set(None);
         log::debug!("paint_impl before call_draw elapsed={:?}", start.elapsed());
 
         self.call_draw(frame).ok();
@@ -176,6 +198,11 @@ impl crate::TermWindow {
 
         let start = Instant::now();
         let gl_state = self.render_state.as_ref().unwrap();
+        if gl_state.has_animated_background_shader() {
+            // The shader animates over time, so keep repainting
+            let fps = self.config.animation_fps.max(1) as u64;
+            self.update_next_frame_time(Some(start + Duration::from_millis(1000 / fps)));
+        }
         let layer = gl_state
             .layer_for_zindex(0)
             .context("layer_for_zindex(0)")?;
@@ -185,10 +212,18 @@ impl crate::TermWindow {
 
         let mut paint_terminal_background = false;
 
+        // Panes can have their palettes changed via escape sequences;
+        // the window background follows the palette of the active pane
+        // so that it blends in with the content of that pane
+        let active_palette = match panes.iter().find(|p| p.is_active) {
+            Some(pos) => self.pane_palette(&pos.pane),
+            None => Rc::new(self.palette().clone()),
+        };
+
         // Render the full window background
         match (self.window_background.is_empty(), self.allow_images) {
             (false, AllowImage::Yes | AllowImage::Scale(_)) => {
-                let bg_color = self.palette().background.to_linear();
+                let bg_color = active_palette.background.to_linear();
 
                 let top = panes
                     .iter()
@@ -222,15 +257,10 @@ impl crate::TermWindow {
 
         if paint_terminal_background {
             // Regular window background color
-            let background = if panes.len() == 1 {
-                // If we're the only pane, use the pane's palette
-                // to draw the padding background
-                panes[0].pane.palette().background
-            } else {
-                self.palette().background
-            }
-            .to_linear()
-            .mul_alpha(self.config.window_background_opacity);
+            let background = active_palette
+                .background
+                .to_linear()
+                .mul_alpha(self.config.window_background_opacity);
 
             self.filled_rectangle(
                 &mut layers,
@@ -246,6 +276,7 @@ impl crate::TermWindow {
             .context("filled_rectangle for window background")?;
         }
 
+        let active_pane = panes.iter().find(|p| p.is_active).cloned();
         for pos in panes {
             if pos.is_active {
                 self.update_text_cursor(&pos);
@@ -254,13 +285,16 @@ impl crate::TermWindow {
                     mux::Mux::get().record_focus_for_current_identity(pos.pane.pane_id());
                 }
             }
+            if focused {
+                self.pane_monitor_seen(pos.pane.pane_id());
+            }
             self.paint_pane(&pos, &mut layers).context("paint_pane")?;
         }
 
         if let Some(pane) = self.get_active_pane_or_overlay() {
             let splits = self.get_splits();
             for split in &splits {
-                self.paint_split(&mut layers, split, &pane)
+                self.paint_split(&mut layers, split, &pane, active_pane.as_ref())
                     .context("paint_split")?;
             }
         }
@@ -272,6 +306,13 @@ impl crate::TermWindow {
         self.paint_window_borders(&mut layers)
             .context("paint_window_borders")?;
         drop(layers);
+        self.paint_zoom_badge().context("paint_zoom_badge")?;
+        self.paint_scroll_indicator()
+            .context("paint_scroll_indicator")?;
+        self.paint_hyperlink_tooltip()
+            .context("paint_hyperlink_tooltip")?;
+        self.paint_resize_overlay()
+            .context("paint_resize_overlay")?;
         self.paint_modal().context("paint_modal")?;
 
         Ok(())
@@ -1,6 +1,8 @@
 use crate::quad::{HeapQuadAllocator, QuadTrait, TripleLayerQuadAllocator};
 use crate::selection::SelectionRange;
 use crate::termwindow::box_model::*;
+use crate::termwindow::render::command_annotation::{annotate_line, command_annotations};
+use crate::termwindow::render::diff_highlight::{diff_highlights, highlight_line};
 use crate::termwindow::render::{
     same_hyperlink, CursorProperties, LineQuadCacheKey, LineQuadCacheValue, LineToEleShapeCacheKey,
     RenderScreenLineParams,
@@ -14,10 +16,15 @@ use mux::pane::{PaneId, WithPaneLines};
 use mux::renderable::{RenderableDimensions, StableCursorPosition};
 use mux::tab::PositionedPane;
 use ordered_float::NotNan;
+use std::collections::HashMap;
+use std::ops::Range;
 use std::time::Instant;
+use termwiz::cell::unicode_column_width;
+use termwiz::color::AnsiColor;
+use termwiz::surface::CursorVisibility;
 use wezterm_dynamic::Value;
 use wezterm_term::color::{ColorAttribute, ColorPalette};
-use wezterm_term::{Line, StableRowIndex};
+use wezterm_term::{CommandRecord, Line, StableRowIndex};
 use window::color::LinearRgba;
 
 impl crate::TermWindow {
@@ -58,7 +65,7 @@ impl crate::TermWindow {
         let global_cursor_fg = self.palette().cursor_fg;
         let global_cursor_bg = self.palette().cursor_bg;
         let config = self.config.clone();
-        let palette = pos.pane.palette();
+        let palette = self.pane_palette(&pos.pane);
 
         let (padding_left, padding_top) = self.padding_left_top();
 
@@ -77,12 +84,22 @@ impl crate::TermWindow {
         let border = self.get_os_border();
         let top_pixel_y = top_bar_height + padding_top + border.top.get() as f32;
 
+        let pane_id = pos.pane.pane_id();
         let cursor = pos.pane.get_cursor_position();
+        // Typed text that we are showing before the pane echoes it,
+        // with the cursor placed after it
+        let predicted_echo = self.predicted_echo(pane_id, &cursor);
+        let cursor = match &predicted_echo {
+            Some((x, text)) => StableCursorPosition {
+                x: x + unicode_column_width(text, None),
+                ..cursor
+            },
+            None => cursor,
+        };
         if pos.is_active {
-            self.prev_cursor.update(&cursor);
+            self.prev_cursor.update(pane_id, &cursor);
         }
 
-        let pane_id = pos.pane.pane_id();
         let current_viewport = self.get_viewport(pane_id);
         let dims = pos.pane.get_dimensions();
 
@@ -170,6 +187,26 @@ impl crate::TermWindow {
             } else {
                 Some(config.inactive_pane_hsb)
             });
+        } else if config.pane_background_opacity > 0. {
+            // Translucent per-pane backdrop that lets the window
+            // background show through
+
+            let mut quad = self
+                .filled_rectangle(
+                    layers,
+                    0,
+                    background_rect,
+                    palette
+                        .background
+                        .to_linear()
+                        .mul_alpha(config.pane_background_opacity),
+                )
+                .context("filled_rectangle")?;
+            quad.set_hsv(if pos.is_active {
+                None
+            } else {
+                Some(config.inactive_pane_hsb)
+            });
         }
 
         {
@@ -303,14 +340,59 @@ impl crate::TermWindow {
             palette.cursor_fg == global_cursor_fg && palette.cursor_bg == global_cursor_bg;
 
         {
-            let stable_range = match current_viewport {
-                Some(top) => top..top + dims.viewport_rows as StableRowIndex,
-                None => dims.physical_top..dims.physical_top + dims.viewport_rows as StableRowIndex,
+            // When smooth scrolling has left the viewport part way between
+            // two lines, the content is shifted down by that many pixels,
+            // so that the line above the viewport is partially visible at
+            // the top of the pane and the last line is partially visible
+            // at the bottom.  Those lines are clipped to the pane.
+            let scroll_pixel_offset = match current_viewport {
+                Some(_) => self.pane_state(pane_id).scroll_pixel_offset,
+                None => 0.,
+            };
+            let top = current_viewport.unwrap_or(dims.physical_top);
+            let num_rows = dims.viewport_rows as StableRowIndex;
+            let cell_height = self.render_metrics.cell_size.height as f32;
+            let (stable_range, lines_top_pixel_y, clip) = if scroll_pixel_offset > 0. {
+                let pane_top = (self.dimensions.pixel_height as f32 / -2.)
+                    + top_pixel_y
+                    + pos.top as f32 * cell_height;
+                (
+                    top - 1..top + num_rows,
+                    top_pixel_y + scroll_pixel_offset - cell_height,
+                    Some((pane_top, pane_top + num_rows as f32 * cell_height)),
+                )
+            } else {
+                (top..top + num_rows, top_pixel_y, None)
             };
 
             pos.pane
                 .apply_hyperlinks(stable_range.clone(), &self.config.hyperlink_rules);
 
+            let annotations = if self.config.show_command_annotations {
+                command_annotations(&pos.pane, stable_range.clone())
+            } else {
+                HashMap::new()
+            };
+
+            let diff_zones = {
+                let mut state = self.pane_state(pane_id);
+                if state.diff_highlight {
+                    state.diff_zones.output_zones(&pos.pane)
+                } else {
+                    None
+                }
+            };
+            let diff_highlights = match diff_zones {
+                Some(zones) => diff_highlights(&pos.pane, zones, stable_range.clone()),
+                None => HashMap::new(),
+            };
+            let diff_highlight_bg: ColorAttribute = self
+                .config
+                .resolved_palette
+                .diff_highlight_bg
+                .map(Into::into)
+                .unwrap_or(AnsiColor::Navy.into());
+
             struct LineRender<'a, 'b> {
                 term_window: &'a mut crate::TermWindow,
                 selrange: Option<SelectionRange>,
@@ -318,9 +400,13 @@ impl crate::TermWindow {
                 dims: RenderableDimensions,
                 top_pixel_y: f32,
                 left_pixel_x: f32,
+                /// The vertical extent, in quad coordinates, to which
+                /// the lines must be clipped
+                clip: Option<(f32, f32)>,
                 pos: &'a PositionedPane,
                 pane_id: PaneId,
                 cursor: &'a StableCursorPosition,
+                predicted_echo: Option<(usize, String)>,
                 palette: &'a ColorPalette,
                 default_bg: LinearRgba,
                 cursor_border_color: LinearRgba,
@@ -334,6 +420,10 @@ impl crate::TermWindow {
                 filled_box: TextureRect,
                 window_is_transparent: bool,
                 layers: &'a mut TripleLayerQuadAllocator<'b>,
+                palette_generation: usize,
+                annotations: HashMap<StableRowIndex, CommandRecord>,
+                diff_highlights: HashMap<StableRowIndex, Vec<Range<usize>>>,
+                diff_highlight_bg: ColorAttribute,
                 error: Option<anyhow::Error>,
             }
 
@@ -341,16 +431,20 @@ impl crate::TermWindow {
                 + border.left.get() as f32
                 + (pos.left as f32 * self.render_metrics.cell_size.width as f32);
 
+            let palette_generation = self.pane_state(pane_id).palette_generation;
+
             let mut render = LineRender {
                 term_window: self,
                 selrange,
                 rectangular,
                 dims,
-                top_pixel_y,
+                top_pixel_y: lines_top_pixel_y,
                 left_pixel_x,
+                clip,
                 pos,
                 pane_id,
                 cursor: &cursor,
+                predicted_echo,
                 palette: &palette,
                 cursor_border_color,
                 selection_fg,
@@ -364,9 +458,24 @@ impl crate::TermWindow {
                 filled_box,
                 window_is_transparent,
                 layers,
+                palette_generation,
+                annotations,
+                diff_highlights,
+                diff_highlight_bg,
                 error: None,
             };
 
+            fn apply_quads(
+                quads: &HeapQuadAllocator,
+                layers: &mut TripleLayerQuadAllocator,
+                clip: Option<(f32, f32)>,
+            ) -> anyhow::Result<()> {
+                match clip {
+                    Some((top, bottom)) => quads.apply_clipped_to(layers, top, bottom),
+                    None => quads.apply_to(layers),
+                }
+            }
+
             impl<'a, 'b> LineRender<'a, 'b> {
                 fn render_line(
                     &mut self,
@@ -400,7 +509,7 @@ impl crate::TermWindow {
                                 (true, DeadKeyStatus::Composing(composing)) => {
                                     Some(composing.to_string())
                                 }
-                                _ => None,
+                                _ => self.predicted_echo.as_ref().map(|(_, text)| text.clone()),
                             },
                             if self.term_window.config.detect_password_input {
                                 match self.pos.pane.get_metadata() {
@@ -430,6 +539,7 @@ impl crate::TermWindow {
                         config_generation: self.term_window.config.generation(),
                         shape_generation: self.term_window.shape_generation,
                         quad_generation: self.term_window.quad_generation,
+                        palette_generation: self.palette_generation,
                         composing: composing.clone(),
                         selection: selrange.clone(),
                         cursor,
@@ -458,9 +568,7 @@ impl crate::TermWindow {
                             false
                         };
                         if !expired && !hover_changed {
-                            cached_quad
-                                .layers
-                                .apply_to(self.layers)
+                            apply_quads(&cached_quad.layers, self.layers, self.clip)
                                 .context("cached_quad.layers.apply_to")?;
                             self.term_window.update_next_frame_time(cached_quad.expires);
                             return Ok(());
@@ -473,17 +581,24 @@ impl crate::TermWindow {
                     let shape_key = LineToEleShapeCacheKey {
                         shape_hash,
                         shape_generation: quad_key.shape_generation,
-                        composing: if self.cursor.y == stable_row && self.pos.is_active {
-                            if let DeadKeyStatus::Composing(composing) =
-                                &self.term_window.dead_key_status
-                            {
-                                Some((self.cursor.x, composing.to_string()))
-                            } else {
-                                None
+                        composing: if self.cursor.y == stable_row {
+                            match (self.pos.is_active, &self.term_window.dead_key_status) {
+                                (true, DeadKeyStatus::Composing(composing)) => {
+                                    Some((self.cursor.x, composing.to_string()))
+                                }
+                                _ => self.predicted_echo.clone(),
                             }
                         } else {
                             None
                         },
+                        cursor_x: if self.term_window.config.disable_ligatures_at_cursor
+                            && self.cursor.y == stable_row
+                            && self.pos.is_active
+                        {
+                            Some(self.cursor.x)
+                        } else {
+                            None
+                        },
                     };
 
                     let render_result = self
@@ -531,9 +646,13 @@ impl crate::TermWindow {
                     let expires = self.term_window.has_animation.borrow().as_ref().cloned();
                     self.term_window.update_next_frame_time(next_due);
 
-                    buf.apply_to(self.layers)
+                    apply_quads(&buf, self.layers, self.clip)
                         .context("HeapQuadAllocator::apply_to")?;
 
+                    if render_result.shaping_pending {
+                        return Ok(());
+                    }
+
                     let quad_value = LineQuadCacheValue {
                         layers: buf,
                         expires,
@@ -557,7 +676,25 @@ impl crate::TermWindow {
             impl<'a, 'b> WithPaneLines for LineRender<'a, 'b> {
                 fn with_lines_mut(&mut self, stable_top: StableRowIndex, lines: &mut [&mut Line]) {
                     for (line_idx, line) in lines.iter().enumerate() {
-                        if let Err(err) = self.render_line(stable_top, line_idx, line) {
+                        let stable_row = stable_top + line_idx as StableRowIndex;
+                        let highlighted = self
+                            .diff_highlights
+                            .get(&stable_row)
+                            .map(|ranges| highlight_line(line, ranges, self.diff_highlight_bg));
+                        let annotated = self.annotations.get(&stable_row).and_then(|record| {
+                            annotate_line(
+                                highlighted.as_ref().unwrap_or(&**line),
+                                record,
+                                self.dims.cols,
+                            )
+                        });
+                        let result = match annotated.or(highlighted) {
+                            Some(mut annotated) => {
+                                self.render_line(stable_top, line_idx, &&mut annotated)
+                            }
+                            None => self.render_line(stable_top, line_idx, line),
+                        };
+                        if let Err(err) = result {
                             self.error.replace(err);
                             return;
                         }
@@ -571,6 +708,34 @@ impl crate::TermWindow {
             }
         }
 
+        if pos.is_active {
+            // The top left corner of the first row of the viewport,
+            // which is shifted down when smooth scrolling has left
+            // it part way between two lines
+            let scroll_pixel_offset = match current_viewport {
+                Some(_) => self.pane_state(pane_id).scroll_pixel_offset,
+                None => 0.,
+            };
+            let pane_origin = (
+                padding_left
+                    + border.left.get() as f32
+                    + pos.left as f32 * self.render_metrics.cell_size.width as f32,
+                top_pixel_y
+                    + pos.top as f32 * self.render_metrics.cell_size.height as f32
+                    + scroll_pixel_offset,
+            );
+            self.paint_cursor_trail(
+                pane_id,
+                &cursor,
+                &dims,
+                current_viewport,
+                pane_origin,
+                cursor_bg,
+                layers,
+            )
+            .context("paint_cursor_trail")?;
+        }
+
         /*
         if let Some(zone) = zone {
             // TODO: render a thingy to jump to prior prompt
@@ -582,6 +747,87 @@ impl crate::TermWindow {
         Ok(())
     }
 
+    /// When cursor_animation_duration_ms is set, draws a cell sized quad
+    /// that glides from the prior cursor position towards the current
+    /// one, fading out as it reaches its destination.
+    /// Positions are computed relative to pane_origin, the top left
+    /// corner of the first row of the viewport of the pane.
+    fn paint_cursor_trail(
+        &self,
+        pane_id: PaneId,
+        cursor: &StableCursorPosition,
+        dims: &RenderableDimensions,
+        current_viewport: Option<StableRowIndex>,
+        pane_origin: (f32, f32),
+        cursor_bg: LinearRgba,
+        layers: &mut TripleLayerQuadAllocator,
+    ) -> anyhow::Result<()> {
+        let duration_ms = self.config.cursor_animation_duration_ms;
+        if duration_ms == 0
+            || self.focused.is_none()
+            || cursor.visibility != CursorVisibility::Visible
+        {
+            return Ok(());
+        }
+
+        let (prior, moved) = match self.prev_cursor.prior_position(pane_id) {
+            Some(prior) => prior,
+            None => return Ok(()),
+        };
+
+        let progress = moved.elapsed().as_secs_f32() / (duration_ms as f32 / 1000.);
+        if progress >= 1.0 {
+            return Ok(());
+        }
+
+        let viewport_top = current_viewport.unwrap_or(dims.physical_top);
+        let viewport_rows = dims.viewport_rows as StableRowIndex;
+        if prior.y < viewport_top
+            || prior.y >= viewport_top + viewport_rows
+            || cursor.y < viewport_top
+            || cursor.y >= viewport_top + viewport_rows
+        {
+            return Ok(());
+        }
+
+        let cell_width = self.render_metrics.cell_size.width as f32;
+        let cell_height = self.render_metrics.cell_size.height as f32;
+        let (origin_x, origin_y) = pane_origin;
+        let cell_origin = |x: usize, y: StableRowIndex| {
+            (
+                origin_x + x as f32 * cell_width,
+                origin_y + (y - viewport_top) as f32 * cell_height,
+            )
+        };
+        let (from_x, from_y) = cell_origin(prior.x, prior.y);
+        let (to_x, to_y) = cell_origin(cursor.x, cursor.y);
+
+        let t = self
+            .config
+            .cursor_animation_ease
+            .evaluate_at_position(progress)
+            .clamp(0., 1.);
+
+        self.filled_rectangle(
+            layers,
+            2,
+            euclid::rect(
+                from_x + (to_x - from_x) * t,
+                from_y + (to_y - from_y) * t,
+                cell_width,
+                cell_height,
+            ),
+            cursor_bg.mul_alpha(1.0 - t),
+        )?;
+
+        let fps = self.config.animation_fps.max(1) as u64;
+        self.update_next_frame_time(Some(
+            Instant::now() + std::time::Duration::from_millis(1000 / fps),
+        ));
+
+        Ok(())
+    }
+
     pub fn build_pane(&mut self, pos: &PositionedPane) -> anyhow::Result<ComputedElement> {
         // First compute the bounds for the pane background
 
@@ -654,7 +900,7 @@ impl crate::TermWindow {
             pos.height as f32 * cell_height,
         );
 
-        let palette = pos.pane.palette();
+        let palette = self.pane_palette(&pos.pane);
 
         // TODO: visual bell background layer
         // TODO: scrollbar
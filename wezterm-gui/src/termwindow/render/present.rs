@@ -0,0 +1,151 @@
+use ::window::{Window, WindowOps};
+use mux::pane::PaneId;
+use mux::renderable::StableCursorPosition;
+use smol::Timer;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+use termwiz::surface::SequenceNo;
+use wezterm_term::StableRowIndex;
+
+/// How often we repaint a window that is minimized or occluded
+const HIDDEN_REPAINT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Records what was visible in the most recently presented frame,
+/// so that we can avoid painting and presenting an identical frame.
+pub struct PresentedFrame {
+    /// Hash of the window and pane geometry, cursor positions and
+    /// other state that isn't tracked via the pane sequence numbers
+    fingerprint: u64,
+    /// The sequence number of each pane at the time that it was painted
+    seqnos: HashMap<PaneId, SequenceNo>,
+}
+
+#[derive(Hash)]
+struct PaneFingerprint {
+    pane_id: PaneId,
+    is_active: bool,
+    is_zoomed: bool,
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+    viewport_top: StableRowIndex,
+    cursor: StableCursorPosition,
+}
+
+impl crate::TermWindow {
+    /// Returns true if painting now would produce the same frame as the
+    /// one that was most recently presented, in which case the caller
+    /// can skip painting and presenting it.
+    /// If it returns false, the state of the frame that is about to be
+    /// painted is recorded for comparison with the next call.
+    pub fn frame_is_unchanged(&mut self) -> bool {
+        // On Wayland, our paints are driven by frame callbacks that
+        // are only delivered after we commit a new frame, so we must
+        // always present.
+        if self.connection_name == "Wayland" {
+            return false;
+        }
+
+        let panes = self.get_panes_to_render();
+        let mut hasher = DefaultHasher::new();
+        (
+            self.dimensions.pixel_width,
+            self.dimensions.pixel_height,
+            self.dimensions.dpi,
+            self.focused.is_some(),
+            self.show_tab_bar,
+            self.shape_generation,
+        )
+            .hash(&mut hasher);
+
+        let mut visible_rows = vec![];
+        let mut seqnos = HashMap::new();
+        for pos in &panes {
+            let pane_id = pos.pane.pane_id();
+            let dims = pos.pane.get_dimensions();
+            let viewport_top = self.get_viewport(pane_id).unwrap_or(dims.physical_top);
+            PaneFingerprint {
+                pane_id,
+                is_active: pos.is_active,
+                is_zoomed: pos.is_zoomed,
+                left: pos.left,
+                top: pos.top,
+                width: pos.width,
+                height: pos.height,
+                viewport_top,
+                cursor: pos.pane.get_cursor_position(),
+            }
+            .hash(&mut hasher);
+            visible_rows.push(viewport_top..viewport_top + dims.viewport_rows as StableRowIndex);
+            seqnos.insert(pane_id, pos.pane.get_current_seqno());
+        }
+        let fingerprint = hasher.finish();
+
+        // Animations and blinking need a new frame even though
+        // nothing else changed
+        let is_animating = self.has_animation.borrow().is_some();
+
+        if !is_animating {
+            if let Some(prior) = &self.presented_frame {
+                let unchanged = prior.fingerprint == fingerprint
+                    && prior.seqnos.len() == panes.len()
+                    && panes.iter().zip(visible_rows).all(|(pos, rows)| {
+                        match prior.seqnos.get(&pos.pane.pane_id()) {
+                            Some(&seqno) => pos.pane.get_changed_since(rows, seqno).is_empty(),
+                            None => false,
+                        }
+                    });
+                if unchanged {
+                    return true;
+                }
+            }
+        }
+
+        self.presented_frame.replace(PresentedFrame {
+            fingerprint,
+            seqnos,
+        });
+        false
+    }
+
+    /// Forget the most recently presented frame, so that the next
+    /// paint will always be presented
+    pub fn invalidate_presented_frame(&mut self) {
+        self.presented_frame.take();
+    }
+
+    /// Returns true if the window is minimized or occluded and has
+    /// been painted recently, in which case the caller should skip
+    /// painting.  Output that arrives while hidden is painted at most
+    /// once per HIDDEN_REPAINT_INTERVAL, and the window is repainted
+    /// as soon as it becomes visible again.
+    pub fn defer_hidden_paint(&mut self, window: &Window) -> bool {
+        if self.window_state.can_paint() {
+            self.hidden_repaint_due.take();
+            return false;
+        }
+
+        let now = Instant::now();
+        match self.hidden_repaint_due {
+            Some(due) if now >= due => {
+                self.hidden_repaint_due.take();
+                false
+            }
+            Some(_) => true,
+            None => {
+                let due = now + HIDDEN_REPAINT_INTERVAL;
+                self.hidden_repaint_due.replace(due);
+                let window = window.clone();
+                promise::spawn::spawn(async move {
+                    Timer::at(due).await;
+                    window.invalidate();
+                })
+                .detach();
+                true
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::termwindow::render::chip::chip_element;
+use crate::utilsprites::RenderMetrics;
+use std::time::{Duration, Instant};
+
+/// How long the size is shown at full opacity
+const RESIZE_OVERLAY_HOLD: Duration = Duration::from_millis(1000);
+/// How long it then takes to fade out
+const RESIZE_OVERLAY_FADE: Duration = Duration::from_millis(500);
+
+impl crate::TermWindow {
+    /// Shows the size of the terminal as `cols×rows` in the middle of
+    /// the window for a short time after its dimensions change, so that
+    /// the window can be sized to a particular number of rows and columns.
+    pub fn paint_resize_overlay(&mut self) -> anyhow::Result<()> {
+        let elapsed = match self.resize_overlay_shown {
+            Some(shown) => shown.elapsed(),
+            None => return Ok(()),
+        };
+        if !self.config.show_resize_overlay || elapsed >= RESIZE_OVERLAY_HOLD + RESIZE_OVERLAY_FADE
+        {
+            self.resize_overlay_shown.take();
+            return Ok(());
+        }
+
+        let alpha = if elapsed <= RESIZE_OVERLAY_HOLD {
+            // Repaint when it is time to start fading out
+            self.update_next_frame_time(Some(Instant::now() + (RESIZE_OVERLAY_HOLD - elapsed)));
+            1.0
+        } else {
+            let fps = self.config.animation_fps.max(1) as u64;
+            self.update_next_frame_time(Some(Instant::now() + Duration::from_millis(1000 / fps)));
+            1.0 - (elapsed - RESIZE_OVERLAY_HOLD).as_secs_f32() / RESIZE_OVERLAY_FADE.as_secs_f32()
+        };
+
+        let font = self.fonts.title_font()?;
+        let metrics = RenderMetrics::with_font_metrics(&font.metrics());
+        let palette = self.palette().clone();
+
+        let text = format!("{}×{}", self.terminal_size.cols, self.terminal_size.rows);
+        let element = chip_element(&font, text, &palette, 1., 0.5, alpha);
+
+        let mut computed = self.compute_chip(&element, &metrics)?;
+
+        computed.translate(euclid::vec2(
+            (self.dimensions.pixel_width as f32 - computed.bounds.width()) / 2.,
+            (self.dimensions.pixel_height as f32 - computed.bounds.height()) / 2.,
+        ));
+
+        let gl_state = self.render_state.as_ref().unwrap();
+        self.render_element(&computed, gl_state, None)
+    }
+}
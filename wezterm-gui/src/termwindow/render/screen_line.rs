@@ -1,13 +1,13 @@
 use crate::quad::{QuadTrait, TripleLayerQuadAllocator, TripleLayerQuadAllocatorTrait};
 use crate::termwindow::render::{
-    resolve_fg_color_attr, same_hyperlink, update_next_frame_time, ClusterStyleCache,
-    ComputeCellFgBgParams, ComputeCellFgBgResult, LineToElementParams, LineToElementShape,
-    RenderScreenLineParams, RenderScreenLineResult,
+    resolve_fg_color_attr, reverse_colors, same_hyperlink, update_next_frame_time,
+    ClusterStyleCache, ComputeCellFgBgParams, ComputeCellFgBgResult, LineToElementParams,
+    LineToElementShape, RenderScreenLineParams, RenderScreenLineResult,
 };
 use crate::termwindow::LineToElementShapeItem;
 use ::window::DeadKeyStatus;
 use anyhow::Context;
-use config::{HsbTransform, TextStyle};
+use config::{HsbTransform, TextBlinkStyle, TextStyle};
 use std::ops::Range;
 use std::rc::Rc;
 use std::time::Instant;
@@ -34,6 +34,7 @@ impl crate::TermWindow {
             // rendering the top row, so we have nothing more to do here.
             return Ok(RenderScreenLineResult {
                 invalidate_on_hover_change: false,
+                shaping_pending: false,
             });
         }
 
@@ -115,6 +116,7 @@ impl crate::TermWindow {
 
         let mut shaped = None;
         let mut invalidate_on_hover_change = false;
+        let mut shaping_pending = false;
 
         if let Some(shape_key) = &params.shape_key {
             let mut cache = self.line_to_ele_shape_cache.borrow_mut();
@@ -150,8 +152,9 @@ impl crate::TermWindow {
                 shape_key: &params.shape_key,
             };
 
-            let (shaped, invalidate_on_hover) = self.build_line_element_shape(params)?;
+            let (shaped, invalidate_on_hover, pending) = self.build_line_element_shape(params)?;
             invalidate_on_hover_change = invalidate_on_hover;
+            shaping_pending = pending;
             shaped
         };
 
@@ -217,7 +220,14 @@ impl crate::TermWindow {
 
                 // Check the line reverse_video flag and flip.
                 if attrs.reverse() == !params.dims.reverse_video {
-                    std::mem::swap(&mut fg, &mut bg);
+                    (fg, bg) = reverse_colors(
+                        &attrs,
+                        fg,
+                        bg,
+                        &params.palette,
+                        &params.config,
+                        &Default::default(),
+                    );
                     bg_default = false;
                 }
 
@@ -714,13 +724,14 @@ impl crate::TermWindow {
 
         Ok(RenderScreenLineResult {
             invalidate_on_hover_change,
+            shaping_pending,
         })
     }
 
     fn build_line_element_shape(
         &self,
         params: LineToElementParams,
-    ) -> anyhow::Result<(Rc<Vec<LineToElementShape>>, bool)> {
+    ) -> anyhow::Result<(Rc<Vec<LineToElementShape>>, bool, bool)> {
         let (bidi_enabled, bidi_direction) = params.line.bidi_info();
         let bidi_hint = if bidi_enabled {
             Some(bidi_direction)
@@ -739,12 +750,24 @@ impl crate::TermWindow {
             params.line.cluster(bidi_hint)
         };
 
+        // Shape the cell under the cursor on its own, breaking up any
+        // ligature that it is part of, so that the character being
+        // edited is visible.  The rest of the line is unaffected.
+        let cell_clusters = match params.shape_key.as_ref().and_then(|k| k.cursor_x) {
+            Some(cursor_x) => cell_clusters
+                .into_iter()
+                .flat_map(|cluster| cluster.isolate_cell(cursor_x))
+                .collect(),
+            None => cell_clusters,
+        };
+
         let gl_state = self.render_state.as_ref().unwrap();
         let mut shaped = vec![];
         let mut last_style = None;
         let mut x_pos = 0.;
         let mut expires = None;
         let mut invalidate_on_hover_change = false;
+        let mut shaping_pending = false;
 
         for cluster in &cell_clusters {
             if !matches!(last_style.as_ref(), Some(ClusterStyleCache{attrs,..}) if *attrs == &cluster.attrs)
@@ -786,7 +809,8 @@ impl crate::TermWindow {
 
                     // Check the line reverse_video flag and flip.
                     if attrs.reverse() == !params.reverse_video {
-                        std::mem::swap(&mut fg, &mut bg);
+                        (fg, bg) =
+                            reverse_colors(&attrs, fg, bg, &params.palette, &params.config, style);
                         bg_default = false;
                     }
 
@@ -805,20 +829,37 @@ impl crate::TermWindow {
                         )),
                     };
                     if let Some((blink_rate, mut colorease)) = blink_rate {
-                        if blink_rate != 0 {
-                            let (intensity, next) = colorease.intensity_continuous();
-
-                            let (r1, g1, b1, a) = bg.tuple();
-                            let (r, g, b, _a) = fg.tuple();
-                            fg = LinearRgba::with_components(
-                                r1 + (r - r1) * intensity,
-                                g1 + (g - g1) * intensity,
-                                b1 + (b - b1) * intensity,
-                                a,
-                            );
-
-                            update_next_frame_time(&mut expires, Some(next));
-                            self.update_next_frame_time(Some(next));
+                        match params.config.text_blink_style {
+                            TextBlinkStyle::Blink if blink_rate != 0 => {
+                                let (intensity, next) = colorease.intensity_continuous();
+
+                                let (r1, g1, b1, a) = bg.tuple();
+                                let (r, g, b, _a) = fg.tuple();
+                                fg = LinearRgba::with_components(
+                                    r1 + (r - r1) * intensity,
+                                    g1 + (g - g1) * intensity,
+                                    b1 + (b - b1) * intensity,
+                                    a,
+                                );
+
+                                update_next_frame_time(&mut expires, Some(next));
+                                self.update_next_frame_time(Some(next));
+                            }
+                            TextBlinkStyle::BackgroundTint => {
+                                // Rather than animating, statically tint the
+                                // background towards the text color
+                                let (r1, g1, b1, a) = bg.tuple();
+                                let (r, g, b, _a) = fg.tuple();
+                                let amount = params.config.text_blink_tint_amount;
+                                bg = LinearRgba::with_components(
+                                    r1 + (r - r1) * amount,
+                                    g1 + (g - g1) * amount,
+                                    b1 + (b - b1) * amount,
+                                    a,
+                                );
+                                bg_default = false;
+                            }
+                            TextBlinkStyle::Blink | TextBlinkStyle::Steady => {}
                         }
                     }
 
@@ -855,17 +896,43 @@ impl crate::TermWindow {
 
             let style_params = last_style.as_ref().expect("we just set it up").clone();
 
-            let glyph_info = self.cached_cluster_shape(
-                style_params.style,
-                &cluster,
-                &gl_state,
-                None,
-                &self.render_metrics,
-            )?;
-            let pixel_width = glyph_info
-                .iter()
-                .map(|info| info.glyph.x_advance.get() as f32)
-                .sum();
+            // Only pane content can be shaped in the background,
+            // as that is the only place where we cache the results
+            // per line and can tell that they are incomplete
+            let glyph_info = if params.shape_key.is_some() {
+                self.cached_cluster_shape_or_defer(
+                    style_params.style,
+                    &cluster,
+                    &gl_state,
+                    &self.render_metrics,
+                )?
+            } else {
+                Some(self.cached_cluster_shape(
+                    style_params.style,
+                    &cluster,
+                    &gl_state,
+                    None,
+                    &self.render_metrics,
+                )?)
+            };
+            let (glyph_info, pixel_width) = match glyph_info {
+                Some(glyph_info) => {
+                    let pixel_width = glyph_info
+                        .iter()
+                        .map(|info| info.glyph.x_advance.get() as f32)
+                        .sum();
+                    (glyph_info, pixel_width)
+                }
+                None => {
+                    // Leave a gap where the text will be drawn
+                    // once it has been shaped
+                    shaping_pending = true;
+                    (
+                        Rc::new(vec![]),
+                        cluster.width as f32 * self.render_metrics.cell_size.width as f32,
+                    )
+                }
+            };
 
             shaped.push(LineToElementShape {
                 underline_tex_rect: style_params.underline_tex_rect,
@@ -883,22 +950,26 @@ impl crate::TermWindow {
 
         let shaped = Rc::new(shaped);
 
-        if let Some(shape_key) = params.shape_key {
-            self.line_to_ele_shape_cache.borrow_mut().put(
-                shape_key.clone(),
-                LineToElementShapeItem {
-                    expires,
-                    shaped: Rc::clone(&shaped),
-                    invalidate_on_hover_change,
-                    current_highlight: if invalidate_on_hover_change {
-                        self.current_highlight.clone()
-                    } else {
-                        None
+        // Don't cache incomplete lines; they will be revised once
+        // the background shaping is complete
+        if !shaping_pending {
+            if let Some(shape_key) = params.shape_key {
+                self.line_to_ele_shape_cache.borrow_mut().put(
+                    shape_key.clone(),
+                    LineToElementShapeItem {
+                        expires,
+                        shaped: Rc::clone(&shaped),
+                        invalidate_on_hover_change,
+                        current_highlight: if invalidate_on_hover_change {
+                            self.current_highlight.clone()
+                        } else {
+                            None
+                        },
                     },
-                },
-            );
+                );
+            }
         }
 
-        Ok((shaped, invalidate_on_hover_change))
+        Ok((shaped, invalidate_on_hover_change, shaping_pending))
     }
 }
@@ -0,0 +1,110 @@
+use crate::termwindow::render::chip::chip_element;
+use crate::termwindow::UIItemType;
+use crate::utilsprites::RenderMetrics;
+use config::ScrollPositionIndicator;
+
+/// Formats n with commas separating the groups of thousands
+fn group_thousands(n: isize) -> String {
+    let digits = n.to_string();
+    let mut result = String::new();
+    for (idx, c) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx) % 3 == 0 {
+            result.push(',');
+        }
+        result.push(c);
+    }
+    result
+}
+
+impl crate::TermWindow {
+    /// Shows a small chip in the bottom right corner of the active pane
+    /// while its viewport is scrolled away from the bottom, describing
+    /// how far back it is.  Clicking it scrolls back to the bottom.
+    pub fn paint_scroll_indicator(&mut self) -> anyhow::Result<()> {
+        let indicator = self.config.scroll_position_indicator;
+        if indicator == ScrollPositionIndicator::None {
+            return Ok(());
+        }
+        let pos = match self
+            .get_panes_to_render()
+            .into_iter()
+            .find(|pos| pos.is_active)
+        {
+            Some(pos) => pos,
+            None => return Ok(()),
+        };
+        let top = match self.get_viewport(pos.pane.pane_id()) {
+            Some(top) => top,
+            None => return Ok(()),
+        };
+        let dims = pos.pane.get_dimensions();
+        let lines_below = dims.physical_top - top;
+        if lines_below <= 0 {
+            return Ok(());
+        }
+
+        let text = match indicator {
+            ScrollPositionIndicator::LinesBelow if lines_below == 1 => "1 line below".to_string(),
+            ScrollPositionIndicator::LinesBelow => {
+                format!("{} lines below", group_thousands(lines_below))
+            }
+            ScrollPositionIndicator::Percentage => {
+                let scrollable = (dims.physical_top - dims.scrollback_top).max(1);
+                format!("{}%", 100 * (top - dims.scrollback_top) / scrollable)
+            }
+            ScrollPositionIndicator::None => return Ok(()),
+        };
+
+        let font = self.fonts.title_font()?;
+        let metrics = RenderMetrics::with_font_metrics(&font.metrics());
+        let palette = self.palette().clone();
+
+        let element = chip_element(&font, text, &palette, 0.5, 0.1, 1.0)
+            .item_type(UIItemType::ScrollPositionIndicator);
+
+        let mut computed = self.compute_chip(&element, &metrics)?;
+
+        // Place it just inside the bottom right corner of the pane,
+        // next to the scrollbar
+        let border = self.get_os_border();
+        let (padding_left, padding_top) = self.padding_left_top();
+        let top_bar_height = if self.show_tab_bar && !self.config.tab_bar_at_bottom {
+            self.tab_bar_pixel_height()?
+        } else {
+            0.
+        };
+        let cell_width = self.render_metrics.cell_size.width as f32;
+        let cell_height = self.render_metrics.cell_size.height as f32;
+        let pane_right =
+            padding_left + border.left.get() as f32 + (pos.left + pos.width) as f32 * cell_width;
+        let pane_bottom = top_bar_height
+            + padding_top
+            + border.top.get() as f32
+            + (pos.top + pos.height) as f32 * cell_height;
+
+        computed.translate(euclid::vec2(
+            pane_right - (computed.bounds.width() + metrics.cell_size.width as f32 / 2.),
+            pane_bottom - (computed.bounds.height() + metrics.cell_size.height as f32 / 4.),
+        ));
+
+        let mut ui_items = computed.ui_items();
+        let gl_state = self.render_state.as_ref().unwrap();
+        self.render_element(&computed, gl_state, None)?;
+        self.ui_items.append(&mut ui_items);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn thousands() {
+        assert_eq!(group_thousands(7), "7");
+        assert_eq!(group_thousands(999), "999");
+        assert_eq!(group_thousands(1234), "1,234");
+        assert_eq!(group_thousands(1234567), "1,234,567");
+    }
+}
@@ -1,18 +1,69 @@
 use crate::termwindow::render::TripleLayerQuadAllocator;
 use crate::termwindow::{UIItem, UIItemType};
 use mux::pane::Pane;
-use mux::tab::{PositionedSplit, SplitDirection};
+use mux::tab::{PositionedPane, PositionedSplit, SplitDirection};
+use std::ops::Range;
 use std::sync::Arc;
 
+/// If split runs along one of the edges of the pane that occupies
+/// the cells in cols and rows, returns the portion of the split,
+/// in cells, that is next to the pane
+fn split_segment_bordering_pane(
+    split: &PositionedSplit,
+    cols: Range<usize>,
+    rows: Range<usize>,
+) -> Option<Range<usize>> {
+    // The position of the split, the start of the split along its
+    // length, and the extent of the pane across and along the split
+    let (split_pos, split_start, across, along) = match split.direction {
+        SplitDirection::Horizontal => (split.left, split.top, cols, rows),
+        SplitDirection::Vertical => (split.top, split.left, rows, cols),
+    };
+    let adjacent = across.end == split_pos || across.start == split_pos + 1;
+    let start = along.start.max(split_start);
+    let end = along.end.min(split_start + split.size);
+    if adjacent && start < end {
+        Some(start..end)
+    } else {
+        None
+    }
+}
+
 impl crate::TermWindow {
     pub fn paint_split(
         &mut self,
         layers: &mut TripleLayerQuadAllocator,
         split: &PositionedSplit,
         pane: &Arc<dyn Pane>,
+        active_pane: Option<&PositionedPane>,
     ) -> anyhow::Result<()> {
-        let palette = pane.palette();
-        let foreground = palette.split.to_linear();
+        let palette = self.pane_palette(pane);
+        let colors = &self.config.resolved_palette;
+        let is_split = |item: &UIItem| match &item.item_type {
+            UIItemType::Split(s) => s.index == split.index,
+            _ => false,
+        };
+        let hovered = self.last_ui_item.as_ref().map(is_split).unwrap_or(false)
+            || self
+                .dragging
+                .as_ref()
+                .map(|(item, _)| is_split(item))
+                .unwrap_or(false);
+        // The part of the split next to the active pane is drawn
+        // over the rest of it using the split_active color
+        let active_segment = match (&colors.split_active, active_pane) {
+            (Some(active), Some(pos)) if !hovered => split_segment_bordering_pane(
+                split,
+                pos.left..pos.left + pos.width,
+                pos.top..pos.top + pos.height,
+            )
+            .map(|range| (range, active.to_linear())),
+            _ => None,
+        };
+        let foreground = match &colors.split_hover {
+            Some(hover) if hovered => hover.to_linear(),
+            _ => palette.split.to_linear(),
+        };
         let cell_width = self.render_metrics.cell_size.width as f32;
         let cell_height = self.render_metrics.cell_size.height as f32;
 
@@ -76,6 +127,86 @@ impl crate::TermWindow {
             });
         }
 
+        if let Some((range, color)) = active_segment {
+            let start = range.start as f32 - 0.5;
+            let len = (range.end - range.start) as f32 + 1.;
+            let rect = if split.direction == SplitDirection::Horizontal {
+                euclid::rect(
+                    pos_x + (cell_width / 2.0),
+                    first_row_offset + padding_top + start * cell_height,
+                    self.render_metrics.underline_height as f32,
+                    len * cell_height,
+                )
+            } else {
+                euclid::rect(
+                    padding_left + border.left.get() as f32 + start * cell_width,
+                    pos_y + (cell_height / 2.0),
+                    len * cell_width,
+                    self.render_metrics.underline_height as f32,
+                )
+            };
+            self.filled_rectangle(layers, 2, rect, color)?;
+        }
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn split(direction: SplitDirection, left: usize, top: usize, size: usize) -> PositionedSplit {
+        PositionedSplit {
+            index: 0,
+            direction,
+            left,
+            top,
+            size,
+        }
+    }
+
+    #[test]
+    fn side_by_side() {
+        // Two 40x24 panes either side of a split in column 40
+        let split = split(SplitDirection::Horizontal, 40, 0, 24);
+        assert_eq!(
+            split_segment_bordering_pane(&split, 0..40, 0..24),
+            Some(0..24)
+        );
+        assert_eq!(
+            split_segment_bordering_pane(&split, 41..81, 0..24),
+            Some(0..24)
+        );
+        // Not next to the split
+        assert_eq!(split_segment_bordering_pane(&split, 50..81, 0..24), None);
+        // The right hand side is split into two
+        assert_eq!(
+            split_segment_bordering_pane(&split, 41..81, 0..12),
+            Some(0..12)
+        );
+        assert_eq!(
+            split_segment_bordering_pane(&split, 41..81, 13..24),
+            Some(13..24)
+        );
+    }
+
+    #[test]
+    fn above_and_below() {
+        // The right hand side of the window is split in row 12
+        let split = split(SplitDirection::Vertical, 41, 12, 40);
+        assert_eq!(
+            split_segment_bordering_pane(&split, 41..81, 0..12),
+            Some(41..81)
+        );
+        assert_eq!(
+            split_segment_bordering_pane(&split, 41..81, 13..24),
+            Some(41..81)
+        );
+        // The pane on the left hand side spans the split
+        assert_eq!(split_segment_bordering_pane(&split, 0..40, 0..24), None);
+        // A pane below and to the left of the split is next to its row,
+        // but doesn't share any columns with it
+        assert_eq!(split_segment_bordering_pane(&split, 0..40, 13..24), None);
+    }
+}
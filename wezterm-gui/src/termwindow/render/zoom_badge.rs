@@ -0,0 +1,47 @@
+use crate::termwindow::render::chip::chip_element;
+use crate::utilsprites::RenderMetrics;
+use mux::Mux;
+
+impl crate::TermWindow {
+    /// Shows a small `[Z]` chip in the top right corner of the pane
+    /// area while the active tab has a zoomed pane, so that it is
+    /// clear that other panes are hidden.
+    pub fn paint_zoom_badge(&mut self) -> anyhow::Result<()> {
+        if !self.config.show_zoom_indicator {
+            return Ok(());
+        }
+        let is_zoomed = match Mux::get().get_active_tab_for_window(self.mux_window_id) {
+            Some(tab) => tab.get_zoomed_pane().is_some(),
+            None => false,
+        };
+        if !is_zoomed {
+            return Ok(());
+        }
+
+        let font = self.fonts.title_font()?;
+        let metrics = RenderMetrics::with_font_metrics(&font.metrics());
+        let palette = self.palette().clone();
+
+        let element = chip_element(&font, "[Z]".to_string(), &palette, 0.25, 0.1, 1.0);
+
+        let border = self.get_os_border();
+        let top_bar_height = if self.show_tab_bar && !self.config.tab_bar_at_bottom {
+            self.tab_bar_pixel_height()?
+        } else {
+            0.
+        };
+
+        let mut computed = self.compute_chip(&element, &metrics)?;
+
+        computed.translate(euclid::vec2(
+            self.dimensions.pixel_width as f32
+                - (computed.bounds.width()
+                    + border.right.get() as f32
+                    + metrics.cell_size.width as f32 / 2.),
+            top_bar_height + border.top.get() as f32 + metrics.cell_size.height as f32 / 4.,
+        ));
+
+        let gl_state = self.render_state.as_ref().unwrap();
+        self.render_element(&computed, gl_state, None)
+    }
+}
@@ -1,11 +1,20 @@
+use crate::overlay::{confirm_window_resize, start_overlay_pane};
 use crate::resize_increment_calculator::ResizeIncrementCalculator;
 use crate::utilsprites::RenderMetrics;
-use ::window::{Dimensions, ResizeIncrement, Window, WindowOps, WindowState};
-use config::{ConfigHandle, DimensionContext};
+use ::window::{
+    Connection, ConnectionOps, Dimensions, ResizeIncrement, Window, WindowOps, WindowState,
+};
+use config::{ConfigHandle, DimensionContext, WindowResizeRequests};
+use mux::pane::PaneId;
 use mux::Mux;
 use std::rc::Rc;
+use std::time::Instant;
 use wezterm_font::FontConfiguration;
-use wezterm_term::TerminalSize;
+use wezterm_term::{TerminalSize, WindowResizeRequest};
+
+/// The largest number of rows or columns that a program can ask for
+/// when we can't find out how big the screen is
+const MAX_RESIZE_REQUEST_CELLS: usize = 1000;
 
 #[derive(Debug, Clone, Copy)]
 pub struct RowsAndCols {
@@ -37,8 +46,12 @@ impl super::TermWindow {
         );
         if dimensions.pixel_width == 0 || dimensions.pixel_height == 0 {
             // on windows, this can happen when minimizing the window.
-            // NOP!
+            // NOP, other than noting that we are hidden so that we
+            // can paint less frequently
             log::trace!("new dimensions are zero: NOP!");
+            if !window_state.can_paint() {
+                self.window_state |= WindowState::HIDDEN;
+            }
             return;
         }
         if self.dimensions == dimensions && self.window_state == window_state {
@@ -46,6 +59,17 @@ impl super::TermWindow {
             log::trace!("dimensions didn't change NOP!");
             return;
         }
+        if self.dimensions == dimensions && self.window_state ^ window_state == WindowState::HIDDEN
+        {
+            // Only the visibility changed, so there is nothing to lay out.
+            // We paint less often while hidden, so catch up now if
+            // we just became visible again
+            self.window_state = window_state;
+            if window_state.can_paint() {
+                window.invalidate();
+            }
+            return;
+        }
         let last_state = self.window_state;
         self.window_state = window_state;
         self.quad_generation += 1;
@@ -62,12 +86,29 @@ impl super::TermWindow {
         if live_resizing && self.dimensions.dpi == dimensions.dpi {
             self.apply_dimensions(&dimensions, None, window);
         } else {
-            self.scaling_changed(dimensions, self.fonts.get_font_scale(), window);
+            self.scaling_changed(dimensions, self.user_font_scale, window);
         }
         if let Some(modal) = self.get_modal() {
             modal.reconfigure(self);
         }
-        self.emit_window_event("window-resized", None);
+        self.emit_resized_event_if_changed();
+    }
+
+    /// Emits the `window-resized` event if the dimensions, dpi,
+    /// window state or terminal size have changed since the last
+    /// time that we emitted it.  This covers changes that don't
+    /// originate from the window being resized, such as changing
+    /// the font size or moving to a monitor with a different dpi.
+    fn emit_resized_event_if_changed(&mut self) {
+        let current = (
+            self.dimensions,
+            self.window_state,
+            self.terminal_size.rows,
+            self.terminal_size.cols,
+        );
+        if self.last_resized_event.replace(current) != Some(current) {
+            self.emit_window_event("window-resized", None);
+        }
     }
 
     pub fn apply_pending_scale_changes(&mut self) {
@@ -75,7 +116,7 @@ impl super::TermWindow {
             match self.pending_scale_changes.pop_front() {
                 Some(ScaleChange::Relative(change)) => {
                     if let Some(window) = self.window.as_ref().map(|w| w.clone()) {
-                        self.adjust_font_scale(self.fonts.get_font_scale() * change, &window);
+                        self.adjust_font_scale(self.user_font_scale * change, &window);
                     }
                 }
                 Some(ScaleChange::Absolute(change)) => {
@@ -290,6 +331,11 @@ impl super::TermWindow {
 
         log::trace!("apply_dimensions computed size {:?}, dims {:?}", size, dims);
 
+        if self.config.show_resize_overlay
+            && (size.rows, size.cols) != (self.terminal_size.rows, self.terminal_size.cols)
+        {
+            self.resize_overlay_shown.replace(Instant::now());
+        }
         self.terminal_size = size;
 
         let mux = Mux::get();
@@ -340,6 +386,118 @@ impl super::TermWindow {
                 self.set_inner_size(window, dims.pixel_width, dims.pixel_height);
             }
         }
+
+        self.emit_resized_event_if_changed();
+    }
+
+    /// Called when the program running in pane_id has asked
+    /// for the window to be resized
+    pub fn window_resize_requested(&mut self, pane_id: PaneId, request: WindowResizeRequest) {
+        let mux = Mux::get();
+        match mux.resolve_pane_id(pane_id) {
+            Some((_domain, window_id, _tab_id)) if window_id == self.mux_window_id => {}
+            _ => return,
+        }
+
+        match self.config.window_resize_requests {
+            WindowResizeRequests::Deny => {
+                log::debug!(
+                    "ignoring {:?} from pane {} because window_resize_requests = Deny",
+                    request,
+                    pane_id
+                );
+            }
+            WindowResizeRequests::Allow => self.apply_window_resize_request(pane_id, request),
+            WindowResizeRequests::Ask => {
+                let pane = match mux.get_pane(pane_id) {
+                    Some(pane) => pane,
+                    None => return,
+                };
+                if self.pane_state(pane_id).overlay.is_some() {
+                    // Don't stack up prompts for a program that
+                    // keeps asking
+                    return;
+                }
+                let window = self.window.clone().unwrap();
+                let (overlay, future) = start_overlay_pane(self, &pane, move |pane_id, term| {
+                    confirm_window_resize(pane_id, term, window, request)
+                });
+                self.assign_overlay_for_pane(pane_id, overlay);
+                promise::spawn::spawn(future).detach();
+            }
+        }
+    }
+
+    /// Resizes the window so that pane_id has the size in request.
+    /// The window grows or shrinks by the difference between the
+    /// current and requested sizes of the pane, so that this also
+    /// does the right thing when the tab is split.
+    pub fn apply_window_resize_request(&mut self, pane_id: PaneId, request: WindowResizeRequest) {
+        let window = match self.window.clone() {
+            Some(window) => window,
+            None => return,
+        };
+        let pane = match Mux::get().get_pane(pane_id) {
+            Some(pane) => pane,
+            None => return,
+        };
+        let dims = pane.get_dimensions();
+        let cell_width = self.render_metrics.cell_size.width.max(1) as usize;
+        let cell_height = self.render_metrics.cell_size.height.max(1) as usize;
+
+        let (cols, rows) = match request {
+            WindowResizeRequest::Maximize => {
+                window.maximize();
+                return;
+            }
+            WindowResizeRequest::RestoreMaximized => {
+                window.restore();
+                return;
+            }
+            WindowResizeRequest::Cells { width, height } => (
+                width.unwrap_or(dims.cols),
+                height.unwrap_or(dims.viewport_rows),
+            ),
+            WindowResizeRequest::Pixels { width, height } => (
+                width.map(|w| w / cell_width).unwrap_or(dims.cols),
+                height
+                    .map(|h| h / cell_height)
+                    .unwrap_or(dims.viewport_rows),
+            ),
+        };
+
+        let current = self.current_cell_dimensions();
+        let rows = current
+            .rows
+            .saturating_add(rows)
+            .saturating_sub(dims.viewport_rows);
+        let cols = current.cols.saturating_add(cols).saturating_sub(dims.cols);
+
+        // Don't let the program grow the window beyond the screen
+        let (max_rows, max_cols) = match Connection::get().and_then(|conn| conn.screens().ok()) {
+            Some(screens) => {
+                let screen = screens.active.rect;
+                // The space taken up by the padding, tab bar and so on
+                let chrome_width = (self.dimensions.pixel_width as usize)
+                    .saturating_sub(current.cols * cell_width);
+                let chrome_height = (self.dimensions.pixel_height as usize)
+                    .saturating_sub(current.rows * cell_height);
+                (
+                    (screen.height().max(0) as usize).saturating_sub(chrome_height) / cell_height,
+                    (screen.width().max(0) as usize).saturating_sub(chrome_width) / cell_width,
+                )
+            }
+            None => (MAX_RESIZE_REQUEST_CELLS, MAX_RESIZE_REQUEST_CELLS),
+        };
+        let rows = rows.min(max_rows).max(1);
+        let cols = cols.min(max_cols).max(1);
+
+        if (rows, cols) == (current.rows, current.cols) {
+            return;
+        }
+
+        let dimensions = self.dimensions;
+        self.apply_dimensions(&dimensions, Some(RowsAndCols { rows, cols }), &window);
     }
 
     pub fn current_cell_dimensions(&self) -> RowsAndCols {
@@ -351,8 +509,11 @@ impl super::TermWindow {
 
     #[allow(clippy::float_cmp)]
     pub fn scaling_changed(&mut self, dimensions: Dimensions, font_scale: f64, window: &Window) {
+        /// Converts n pixels at dpi into the equivalent number of pixels
+        /// at 96 dpi, so that sizes at different dpis can be compared
+        /// using the pixel tolerance of close_enough below
         fn dpi_adjusted(n: usize, dpi: usize) -> f32 {
-            n as f32 / dpi as f32
+            n as f32 * 96. / dpi as f32
         }
 
         /// On Windows, scaling changes may adjust the pixel geometry by a few pixels,
@@ -452,6 +613,10 @@ impl super::TermWindow {
             // Now revise the pty size to fit the window
             self.apply_dimensions(&dimensions, None, window);
         }
+
+        // Remember the scale that was actually applied, which may differ
+        // from the requested scale if that would make the font too small
+        self.user_font_scale = self.fonts.get_font_scale();
     }
 
     pub fn decrease_font_size(&mut self) {
@@ -472,12 +637,20 @@ impl super::TermWindow {
         self.apply_pending_scale_changes();
     }
 
-    pub fn set_window_size(&mut self, size: TerminalSize, window: &Window) -> anyhow::Result<()> {
+    /// Resizes the window to hold size cells when the font
+    /// is scaled by font_scale
+    pub fn set_window_size(
+        &mut self,
+        size: TerminalSize,
+        font_scale: f64,
+        window: &Window,
+    ) -> anyhow::Result<()> {
         let config = &self.config;
         let fontconfig = Rc::new(FontConfiguration::new(
             Some(config.clone()),
             self.dimensions.dpi,
         )?);
+        fontconfig.change_scaling(font_scale, self.dimensions.dpi);
         let render_metrics = RenderMetrics::new(&fontconfig)?;
 
         let terminal_size = TerminalSize {
@@ -520,7 +693,8 @@ impl super::TermWindow {
             dpi: self.dimensions.dpi,
         };
 
-        self.apply_scale_change(&dimensions, 1.0);
+        self.apply_scale_change(&dimensions, font_scale);
+        self.user_font_scale = self.fonts.get_font_scale();
         self.apply_dimensions(
             &dimensions,
             Some(RowsAndCols {
@@ -540,7 +714,7 @@ impl super::TermWindow {
                 self.dimensions.dpi as f64,
             )?),
         );
-        self.set_window_size(size, window)
+        self.set_window_size(size, 1.0, window)
     }
 
     pub fn effective_right_padding(&self, config: &ConfigHandle) -> usize {
@@ -0,0 +1,187 @@
+use crate::screenshot::{encode_png, screenshot_path};
+use crate::termwindow::TermWindowNotif;
+use ::window::glium;
+use ::window::glium::texture::{RawImage2d, SrgbTexture2d};
+use ::window::glium::uniforms::MagnifySamplerFilter;
+use ::window::glium::{BlitTarget, Surface};
+use ::window::{Clipboard, Window, WindowOps};
+use anyhow::{anyhow, Context};
+use image::RgbaImage;
+use std::path::PathBuf;
+use wezterm_toast_notification::ToastNotification;
+
+impl super::TermWindow {
+    /// Arranges for the next frame that is painted to be saved
+    /// to a PNG image and copied to the clipboard
+    pub fn capture_screenshot(&mut self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.gl.is_some() || self.webgpu.is_some(),
+            "the window has no renderer to capture a frame from"
+        );
+        self.screenshot_path.replace(screenshot_path(&self.config));
+        self.invalidate_presented_frame();
+        if let Some(window) = self.window.as_ref() {
+            window.invalidate();
+        }
+        Ok(())
+    }
+
+    /// Called with the frame that is about to be presented,
+    /// to save it if a screenshot was requested
+    pub fn save_screenshot_glium(&mut self, frame: &glium::Frame) {
+        if let Some(path) = self.screenshot_path.take() {
+            let image = self.read_frame_glium(frame);
+            self.save_screenshot(image, path);
+        }
+    }
+
+    /// Called with the surface texture that is about to be
+    /// presented, to save it if a screenshot was requested
+    pub fn save_screenshot_webgpu(&mut self, texture: &wgpu::Texture) {
+        if let Some(path) = self.screenshot_path.take() {
+            let image = self.read_frame_webgpu(texture);
+            self.save_screenshot(image, path);
+        }
+    }
+
+    fn save_screenshot(&self, image: anyhow::Result<RgbaImage>, path: PathBuf) {
+        match (image, self.window.clone()) {
+            (Ok(image), Some(window)) => save_in_background(image, path, window),
+            (Ok(_), None) => {}
+            (Err(err), _) => log::error!("Failed to capture screenshot: {:#}", err),
+        }
+    }
+
+    fn read_frame_glium(&self, frame: &glium::Frame) -> anyhow::Result<RgbaImage> {
+        let gl = self
+            .gl
+            .as_ref()
+            .ok_or_else(|| anyhow!("no OpenGL context"))?;
+        let (width, height) = frame.get_dimensions();
+
+        // Copy the back buffer to an offscreen texture that we can read
+        let texture = SrgbTexture2d::empty(gl, width, height)?;
+        let target = glium::framebuffer::SimpleFrameBuffer::new(gl, &texture)?;
+        frame.blit_whole_color_to(
+            &target,
+            &BlitTarget {
+                left: 0,
+                bottom: 0,
+                width: width as i32,
+                height: height as i32,
+            },
+            MagnifySamplerFilter::Nearest,
+        );
+        let raw: RawImage2d<u8> = texture.read();
+
+        let image = RgbaImage::from_raw(raw.width, raw.height, raw.data.into_owned())
+            .context("frame data doesn't match its dimensions")?;
+        // OpenGL rows run from the bottom to the top
+        Ok(image::imageops::flip_vertical(&image))
+    }
+
+    fn read_frame_webgpu(&self, texture: &wgpu::Texture) -> anyhow::Result<RgbaImage> {
+        let webgpu = self
+            .webgpu
+            .as_ref()
+            .ok_or_else(|| anyhow!("no WebGpu state"))?;
+        anyhow::ensure!(
+            texture.usage().contains(wgpu::TextureUsages::COPY_SRC),
+            "the surface doesn't allow reading back frames"
+        );
+        let bgra = match texture.format() {
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            format => anyhow::bail!("cannot read back frames in {:?} format", format),
+        };
+
+        // Rows in the buffer must be padded to a multiple
+        // of COPY_BYTES_PER_ROW_ALIGNMENT
+        let width = texture.width();
+        let height = texture.height();
+        let row_bytes = width * 4;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row_bytes = (row_bytes + align - 1) / align * align;
+
+        let buffer = webgpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Screenshot Buffer"),
+            size: (padded_row_bytes * height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = webgpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Screenshot Encoder"),
+            });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+        webgpu.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            tx.send(result).ok();
+        });
+        webgpu.device.poll(wgpu::Maintain::Wait);
+        rx.recv()?.context("mapping screenshot buffer")?;
+
+        let mut pixels = Vec::with_capacity((row_bytes * height) as usize);
+        for row in slice.get_mapped_range().chunks(padded_row_bytes as usize) {
+            pixels.extend_from_slice(&row[..row_bytes as usize]);
+        }
+        buffer.unmap();
+
+        if bgra {
+            for px in pixels.chunks_exact_mut(4) {
+                px.swap(0, 2);
+            }
+        }
+
+        RgbaImage::from_raw(width, height, pixels)
+            .context("frame data doesn't match its dimensions")
+    }
+}
+
+/// Encodes and saves image on a background thread, so that large
+/// windows don't stall the GUI, then copies it to the clipboard
+/// and lets the user know where it was saved
+fn save_in_background(image: RgbaImage, path: PathBuf, window: Window) {
+    std::thread::spawn(move || {
+        let png = match encode_png(&image) {
+            Ok(png) => png,
+            Err(err) => {
+                log::error!("Failed to encode screenshot: {:#}", err);
+                return;
+            }
+        };
+        if let Err(err) = std::fs::write(&path, &png) {
+            log::error!("Failed to save screenshot to {}: {:#}", path.display(), err);
+        } else {
+            log::info!("Saved screenshot to {}", path.display());
+            ToastNotification {
+                title: "Screenshot saved".to_string(),
+                message: path.display().to_string(),
+                url: None,
+                timeout: Some(std::time::Duration::from_secs(5)),
+            }
+            .show();
+        }
+
+        window.notify(TermWindowNotif::Apply(Box::new(move |tw| {
+            if let Some(window) = tw.window.as_ref() {
+                window.set_clipboard_image(Clipboard::Clipboard, png);
+            }
+        })));
+    });
+}
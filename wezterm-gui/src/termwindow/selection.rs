@@ -2,7 +2,9 @@ use crate::selection::{Selection, SelectionCoordinate, SelectionMode, SelectionR
 use ::window::WindowOps;
 use mux::pane::{Pane, PaneId};
 use std::cell::RefMut;
+use std::ops::Range;
 use std::sync::Arc;
+use termwiz::cell::SemanticType;
 use termwiz::surface::Line;
 use wezterm_term::StableRowIndex;
 
@@ -64,6 +66,9 @@ impl super::TermWindow {
 
     /// Returns the selection text only
     pub fn selection_text(&self, pane: &Arc<dyn Pane>) -> String {
+        let strip_prompts = self.config.selection_strip_prompts;
+        let join_wrapped = self.config.selection_join_wrapped_lines;
+        let trim_trailing = self.config.selection_trim_trailing_whitespace;
         let mut s = String::new();
         let rectangular = self.selection(pane.pane_id()).rectangular;
         if let Some(sel) = self
@@ -73,12 +78,15 @@ impl super::TermWindow {
             .map(|r| r.normalize())
         {
             let mut last_was_wrapped = false;
+            // Newlines are only emitted ahead of the text that follows
+            // them, so that rows that are skipped don't leave blank lines
+            let mut newline_pending = false;
             let first_row = sel.rows().start;
             let last_row = sel.rows().end;
 
             for line in pane.get_logical_lines(sel.rows()) {
-                if !s.is_empty() && !last_was_wrapped {
-                    s.push('\n');
+                if !last_was_wrapped {
+                    newline_pending = true;
                 }
                 let last_idx = line.physical_lines.len().saturating_sub(1);
                 for (idx, phys) in line.physical_lines.iter().enumerate() {
@@ -87,16 +95,25 @@ impl super::TermWindow {
                         let last_phys_idx = phys.len().saturating_sub(1);
                         let cols = sel.cols_for_row(this_row, rectangular);
                         let last_col_idx = cols.end.saturating_sub(1).min(last_phys_idx);
-                        let col_span = phys.columns_as_str(cols);
-                        // Only trim trailing whitespace if we are the last line
-                        // in a wrapped sequence
-                        if idx == last_idx {
-                            s.push_str(col_span.trim_end());
-                        } else {
-                            s.push_str(&col_span);
+                        if !join_wrapped && idx > 0 {
+                            newline_pending = true;
+                        }
+                        if let Some(col_span) = columns_as_str(phys, cols, strip_prompts) {
+                            if newline_pending && !s.is_empty() {
+                                s.push('\n');
+                            }
+                            newline_pending = false;
+                            // Only trim trailing whitespace if we are the last line
+                            // in a wrapped sequence
+                            if trim_trailing && (idx == last_idx || !join_wrapped) {
+                                s.push_str(col_span.trim_end());
+                            } else {
+                                s.push_str(&col_span);
+                            }
                         }
 
-                        last_was_wrapped = last_col_idx == last_phys_idx
+                        last_was_wrapped = join_wrapped
+                            && last_col_idx == last_phys_idx
                             && phys
                                 .get_cell(last_col_idx)
                                 .map(|c| c.attrs().wrapped())
@@ -281,3 +298,99 @@ impl super::TermWindow {
         self.window.as_ref().unwrap().invalidate();
     }
 }
+
+/// Returns the text of the cells in range.
+/// If strip_prompts is true, the run of cells at the start of the
+/// range that shell integration marked as being part of a prompt is
+/// skipped, and None is returned if only whitespace follows it, so
+/// that the lines of a multi-line prompt are omitted entirely.
+fn columns_as_str(line: &Line, range: Range<usize>, strip_prompts: bool) -> Option<String> {
+    if !strip_prompts {
+        return Some(line.columns_as_str(range));
+    }
+    let mut s = String::new();
+    let mut in_prompt = true;
+    let mut stripped = false;
+    for c in line.visible_cells() {
+        if c.cell_index() < range.start {
+            continue;
+        }
+        if c.cell_index() >= range.end {
+            break;
+        }
+        if in_prompt && c.attrs().semantic_type() == SemanticType::Prompt {
+            stripped = true;
+            continue;
+        }
+        in_prompt = false;
+        s.push_str(c.str());
+    }
+    if stripped && s.trim().is_empty() {
+        None
+    } else {
+        Some(s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use termwiz::cell::CellAttributes;
+    use termwiz::surface::SEQ_ZERO;
+
+    /// Builds a line from (text, semantic type) pairs
+    fn line(parts: &[(&str, SemanticType)]) -> Line {
+        let mut line = Line::with_width(0, SEQ_ZERO);
+        for (text, semantic_type) in parts {
+            let mut attrs = CellAttributes::default();
+            attrs.set_semantic_type(*semantic_type);
+            line.append_line(Line::from_text(text, &attrs, SEQ_ZERO, None), SEQ_ZERO);
+        }
+        line
+    }
+
+    #[test]
+    fn strip_leading_prompt() {
+        let line = line(&[
+            ("$ ", SemanticType::Prompt),
+            ("ls -l", SemanticType::Input),
+            (" [main]", SemanticType::Prompt),
+        ]);
+        let all = 0..line.len();
+        assert_eq!(
+            columns_as_str(&line, all.clone(), false).as_deref(),
+            Some("$ ls -l [main]")
+        );
+        // Only the prompt at the start of the line is removed
+        assert_eq!(
+            columns_as_str(&line, all, true).as_deref(),
+            Some("ls -l [main]")
+        );
+        // Including when the range starts part way into it
+        assert_eq!(columns_as_str(&line, 1..5, true).as_deref(), Some("ls "));
+        assert_eq!(
+            columns_as_str(&line, 5..line.len(), true).as_deref(),
+            Some("-l [main]")
+        );
+    }
+
+    #[test]
+    fn prompt_only_line() {
+        let prompt = line(&[("~/src/wezterm", SemanticType::Prompt)]);
+        assert_eq!(columns_as_str(&prompt, 0..prompt.len(), true), None);
+        assert_eq!(
+            columns_as_str(&prompt, 0..prompt.len(), false).as_deref(),
+            Some("~/src/wezterm")
+        );
+
+        let prompt = line(&[("> ", SemanticType::Prompt), ("  ", SemanticType::Output)]);
+        assert_eq!(columns_as_str(&prompt, 0..prompt.len(), true), None);
+
+        // A blank line that isn't part of a prompt is preserved
+        let blank = line(&[("  ", SemanticType::Output)]);
+        assert_eq!(
+            columns_as_str(&blank, 0..blank.len(), true).as_deref(),
+            Some("  ")
+        );
+    }
+}
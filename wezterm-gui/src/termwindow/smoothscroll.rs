@@ -0,0 +1,193 @@
+//! Pixel precise scrolling of the scrollback in response to touchpad
+//! gestures, with momentum that continues after the fingers are lifted
+//! on systems that don't provide it themselves.
+use ::window::{Modifiers, WindowOps};
+use mux::pane::Pane;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The fraction of its velocity that a coasting scroll retains
+/// after one second
+const FRICTION: f32 = 0.03;
+
+/// Coasting stops once the velocity, in pixels per second,
+/// drops below this value
+const MIN_VELOCITY: f32 = 20.;
+
+/// If the fingers were resting for longer than this before they were
+/// lifted, the gesture ends without any momentum
+const MAX_LIFT_DELAY: Duration = Duration::from_millis(80);
+
+pub struct KineticScroll {
+    pane: Arc<dyn Pane>,
+    /// In pixels per second; positive values scroll up
+    velocity: f32,
+    last_update: Instant,
+    /// Whether the gesture has ended and we are scrolling
+    /// under momentum
+    coasting: bool,
+}
+
+impl super::TermWindow {
+    /// Handles a VertPixelScroll event.
+    /// Returns None if the viewport was scrolled smoothly, otherwise
+    /// the pixels are accumulated and the number of whole lines that
+    /// they amount to is returned, so that the event can be processed
+    /// as though it came from a mouse wheel.
+    pub fn pixel_scroll(
+        &mut self,
+        pane: &Arc<dyn Pane>,
+        pixels: i16,
+        modifiers: Modifiers,
+    ) -> Option<i16> {
+        // Mouse reporting, the alt screen and mouse bindings that use
+        // modifiers all operate on lines
+        let smooth = self.config.smooth_scrolling
+            && modifiers == Modifiers::NONE
+            && !pane.is_mouse_grabbed()
+            && !pane.is_alt_screen_active();
+
+        if smooth {
+            let now = Instant::now();
+            let velocity = match self.kinetic_scroll.take() {
+                Some(kinetic)
+                    if kinetic.pane.pane_id() == pane.pane_id()
+                        && !kinetic.coasting
+                        && kinetic.velocity * f32::from(pixels) >= 0. =>
+                {
+                    let elapsed = now
+                        .duration_since(kinetic.last_update)
+                        .max(Duration::from_millis(1))
+                        .as_secs_f32();
+                    (kinetic.velocity + f32::from(pixels) / elapsed) / 2.
+                }
+                _ => 0.,
+            };
+            self.scroll_by_pixels(pane, pixels.into());
+            self.kinetic_scroll.replace(KineticScroll {
+                pane: Arc::clone(pane),
+                velocity,
+                last_update: now,
+                coasting: false,
+            });
+            return None;
+        }
+
+        let cell_height = self.render_metrics.cell_size.height.max(1);
+        let pixels = pixels as isize;
+        if pixels.signum() != self.pixel_scroll_remainder.signum() {
+            self.pixel_scroll_remainder = 0;
+        }
+        self.pixel_scroll_remainder += pixels;
+        let lines = self.pixel_scroll_remainder / cell_height;
+        self.pixel_scroll_remainder -= lines * cell_height;
+        if lines == 0 {
+            None
+        } else {
+            Some(lines as i16)
+        }
+    }
+
+    /// Handles a PixelScrollEnd event by continuing to scroll
+    /// with momentum
+    pub fn end_pixel_scroll(&mut self) {
+        if !self.config.smooth_scrolling {
+            return;
+        }
+        let start = match self.kinetic_scroll.as_mut() {
+            Some(kinetic) if !kinetic.coasting => {
+                kinetic.last_update.elapsed() <= MAX_LIFT_DELAY
+                    && kinetic.velocity.abs() >= MIN_VELOCITY
+            }
+            _ => return,
+        };
+        if start {
+            if let Some(kinetic) = self.kinetic_scroll.as_mut() {
+                kinetic.coasting = true;
+                kinetic.last_update = Instant::now();
+            }
+            if let Some(window) = self.window.as_ref() {
+                window.invalidate();
+            }
+        } else {
+            self.kinetic_scroll.take();
+        }
+    }
+
+    /// Called at the start of each frame to move a coasting
+    /// scroll along
+    pub fn advance_kinetic_scroll(&mut self) {
+        let (pane, pixels) = match self.kinetic_scroll.as_mut() {
+            Some(kinetic) if kinetic.coasting => {
+                let now = Instant::now();
+                let elapsed = now.duration_since(kinetic.last_update).as_secs_f32();
+                kinetic.last_update = now;
+                let pixels = kinetic.velocity * elapsed;
+                kinetic.velocity *= FRICTION.powf(elapsed);
+                (Arc::clone(&kinetic.pane), pixels)
+            }
+            _ => return,
+        };
+
+        let moved = self.scroll_by_pixels(&pane, pixels);
+        let still_coasting = moved
+            && self
+                .kinetic_scroll
+                .as_ref()
+                .map(|kinetic| kinetic.velocity.abs() >= MIN_VELOCITY)
+                .unwrap_or(false);
+        if still_coasting {
+            let frame_interval = Duration::from_millis(1000 / self.config.max_fps.max(1) as u64);
+            self.update_next_frame_time(Some(Instant::now() + frame_interval));
+        } else {
+            self.kinetic_scroll.take();
+        }
+    }
+
+    /// Moves the viewport of pane by the specified number of pixels;
+    /// positive values scroll up.  The portion that doesn't amount
+    /// to a whole line is retained as the scroll_pixel_offset of the pane.
+    /// Returns false if the viewport was already at the top or bottom
+    /// of the scrollback.
+    fn scroll_by_pixels(&mut self, pane: &Arc<dyn Pane>, pixels: f32) -> bool {
+        let pane_id = pane.pane_id();
+        let dims = pane.get_dimensions();
+        let cell_height = self.render_metrics.cell_size.height.max(1) as f32;
+
+        let top = self.get_viewport(pane_id).unwrap_or(dims.physical_top);
+        let offset = self.pane_state(pane_id).scroll_pixel_offset;
+
+        // The position of the top of the viewport, in pixels,
+        // relative to the top of stable row 0
+        let current = (top as f32 * cell_height) - offset;
+        let position = (current - pixels)
+            .max(dims.scrollback_top as f32 * cell_height)
+            .min(dims.physical_top as f32 * cell_height);
+
+        // The content is drawn shifted down by the offset, so the top
+        // row is the first one that is entirely visible
+        let row = (position / cell_height).ceil();
+        let offset = (row * cell_height) - position;
+
+        // Preserve the momentum that set_viewport would otherwise cancel
+        let kinetic = self.kinetic_scroll.take();
+        self.set_viewport(pane_id, Some(row as isize), dims);
+        self.kinetic_scroll = kinetic;
+        self.pane_state(pane_id).scroll_pixel_offset = offset;
+
+        position != current
+    }
+
+    /// Removes any partial line offset, so that the display
+    /// lines up with the cell grid again
+    pub fn snap_scroll_pixel_offset(&mut self, pane: &Arc<dyn Pane>) {
+        self.kinetic_scroll.take();
+        let pane_id = pane.pane_id();
+        let snapped = std::mem::take(&mut self.pane_state(pane_id).scroll_pixel_offset) != 0.;
+        if snapped {
+            if let Some(window) = self.window.as_ref() {
+                window.invalidate();
+            }
+        }
+    }
+}
@@ -1,7 +1,40 @@
 use crate::spawn::SpawnWhere;
-use config::keyassignment::{SpawnCommand, SpawnTabDomain};
+use anyhow::Context;
+use config::keyassignment::{PaneDirection, SpawnCommand, SpawnTabDomain, ViewScrollbackArguments};
 use config::TermConfig;
-use std::sync::Arc;
+use mux::domain::LocalDomain;
+use mux::pane::Pane;
+use mux::tab::{SplitDirection, SplitRequest};
+use mux::{Mux, MuxNotification};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tempfile::TempPath;
+
+/// Writes the text of the scrollback and viewport of pane to a
+/// temporary file, which is removed when the returned path is dropped
+fn write_scrollback_file(pane: &Arc<dyn Pane>) -> anyhow::Result<TempPath> {
+    let dims = pane.get_dimensions();
+    let bottom_row = dims.physical_top + dims.viewport_rows as isize;
+    let mut text = String::new();
+    let mut line_text = String::new();
+    for line in pane.get_logical_lines(dims.scrollback_top..bottom_row) {
+        line_text.clear();
+        for cell in line.logical.visible_cells() {
+            line_text.push_str(cell.str());
+        }
+        text.push_str(line_text.trim_end());
+        text.push('\n');
+    }
+
+    let mut file = tempfile::Builder::new()
+        .prefix("wezterm-scrollback-")
+        .suffix(".txt")
+        .tempfile()
+        .context("creating scrollback file")?;
+    file.write_all(text.as_bytes())
+        .context("writing scrollback file")?;
+    Ok(file.into_temp_path())
+}
 
 impl super::TermWindow {
     pub fn spawn_command(&self, spawn: &SpawnCommand, spawn_where: SpawnWhere) {
@@ -24,6 +57,101 @@ impl super::TermWindow {
         )
     }
 
+    /// Writes the scrollback of pane to a temporary file and runs
+    /// the scrollback_viewer on it in a new tab or split.
+    /// The file is removed when the viewer exits.
+    /// If there is no viewer, the file is opened with the application
+    /// that the system associates with text files.
+    pub fn view_scrollback(
+        &self,
+        pane: &Arc<dyn Pane>,
+        args: &ViewScrollbackArguments,
+    ) -> anyhow::Result<()> {
+        let spawn_where = match args.split {
+            None => SpawnWhere::NewTab,
+            Some(direction) => SpawnWhere::SplitPane(SplitRequest {
+                direction: match direction {
+                    PaneDirection::Up | PaneDirection::Down => SplitDirection::Vertical,
+                    PaneDirection::Left | PaneDirection::Right => SplitDirection::Horizontal,
+                    PaneDirection::Next | PaneDirection::Prev => {
+                        anyhow::bail!("Invalid direction {direction:?} for ViewScrollback")
+                    }
+                },
+                target_is_second: matches!(direction, PaneDirection::Down | PaneDirection::Right),
+                ..Default::default()
+            }),
+        };
+
+        let mut argv = args
+            .args
+            .clone()
+            .unwrap_or_else(|| self.config.scrollback_viewer.clone());
+        if argv.is_empty() {
+            // We can't tell when the associated application is done
+            // with the file, so it is left in the temporary directory
+            let path = write_scrollback_file(pane)?
+                .keep()
+                .context("keeping scrollback file")?;
+            wezterm_open_url::open_url(&path.to_string_lossy());
+            return Ok(());
+        }
+
+        // The file is written on this machine, so the viewer has to run
+        // here too, which we can only do in the domain of the pane if
+        // that is a local domain
+        let domain = Mux::get()
+            .get_domain(pane.domain_id())
+            .ok_or_else(|| anyhow::anyhow!("pane {} has no domain", pane.pane_id()))?;
+        if domain.downcast_ref::<LocalDomain>().is_none() {
+            anyhow::bail!(
+                "ViewScrollback is not supported for panes in domain {}",
+                domain.domain_name()
+            );
+        }
+
+        let path = write_scrollback_file(pane)?;
+        argv.push(path.to_string_lossy().into_owned());
+
+        let spawn = SpawnCommand {
+            args: Some(argv),
+            domain: SpawnTabDomain::DomainId(domain.domain_id()),
+            ..Default::default()
+        };
+        let size = self.terminal_size;
+        let term_config = Arc::new(TermConfig::with_config(self.config.clone()));
+        let src_window_id = self.mux_window_id;
+
+        promise::spawn::spawn(async move {
+            match crate::spawn::spawn_command_internal(
+                spawn,
+                spawn_where,
+                size,
+                Some(src_window_id),
+                term_config,
+            )
+            .await
+            {
+                Ok(pane) => {
+                    let pane_id = pane.pane_id();
+                    let path = Mutex::new(Some(path));
+                    Mux::get().subscribe(move |n| match n {
+                        MuxNotification::PaneExited(id) | MuxNotification::PaneRemoved(id)
+                            if id == pane_id =>
+                        {
+                            path.lock().unwrap().take();
+                            false
+                        }
+                        _ => true,
+                    });
+                }
+                Err(err) => log::error!("Failed to spawn scrollback viewer: {:#}", err),
+            }
+        })
+        .detach();
+
+        Ok(())
+    }
+
     pub fn spawn_tab(&mut self, domain: &SpawnTabDomain) {
         self.spawn_command(
             &SpawnCommand {
@@ -1,6 +1,6 @@
 use crate::quad::Vertex;
 use anyhow::anyhow;
-use config::{ConfigHandle, GpuInfo, WebGpuPowerPreference};
+use config::{ConfigHandle, GpuInfo, PresentMode, WebGpuPowerPreference};
 use std::cell::RefCell;
 use std::sync::Arc;
 use wgpu::util::DeviceExt;
@@ -16,6 +16,8 @@ pub struct ShaderUniform {
     pub foreground_text_hsb: [f32; 3],
     pub milliseconds: u32,
     pub projection: [[f32; 4]; 4],
+    pub window_hsb: [f32; 3],
+    pub _padding: f32,
     // sampler2D atlas_nearest_sampler;
     // sampler2D atlas_linear_sampler;
 }
@@ -354,12 +356,34 @@ impl WebGpuState {
             vec![]
         };
 
+        let present_mode = match config.present_mode {
+            PresentMode::Auto | PresentMode::VSync => wgpu::PresentMode::Fifo,
+            PresentMode::Immediate => wgpu::PresentMode::Immediate,
+            PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+        };
+        let present_mode = if caps.present_modes.contains(&present_mode) {
+            present_mode
+        } else {
+            log::warn!(
+                "present_mode {:?} is not supported by this adapter, using vsync instead",
+                config.present_mode
+            );
+            wgpu::PresentMode::Fifo
+        };
+
+        // Reading back frames for CaptureScreenshot requires COPY_SRC
+        let usage = if caps.usages.contains(wgpu::TextureUsages::COPY_SRC) {
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC
+        } else {
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+        };
+
         let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage,
             format,
             width: dimensions.pixel_width as u32,
             height: dimensions.pixel_height as u32,
-            present_mode: wgpu::PresentMode::Fifo,
+            present_mode,
             alpha_mode: if caps
                 .alpha_modes
                 .contains(&wgpu::CompositeAlphaMode::PostMultiplied)
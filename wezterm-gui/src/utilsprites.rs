@@ -16,6 +16,8 @@ pub struct RenderMetrics {
     pub descender_plus_two: IntPixelLength,
     pub underline_height: IntPixelLength,
     pub strike_row: IntPixelLength,
+    pub strike_height: IntPixelLength,
+    pub overline_row: IntPixelLength,
     pub cell_size: Size,
 }
 
@@ -32,13 +34,26 @@ impl RenderMetrics {
             (cell_height as f64 + (metrics.descender - metrics.underline_position).get()) as isize;
         let descender_plus_two =
             (2 * underline_height + descender_row).min(cell_height as isize - underline_height);
-        let strike_row = descender_row / 2;
+        let strike_height = metrics
+            .strikethrough_thickness
+            .map(|t| t.get().round().max(1.) as isize)
+            .unwrap_or(underline_height);
+        let strike_row = match metrics.strikethrough_position {
+            Some(position) => {
+                ((cell_height as f64 + metrics.descender.get()) - position.get()).round() as isize
+            }
+            None => descender_row / 2,
+        }
+        .max(0)
+        .min(cell_height as isize - strike_height);
 
         Self {
             descender: metrics.descender,
             descender_row,
             descender_plus_two,
             strike_row,
+            strike_height,
+            overline_row: 0,
             cell_size: Size::new(cell_width as isize, cell_height as isize),
             underline_height,
         }
@@ -58,6 +73,8 @@ impl RenderMetrics {
             descender_plus_two: self.descender_plus_two - adjust,
             underline_height: self.underline_height,
             strike_row: self.strike_row,
+            strike_height: self.strike_height,
+            overline_row: self.overline_row,
             cell_size: size,
         }
     }
@@ -110,11 +127,33 @@ impl RenderMetrics {
             - line_height_y_adjust) as isize;
         let descender_plus_two =
             (2 * underline_height + descender_row).min(cell_height as isize - underline_height);
+
+        let font_strike_height = metrics
+            .strikethrough_thickness
+            .map(|t| t.get())
+            .unwrap_or(underline_height as f64);
+        let strike_height = match &config.strikethrough_thickness {
+            None => font_strike_height.round().max(1.) as isize,
+            Some(d) => d
+                .evaluate_as_pixels(DimensionContext {
+                    dpi: fonts.get_dpi() as f32,
+                    pixel_max: font_strike_height as f32,
+                    pixel_cell: cell_height as f32,
+                })
+                .max(1.) as isize,
+        };
+
+        // Prefer the strikeout position from the OS/2 table of the font,
+        // which is specified relative to the baseline
+        let baseline = cell_height as f64 + metrics.descender.get() - line_height_y_adjust;
+        let default_strike_row = match metrics.strikethrough_position {
+            Some(position) => baseline - position.get(),
+            None => (cell_height as f64 + (metrics.descender.get() - underline_position)) / 2.,
+        };
         let strike_row = match &config.strikethrough_position {
-            None => {
-                ((cell_height as f64 + (metrics.descender.get() - underline_position)) / 2.)
-                    as isize
-            }
+            None => default_strike_row.round() as isize,
+            // Percentages remain relative to the underline derived position,
+            // as they were before the font's strikeout position was used
             Some(d) => d
                 .evaluate_as_pixels(DimensionContext {
                     dpi: fonts.get_dpi() as f32,
@@ -122,13 +161,33 @@ impl RenderMetrics {
                     pixel_cell: cell_height as f32,
                 })
                 .round() as isize,
-        };
+        }
+        .max(0)
+        .min(cell_height as isize - strike_height);
+
+        // The overline sits at the top of the font's own line, rather
+        // than the top of the cell, when line_height adds extra space
+        let default_overline_row = line_height_y_adjust.max(0.);
+        let overline_row = match &config.overline_position {
+            None => default_overline_row.round() as isize,
+            Some(d) => d
+                .evaluate_as_pixels(DimensionContext {
+                    dpi: fonts.get_dpi() as f32,
+                    pixel_max: default_overline_row as f32,
+                    pixel_cell: cell_height as f32,
+                })
+                .round() as isize,
+        }
+        .max(0)
+        .min(cell_height as isize - underline_height);
 
         Ok(Self {
             descender: metrics.descender - PixelLength::new(line_height_y_adjust),
             descender_row,
             descender_plus_two,
             strike_row,
+            strike_height,
+            overline_row,
             cell_size: Size::new(cell_width as isize, cell_height as isize),
             underline_height,
         })
@@ -1194,6 +1194,14 @@ pub enum MouseEventKind {
     Release(MousePress),
     VertWheel(i16),
     HorzWheel(i16),
+    /// Precise vertical scrolling from a trackpad or similar device,
+    /// measured in pixels.  As with VertWheel, positive values scroll up.
+    VertPixelScroll(i16),
+    /// The user has lifted their fingers at the end of a sequence of
+    /// VertPixelScroll events.  Only generated on systems that don't
+    /// synthesize their own momentum scrolling, so that the application
+    /// can apply momentum itself.
+    PixelScrollEnd,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -85,6 +85,12 @@ where
                 handler.process_one(decoded);
             }
             Ok(Item::WritePdu(decoded)) => {
+                let pushed_pane = match &decoded.pdu {
+                    Pdu::GetPaneRenderChangesResponse(resp) if decoded.serial == 0 => {
+                        Some(resp.pane_id)
+                    }
+                    _ => None,
+                };
                 match decoded.pdu.encode_async(&mut stream, decoded.serial).await {
                     Ok(()) => {}
                     Err(err) => {
@@ -107,6 +113,9 @@ where
                         return Err(err).context("flushing PDU to client");
                     }
                 }
+                if let Some(pane_id) = pushed_pane {
+                    handler.pane_push_written(pane_id);
+                }
             }
             Ok(Item::Notif(MuxNotification::PaneOutput(pane_id))) => {
                 handler.schedule_pane_push(pane_id);
@@ -204,6 +213,7 @@ where
             }
             Ok(Item::Notif(MuxNotification::ActiveWorkspaceChanged(_))) => {}
             Ok(Item::Notif(MuxNotification::Empty)) => {}
+            Ok(Item::Notif(MuxNotification::PaneExited(_))) => {}
             Err(err) => {
                 log::error!("process_async Err {}", err);
                 return Ok(());
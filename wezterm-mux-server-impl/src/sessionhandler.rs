@@ -11,7 +11,7 @@ use mux::{Mux, MuxNotification};
 use promise::spawn::spawn_into_main_thread;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use termwiz::surface::SequenceNo;
 use url::Url;
 use wezterm_term::terminal::Alert;
@@ -42,13 +42,57 @@ pub(crate) struct PerPane {
     working_dir: Option<Url>,
     dimensions: RenderableDimensions,
     mouse_grabbed: bool,
+    alt_screen_active: bool,
     sent_initial_palette: bool,
     seqno: SequenceNo,
     config_generation: usize,
     pub(crate) notifications: Vec<Alert>,
+    /// Whether a push of the changes in this pane is already
+    /// scheduled; further changes will be picked up by it
+    push_scheduled: bool,
+    last_push: Option<Instant>,
+    /// Whether an update for this pane has been queued but not
+    /// yet written to the client.  We don't compute another until
+    /// it has been written, so that a pane that is producing a lot
+    /// of output can't fill the connection with stale updates that
+    /// delay those for other panes.
+    push_in_flight: bool,
+    /// Set when changes were made while push_in_flight was set
+    push_deferred: bool,
 }
 
 impl PerPane {
+    /// Called when the pane has changed.  Returns the delay after which
+    /// its changes should be pushed to the client, or None if they will
+    /// be picked up by a push that is already scheduled or in flight.
+    fn push_needed(&mut self, interval: Duration) -> Option<Duration> {
+        if self.push_in_flight {
+            self.push_deferred = true;
+            return None;
+        }
+        if self.push_scheduled {
+            return None;
+        }
+        self.push_scheduled = true;
+        Some(match self.last_push {
+            Some(last) => interval.saturating_sub(last.elapsed()),
+            None => Duration::ZERO,
+        })
+    }
+
+    /// Called when a scheduled push is about to compute the changes
+    fn push_starting(&mut self) {
+        self.push_scheduled = false;
+        self.last_push.replace(Instant::now());
+    }
+
+    /// Called once the update computed by a push has been written to
+    /// the client.  Returns true if the pane changed in the meantime.
+    fn push_written(&mut self) -> bool {
+        self.push_in_flight = false;
+        std::mem::take(&mut self.push_deferred)
+    }
+
     fn compute_changes(
         &mut self,
         pane: &Arc<dyn Pane>,
@@ -60,6 +104,11 @@ impl PerPane {
             changed = true;
         }
 
+        let alt_screen_active = pane.is_alt_screen_active();
+        if alt_screen_active != self.alt_screen_active {
+            changed = true;
+        }
+
         let dims = pane.get_dimensions();
         if dims != self.dimensions {
             changed = true;
@@ -126,11 +175,14 @@ impl PerPane {
         self.working_dir = working_dir.clone();
         self.dimensions = dims;
         self.mouse_grabbed = mouse_grabbed;
+        self.alt_screen_active = alt_screen_active;
+        self.push_in_flight = true;
 
         let bonus_lines = bonus_lines.into();
         Some(GetPaneRenderChangesResponse {
             pane_id: pane.pane_id(),
             mouse_grabbed,
+            alt_screen_active,
             dirty_lines: all_dirty_lines.iter().cloned().collect(),
             dimensions: dims,
             cursor_position,
@@ -233,7 +285,16 @@ impl SessionHandler {
     pub fn schedule_pane_push(&mut self, pane_id: PaneId) {
         let sender = self.to_write_tx.clone();
         let per_pane = self.per_pane(pane_id);
+        let interval = Duration::from_millis(config::configuration().mux_client_update_interval_ms);
+        let delay = match per_pane.lock().unwrap().push_needed(interval) {
+            Some(delay) => delay,
+            None => return,
+        };
         spawn_into_main_thread(async move {
+            if !delay.is_zero() {
+                smol::Timer::after(delay).await;
+            }
+            per_pane.lock().unwrap().push_starting();
             let mux = Mux::get();
             let pane = mux
                 .get_pane(pane_id)
@@ -244,6 +305,15 @@ impl SessionHandler {
         .detach();
     }
 
+    /// Called once an update for pane_id has been written to the
+    /// client, to push any changes that were held back meanwhile
+    pub fn pane_push_written(&mut self, pane_id: PaneId) {
+        let deferred = self.per_pane(pane_id).lock().unwrap().push_written();
+        if deferred {
+            self.schedule_pane_push(pane_id);
+        }
+    }
+
     pub fn process_one(&mut self, decoded: DecodedPdu) {
         let start = Instant::now();
         let sender = self.to_write_tx.clone();
@@ -469,6 +539,22 @@ impl SessionHandler {
                 })
                 .detach();
             }
+            Pdu::ScrollViewportIntoScrollback(ScrollViewportIntoScrollback { pane_id }) => {
+                spawn_into_main_thread(async move {
+                    catch(
+                        move || {
+                            let mux = Mux::get();
+                            let pane = mux
+                                .get_pane(pane_id)
+                                .ok_or_else(|| anyhow!("no such pane {}", pane_id))?;
+                            pane.scroll_viewport_into_scrollback();
+                            Ok(Pdu::UnitResponse(UnitResponse {}))
+                        },
+                        send_response,
+                    );
+                })
+                .detach();
+            }
             Pdu::KillPane(KillPane { pane_id }) => {
                 let sender = self.to_write_tx.clone();
                 let per_pane = self.per_pane(pane_id);
@@ -987,6 +1073,31 @@ impl SessionHandler {
                 .detach();
             }
 
+            Pdu::RecordPane(RecordPane {
+                pane_id,
+                stop,
+                path,
+            }) => {
+                spawn_into_main_thread(async move {
+                    catch(
+                        move || {
+                            let path = if stop {
+                                mux::recording::stop_recording(pane_id)
+                            } else {
+                                let mux = Mux::get();
+                                let pane = mux
+                                    .get_pane(pane_id)
+                                    .ok_or_else(|| anyhow!("no such pane {}", pane_id))?;
+                                Some(mux::recording::start_recording(&pane, path)?)
+                            };
+                            Ok(Pdu::RecordPaneResponse(RecordPaneResponse { path }))
+                        },
+                        send_response,
+                    )
+                })
+                .detach();
+            }
+
             Pdu::Invalid { .. } => send_response(Err(anyhow!("invalid PDU {:?}", decoded.pdu))),
             Pdu::Pong { .. }
             | Pdu::ListPanesResponse { .. }
@@ -997,6 +1108,7 @@ impl SessionHandler {
             | Pdu::UnitResponse { .. }
             | Pdu::LivenessResponse { .. }
             | Pdu::GetPaneDirectionResponse { .. }
+            | Pdu::RecordPaneResponse { .. }
             | Pdu::SearchScrollbackResponse { .. }
             | Pdu::GetLinesResponse { .. }
             | Pdu::GetCodecVersionResponse { .. }
@@ -1083,6 +1195,7 @@ async fn domain_spawn_v2(spawn: SpawnV2, client_id: Option<Arc<ClientId>>) -> an
             None, // optional current pane_id
             spawn.workspace,
             None, // optional gui window position
+            spawn.class,
         )
         .await?;
 
@@ -1125,3 +1238,47 @@ async fn move_pane(
         window_id,
     }))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn coalesce_pushes() {
+        let interval = Duration::from_millis(1000);
+        let mut per_pane = PerPane::default();
+
+        // The first change is pushed straight away
+        assert_eq!(per_pane.push_needed(interval), Some(Duration::ZERO));
+        // and later changes are picked up by that push
+        assert_eq!(per_pane.push_needed(interval), None);
+
+        per_pane.push_starting();
+        // compute_changes found something to send
+        per_pane.push_in_flight = true;
+
+        // Changes made while the update is being written wait for it
+        assert_eq!(per_pane.push_needed(interval), None);
+        assert_eq!(per_pane.push_needed(interval), None);
+        assert!(per_pane.push_written());
+
+        // and are then pushed once, no sooner than the interval
+        // after the previous push
+        let delay = per_pane.push_needed(interval).unwrap();
+        assert!(delay > Duration::ZERO && delay <= interval, "{delay:?}");
+        assert_eq!(per_pane.push_needed(interval), None);
+
+        per_pane.push_starting();
+        per_pane.push_in_flight = true;
+        // Nothing changed while it was being written
+        assert!(!per_pane.push_written());
+    }
+
+    #[test]
+    fn no_interval() {
+        let mut per_pane = PerPane::default();
+        assert_eq!(per_pane.push_needed(Duration::ZERO), Some(Duration::ZERO));
+        per_pane.push_starting();
+        assert_eq!(per_pane.push_needed(Duration::ZERO), Some(Duration::ZERO));
+    }
+}
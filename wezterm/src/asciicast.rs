@@ -4,13 +4,14 @@ use chrono::{DateTime, Utc};
 use clap::Parser;
 use config::ConfigHandle;
 use filedescriptor::FileDescriptor;
+use mux::replay::{PlayControl, Player, ReplayEvent};
 use portable_pty::{native_pty_system, PtySize};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::ffi::{OsStr, OsString};
 use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 use std::path::PathBuf;
-use std::sync::mpsc::channel;
+use std::sync::mpsc::{channel, RecvTimeoutError};
 use std::time::{Duration, Instant};
 use termwiz::escape::parser::Parser as TWParser;
 use termwiz::escape::Action;
@@ -528,6 +529,7 @@ impl PlayCommand {
         let (tx, rx) = channel();
         let mut sent_parser = TWParser::new();
         let mut sent_actions = vec![];
+        let mut answer_back = vec![];
 
         if self.explain_only {
             for line in cast_file.lines() {
@@ -555,6 +557,16 @@ impl PlayCommand {
                 );
             }
 
+            let mut events = vec![];
+            for line in cast_file.lines() {
+                let line = line?;
+                let event: Event = serde_json::from_str(&line)?;
+                if event.1 == "o" || event.1 == "r" {
+                    events.push(ReplayEvent(event.0, event.1, event.2));
+                }
+            }
+            let mut player = Player::new(events);
+
             tty.set_raw()?;
 
             {
@@ -573,20 +585,58 @@ impl PlayCommand {
                 });
             }
 
-            let start = Instant::now();
+            let mut last_tick = Instant::now();
+            let mut stdin_open = true;
+
+            while !player.is_finished() {
+                let timeout = player.time_to_next_event();
+                let input = if stdin_open {
+                    let result = match timeout {
+                        Some(timeout) => rx.recv_timeout(timeout),
+                        None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                    };
+                    match result {
+                        Ok(Message::Stdin(data)) => Some(data),
+                        Ok(_) | Err(RecvTimeoutError::Timeout) => None,
+                        Err(RecvTimeoutError::Disconnected) => {
+                            stdin_open = false;
+                            None
+                        }
+                    }
+                } else {
+                    match timeout {
+                        Some(timeout) => {
+                            std::thread::sleep(timeout);
+                            None
+                        }
+                        // Paused with no way to resume
+                        None => break,
+                    }
+                };
 
-            for line in cast_file.lines() {
-                let line = line?;
-                let event: Event = serde_json::from_str(&line)?;
-                if event.1 != "o" {
-                    continue;
+                let now = Instant::now();
+                player.advance(now - last_tick);
+                last_tick = now;
+
+                if let Some(data) = input {
+                    match PlayControl::from_input(&data) {
+                        Some(PlayControl::Quit) => break,
+                        Some(control) => player.control(control),
+                        None => answer_back.push(data),
+                    }
                 }
-                let target = start + Duration::from_secs_f32(event.0);
-                let duration = target.saturating_duration_since(Instant::now());
-                std::thread::sleep(duration);
 
-                tty.write_all(&event.2.as_bytes())?;
-                sent_parser.parse(&event.2.as_bytes(), |act| sent_actions.push(act));
+                if player.take_reset() {
+                    // Seeking backwards: start over from a clean slate
+                    // and quickly catch up to the new position
+                    tty.write_all(b"\x1bc")?;
+                }
+                while let Some(event) = player.next_due_event() {
+                    if let Some(bytes) = event.terminal_bytes() {
+                        tty.write_all(&bytes)?;
+                        sent_parser.parse(&bytes, |act| sent_actions.push(act));
+                    }
+                }
             }
 
             std::thread::sleep(Duration::from_millis(100));
@@ -605,21 +655,21 @@ impl PlayCommand {
             if self.explain {
                 println!("< RECV");
             }
-            let mut parser = TWParser::new();
             while let Ok(msg) = rx.try_recv() {
                 match msg {
-                    Message::Stdin(data) => {
-                        if self.explain {
-                            let answer_back = String::from_utf8_lossy(&data);
-                            println!("\t{:?}", answer_back);
-                            parser.parse(&data, |action| {
-                                println!("\t{:?}", action);
-                            });
-                        }
-                    }
+                    Message::Stdin(data) => answer_back.push(data),
                     _ => unreachable!(),
                 }
             }
+            if self.explain {
+                let mut parser = TWParser::new();
+                for data in answer_back {
+                    println!("\t{:?}", String::from_utf8_lossy(&data));
+                    parser.parse(&data, |action| {
+                        println!("\t{:?}", action);
+                    });
+                }
+            }
         }
 
         Ok(())
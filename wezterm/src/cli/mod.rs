@@ -14,6 +14,7 @@ mod list;
 mod list_clients;
 mod move_pane_to_new_tab;
 mod proxy;
+mod record_pane;
 mod rename_workspace;
 mod send_text;
 mod set_tab_title;
@@ -163,6 +164,12 @@ Outputs the pane-id for the newly created pane on success"
     /// Zoom, unzoom, or toggle zoom state
     #[command(name = "zoom-pane", rename_all = "kebab")]
     ZoomPane(zoom_pane::ZoomPane),
+
+    /// Start or stop recording a pane as an asciicast.
+    ///
+    /// Prints the path to the recording on success.
+    #[command(name = "record-pane", rename_all = "kebab")]
+    RecordPane(record_pane::RecordPane),
 }
 
 async fn run_cli_async(opts: &crate::Opt, cli: CliCommand) -> anyhow::Result<()> {
@@ -199,6 +206,7 @@ async fn run_cli_async(opts: &crate::Opt, cli: CliCommand) -> anyhow::Result<()>
         CliSubCommand::SetWindowTitle(cmd) => cmd.run(client).await,
         CliSubCommand::RenameWorkspace(cmd) => cmd.run(client).await,
         CliSubCommand::ZoomPane(cmd) => cmd.run(client).await,
+        CliSubCommand::RecordPane(cmd) => cmd.run(client).await,
     }
 }
 
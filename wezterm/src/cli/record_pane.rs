@@ -0,0 +1,46 @@
+use clap::Parser;
+use mux::pane::PaneId;
+use std::path::PathBuf;
+use wezterm_client::client::Client;
+
+#[derive(Debug, Parser, Clone)]
+pub struct RecordPane {
+    /// Specify the target pane.
+    /// The default is to use the current pane based on the
+    /// environment variable WEZTERM_PANE.
+    #[arg(long)]
+    pane_id: Option<PaneId>,
+
+    /// Stop recording the pane, rather than starting
+    #[arg(long, conflicts_with = "output")]
+    stop: bool,
+
+    /// Where to save the recording.
+    /// The default is a new file in the recording_directory
+    /// defined by your wezterm configuration.
+    /// Relative paths are resolved against the current directory.
+    #[arg(long, short = 'o', value_hint=clap::ValueHint::FilePath)]
+    output: Option<PathBuf>,
+}
+
+impl RecordPane {
+    pub async fn run(&self, client: Client) -> anyhow::Result<()> {
+        let pane_id = client.resolve_pane_id(self.pane_id).await?;
+        let path = match &self.output {
+            Some(path) => Some(std::env::current_dir()?.join(path)),
+            None => None,
+        };
+        let response = client
+            .record_pane(codec::RecordPane {
+                pane_id,
+                stop: self.stop,
+                path,
+            })
+            .await?;
+        match response.path {
+            Some(path) => println!("{}", path.display()),
+            None => anyhow::bail!("pane {pane_id} is not being recorded"),
+        }
+        Ok(())
+    }
+}
@@ -43,6 +43,13 @@ pub struct SpawnCommand {
     #[arg(long, requires = "new_window")]
     workspace: Option<String>,
 
+    /// When creating a new window, override the windowing system
+    /// class of the new window, so that window manager rules can
+    /// target it specifically.
+    /// Requires `--new-window`.
+    #[arg(long, requires = "new_window")]
+    class: Option<String>,
+
     /// Instead of executing your shell, run PROG.
     /// For example: `wezterm cli spawn -- bash -l` will spawn bash
     /// as if it were a login shell.
@@ -113,6 +120,7 @@ impl SpawnCommand {
                 command_dir: resolve_relative_cwd(self.cwd)?,
                 size,
                 workspace,
+                class: self.class,
             })
             .await?;
 
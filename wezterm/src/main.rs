@@ -18,6 +18,7 @@ use wezterm_gui_subcommands::*;
 
 mod asciicast;
 mod cli;
+mod shell_integration;
 
 //    let message = "; ❤ 😍🤢\n\x1b[91;mw00t\n\x1b[37;104;m bleet\x1b[0;m.";
 
@@ -113,9 +114,21 @@ enum SubCommand {
     #[command(name = "ls-fonts", about = "Display information about fonts")]
     LsFonts(LsFontsCommand),
 
+    #[command(
+        name = "screenshot",
+        about = "Render a pane to a PNG image without a window"
+    )]
+    Screenshot(ScreenshotCommand),
+
     #[command(name = "show-keys", about = "Show key assignments")]
     ShowKeys(ShowKeysCommand),
 
+    #[command(
+        name = "check-config",
+        about = "Load and validate the configuration without starting the GUI"
+    )]
+    CheckConfig(CheckConfigCommand),
+
     #[command(name = "cli", about = "Interact with experimental mux server")]
     Cli(cli::CliCommand),
 
@@ -135,6 +148,12 @@ enum SubCommand {
     #[command(name = "replay", about = "Replay an asciicast terminal session")]
     Replay(asciicast::PlayCommand),
 
+    #[command(
+        name = "shell-integration",
+        about = "Print or install the shell integration for bash, zsh or fish"
+    )]
+    ShellIntegration(shell_integration::ShellIntegrationCommand),
+
     /// Generate shell completion information
     #[command(name = "shell-completion")]
     ShellCompletion {
@@ -739,7 +758,9 @@ fn run() -> anyhow::Result<()> {
         SubCommand::Start(_)
         | SubCommand::BlockingStart(_)
         | SubCommand::LsFonts(_)
+        | SubCommand::Screenshot(_)
         | SubCommand::ShowKeys(_)
+        | SubCommand::CheckConfig(_)
         | SubCommand::Ssh(_)
         | SubCommand::Serial(_)
         | SubCommand::Connect(_) => delegate_to_gui(saver),
@@ -748,6 +769,7 @@ fn run() -> anyhow::Result<()> {
         SubCommand::Cli(cli) => cli::run_cli(&opts, cli),
         SubCommand::Record(cmd) => cmd.run(init_config(&opts)?),
         SubCommand::Replay(cmd) => cmd.run(),
+        SubCommand::ShellIntegration(cmd) => cmd.run(),
         SubCommand::ShellCompletion { shell } => {
             use clap::CommandFactory;
             let mut cmd = Opt::command();
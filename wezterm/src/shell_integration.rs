@@ -0,0 +1,124 @@
+use anyhow::Context;
+use clap::{Parser, ValueEnum};
+use config::{DATA_DIR, HOME_DIR};
+use mux::shell_integration::IntegratedShell;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ShellName {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl From<ShellName> for IntegratedShell {
+    fn from(shell: ShellName) -> IntegratedShell {
+        match shell {
+            ShellName::Bash => IntegratedShell::Bash,
+            ShellName::Zsh => IntegratedShell::Zsh,
+            ShellName::Fish => IntegratedShell::Fish,
+        }
+    }
+}
+
+#[derive(Debug, Parser, Clone)]
+pub struct ShellIntegrationCommand {
+    /// Which shell to generate the integration for.
+    /// If omitted, the shell is determined from the SHELL
+    /// environment variable.
+    #[arg(long, value_enum)]
+    shell: Option<ShellName>,
+
+    /// Rather than printing the integration script, install it
+    /// so that it is loaded by each new interactive shell
+    #[arg(long)]
+    install: bool,
+}
+
+const MARKER: &str = "# Added by `wezterm shell-integration --install`";
+
+fn append_source_line(rc_file: &Path, script: &Path) -> anyhow::Result<()> {
+    let existing = std::fs::read_to_string(rc_file).unwrap_or_default();
+    if existing.contains(MARKER) {
+        println!("{} already loads the shell integration", rc_file.display());
+        return Ok(());
+    }
+
+    let script = script
+        .to_str()
+        .context("shell integration path is not UTF-8")?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(rc_file)
+        .with_context(|| format!("opening {}", rc_file.display()))?;
+    writeln!(
+        file,
+        "\n{MARKER}\nif [ -f \"{script}\" ] ; then . \"{script}\" ; fi"
+    )?;
+    println!(
+        "Updated {} to load the shell integration",
+        rc_file.display()
+    );
+    Ok(())
+}
+
+impl ShellIntegrationCommand {
+    fn resolve_shell(&self) -> anyhow::Result<IntegratedShell> {
+        if let Some(shell) = self.shell {
+            return Ok(shell.into());
+        }
+        let shell = std::env::var("SHELL")
+            .context("SHELL is not set in the environment; use --shell to specify the shell")?;
+        IntegratedShell::from_program(&shell).ok_or_else(|| {
+            anyhow::anyhow!(
+                "shell integration is not available for {shell}; \
+                 use --shell to specify the shell"
+            )
+        })
+    }
+
+    pub fn run(&self) -> anyhow::Result<()> {
+        let shell = self.resolve_shell()?;
+        if !self.install {
+            print!("{}", shell.script());
+            return Ok(());
+        }
+
+        match shell {
+            IntegratedShell::Bash | IntegratedShell::Zsh => {
+                let dir = DATA_DIR.join("shell-integration");
+                config::create_user_owned_dirs(&dir)?;
+                let script = dir.join("wezterm.sh");
+                std::fs::write(&script, shell.script())
+                    .with_context(|| format!("writing {}", script.display()))?;
+                println!("Wrote {}", script.display());
+
+                let rc_file = if shell == IntegratedShell::Bash {
+                    HOME_DIR.join(".bashrc")
+                } else {
+                    std::env::var_os("ZDOTDIR")
+                        .map(PathBuf::from)
+                        .unwrap_or_else(|| HOME_DIR.clone())
+                        .join(".zshrc")
+                };
+                append_source_line(&rc_file, &script)
+            }
+            IntegratedShell::Fish => {
+                // fish automatically loads everything in conf.d
+                let dir = std::env::var_os("XDG_CONFIG_HOME")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| HOME_DIR.join(".config"))
+                    .join("fish")
+                    .join("conf.d");
+                config::create_user_owned_dirs(&dir)?;
+                let script = dir.join("wezterm.fish");
+                std::fs::write(&script, shell.script())
+                    .with_context(|| format!("writing {}", script.display()))?;
+                println!("Wrote {}", script.display());
+                Ok(())
+            }
+        }
+    }
+}
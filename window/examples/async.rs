@@ -81,6 +81,7 @@ impl MyWindow {
             WindowEvent::AppearanceChanged(_)
             | WindowEvent::AdviseDeadKeyStatus(_)
             | WindowEvent::AdviseModifiersLedStatus(_, _)
+            | WindowEvent::KeyboardLayoutChanged(_)
             | WindowEvent::Notification(_)
             | WindowEvent::FocusChanged(_)
             | WindowEvent::DraggedFile(_)
@@ -89,6 +90,7 @@ impl MyWindow {
             | WindowEvent::DroppedString(_)
             | WindowEvent::PerformKeyAssignment(_)
             | WindowEvent::MouseLeave
+            | WindowEvent::Exposed
             | WindowEvent::SetInnerSizeCompleted => {}
         }
     }
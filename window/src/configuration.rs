@@ -10,3 +10,52 @@ pub(crate) fn prefer_swrast() -> bool {
     }
     config::configuration().front_end == config::FrontEndSelection::Software
 }
+
+/// Returns the OpenGL swap interval that corresponds to the configured
+/// `present_mode`.  OpenGL has no equivalent of mailbox presentation,
+/// so that is treated the same as presenting immediately, which is what
+/// a compositor will effectively turn it into.
+pub(crate) fn swap_interval() -> i32 {
+    match config::configuration().present_mode {
+        config::PresentMode::VSync => 1,
+        config::PresentMode::Auto
+        | config::PresentMode::Immediate
+        | config::PresentMode::Mailbox => 0,
+    }
+}
+
+/// Sets the environment variables that Mesa and the NVIDIA driver
+/// consult to decide which GPU to use for new OpenGL contexts,
+/// according to the `opengl_power_preference` and
+/// `opengl_preferred_adapter` options.
+/// Modifying the environment is not thread safe, so this must be
+/// called once during startup, before any other threads are spawned.
+/// Variables that the user has already set are left alone, and the
+/// ones set here are removed from the environment of the programs
+/// that we spawn, so that the choice doesn't leak into them.
+pub fn apply_gpu_preference_env() {
+    if !cfg!(all(unix, not(target_os = "macos"))) {
+        return;
+    }
+
+    let config = config::configuration();
+    let vars = if let Some(adapter) = &config.opengl_preferred_adapter {
+        vec![("DRI_PRIME", adapter.to_string())]
+    } else if config.opengl_power_preference == config::WebGpuPowerPreference::HighPerformance {
+        vec![
+            ("DRI_PRIME", "1".to_string()),
+            ("__NV_PRIME_RENDER_OFFLOAD", "1".to_string()),
+        ]
+    } else {
+        vec![]
+    };
+
+    for (name, value) in vars {
+        if std::env::var_os(name).is_some() {
+            continue;
+        }
+        log::trace!("GPU preference: setting {name}={value}");
+        std::env::set_var(name, value);
+        config::remove_from_spawned_env(name);
+    }
+}
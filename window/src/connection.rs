@@ -4,6 +4,7 @@ use anyhow::Result as Fallible;
 use config::keyassignment::KeyAssignment;
 use config::DimensionContext;
 use std::cell::RefCell;
+use std::path::Path;
 use std::rc::Rc;
 use std::sync::Mutex;
 
@@ -76,6 +77,11 @@ pub trait ConnectionOps {
     /// Perform the system beep/notification sound
     fn beep(&self) {}
 
+    /// Asynchronously play the sound file at the specified path
+    fn play_sound_file(&self, _path: &Path) -> Fallible<()> {
+        anyhow::bail!("playing sound files is not supported on this system");
+    }
+
     /// Returns information about the screens
     fn screens(&self) -> anyhow::Result<Screens> {
         anyhow::bail!("Unable to query screen information");
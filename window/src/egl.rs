@@ -527,7 +527,31 @@ impl GlState {
                 extensions,
             });
 
+            let err = match Self::create_with_existing_connection(&connection, window) {
+                Ok(state) => return Ok(state),
+                Err(err) if connection.is_opengl => err,
+                Err(err) => return Err(err),
+            };
+
+            // Some drivers, particularly on ARM boards and some Wayland
+            // compositors, advertise desktop OpenGL but are unable to
+            // create a suitable context; retry with GL ES before giving up.
+            log::warn!(
+                "Failed to create an OpenGL context: {:#}. Falling back to OpenGL ES",
+                err
+            );
+            let mut connection = Rc::try_unwrap(connection)
+                .map_err(|_| anyhow!("EGL connection is unexpectedly shared"))?;
+            unsafe {
+                if connection.egl.egl.BindAPI(ffi::OPENGL_ES_API) == 0 {
+                    return Err(err.context("Unable to bind to GL ES"));
+                }
+            }
+            connection.is_opengl = false;
+            let connection = Rc::new(connection);
+
             Self::create_with_existing_connection(&connection, window)
+                .map_err(|es_err| anyhow!("OpenGL: {:#}, OpenGL ES: {:#}", err, es_err))
         })
     }
 
@@ -633,6 +657,11 @@ impl GlState {
                 Ok(c) => c,
                 Err(e) => {
                     errors.push_str(&format!("{:#} {:x?}\n", e, config));
+                    // Release the surface, otherwise a subsequent attempt
+                    // to create a surface for this window will fail
+                    unsafe {
+                        connection.DestroySurface(connection.display, surface);
+                    }
                     continue;
                 }
             };
@@ -640,10 +669,13 @@ impl GlState {
             log::trace!("Successfully created a surface using this configuration");
             connection.egl.log_config_info(connection.display, config);
 
-            // Request non-blocking buffer swaps; we'll manage throttling
-            // frames at the application level.
+            // Unless vsync was requested, use non-blocking buffer swaps;
+            // we'll manage throttling frames at the application level.
             unsafe {
-                connection.egl.egl.SwapInterval(connection.display, 0);
+                connection
+                    .egl
+                    .egl
+                    .SwapInterval(connection.display, crate::configuration::swap_interval());
             }
 
             return Ok(Self {
@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use bitflags::bitflags;
-use config::window::WindowLevel;
+use config::window::{UserAttentionType, WindowLevel, WindowProgress};
 use config::{ConfigHandle, Dimension, GeometryOrigin};
 use promise::Future;
 use std::any::Any;
@@ -33,6 +33,7 @@ pub fn default_dpi() -> f64 {
 mod egl;
 
 pub use bitmaps::{BitmapImage, Image};
+pub use configuration::apply_gpu_preference_env;
 pub use connection::*;
 pub use glium;
 pub use os::*;
@@ -179,6 +180,12 @@ pub enum WindowEvent {
     /// be repainted
     NeedRepaint,
 
+    /// Called when the window system has discarded some or all of
+    /// the window contents, such as when an obscured portion of the
+    /// window is uncovered.  It is followed by NeedRepaint, which must
+    /// then paint a complete frame.
+    Exposed,
+
     /// Called when the window gains/loses focus
     FocusChanged(bool),
 
@@ -216,6 +223,10 @@ pub enum WindowEvent {
     PerformKeyAssignment(config::keyassignment::KeyAssignment),
 
     AdviseModifiersLedStatus(Modifiers, KeyboardLedStatus),
+
+    /// Called with the name of the active keyboard layout when the
+    /// window first learns of it, and whenever it subsequently changes
+    KeyboardLayoutChanged(String),
 }
 
 pub struct WindowEventSender {
@@ -319,6 +330,11 @@ pub trait WindowOps {
     /// Set some text in the clipboard
     fn set_clipboard(&self, clipboard: Clipboard, text: String);
 
+    /// Set a PNG encoded image in the clipboard
+    fn set_clipboard_image(&self, _clipboard: Clipboard, _png: Vec<u8>) {
+        log::warn!("copying images to the clipboard is not supported on this system");
+    }
+
     /// Set window level. Depending on the environment and user preferences
     fn set_window_level(&self, _level: WindowLevel) {}
 
@@ -331,6 +347,33 @@ pub trait WindowOps {
     fn restore(&self) {}
     fn focus(&self) {}
 
+    /// Ask the window system to draw the user's attention to this
+    /// window, for example by flashing its taskbar entry, bouncing
+    /// the dock icon or setting the urgency hint.
+    /// The request is cleared when the window is focused.
+    fn request_attention(&self, _attention: UserAttentionType) {}
+
+    /// Show the progress of a long running operation in the
+    /// taskbar entry of the window.
+    /// This is currently only supported on Windows.
+    fn set_progress(&self, _progress: WindowProgress) {}
+
+    /// Show a short label, such as a count, as a badge on the
+    /// dock icon of the application; None removes the badge.
+    /// This is currently only supported on macOS.
+    fn set_badge(&self, _badge: Option<String>) {}
+
+    /// Start dragging text out of the window, so that it can be
+    /// dropped into another application.  This must be called while
+    /// the left mouse button is held down.
+    /// This is currently only supported on macOS and Wayland.
+    fn start_text_drag(&self, _text: String) {}
+
+    /// Returns true if start_text_drag is implemented for this window
+    fn supports_text_drag(&self) -> bool {
+        false
+    }
+
     fn toggle_fullscreen(&self) {}
 
     fn config_did_change(&self, _config: &config::ConfigHandle) {}
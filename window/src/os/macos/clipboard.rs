@@ -1,7 +1,7 @@
 use crate::macos::{nsstring, nsstring_to_str};
-use cocoa::appkit::{NSFilenamesPboardType, NSPasteboard, NSStringPboardType};
+use cocoa::appkit::{NSFilenamesPboardType, NSPasteboard, NSPasteboardTypePNG, NSStringPboardType};
 use cocoa::base::*;
-use cocoa::foundation::NSArray;
+use cocoa::foundation::{NSArray, NSData};
 
 pub struct Clipboard {
     pasteboard: id,
@@ -48,4 +48,18 @@ impl Clipboard {
             Ok(())
         }
     }
+
+    pub fn write_png(&mut self, png: &[u8]) -> anyhow::Result<()> {
+        unsafe {
+            self.pasteboard.clearContents();
+            let data = NSData::dataWithBytes_length_(
+                nil,
+                png.as_ptr() as *const std::os::raw::c_void,
+                png.len() as u64,
+            );
+            let success: BOOL = self.pasteboard.setData_forType(data, NSPasteboardTypePNG);
+            anyhow::ensure!(success == YES, "pasteboard write returned false");
+            Ok(())
+        }
+    }
 }
@@ -1,8 +1,8 @@
 // let () = msg_send! is a common pattern for objc
 #![allow(clippy::let_unit_value)]
 
-use super::nsstring_to_str;
 use super::window::WindowInner;
+use super::{nsstring, nsstring_to_str};
 use crate::connection::ConnectionOps;
 use crate::os::macos::app::create_app_delegate;
 use crate::screen::{ScreenInfo, Screens};
@@ -11,11 +11,13 @@ use crate::Appearance;
 use cocoa::appkit::{NSApp, NSApplication, NSApplicationActivationPolicyRegular, NSScreen};
 use cocoa::base::{id, nil};
 use cocoa::foundation::{NSArray, NSInteger};
+use objc::rc::StrongPtr;
 use objc::runtime::{Object, BOOL, YES};
 use objc::*;
 use serde::Deserialize;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::Path;
 use std::rc::Rc;
 use std::sync::atomic::AtomicUsize;
 
@@ -172,6 +174,29 @@ impl ConnectionOps for Connection {
         }
     }
 
+    fn play_sound_file(&self, path: &Path) -> anyhow::Result<()> {
+        let path = path
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("{} is not valid UTF-8", path.display()))?;
+        unsafe {
+            let sound: id = msg_send![class!(NSSound), alloc];
+            let sound: id = msg_send![sound,
+                initWithContentsOfFile: *nsstring(path)
+                byReference: YES];
+            if sound.is_null() {
+                anyhow::bail!("unable to load sound file {}", path);
+            }
+            let sound = StrongPtr::new(sound);
+            let playing: BOOL = msg_send![*sound, play];
+            if playing != YES {
+                anyhow::bail!("unable to play sound file {}", path);
+            }
+            // NSSound retains itself while it is playing, so it is
+            // fine for our reference to be released here
+        }
+        Ok(())
+    }
+
     fn screens(&self) -> anyhow::Result<Screens> {
         let mut by_name = HashMap::new();
         let mut virtual_rect = euclid::rect(0, 0, 0, 0);
@@ -26,7 +26,7 @@ use cocoa::foundation::{
     NSArray, NSAutoreleasePool, NSFastEnumeration, NSInteger, NSNotFound, NSPoint, NSRect, NSSize,
     NSUInteger,
 };
-use config::window::WindowLevel;
+use config::window::{UserAttentionType, WindowLevel};
 use config::ConfigHandle;
 use core_foundation::base::{CFTypeID, TCFType};
 use core_foundation::bundle::{CFBundleGetBundleWithIdentifier, CFBundleGetFunctionPointerForName};
@@ -56,6 +56,8 @@ use wezterm_input_types::{is_ascii_control, IntegratedTitleButtonStyle, Keyboard
 const NSViewLayerContentsPlacementTopLeft: NSInteger = 11;
 #[allow(non_upper_case_globals)]
 const NSViewLayerContentsRedrawDuringViewResize: NSInteger = 2;
+#[allow(non_upper_case_globals)]
+const NSWindowOcclusionStateVisible: NSUInteger = 1 << 1;
 
 #[link(name = "CoreGraphics", kind = "framework")]
 extern "C" {
@@ -241,25 +243,34 @@ mod cglbits {
 
     impl GlState {
         pub fn create(view: id) -> anyhow::Result<Self> {
+            let mut attributes = vec![
+                appkit::NSOpenGLPFAOpenGLProfile as u32,
+                appkit::NSOpenGLProfileVersion3_2Core as u32,
+                appkit::NSOpenGLPFAClosestPolicy as u32,
+                appkit::NSOpenGLPFAColorSize as u32,
+                32,
+                appkit::NSOpenGLPFAAlphaSize as u32,
+                8,
+                appkit::NSOpenGLPFADepthSize as u32,
+                24,
+                appkit::NSOpenGLPFAStencilSize as u32,
+                8,
+                appkit::NSOpenGLPFAAccelerated as u32,
+                appkit::NSOpenGLPFADoubleBuffer as u32,
+            ];
+            // Allowing offline renderers is what permits the system to
+            // keep us on the integrated GPU; leaving it out forces a
+            // switch to the discrete GPU.
+            if config::configuration().opengl_power_preference
+                == config::WebGpuPowerPreference::LowPower
+            {
+                attributes.push(appkit::NSOpenGLPFAAllowOfflineRenderers as u32);
+            }
+            attributes.push(0);
+
             log::trace!("Calling NSOpenGLPixelFormat::initWithAttributes");
             let pixel_format = unsafe {
-                StrongPtr::new(NSOpenGLPixelFormat::alloc(nil).initWithAttributes_(&[
-                    appkit::NSOpenGLPFAOpenGLProfile as u32,
-                    appkit::NSOpenGLProfileVersion3_2Core as u32,
-                    appkit::NSOpenGLPFAClosestPolicy as u32,
-                    appkit::NSOpenGLPFAColorSize as u32,
-                    32,
-                    appkit::NSOpenGLPFAAlphaSize as u32,
-                    8,
-                    appkit::NSOpenGLPFADepthSize as u32,
-                    24,
-                    appkit::NSOpenGLPFAStencilSize as u32,
-                    8,
-                    appkit::NSOpenGLPFAAllowOfflineRenderers as u32,
-                    appkit::NSOpenGLPFAAccelerated as u32,
-                    appkit::NSOpenGLPFADoubleBuffer as u32,
-                    0,
-                ]))
+                StrongPtr::new(NSOpenGLPixelFormat::alloc(nil).initWithAttributes_(&attributes))
             };
             log::trace!("NSOpenGLPixelFormat::initWithAttributes returned");
             ensure!(
@@ -289,9 +300,9 @@ mod cglbits {
 
                 gl_context.setView_(view);
 
-                // Explicitly disable vsync; we'll manage throttling frames at
-                // the application level
-                let swap_interval: cgl::GLint = 0;
+                // Unless vsync was requested, explicitly disable it;
+                // we'll manage throttling frames at the application level
+                let swap_interval: cgl::GLint = crate::configuration::swap_interval();
                 gl_context.setValues_forParameter_(
                     &swap_interval,
                     cocoa::appkit::NSOpenGLContextParameter::NSOpenGLCPSwapInterval,
@@ -492,6 +503,7 @@ impl Window {
                 tracking_rect_tag: 0,
                 hscroll_remainder: 0.,
                 vscroll_remainder: 0.,
+                vpixel_remainder: 0.,
                 last_wheel: Instant::now(),
                 key_is_down: None,
                 dead_pending: None,
@@ -777,6 +789,37 @@ impl WindowOps for Window {
         });
     }
 
+    fn request_attention(&self, attention: UserAttentionType) {
+        Connection::with_window_inner(self.id, move |inner| {
+            inner.request_attention(attention);
+            Ok(())
+        });
+    }
+
+    fn start_text_drag(&self, text: String) {
+        // This is called while the mouse event that started the drag is
+        // being dispatched, and mouse_common needs to see the text as
+        // soon as that returns, so we can't defer it to with_window_inner
+        if let Some(window_view) = WindowView::get_this(unsafe { &*self.ns_view }) {
+            window_view.pending_text_drag.replace(Some(text));
+        }
+    }
+
+    fn supports_text_drag(&self) -> bool {
+        true
+    }
+
+    fn set_badge(&self, badge: Option<String>) {
+        // The dock tile belongs to the application rather than
+        // to any one of its windows
+        let label = badge.map(|badge| nsstring(&badge));
+        unsafe {
+            let dock_tile: id = msg_send![appkit::NSApp(), dockTile];
+            let label: id = label.as_ref().map(|label| **label).unwrap_or(nil);
+            let () = msg_send![dock_tile, setBadgeLabel: label];
+        }
+    }
+
     fn set_window_level(&self, level: WindowLevel) {
         Connection::with_window_inner(self.id, move |inner| {
             inner.set_window_level(level);
@@ -824,6 +867,12 @@ impl WindowOps for Window {
         ClipboardContext::new().write(text).ok();
     }
 
+    fn set_clipboard_image(&self, _clipboard: Clipboard, png: Vec<u8>) {
+        if let Err(err) = ClipboardContext::new().write_png(&png) {
+            log::error!("Failed to copy image to clipboard: {:#}", err);
+        }
+    }
+
     fn toggle_fullscreen(&self) {
         Connection::with_window_inner(self.id, move |inner| {
             inner.toggle_fullscreen();
@@ -1197,6 +1246,20 @@ impl WindowInner {
         }
     }
 
+    fn request_attention(&mut self, attention: UserAttentionType) {
+        // NSRequestUserAttentionType; the request is cancelled
+        // automatically when the application is activated
+        const NS_CRITICAL_REQUEST: NSInteger = 0;
+        const NS_INFORMATIONAL_REQUEST: NSInteger = 10;
+        let request_type = match attention {
+            UserAttentionType::Critical => NS_CRITICAL_REQUEST,
+            UserAttentionType::Informational => NS_INFORMATIONAL_REQUEST,
+        };
+        unsafe {
+            let _: NSInteger = msg_send![appkit::NSApp(), requestUserAttention: request_type];
+        }
+    }
+
     fn set_window_level(&mut self, level: WindowLevel) {
         unsafe {
             NSWindow::setLevel_(*self.window, window_level_to_nswindow_level(level));
@@ -1417,6 +1480,8 @@ struct Inner {
     tracking_rect_tag: NSInteger,
     hscroll_remainder: f64,
     vscroll_remainder: f64,
+    /// Fractional pixels left over from precise vertical scrolling
+    vpixel_remainder: f64,
     last_wheel: Instant,
     /// We use this to avoid double-emitting events when
     /// procesing key-up events.
@@ -1694,6 +1759,11 @@ const WINDOW_CLS_NAME: &str = "WezTermWindow";
 
 struct WindowView {
     inner: Rc<RefCell<Inner>>,
+    /// Text to drag out of the window when the mouse is next
+    /// dragged; AppKit needs that mouse event to start the drag.
+    /// This is set from within the dispatch of a mouse event, while
+    /// inner is borrowed, so it is kept separately.
+    pending_text_drag: RefCell<Option<String>>,
 }
 
 pub fn superclass(this: &Object) -> &'static Class {
@@ -2202,6 +2272,7 @@ impl WindowView {
             modifiers = key_modifiers(nsevent.modifierFlags());
             screen_coords = NSEvent::mouseLocation(nsevent);
         }
+        let is_move = matches!(kind, MouseEventKind::Move);
         let event = MouseEvent {
             kind,
             coords: Point::new(coords.x as isize, coords.y as isize),
@@ -2213,9 +2284,49 @@ impl WindowView {
         if let Some(myself) = Self::get_this(this) {
             let mut inner = myself.inner.borrow_mut();
             inner.events.dispatch(WindowEvent::MouseEvent(event));
+            drop(inner);
+            // A drag that hasn't begun by the time that the button
+            // is released is abandoned
+            let pending_text_drag = myself.pending_text_drag.take().filter(|_| is_move);
+            if let Some(text) = pending_text_drag {
+                Self::begin_text_drag(view, nsevent, &text);
+            }
+        }
+    }
+
+    /// Starts a dragging session for text from within the handler
+    /// for the mouse event nsevent
+    fn begin_text_drag(view: id, nsevent: id, text: &str) {
+        unsafe {
+            let item: id = msg_send![class!(NSPasteboardItem), new];
+            let text = nsstring(text);
+            let _: BOOL = msg_send![item, setString: *text forType: appkit::NSStringPboardType];
+
+            let dragging_item: id = msg_send![class!(NSDraggingItem), alloc];
+            let dragging_item: id = msg_send![dragging_item, initWithPasteboardWriter: item];
+            let () = msg_send![item, release];
+
+            let point = NSView::convertPoint_fromView_(view, nsevent.locationInWindow(), nil);
+            let frame = NSRect::new(point, NSSize::new(1., 1.));
+            let () = msg_send![dragging_item, setDraggingFrame: frame contents: nil];
+
+            let items = NSArray::arrayWithObject(nil, dragging_item);
+            let _: id =
+                msg_send![view, beginDraggingSessionWithItems: items event: nsevent source: view];
+            let () = msg_send![dragging_item, release];
         }
     }
 
+    extern "C" fn dragging_source_operation_mask(
+        _this: &mut Object,
+        _sel: Sel,
+        _session: id,
+        _context: NSInteger,
+    ) -> NSUInteger {
+        // NSDragOperationCopy
+        1
+    }
+
     extern "C" fn mouse_up(this: &mut Object, _sel: Sel, nsevent: id) {
         Self::mouse_common(this, nsevent, MouseEventKind::Release(MousePress::Left));
     }
@@ -2241,6 +2352,53 @@ impl WindowView {
 
     extern "C" fn scroll_wheel(this: &mut Object, _sel: Sel, nsevent: id) {
         let precise = unsafe { nsevent.hasPreciseScrollingDeltas() } == YES;
+        let smooth = match Self::get_this(this) {
+            Some(myself) => myself.inner.borrow().config.smooth_scrolling,
+            None => false,
+        };
+
+        // When smooth scrolling is disabled, the precise deltas are
+        // converted to lines below, as they always have been
+        if precise && smooth {
+            let vert = unsafe { nsevent.scrollingDeltaY() };
+            let horz = unsafe { nsevent.scrollingDeltaX() };
+            if vert.abs() > horz.abs() {
+                // Report the distance in backing pixels so that the
+                // terminal can scroll smoothly.  The system generates
+                // momentum events of its own after the fingers are lifted,
+                // so we don't need to send PixelScrollEnd.
+                let pixels = unsafe {
+                    let rect = NSRect::new(NSPoint::new(0., 0.), NSSize::new(0., vert));
+                    f64::copysign(
+                        NSView::convertRectToBacking(this as id, rect).size.height,
+                        vert,
+                    )
+                };
+                let pixels = match Self::get_this(this) {
+                    Some(myself) => {
+                        let mut inner = myself.inner.borrow_mut();
+                        if pixels.signum() != inner.vpixel_remainder.signum() {
+                            inner.vpixel_remainder = 0.;
+                        }
+                        let pixels = pixels + inner.vpixel_remainder;
+                        inner.vpixel_remainder = pixels.fract();
+                        inner.last_wheel = Instant::now();
+                        pixels.trunc()
+                    }
+                    None => pixels.trunc(),
+                };
+                if pixels != 0. {
+                    Self::mouse_common(
+                        this,
+                        nsevent,
+                        MouseEventKind::VertPixelScroll(
+                            pixels.clamp(i16::MIN.into(), i16::MAX.into()) as i16,
+                        ),
+                    );
+                }
+                return;
+            }
+        }
         let scale = if precise {
             // Devices with precise deltas report number of pixels scrolled.
             // At this layer we don't know how many pixels comprise a cell
@@ -2758,6 +2916,13 @@ impl WindowView {
         }
     }
 
+    extern "C" fn did_change_occlusion_state(this: &mut Object, _sel: Sel, notification: id) {
+        log::trace!("did_change_occlusion_state");
+        // Visibility is reported as part of the window state,
+        // so we treat this as a (possibly size-preserving) resize
+        Self::did_resize(this, sel!(windowDidResize:), notification);
+    }
+
     extern "C" fn will_start_live_resize(this: &mut Object, _sel: Sel, _notification: id) {
         if let Some(this) = Self::get_this(this) {
             let mut inner = this.inner.borrow_mut();
@@ -2835,6 +3000,19 @@ impl WindowView {
                 _ => WindowState::default(),
             };
 
+            // Minimized windows, windows on another space and windows that
+            // are entirely covered by other windows are not visible
+            let is_visible = inner.window.as_ref().map_or(true, |window| {
+                let window = window.load();
+                let occlusion: NSUInteger = unsafe { msg_send![*window, occlusionState] };
+                occlusion & NSWindowOcclusionStateVisible != 0
+            });
+            let visibility_state = if is_visible {
+                WindowState::default()
+            } else {
+                WindowState::HIDDEN
+            };
+
             let dpi = inner
                 .window
                 .as_ref()
@@ -2851,7 +3029,7 @@ impl WindowView {
                     pixel_height: height as usize,
                     dpi,
                 },
-                window_state: screen_state | level_state,
+                window_state: screen_state | level_state | visibility_state,
                 live_resizing,
             });
         }
@@ -3026,6 +3204,7 @@ impl WindowView {
 
         let view = Box::into_raw(Box::new(Self {
             inner: Rc::clone(&inner),
+            pending_text_drag: RefCell::new(None),
         }));
 
         unsafe {
@@ -3139,6 +3318,10 @@ impl WindowView {
                 sel!(windowDidChangeScreen:),
                 Self::did_change_screen as extern "C" fn(&mut Object, Sel, id),
             );
+            cls.add_method(
+                sel!(windowDidChangeOcclusionState:),
+                Self::did_change_occlusion_state as extern "C" fn(&mut Object, Sel, id),
+            );
 
             cls.add_method(
                 sel!(windowDidBecomeKey:),
@@ -3157,6 +3340,12 @@ impl WindowView {
                 sel!(mouseDragged:),
                 Self::mouse_moved_or_dragged as extern "C" fn(&mut Object, Sel, id),
             );
+            // NSDraggingSource, for dragging text out of the window
+            cls.add_method(
+                sel!(draggingSession:sourceOperationMaskForDraggingContext:),
+                Self::dragging_source_operation_mask
+                    as extern "C" fn(&mut Object, Sel, id, NSInteger) -> NSUInteger,
+            );
             cls.add_method(
                 sel!(rightMouseDragged:),
                 Self::mouse_moved_or_dragged as extern "C" fn(&mut Object, Sel, id),
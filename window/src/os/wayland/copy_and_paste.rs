@@ -12,7 +12,7 @@ use wayland_protocols::wp::primary_selection::zv1::client::zwp_primary_selection
 
 use crate::{Clipboard, ConnectionOps};
 
-use super::data_device::TEXT_MIME_TYPE;
+use super::data_device::{PNG_MIME_TYPE, TEXT_MIME_TYPE};
 use super::state::WaylandState;
 
 #[derive(Default)]
@@ -89,11 +89,40 @@ impl CopyAndPaste {
                     .data_device_manager_state
                     .create_copy_paste_source(&qh, vec![TEXT_MIME_TYPE]);
                 source.set_selection(data_device.as_ref().unwrap(), last_serial);
-                wayland_state.copy_paste_source.replace((source, data));
+                wayland_state.copy_paste_source.replace((
+                    source,
+                    TEXT_MIME_TYPE,
+                    data.into_bytes(),
+                ));
             }
         }
     }
 
+    /// Places a PNG encoded image in the clipboard.
+    /// The primary selection only carries text, so images
+    /// are always placed in the clipboard.
+    pub(super) fn set_clipboard_image(&mut self, png: Vec<u8>) {
+        let conn = crate::Connection::get().unwrap().wayland();
+        let qh = conn.event_queue.borrow().handle();
+        let mut wayland_state = conn.wayland_state.borrow_mut();
+        let last_serial = *wayland_state.last_serial.borrow();
+
+        let data_device = match wayland_state.data_device.as_ref() {
+            Some(data_device) => data_device,
+            None => {
+                log::warn!("set_clipboard_image: no data device");
+                return;
+            }
+        };
+        let source = wayland_state
+            .data_device_manager_state
+            .create_copy_paste_source(&qh, vec![PNG_MIME_TYPE]);
+        source.set_selection(data_device, last_serial);
+        wayland_state
+            .copy_paste_source
+            .replace((source, PNG_MIME_TYPE, png));
+    }
+
     pub(super) fn confirm_selection(&mut self, offer: SelectionOffer) {
         self.data_offer.replace(offer);
     }
@@ -111,8 +140,8 @@ impl WaylandState {
     }
 }
 
-pub(super) fn write_selection_to_pipe(fd: WritePipe, text: &str) {
-    if let Err(e) = write_pipe_with_timeout(fd, text.as_bytes()) {
+pub(super) fn write_selection_to_pipe(fd: WritePipe, data: &[u8]) {
+    if let Err(e) = write_pipe_with_timeout(fd, data) {
         log::error!("while sending primary selection to pipe: {}", e);
     }
 }
@@ -182,7 +211,7 @@ impl PrimarySelectionSourceHandler for WaylandState {
             if ps_source.inner() != source {
                 return;
             }
-            write_selection_to_pipe(write_pipe, data);
+            write_selection_to_pipe(write_pipe, data.as_bytes());
         }
     }
 
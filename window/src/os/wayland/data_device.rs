@@ -17,6 +17,7 @@ use super::state::WaylandState;
 
 pub(super) const TEXT_MIME_TYPE: &str = "text/plain;charset=utf-8";
 pub(super) const URI_MIME_TYPE: &str = "text/uri-list";
+pub(super) const PNG_MIME_TYPE: &str = "image/png";
 
 impl DataDeviceHandler for WaylandState {
     fn enter(
@@ -165,7 +166,7 @@ impl DataOfferHandler for WaylandState {
     }
 }
 
-// We seem to to ignore all events other than sending_request and cancelled
+// We ignore all events other than send_request, cancelled and dnd_finished
 impl DataSourceHandler for WaylandState {
     fn accept_mime(
         &mut self,
@@ -184,15 +185,23 @@ impl DataSourceHandler for WaylandState {
         mime: String,
         fd: WritePipe,
     ) {
+        if let Some((cp_source, cp_mime, data)) = &self.copy_paste_source {
+            if cp_source.inner() == source {
+                if mime == *cp_mime {
+                    write_selection_to_pipe(fd, data);
+                }
+                return;
+            }
+        }
+
         if mime != TEXT_MIME_TYPE {
             return;
         }
 
-        if let Some((cp_source, data)) = &self.copy_paste_source {
-            if cp_source.inner() != source {
-                return;
+        if let Some((drag_source, data)) = &self.drag_source {
+            if drag_source.inner() == source {
+                write_selection_to_pipe(fd, data.as_bytes());
             }
-            write_selection_to_pipe(fd, data);
         }
     }
 
@@ -202,7 +211,14 @@ impl DataSourceHandler for WaylandState {
         _qh: &wayland_client::QueueHandle<Self>,
         source: &wayland_client::protocol::wl_data_source::WlDataSource,
     ) {
-        self.copy_paste_source.take();
+        match &self.drag_source {
+            Some((drag_source, _)) if drag_source.inner() == source => {
+                self.drag_source.take();
+            }
+            _ => {
+                self.copy_paste_source.take();
+            }
+        }
         source.destroy();
     }
 
@@ -218,8 +234,14 @@ impl DataSourceHandler for WaylandState {
         &mut self,
         _conn: &wayland_client::Connection,
         _qh: &wayland_client::QueueHandle<Self>,
-        _source: &wayland_client::protocol::wl_data_source::WlDataSource,
+        source: &wayland_client::protocol::wl_data_source::WlDataSource,
     ) {
+        if let Some((drag_source, _)) = &self.drag_source {
+            if drag_source.inner() == source {
+                self.drag_source.take();
+                source.destroy();
+            }
+        }
     }
 
     fn action(
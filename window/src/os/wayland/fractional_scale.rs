@@ -0,0 +1,128 @@
+//! Implements wp_fractional_scale_v1 together with wp_viewporter so that
+//! we can render at the non-integer scale that the compositor prefers
+//! for a surface, rather than rendering at the next integer scale and
+//! having the compositor downscale (and blur) the result.
+use smithay_client_toolkit::globals::GlobalData;
+use wayland_client::globals::{BindError, GlobalList};
+use wayland_client::protocol::wl_surface::WlSurface;
+use wayland_client::{Dispatch, Proxy, QueueHandle};
+use wayland_protocols::wp::fractional_scale::v1::client::wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1;
+use wayland_protocols::wp::fractional_scale::v1::client::wp_fractional_scale_v1::{
+    Event as FractionalScaleEvent, WpFractionalScaleV1,
+};
+use wayland_protocols::wp::viewporter::client::wp_viewport::WpViewport;
+use wayland_protocols::wp::viewporter::client::wp_viewporter::WpViewporter;
+
+use super::state::WaylandState;
+use super::WaylandConnection;
+
+/// The preferred_scale event expresses the scale as a fraction
+/// with this denominator
+const SCALE_DENOMINATOR: f64 = 120.;
+
+pub(super) struct FractionalScaleState {
+    manager: WpFractionalScaleManagerV1,
+    viewporter: WpViewporter,
+}
+
+impl FractionalScaleState {
+    /// Both globals are required: the fractional scale is only
+    /// useful if we can tell the compositor the logical size of
+    /// the buffer that we render at that scale.
+    pub(super) fn bind(
+        globals: &GlobalList,
+        queue_handle: &QueueHandle<WaylandState>,
+    ) -> Result<Self, BindError> {
+        let manager = globals.bind(queue_handle, 1..=1, GlobalData)?;
+        let viewporter = globals.bind(queue_handle, 1..=1, GlobalData)?;
+        Ok(Self {
+            manager,
+            viewporter,
+        })
+    }
+
+    /// Creates the per-surface objects for the window identified
+    /// by window_id
+    pub(super) fn create_for_surface(
+        &self,
+        surface: &WlSurface,
+        window_id: usize,
+        queue_handle: &QueueHandle<WaylandState>,
+    ) -> (WpFractionalScaleV1, WpViewport) {
+        let fractional_scale = self.manager.get_fractional_scale(
+            surface,
+            queue_handle,
+            FractionalScaleData { window_id },
+        );
+        let viewport = self
+            .viewporter
+            .get_viewport(surface, queue_handle, GlobalData);
+        (fractional_scale, viewport)
+    }
+}
+
+pub(super) struct FractionalScaleData {
+    window_id: usize,
+}
+
+impl Dispatch<WpFractionalScaleManagerV1, GlobalData, WaylandState> for FractionalScaleState {
+    fn event(
+        _state: &mut WaylandState,
+        _proxy: &WpFractionalScaleManagerV1,
+        _event: <WpFractionalScaleManagerV1 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &wayland_client::Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // No events from WpFractionalScaleManagerV1
+        unreachable!();
+    }
+}
+
+impl Dispatch<WpViewporter, GlobalData, WaylandState> for FractionalScaleState {
+    fn event(
+        _state: &mut WaylandState,
+        _proxy: &WpViewporter,
+        _event: <WpViewporter as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &wayland_client::Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // No events from WpViewporter
+        unreachable!();
+    }
+}
+
+impl Dispatch<WpViewport, GlobalData, WaylandState> for FractionalScaleState {
+    fn event(
+        _state: &mut WaylandState,
+        _proxy: &WpViewport,
+        _event: <WpViewport as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &wayland_client::Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        // No events from WpViewport
+        unreachable!();
+    }
+}
+
+impl Dispatch<WpFractionalScaleV1, FractionalScaleData, WaylandState> for FractionalScaleState {
+    fn event(
+        _state: &mut WaylandState,
+        _proxy: &WpFractionalScaleV1,
+        event: <WpFractionalScaleV1 as Proxy>::Event,
+        data: &FractionalScaleData,
+        _conn: &wayland_client::Connection,
+        _qhandle: &QueueHandle<WaylandState>,
+    ) {
+        log::trace!("WpFractionalScaleV1 event: {event:?}");
+        if let FractionalScaleEvent::PreferredScale { scale } = event {
+            let scale = scale as f64 / SCALE_DENOMINATOR;
+            WaylandConnection::with_window_inner(data.window_id, move |inner| {
+                inner.set_fractional_scale(scale);
+                Ok(())
+            });
+        }
+    }
+}
@@ -9,6 +9,7 @@ pub use connection::*;
 pub use output::*;
 mod copy_and_paste;
 mod drag_and_drop;
+mod fractional_scale;
 // mod frame;
 mod data_device;
 mod keyboard;
@@ -8,7 +8,7 @@ use smithay_client_toolkit::seat::pointer::{
     PointerData, PointerDataExt, PointerEvent, PointerEventKind, PointerHandler,
 };
 use wayland_client::backend::ObjectId;
-use wayland_client::protocol::wl_pointer::{ButtonState, WlPointer};
+use wayland_client::protocol::wl_pointer::{AxisSource, ButtonState, WlPointer};
 use wayland_client::protocol::wl_seat::WlSeat;
 use wayland_client::{Connection, Proxy, QueueHandle};
 use wezterm_input_types::MousePress;
@@ -45,6 +45,9 @@ impl PointerHandler for WaylandState {
                 *self.last_serial.borrow_mut() = serial;
                 pstate.serial = serial;
             }
+            if let PointerEventKind::Press { serial, .. } = &evt.kind {
+                *self.last_button_press_serial.borrow_mut() = *serial;
+            }
             if let Some(pending) = self
                 .surface_to_pending
                 .get(&self.active_surface_id.borrow().as_ref().unwrap())
@@ -96,6 +99,9 @@ pub struct PendingMouse {
     surface_coords: Option<(f64, f64)>,
     button: Vec<(MousePress, ButtonState)>,
     scroll: Option<(f64, f64)>,
+    /// Vertical scrolling from a touchpad, in surface coordinates
+    pixel_scroll: Option<f64>,
+    pixel_scroll_end: bool,
     in_window: bool,
 }
 
@@ -109,6 +115,8 @@ impl PendingMouse {
             copy_and_paste: Arc::clone(copy_and_paste),
             button: vec![],
             scroll: None,
+            pixel_scroll: None,
+            pixel_scroll_end: false,
             surface_coords: None,
             in_window: false,
         }))
@@ -157,12 +165,25 @@ impl PendingMouse {
             PointerEventKind::Axis {
                 horizontal,
                 vertical,
+                source,
                 ..
             } => {
-                let changed = self.scroll.is_none();
+                let changed =
+                    self.scroll.is_none() && self.pixel_scroll.is_none() && !self.pixel_scroll_end;
                 let (x, y) = self.scroll.take().unwrap_or((0., 0.));
-                self.scroll
-                    .replace((x + horizontal.absolute, y + vertical.absolute));
+                // Touchpad scrolls are reported in pixels for smooth
+                // scrolling, otherwise they are converted to lines
+                // like any other scroll
+                if source == Some(AxisSource::Finger) && config::configuration().smooth_scrolling {
+                    self.scroll.replace((x + horizontal.absolute, y));
+                    *self.pixel_scroll.get_or_insert(0.) += vertical.absolute;
+                } else {
+                    self.scroll
+                        .replace((x + horizontal.absolute, y + vertical.absolute));
+                }
+                if vertical.stop {
+                    self.pixel_scroll_end = true;
+                }
                 changed
             }
         }
@@ -185,6 +206,14 @@ impl PendingMouse {
         pending.lock().unwrap().scroll.take()
     }
 
+    pub(super) fn pixel_scroll(pending: &Arc<Mutex<Self>>) -> Option<f64> {
+        pending.lock().unwrap().pixel_scroll.take()
+    }
+
+    pub(super) fn pixel_scroll_end(pending: &Arc<Mutex<Self>>) -> bool {
+        std::mem::take(&mut pending.lock().unwrap().pixel_scroll_end)
+    }
+
     pub(super) fn in_window(pending: &Arc<Mutex<Self>>) -> bool {
         pending.lock().unwrap().in_window
     }
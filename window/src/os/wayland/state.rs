@@ -3,9 +3,12 @@ use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
+use smithay_client_toolkit::activation::{
+    ActivationHandler, ActivationState, RequestData, RequestDataExt,
+};
 use smithay_client_toolkit::compositor::{CompositorState, SurfaceData};
 use smithay_client_toolkit::data_device_manager::data_device::DataDevice;
-use smithay_client_toolkit::data_device_manager::data_source::CopyPasteSource;
+use smithay_client_toolkit::data_device_manager::data_source::{CopyPasteSource, DragSource};
 use smithay_client_toolkit::data_device_manager::DataDeviceManagerState;
 use smithay_client_toolkit::globals::GlobalData;
 use smithay_client_toolkit::output::{OutputHandler, OutputState};
@@ -23,18 +26,23 @@ use smithay_client_toolkit::shm::slot::SlotPool;
 use smithay_client_toolkit::shm::{Shm, ShmHandler};
 use smithay_client_toolkit::subcompositor::SubcompositorState;
 use smithay_client_toolkit::{
-    delegate_compositor, delegate_data_device, delegate_output, delegate_pointer, delegate_primary_selection, delegate_registry, delegate_seat, delegate_shm, delegate_subcompositor, delegate_xdg_shell, delegate_xdg_window, registry_handlers
+    delegate_activation, delegate_compositor, delegate_data_device, delegate_output, delegate_pointer, delegate_primary_selection, delegate_registry, delegate_seat, delegate_shm, delegate_subcompositor, delegate_xdg_shell, delegate_xdg_window, registry_handlers
 };
 use wayland_client::backend::ObjectId;
 use wayland_client::globals::GlobalList;
 use wayland_client::protocol::wl_keyboard::WlKeyboard;
 use wayland_client::protocol::wl_output::WlOutput;
 use wayland_client::{delegate_dispatch, Connection, QueueHandle};
+use wayland_protocols::wp::fractional_scale::v1::client::wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1;
+use wayland_protocols::wp::fractional_scale::v1::client::wp_fractional_scale_v1::WpFractionalScaleV1;
 use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
 use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_v3::ZwpTextInputV3;
+use wayland_protocols::wp::viewporter::client::wp_viewport::WpViewport;
+use wayland_protocols::wp::viewporter::client::wp_viewporter::WpViewporter;
 
 use crate::x11::KeyboardWithFallback;
 
+use super::fractional_scale::{FractionalScaleData, FractionalScaleState};
 use super::inputhandler::{TextInputData, TextInputState};
 use super::pointer::{PendingMouse, PointerUserData};
 use super::{OutputManagerData, OutputManagerState, SurfaceUserData, WaylandWindowInner};
@@ -50,10 +58,15 @@ pub(super) struct WaylandState {
     pub(super) output_manager: Option<OutputManagerState>,
     pub(super) seat: SeatState,
     pub(super) xdg: XdgShell,
+    pub(super) activation: Option<ActivationState>,
+    pub(super) fractional_scale: Option<FractionalScaleState>,
     pub(super) windows: RefCell<HashMap<usize, Rc<RefCell<WaylandWindowInner>>>>,
 
     pub(super) active_surface_id: RefCell<Option<ObjectId>>,
     pub(super) last_serial: RefCell<u32>,
+    /// The serial of the most recent pointer button press; a drag
+    /// must be started with the serial of the press that began it
+    pub(super) last_button_press_serial: RefCell<u32>,
     pub(super) keyboard: Option<WlKeyboard>,
     pub(super) keyboard_mapper: Option<KeyboardWithFallback>,
     pub(super) key_repeat_delay: i32,
@@ -65,7 +78,10 @@ pub(super) struct WaylandState {
 
     pub(super) data_device_manager_state: DataDeviceManagerState,
     pub(super) data_device: Option<DataDevice>,
-    pub(super) copy_paste_source: Option<(CopyPasteSource, String)>,
+    /// The source for the clipboard, with the mime type of its data
+    pub(super) copy_paste_source: Option<(CopyPasteSource, &'static str, Vec<u8>)>,
+    /// The text that we are dragging out of one of our windows
+    pub(super) drag_source: Option<(DragSource, String)>,
     pub(super) primary_selection_manager: Option<PrimarySelectionManagerState>,
     pub(super) primary_selection_device: Option<PrimarySelectionDevice>,
     pub(super) primary_selection_source: Option<(PrimarySelectionSource, String)>,
@@ -96,8 +112,11 @@ impl WaylandState {
             windows: RefCell::new(HashMap::new()),
             seat: SeatState::new(globals, qh),
             xdg: XdgShell::bind(globals, qh)?,
+            activation: ActivationState::bind(globals, qh).ok(),
+            fractional_scale: FractionalScaleState::bind(globals, qh).ok(),
             active_surface_id: RefCell::new(None),
             last_serial: RefCell::new(0),
+            last_button_press_serial: RefCell::new(0),
             keyboard: None,
             keyboard_mapper: None,
             key_repeat_rate: 25,
@@ -108,6 +127,7 @@ impl WaylandState {
             data_device_manager_state: DataDeviceManagerState::bind(globals, qh)?,
             data_device: None,
             copy_paste_source: None,
+            drag_source: None,
             primary_selection_manager: PrimarySelectionManagerState::bind(globals, qh).ok(),
             primary_selection_device: None,
             primary_selection_source: None,
@@ -150,6 +170,16 @@ impl OutputHandler for WaylandState {
     }
 }
 
+impl ActivationHandler for WaylandState {
+    type RequestData = RequestData;
+
+    fn new_token(&mut self, token: String, data: &Self::RequestData) {
+        if let (Some(activation), Some(surface)) = (&self.activation, data.surface()) {
+            activation.activate::<WaylandState>(surface, token);
+        }
+    }
+}
+
 delegate_registry!(WaylandState);
 
 delegate_shm!(WaylandState);
@@ -167,6 +197,8 @@ delegate_pointer!(WaylandState, pointer: [PointerUserData]);
 delegate_xdg_shell!(WaylandState);
 delegate_xdg_window!(WaylandState);
 
+delegate_activation!(WaylandState);
+
 delegate_primary_selection!(WaylandState);
 
 delegate_dispatch!(WaylandState: [ZwpTextInputManagerV3: GlobalData] => TextInputState);
@@ -175,3 +207,8 @@ delegate_dispatch!(WaylandState: [ZwpTextInputV3: TextInputData] => TextInputSta
 delegate_dispatch!(WaylandState: [ZwlrOutputManagerV1: GlobalData] => OutputManagerState);
 delegate_dispatch!(WaylandState: [ZwlrOutputHeadV1: OutputManagerData] => OutputManagerState);
 delegate_dispatch!(WaylandState: [ZwlrOutputModeV1: OutputManagerData] => OutputManagerState);
+
+delegate_dispatch!(WaylandState: [WpFractionalScaleManagerV1: GlobalData] => FractionalScaleState);
+delegate_dispatch!(WaylandState: [WpFractionalScaleV1: FractionalScaleData] => FractionalScaleState);
+delegate_dispatch!(WaylandState: [WpViewporter: GlobalData] => FractionalScaleState);
+delegate_dispatch!(WaylandState: [WpViewport: GlobalData] => FractionalScaleState);
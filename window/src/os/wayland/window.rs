@@ -13,12 +13,14 @@ use std::time::{Duration, Instant};
 use anyhow::{anyhow, bail};
 use async_io::Timer;
 use async_trait::async_trait;
+use config::window::UserAttentionType;
 use config::ConfigHandle;
 use promise::{Future, Promise};
 use raw_window_handle::{
     HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle,
     WaylandDisplayHandle, WaylandWindowHandle,
 };
+use smithay_client_toolkit::activation::RequestData;
 use smithay_client_toolkit::compositor::{CompositorHandler, SurfaceData, SurfaceDataExt};
 use smithay_client_toolkit::data_device_manager::ReadPipe;
 use smithay_client_toolkit::reexports::csd_frame::{
@@ -34,11 +36,14 @@ use smithay_client_toolkit::shell::xdg::window::{
 use smithay_client_toolkit::shell::xdg::XdgSurface;
 use smithay_client_toolkit::shell::WaylandSurface;
 use wayland_client::protocol::wl_callback::WlCallback;
+use wayland_client::protocol::wl_data_device_manager::DndAction;
 use wayland_client::protocol::wl_keyboard::{Event as WlKeyboardEvent, KeyState};
 use wayland_client::protocol::wl_pointer::{ButtonState, WlPointer};
 use wayland_client::protocol::wl_surface::WlSurface;
 use wayland_client::{Connection as WConnection, Proxy};
 use wayland_egl::{is_available as egl_is_available, WlEglSurface};
+use wayland_protocols::wp::fractional_scale::v1::client::wp_fractional_scale_v1::WpFractionalScaleV1;
+use wayland_protocols::wp::viewporter::client::wp_viewport::WpViewport;
 use wezterm_font::FontConfiguration;
 use wezterm_input_types::{
     KeyboardLedStatus, Modifiers, MouseButtons, MouseEvent, MouseEventKind, MousePress,
@@ -54,6 +59,7 @@ use crate::{
 };
 
 use super::copy_and_paste::CopyAndPaste;
+use super::data_device::TEXT_MIME_TYPE;
 use super::pointer::{PendingMouse, PointerUserData};
 use super::state::WaylandState;
 
@@ -197,6 +203,15 @@ impl WaylandWindow {
             compositor.create_surface_with_data(&qh, surface_data)
         };
 
+        let (fractional_scale, viewport) = match &conn.wayland_state.borrow().fractional_scale {
+            Some(fractional_scale) => {
+                let (fractional_scale, viewport) =
+                    fractional_scale.create_for_surface(&surface, window_id, &qh);
+                (Some(fractional_scale), Some(viewport))
+            }
+            None => (None, None),
+        };
+
         let ResolvedGeometry {
             x: _,
             y: _,
@@ -221,7 +236,9 @@ impl WaylandWindow {
 
         let decor_mode = if decorations == WindowDecorations::NONE {
             None
-        } else if decorations == WindowDecorations::default() {
+        } else if decorations == WindowDecorations::default()
+            && config.wayland_prefer_server_side_decorations
+        {
             Some(DecorationMode::Server)
         } else {
             Some(DecorationMode::Client)
@@ -273,6 +290,9 @@ impl WaylandWindow {
         let inner = Rc::new(RefCell::new(WaylandWindowInner {
             events: WindowEventSender::new(event_handler),
             surface_factor: 1.0,
+            fractional_scale,
+            viewport,
+            preferred_scale: None,
             copy_and_paste,
             invalidated: false,
             window: Some(window),
@@ -284,9 +304,12 @@ impl WaylandWindow {
             mouse_buttons: MouseButtons::NONE,
             hscroll_remainder: 0.0,
             vscroll_remainder: 0.0,
+            vpixel_remainder: 0.0,
+            pixel_scrolling: false,
 
             modifiers: Modifiers::NONE,
             leds: KeyboardLedStatus::empty(),
+            keyboard_layout: None,
 
             key_repeat: None,
             pending_event,
@@ -398,6 +421,13 @@ impl WindowOps for WaylandWindow {
         });
     }
 
+    fn request_attention(&self, attention: UserAttentionType) {
+        WaylandConnection::with_window_inner(self.0, move |inner| {
+            inner.request_attention(attention);
+            Ok(())
+        });
+    }
+
     fn set_inner_size(&self, width: usize, height: usize) {
         WaylandConnection::with_window_inner(self.0, move |inner| {
             inner.set_inner_size(width, height);
@@ -452,6 +482,28 @@ impl WindowOps for WaylandWindow {
             Ok(())
         });
     }
+
+    fn set_clipboard_image(&self, _clipboard: Clipboard, png: Vec<u8>) {
+        WaylandConnection::with_window_inner(self.0, move |inner| {
+            inner
+                .copy_and_paste
+                .lock()
+                .unwrap()
+                .set_clipboard_image(png.clone());
+            Ok(())
+        });
+    }
+
+    fn start_text_drag(&self, text: String) {
+        WaylandConnection::with_window_inner(self.0, move |inner| {
+            inner.start_text_drag(text);
+            Ok(())
+        });
+    }
+
+    fn supports_text_drag(&self) -> bool {
+        true
+    }
 }
 #[derive(Default, Clone, Debug)]
 pub(crate) struct PendingEvent {
@@ -508,6 +560,10 @@ pub(crate) fn read_pipe_with_timeout(mut file: ReadPipe) -> anyhow::Result<Strin
 pub struct WaylandWindowInner {
     pub(crate) events: WindowEventSender,
     surface_factor: f64,
+    fractional_scale: Option<WpFractionalScaleV1>,
+    viewport: Option<WpViewport>,
+    /// The scale most recently reported by wp_fractional_scale_v1
+    preferred_scale: Option<f64>,
     copy_and_paste: Arc<Mutex<CopyAndPaste>>,
     window: Option<XdgWindow>,
     pub(super) window_frame: FallbackFrame<WaylandState>,
@@ -518,8 +574,15 @@ pub struct WaylandWindowInner {
     mouse_buttons: MouseButtons,
     hscroll_remainder: f64,
     vscroll_remainder: f64,
+    /// Fractional pixels left over from touchpad scrolling
+    vpixel_remainder: f64,
+    /// Whether VertPixelScroll events have been sent since the last
+    /// PixelScrollEnd
+    pixel_scrolling: bool,
     modifiers: Modifiers,
     leds: KeyboardLedStatus,
+    /// The keyboard layout most recently reported to the gui
+    keyboard_layout: Option<String>,
     pub(super) key_repeat: Option<(u32, Arc<Mutex<KeyRepeatState>>)>,
     pub(super) pending_event: Arc<Mutex<PendingEvent>>,
     pub(super) pending_mouse: Arc<Mutex<PendingMouse>>,
@@ -543,6 +606,12 @@ pub struct WaylandWindowInner {
 impl WaylandWindowInner {
     fn close(&mut self) {
         self.events.dispatch(WindowEvent::Destroyed);
+        if let Some(fractional_scale) = self.fractional_scale.take() {
+            fractional_scale.destroy();
+        }
+        if let Some(viewport) = self.viewport.take() {
+            viewport.destroy();
+        }
         self.window.take();
     }
 
@@ -635,7 +704,27 @@ impl WaylandWindowInner {
     }
 
     fn surface_to_pixels(&self, surface: i32) -> i32 {
-        (surface as f64 * self.get_dpi_factor()).ceil() as i32
+        // wp_fractional_scale_v1 specifies that the buffer size is
+        // rounded to the nearest pixel; for integer scales this is
+        // the same as the product
+        (surface as f64 * self.get_dpi_factor()).round() as i32
+    }
+
+    /// Called when the compositor tells us the preferred fractional
+    /// scale for our surface.  We treat it like a dpi change, which
+    /// will re-run configure and resize the buffer to match.
+    pub(super) fn set_fractional_scale(&mut self, scale: f64) {
+        if self.preferred_scale == Some(scale) {
+            return;
+        }
+        log::debug!("preferred fractional scale is now {scale}");
+        self.preferred_scale.replace(scale);
+        self.pending_event
+            .lock()
+            .unwrap()
+            .dpi
+            .replace((scale * crate::DEFAULT_DPI) as i32);
+        self.dispatch_pending_event();
     }
 
     fn pixels_to_surface(&self, pixels: i32) -> i32 {
@@ -649,6 +738,19 @@ impl WaylandWindowInner {
         self.events.dispatch(WindowEvent::DroppedFile(paths));
     }
 
+    /// Returns the screen coordinates for a point in the window.
+    /// Wayland doesn't reveal the position of a surface on the screen
+    /// to clients, so the window is treated as though it is at a fixed
+    /// offset; the result is consistent across all of the mouse events
+    /// for the window, which is what is needed to compute how far the
+    /// mouse moved during a drag.
+    fn screen_coords(&self, coords: Point) -> ScreenPoint {
+        ScreenPoint::new(
+            coords.x + self.dimensions.pixel_width as isize,
+            coords.y + self.dimensions.pixel_height as isize,
+        )
+    }
+
     pub(crate) fn dispatch_pending_mouse(&mut self) {
         let pending_mouse = Arc::clone(&self.pending_mouse);
 
@@ -661,10 +763,7 @@ impl WaylandWindowInner {
             let event = MouseEvent {
                 kind: MouseEventKind::Move,
                 coords,
-                screen_coords: ScreenPoint::new(
-                    coords.x + self.dimensions.pixel_width as isize,
-                    coords.y + self.dimensions.pixel_height as isize,
-                ),
+                screen_coords: self.screen_coords(coords),
                 mouse_buttons: self.mouse_buttons,
                 modifiers: self.modifiers,
             };
@@ -692,10 +791,7 @@ impl WaylandWindowInner {
                     _ => continue,
                 },
                 coords: self.last_mouse_coords,
-                screen_coords: ScreenPoint::new(
-                    self.last_mouse_coords.x + self.dimensions.pixel_width as isize,
-                    self.last_mouse_coords.y + self.dimensions.pixel_height as isize,
-                ),
+                screen_coords: self.screen_coords(self.last_mouse_coords),
                 mouse_buttons: self.mouse_buttons,
                 modifiers: self.modifiers,
             };
@@ -716,10 +812,7 @@ impl WaylandWindowInner {
                 let event = MouseEvent {
                     kind: MouseEventKind::HorzWheel(-discrete_x as i16),
                     coords: self.last_mouse_coords,
-                    screen_coords: ScreenPoint::new(
-                        self.last_mouse_coords.x + self.dimensions.pixel_width as isize,
-                        self.last_mouse_coords.y + self.dimensions.pixel_height as isize,
-                    ),
+                    screen_coords: self.screen_coords(self.last_mouse_coords),
                     mouse_buttons: self.mouse_buttons,
                     modifiers: self.modifiers,
                 };
@@ -736,17 +829,53 @@ impl WaylandWindowInner {
                 let event = MouseEvent {
                     kind: MouseEventKind::VertWheel(-discrete_y as i16),
                     coords: self.last_mouse_coords,
-                    screen_coords: ScreenPoint::new(
-                        self.last_mouse_coords.x + self.dimensions.pixel_width as isize,
-                        self.last_mouse_coords.y + self.dimensions.pixel_height as isize,
+                    screen_coords: self.screen_coords(self.last_mouse_coords),
+                    mouse_buttons: self.mouse_buttons,
+                    modifiers: self.modifiers,
+                };
+                self.events.dispatch(WindowEvent::MouseEvent(event));
+            }
+        }
+
+        if let Some(value) = PendingMouse::pixel_scroll(&pending_mouse) {
+            let factor = self.get_dpi_factor() as f64;
+
+            if value.signum() != self.vpixel_remainder.signum() {
+                self.vpixel_remainder = 0.0;
+            }
+            let scaled = (value * factor) + self.vpixel_remainder;
+            let discrete = scaled.trunc();
+            self.vpixel_remainder = scaled - discrete;
+            if discrete != 0. {
+                let event = MouseEvent {
+                    kind: MouseEventKind::VertPixelScroll(
+                        (-discrete).clamp(i16::MIN.into(), i16::MAX.into()) as i16,
                     ),
+                    coords: self.last_mouse_coords,
+                    screen_coords: self.screen_coords(self.last_mouse_coords),
                     mouse_buttons: self.mouse_buttons,
                     modifiers: self.modifiers,
                 };
                 self.events.dispatch(WindowEvent::MouseEvent(event));
+                self.pixel_scrolling = true;
             }
         }
 
+        // Wayland compositors leave it to the client to implement
+        // momentum, so let the gui know when the fingers are lifted
+        if PendingMouse::pixel_scroll_end(&pending_mouse) && self.pixel_scrolling {
+            self.pixel_scrolling = false;
+            self.vpixel_remainder = 0.0;
+            let event = MouseEvent {
+                kind: MouseEventKind::PixelScrollEnd,
+                coords: self.last_mouse_coords,
+                screen_coords: self.screen_coords(self.last_mouse_coords),
+                mouse_buttons: self.mouse_buttons,
+                modifiers: self.modifiers,
+            };
+            self.events.dispatch(WindowEvent::MouseEvent(event));
+        }
+
         if !PendingMouse::in_window(&pending_mouse) {
             self.events.dispatch(WindowEvent::MouseLeave);
             self.refresh_frame();
@@ -771,12 +900,31 @@ impl WaylandWindowInner {
                 self.window_state,
                 window_state
             );
+            let visibility_changed = self.window_state ^ window_state == WindowState::HIDDEN;
             self.window_state = window_state;
+            if visibility_changed {
+                // This doesn't come with a change in size, so the
+                // configure handling below won't report it for us
+                self.events.dispatch(WindowEvent::Resized {
+                    dimensions: self.dimensions,
+                    window_state: self.window_state,
+                    live_resizing: false,
+                });
+            }
+        }
+
+        let mut decorations_changed = false;
+        if let Some(ref window_config) = pending.window_configure {
+            decorations_changed = self.update_decoration_mode(window_config.decoration_mode);
+            self.window_frame.update_state(window_config.state);
+            self.window_frame
+                .update_wm_capabilities(window_config.capabilities);
         }
 
         if pending.configure.is_none() {
-            if pending.dpi.is_some() {
+            if pending.dpi.is_some() || decorations_changed {
                 // Synthesize a pending configure event for the dpi change
+                // or to make room for the frame
                 pending.configure.replace((
                     self.pixels_to_surface(self.dimensions.pixel_width as i32) as u32,
                     self.pixels_to_surface(self.dimensions.pixel_height as i32) as u32,
@@ -785,21 +933,22 @@ impl WaylandWindowInner {
             }
         }
 
-        if let Some(ref window_config) = pending.window_configure {
-            self.window_frame.update_state(window_config.state);
-            self.window_frame
-                .update_wm_capabilities(window_config.capabilities);
-        }
-
         if let Some((mut w, mut h)) = pending.configure.take() {
             log::trace!("Pending configure: w:{w}, h{h} -- {:?}", self.window);
             if self.window.is_some() {
                 let surface_udata = SurfaceUserData::from_wl(self.surface());
-                let factor = surface_udata.surface_data.scale_factor() as f64;
+                let factor = match self.preferred_scale {
+                    Some(scale) if self.viewport.is_some() => scale,
+                    _ => surface_udata.surface_data.scale_factor() as f64,
+                };
                 let old_dimensions = self.dimensions;
 
                 // FIXME: teach this how to resolve dpi_by_screen
-                let dpi = self.config.dpi.unwrap_or(factor * crate::DEFAULT_DPI) as usize;
+                let dpi = self
+                    .config
+                    .dpi
+                    .unwrap_or(factor * crate::DEFAULT_DPI)
+                    .round() as usize;
 
                 // Do this early because this affects surface_to_pixels/pixels_to_surface
                 self.dimensions.dpi = dpi;
@@ -876,7 +1025,16 @@ impl WaylandWindowInner {
                     if let Some(wegl_surface) = self.wegl_surface.as_mut() {
                         wegl_surface.resize(pixel_width, pixel_height, 0, 0);
                     }
-                    if self.surface_factor != factor {
+                    if let (Some(viewport), Some(_)) = (&self.viewport, self.preferred_scale) {
+                        // With a fractional scale, the buffer is always
+                        // at scale 1 and the viewport tells the compositor
+                        // the logical size that it maps to
+                        viewport.set_destination(w as i32, h as i32);
+                        if self.surface_factor != 1.0 {
+                            self.surface().set_buffer_scale(1);
+                            self.surface_factor = 1.0;
+                        }
+                    } else if self.surface_factor != factor {
                         let wayland_conn = Connection::get().unwrap().wayland();
                         let wayland_state = wayland_conn.wayland_state.borrow();
                         let mut pool = wayland_state.mem_pool.borrow_mut();
@@ -912,6 +1070,24 @@ impl WaylandWindowInner {
         }
     }
 
+    /// Reconciles our frame with the decoration mode that the compositor
+    /// decided upon, which may not be the one that we requested: not all
+    /// compositors implement xdg-decoration, and those that do may
+    /// insist on client side decorations.
+    /// Returns true if the frame was shown or hidden.
+    fn update_decoration_mode(&mut self, mode: DecorationMode) -> bool {
+        if self.config.window_decorations == WindowDecorations::NONE {
+            return false;
+        }
+        let hidden = mode == DecorationMode::Server;
+        if hidden == self.window_frame.is_hidden() {
+            return false;
+        }
+        log::debug!("compositor chose {mode:?} decorations");
+        self.window_frame.set_hidden(hidden);
+        true
+    }
+
     fn set_cursor(&mut self, cursor: Option<MouseCursor>) {
         let conn = Connection::get().unwrap().wayland();
         let state = conn.wayland_state.borrow_mut();
@@ -995,6 +1171,52 @@ impl WaylandWindowInner {
         self.title = Some(title);
     }
 
+    fn request_attention(&mut self, _attention: UserAttentionType) {
+        // xdg-activation has no notion of urgency.  Activating with a
+        // token that wasn't obtained from a recent input event isn't
+        // allowed to steal focus, so compositors instead mark the
+        // window as demanding attention.
+        let conn = WaylandConnection::get().unwrap().wayland();
+        let qh = conn.event_queue.borrow().handle();
+        let state = conn.wayland_state.borrow();
+        let activation = match &state.activation {
+            Some(activation) => activation,
+            None => {
+                log::debug!("request_attention: compositor doesn't support xdg-activation");
+                return;
+            }
+        };
+        activation.request_token(
+            &qh,
+            RequestData {
+                app_id: None,
+                seat_and_serial: None,
+                surface: Some(self.surface().clone()),
+            },
+        );
+    }
+
+    fn start_text_drag(&mut self, text: String) {
+        let conn = WaylandConnection::get().unwrap().wayland();
+        let qh = conn.event_queue.borrow().handle();
+        let mut state = conn.wayland_state.borrow_mut();
+        let serial = *state.last_button_press_serial.borrow();
+        let source = state.data_device_manager_state.create_drag_and_drop_source(
+            &qh,
+            vec![TEXT_MIME_TYPE],
+            DndAction::Copy,
+        );
+        let data_device = match state.data_device.as_ref() {
+            Some(data_device) => data_device,
+            None => {
+                log::debug!("start_text_drag: no data device");
+                return;
+            }
+        };
+        source.start_drag(data_device, self.surface(), None, serial);
+        state.drag_source.replace((source, text));
+    }
+
     fn set_resize_increments(&mut self, incr: ResizeIncrement) -> anyhow::Result<()> {
         self.resize_increments.replace(incr);
         Ok(())
@@ -1099,6 +1321,12 @@ impl WaylandWindowInner {
         mapper: &mut KeyboardWithFallback,
         event: WlKeyboardEvent,
     ) {
+        let check_layout = matches!(
+            event,
+            WlKeyboardEvent::Enter { .. }
+                | WlKeyboardEvent::Modifiers { .. }
+                | WlKeyboardEvent::Keymap { .. }
+        );
         match event {
             WlKeyboardEvent::Enter { keys, .. } => {
                 let key_codes = keys
@@ -1162,6 +1390,24 @@ impl WaylandWindowInner {
             }
             _ => {}
         }
+
+        if check_layout {
+            // Switching layouts is reported either as a change of group
+            // in the modifiers event, or as a whole new keymap
+            let layout = mapper.active_layout_name();
+            if self.keyboard_layout.as_ref() != Some(&layout) {
+                if self.keyboard_layout.is_some() {
+                    log::debug!(
+                        "keyboard layout changed from {:?} to {layout:?}",
+                        self.keyboard_layout
+                    );
+                    mapper.layout_changed();
+                }
+                self.keyboard_layout.replace(layout.clone());
+                self.events
+                    .dispatch(WindowEvent::KeyboardLayoutChanged(layout));
+            }
+        }
     }
 
     pub(super) fn frame_action(&mut self, pointer: &WlPointer, serial: u32, action: FrameAction) {
@@ -1252,6 +1498,11 @@ impl WaylandState {
                 if !((configure.state & fs_bits).is_empty()) {
                     state |= WindowState::MAXIMIZED;
                 }
+                // Compositors that support it tell us when we are
+                // minimized or entirely hidden from view
+                if configure.state.contains(SCTKWindowState::SUSPENDED) {
+                    state |= WindowState::HIDDEN;
+                }
 
                 log::debug!(
                     "Config: self.window_state={:?}, states: {:?} {:?}",
@@ -9,12 +9,14 @@ use config::ConfigHandle;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ffi::OsString;
-use std::os::windows::ffi::OsStringExt;
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::path::Path;
 use std::ptr::null_mut;
 use std::rc::Rc;
 use winapi::shared::minwindef::*;
 use winapi::shared::windef::*;
 use winapi::shared::winerror::{ERROR_INSUFFICIENT_BUFFER, ERROR_SUCCESS};
+use winapi::um::playsoundapi::{PlaySoundW, SND_ASYNC, SND_FILENAME, SND_NODEFAULT};
 use winapi::um::shellscalingapi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
 use winapi::um::winbase::INFINITE;
 use winapi::um::wingdi::{
@@ -100,6 +102,21 @@ impl ConnectionOps for Connection {
         }
     }
 
+    fn play_sound_file(&self, path: &Path) -> anyhow::Result<()> {
+        let wide_path: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let ok = unsafe {
+            PlaySoundW(
+                wide_path.as_ptr(),
+                null_mut(),
+                SND_FILENAME | SND_ASYNC | SND_NODEFAULT,
+            )
+        };
+        if ok == 0 {
+            anyhow::bail!("unable to play sound file {}", path.display());
+        }
+        Ok(())
+    }
+
     fn screens(&self) -> anyhow::Result<Screens> {
         let mut info = ScreenInfoHelper::new()?;
         info.enumerate();
@@ -4,11 +4,12 @@ use crate::parameters::{self, Parameters};
 use crate::{
     Appearance, Clipboard, DeadKeyStatus, Dimensions, Handled, KeyCode, KeyEvent, Modifiers,
     MouseButtons, MouseCursor, MouseEvent, MouseEventKind, MousePress, Point, RawKeyEvent, Rect,
-    RequestedWindowGeometry, ResolvedGeometry, ScreenPoint, ScreenRect, ULength, WindowDecorations,
-    WindowEvent, WindowEventSender, WindowOps, WindowState,
+    RequestedWindowGeometry, ResizeIncrement, ResolvedGeometry, ScreenPoint, ScreenRect, ULength,
+    WindowDecorations, WindowEvent, WindowEventSender, WindowOps, WindowState,
 };
 use anyhow::{bail, Context};
 use async_trait::async_trait;
+use config::window::{UserAttentionType, WindowProgress};
 use config::{ConfigHandle, ImePreeditRendering, SystemBackdrop};
 use lazy_static::lazy_static;
 use promise::Future;
@@ -121,11 +122,14 @@ pub(crate) struct WindowInner {
     maximize_button_position: Option<ScreenRect>,
 
     keyboard_info: KeyboardLayoutInfo,
+    /// The keyboard layout most recently reported to the gui
+    keyboard_layout: Option<String>,
     appearance: Appearance,
 
     config: ConfigHandle,
     paint_throttled: bool,
     invalidated: bool,
+    resize_increments: Option<ResizeIncrement>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
@@ -216,6 +220,22 @@ unsafe impl HasRawWindowHandle for WindowInner {
 }
 
 impl WindowInner {
+    /// Tells the gui about the active keyboard layout, if it is
+    /// different from the one that we last told it about
+    fn advise_keyboard_layout(&mut self) {
+        let mut name = [0u16; KL_NAMELENGTH];
+        if unsafe { GetKeyboardLayoutNameW(name.as_mut_ptr()) } == 0 {
+            return;
+        }
+        let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+        let layout = String::from_utf16_lossy(&name[..len]);
+        if self.keyboard_layout.as_ref() != Some(&layout) {
+            self.keyboard_layout.replace(layout.clone());
+            self.events
+                .dispatch(WindowEvent::KeyboardLayoutChanged(layout));
+        }
+    }
+
     fn enable_opengl(&mut self) -> anyhow::Result<Rc<glium::backend::Context>> {
         let conn = Connection::get().unwrap();
 
@@ -532,6 +552,7 @@ impl Window {
             vscroll_remainder: 0,
             hscroll_remainder: 0,
             keyboard_info: KeyboardLayoutInfo::new(),
+            keyboard_layout: None,
             last_size: None,
             in_size_move: false,
             dead_pending: None,
@@ -542,6 +563,7 @@ impl Window {
             config: config.clone(),
             paint_throttled: false,
             invalidated: true,
+            resize_increments: None,
         }));
 
         // Careful: `raw` owns a ref to inner, but there is no Drop impl
@@ -745,6 +767,53 @@ unsafe impl HasRawWindowHandle for Window {
     }
 }
 
+/// Updates the progress indicator shown on the taskbar button of hwnd
+fn set_taskbar_progress(hwnd: HWND, progress: WindowProgress) {
+    use winapi::shared::wtypesbase::CLSCTX_INPROC_SERVER;
+    use winapi::um::combaseapi::{CoCreateInstance, CoInitializeEx};
+    use winapi::um::objbase::COINIT_APARTMENTTHREADED;
+    use winapi::um::shobjidl_core::{
+        CLSID_TaskbarList, ITaskbarList3, TBPF_ERROR, TBPF_INDETERMINATE, TBPF_NOPROGRESS,
+        TBPF_NORMAL, TBPF_PAUSED,
+    };
+    use winapi::Interface;
+
+    let (state, value) = match progress {
+        WindowProgress::None => (TBPF_NOPROGRESS, None),
+        WindowProgress::Normal(pct) => (TBPF_NORMAL, Some(pct)),
+        WindowProgress::Error(pct) => (TBPF_ERROR, Some(pct)),
+        WindowProgress::Indeterminate => (TBPF_INDETERMINATE, None),
+        WindowProgress::Paused(pct) => (TBPF_PAUSED, Some(pct)),
+    };
+
+    unsafe {
+        // This is harmless if COM was already initialized
+        // for this thread
+        CoInitializeEx(null_mut(), COINIT_APARTMENTTHREADED);
+
+        let mut taskbar: *mut ITaskbarList3 = null_mut();
+        let res = CoCreateInstance(
+            &CLSID_TaskbarList,
+            null_mut(),
+            CLSCTX_INPROC_SERVER,
+            &ITaskbarList3::uuidof(),
+            &mut taskbar as *mut _ as *mut _,
+        );
+        if res != S_OK || taskbar.is_null() {
+            log::warn!("Unable to create ITaskbarList3: {:#x}", res);
+            return;
+        }
+
+        if (*taskbar).HrInit() == S_OK {
+            (*taskbar).SetProgressState(hwnd, state);
+            if let Some(pct) = value {
+                (*taskbar).SetProgressValue(hwnd, pct.min(100) as u64, 100);
+            }
+        }
+        (*taskbar).Release();
+    }
+}
+
 #[async_trait(?Send)]
 impl WindowOps for Window {
     async fn enable_opengl(&self) -> anyhow::Result<Rc<glium::backend::Context>> {
@@ -836,6 +905,40 @@ impl WindowOps for Window {
         .detach();
     }
 
+    fn request_attention(&self, attention: UserAttentionType) {
+        let window = self.0;
+        let handle = window.0;
+        promise::spawn::spawn(async move {
+            let (flags, count) = match attention {
+                // Flash until the window is brought to the foreground
+                UserAttentionType::Critical => (FLASHW_ALL | FLASHW_TIMERNOFG, 0),
+                // Flash once; the taskbar button stays highlighted
+                // until the window is activated
+                UserAttentionType::Informational => (FLASHW_TRAY, 1),
+            };
+            let mut info = FLASHWINFO {
+                cbSize: std::mem::size_of::<FLASHWINFO>() as u32,
+                hwnd: handle,
+                dwFlags: flags,
+                uCount: count,
+                dwTimeout: 0,
+            };
+            unsafe {
+                FlashWindowEx(&mut info);
+            }
+        })
+        .detach();
+    }
+
+    fn set_progress(&self, progress: WindowProgress) {
+        let window = self.0;
+        let handle = window.0;
+        promise::spawn::spawn(async move {
+            set_taskbar_progress(handle, progress);
+        })
+        .detach();
+    }
+
     fn maximize(&self) {
         schedule_show_window(self.0, ShowWindowCommand::Maximize);
     }
@@ -882,6 +985,17 @@ impl WindowOps for Window {
         });
     }
 
+    fn set_resize_increments(&self, incr: ResizeIncrement) {
+        Connection::with_window_inner(self.0, move |inner| {
+            inner.resize_increments = if incr.x > 1 || incr.y > 1 {
+                Some(incr)
+            } else {
+                None
+            };
+            Ok(())
+        });
+    }
+
     fn set_text_cursor_position(&self, cursor: Rect) {
         Connection::with_window_inner(self.0, move |inner| {
             inner.set_text_cursor_position(cursor);
@@ -959,6 +1073,24 @@ impl WindowOps for Window {
         clipboard_win::set_clipboard_string(&text).ok();
     }
 
+    fn set_clipboard_image(&self, _clipboard: Clipboard, png: Vec<u8>) {
+        fn set_png(png: &[u8]) -> anyhow::Result<()> {
+            // Applications that accept images with transparency look
+            // for the registered "PNG" format
+            let format = clipboard_win::raw::register_format("PNG")
+                .context("registering PNG clipboard format")?;
+            let clipboard = clipboard_win::Clipboard::new().context("opening clipboard")?;
+            clipboard.empty().context("emptying clipboard")?;
+            clipboard
+                .set(format, png)
+                .context("setting clipboard image")?;
+            Ok(())
+        }
+        if let Err(err) = set_png(&png) {
+            log::error!("Failed to copy image to clipboard: {:#}", err);
+        }
+    }
+
     fn set_window_drag_position(&self, coords: ScreenPoint) {
         Connection::with_window_inner(self.0, move |inner| {
             inner.window_drag_position = Some(coords);
@@ -1538,6 +1670,102 @@ unsafe fn wm_enter_exit_size_move(
     Some(0)
 }
 
+/// Adjusts the proposed window rect during an interactive resize so
+/// that the client area is a multiple of the resize increments,
+/// which Windows doesn't support natively
+unsafe fn wm_sizing(hwnd: HWND, _msg: UINT, wparam: WPARAM, lparam: LPARAM) -> Option<LRESULT> {
+    let incr = rc_from_hwnd(hwnd).and_then(|inner| inner.borrow().resize_increments);
+
+    if let Some(incr) = incr {
+        let rect = &mut *(lparam as *mut RECT);
+
+        // The proposed rect is for the whole window, so account
+        // for the frame around the client area
+        let mut window_rect: RECT = std::mem::zeroed();
+        let mut client_rect: RECT = std::mem::zeroed();
+        GetWindowRect(hwnd, &mut window_rect);
+        GetClientRect(hwnd, &mut client_rect);
+        let frame_width = (window_rect.right - window_rect.left) - client_rect.right;
+        let frame_height = (window_rect.bottom - window_rect.top) - client_rect.bottom;
+
+        fn snap(size: i32, base: u16, incr: u16) -> i32 {
+            let base = base as i32;
+            let incr = (incr as i32).max(1);
+            base + ((size - base).max(incr) / incr) * incr
+        }
+
+        let width = snap(
+            rect.right - rect.left - frame_width,
+            incr.base_width,
+            incr.x,
+        ) + frame_width;
+        let height = snap(
+            rect.bottom - rect.top - frame_height,
+            incr.base_height,
+            incr.y,
+        ) + frame_height;
+
+        // Keep the edge opposite to the one being dragged in place
+        let edge = wparam as u32;
+        let is_edge = |edges: &[_]| edges.iter().any(|&e| e as u32 == edge);
+        if is_edge(&[WMSZ_LEFT, WMSZ_TOPLEFT, WMSZ_BOTTOMLEFT]) {
+            rect.left = rect.right - width;
+        } else {
+            rect.right = rect.left + width;
+        }
+        if is_edge(&[WMSZ_TOP, WMSZ_TOPLEFT, WMSZ_TOPRIGHT]) {
+            rect.top = rect.bottom - height;
+        } else {
+            rect.bottom = rect.top + height;
+        }
+    }
+
+    // Allow events to be processed during live resize
+    crate::spawn::SPAWN_QUEUE.run();
+
+    if incr.is_some() {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+/// Sent when the window moves to a monitor with a different dpi, which
+/// can happen part way through dragging it there.
+/// We apply the window rect suggested by the system, which keeps the
+/// window at the same physical size, and make sure that a resize is
+/// generated for the new dpi even if the pixel size didn't change, so
+/// that the fonts are rescaled and the cell grid is preserved, rather
+/// than the window being blurry or mis-sized until it is next resized.
+unsafe fn wm_dpichanged(
+    hwnd: HWND,
+    _msg: UINT,
+    _wparam: WPARAM,
+    lparam: LPARAM,
+) -> Option<LRESULT> {
+    let suggested = &*(lparam as *const RECT);
+    SetWindowPos(
+        hwnd,
+        null_mut(),
+        suggested.left,
+        suggested.top,
+        rect_width(suggested),
+        rect_height(suggested),
+        SWP_NOZORDER | SWP_NOACTIVATE,
+    );
+
+    let mut should_paint = false;
+    if let Some(inner) = rc_from_hwnd(hwnd) {
+        let mut inner = inner.borrow_mut();
+        should_paint = inner.check_and_call_resize_if_needed();
+    }
+    if should_paint {
+        wm_paint(hwnd, 0, 0, 0)?;
+    }
+
+    Some(0)
+}
+
 /// We handle WM_WINDOWPOSCHANGED and dispatch directly to our wm_size as it
 /// is a bit more efficient than letting DefWindowProcW parse this and
 /// trigger WM_SIZE.
@@ -1578,10 +1806,26 @@ unsafe fn wm_set_focus(
     _wparam: WPARAM,
     _lparam: LPARAM,
 ) -> Option<LRESULT> {
-    rc_from_hwnd(hwnd)?
-        .borrow_mut()
-        .events
-        .dispatch(WindowEvent::FocusChanged(true));
+    let inner = rc_from_hwnd(hwnd)?;
+    let mut inner = inner.borrow_mut();
+    inner.events.dispatch(WindowEvent::FocusChanged(true));
+    inner.advise_keyboard_layout();
+    None
+}
+
+unsafe fn wm_input_lang_change(
+    hwnd: HWND,
+    _msg: UINT,
+    _wparam: WPARAM,
+    _lparam: LPARAM,
+) -> Option<LRESULT> {
+    let inner = rc_from_hwnd(hwnd)?;
+    let mut inner = inner.borrow_mut();
+    // A dead key that was pressed with the prior layout
+    // has no meaning in the new one
+    inner.dead_pending.take();
+    inner.keyboard_info.update();
+    inner.advise_keyboard_layout();
     None
 }
 
@@ -2911,15 +3155,13 @@ unsafe fn do_wnd_proc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LPARAM) ->
         WM_PAINT => wm_paint(hwnd, msg, wparam, lparam),
         WM_ENTERSIZEMOVE | WM_EXITSIZEMOVE => wm_enter_exit_size_move(hwnd, msg, wparam, lparam),
         WM_WINDOWPOSCHANGED => wm_windowposchanged(hwnd, msg, wparam, lparam),
+        WM_DPICHANGED => wm_dpichanged(hwnd, msg, wparam, lparam),
         WM_SETFOCUS => wm_set_focus(hwnd, msg, wparam, lparam),
         WM_KILLFOCUS => wm_kill_focus(hwnd, msg, wparam, lparam),
+        WM_INPUTLANGCHANGE => wm_input_lang_change(hwnd, msg, wparam, lparam),
         WM_DEADCHAR | WM_KEYDOWN | WM_KEYUP | WM_SYSCHAR | WM_CHAR | WM_IME_CHAR | WM_SYSKEYUP
         | WM_SYSKEYDOWN => key(hwnd, msg, wparam, lparam),
-        WM_SIZING => {
-            // Allow events to be processed during live resize
-            crate::spawn::SPAWN_QUEUE.run();
-            None
-        }
+        WM_SIZING => wm_sizing(hwnd, msg, wparam, lparam),
         WM_SETTINGCHANGE | WM_DWMCOMPOSITIONCHANGED => apply_theme(hwnd),
         WM_IME_SETCONTEXT => ime_set_context(hwnd, msg, wparam, lparam),
         WM_IME_COMPOSITION => ime_composition(hwnd, msg, wparam, lparam),
@@ -63,6 +63,7 @@ pub struct XConnection {
     pub atom_targets: Atom,
     pub atom_clipboard: Atom,
     pub atom_texturilist: Atom,
+    pub atom_png: Atom,
     pub atom_xmozurl: Atom,
     pub atom_xdndaware: Atom,
     pub atom_xdndtypelist: Atom,
@@ -86,6 +87,7 @@ pub struct XConnection {
     pub atom_state_maximized_horz: Atom,
     pub atom_state_hidden: Atom,
     pub atom_state_fullscreen: Atom,
+    pub atom_state_demands_attention: Atom,
     pub atom_net_wm_state: Atom,
     pub atom_motif_wm_hints: Atom,
     pub atom_net_wm_pid: Atom,
@@ -599,14 +601,29 @@ impl XConnection {
             // xkbcommon depends on those events in order to:
             //    - update modifiers state
             //    - update keymap/state on keyboard changes
-            if let Some((mods, leds)) = self.keyboard.process_xkb_event(&self.conn, event)? {
+            // We compare the layout before and after so that we can
+            // notice when the user switches to a different layout.
+            let prior_layout = self.keyboard.active_layout_name();
+            let mods_leds = self.keyboard.process_xkb_event(&self.conn, event)?;
+            let layout = self.keyboard.active_layout_name();
+            let layout_changed = layout != prior_layout;
+            if layout_changed {
+                log::debug!("keyboard layout changed from {prior_layout:?} to {layout:?}");
+                self.keyboard.layout_changed();
+            }
+            if mods_leds.is_some() || layout_changed {
                 // route changed state to the window with focus
                 for window in self.windows.borrow().values() {
                     let mut window = window.lock().unwrap();
                     if window.has_focus == Some(true) {
-                        window
-                            .events
-                            .dispatch(crate::WindowEvent::AdviseModifiersLedStatus(mods, leds));
+                        if let Some((mods, leds)) = mods_leds {
+                            window
+                                .events
+                                .dispatch(crate::WindowEvent::AdviseModifiersLedStatus(mods, leds));
+                        }
+                        if layout_changed {
+                            window.advise_keyboard_layout(layout);
+                        }
                         break;
                     }
                 }
@@ -680,6 +697,7 @@ impl XConnection {
         let atom_targets = Self::intern_atom(&conn, "TARGETS")?;
         let atom_clipboard = Self::intern_atom(&conn, "CLIPBOARD")?;
         let atom_texturilist = Self::intern_atom(&conn, "text/uri-list")?;
+        let atom_png = Self::intern_atom(&conn, "image/png")?;
         let atom_xmozurl = Self::intern_atom(&conn, "text/x-moz-url")?;
         let atom_xdndaware = Self::intern_atom(&conn, "XdndAware")?;
         let atom_xdndtypelist = Self::intern_atom(&conn, "XdndTypeList")?;
@@ -704,6 +722,8 @@ impl XConnection {
         let atom_state_maximized_horz = Self::intern_atom(&conn, "_NET_WM_STATE_MAXIMIZED_HORZ")?;
         let atom_state_hidden = Self::intern_atom(&conn, "_NET_WM_STATE_HIDDEN")?;
         let atom_state_fullscreen = Self::intern_atom(&conn, "_NET_WM_STATE_FULLSCREEN")?;
+        let atom_state_demands_attention =
+            Self::intern_atom(&conn, "_NET_WM_STATE_DEMANDS_ATTENTION")?;
         let atom_net_wm_state = Self::intern_atom(&conn, "_NET_WM_STATE")?;
         let atom_motif_wm_hints = Self::intern_atom(&conn, "_MOTIF_WM_HINTS")?;
         let atom_net_wm_pid = Self::intern_atom(&conn, "_NET_WM_PID")?;
@@ -817,6 +837,7 @@ impl XConnection {
             atom_protocols,
             atom_clipboard,
             atom_texturilist,
+            atom_png,
             atom_xmozurl,
             atom_xdndaware,
             atom_xdndtypelist,
@@ -841,6 +862,7 @@ impl XConnection {
             atom_state_maximized_horz,
             atom_state_hidden,
             atom_state_fullscreen,
+            atom_state_demands_attention,
             atom_net_wm_state,
             atom_motif_wm_hints,
             atom_net_wm_pid,
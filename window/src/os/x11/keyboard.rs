@@ -146,6 +146,13 @@ impl Compose {
     }
 }
 
+/// Returns true if sym is one of the dead keysyms, as opposed to
+/// the Multi_key or some other key that starts a compose sequence
+fn is_dead_keysym(sym: xkb::Keysym) -> bool {
+    let sym: u32 = sym.into();
+    (xkb::keysyms::KEY_dead_grave..=xkb::keysyms::KEY_dead_greek).contains(&sym)
+}
+
 fn default_keymap(context: &xkb::Context) -> Option<xkb::Keymap> {
     // use $XKB_DEFAULT_RULES or system default
     let system_default_rules = "";
@@ -318,6 +325,23 @@ impl KeyboardWithFallback {
             let selected_feed = self.selected.compose_feed(xcode, xsym);
 
             match selected_feed {
+                FeedResult::Composing(composition)
+                    if is_dead_keysym(xsym) && !config::configuration().use_dead_keys =>
+                {
+                    // Rather than holding the dead key until the next
+                    // keypress, produce the character from the keycap,
+                    // which is what the composition resolved to
+                    self.selected.compose_clear();
+                    self.fallback.compose_clear();
+                    if !composition.trim().is_empty() {
+                        kc.replace(crate::KeyCode::composed(&composition));
+                    }
+                    log::trace!(
+                        "process_key_event: use_dead_keys=false, \
+                         dead key {xsym:?} -> {composition:?}. kc -> {kc:?}"
+                    );
+                    xsym
+                }
                 FeedResult::Composing(composition) => {
                     log::trace!(
                         "process_key_event: RawKeyEvent FeedResult::Composing: {:?}",
@@ -560,6 +584,19 @@ impl KeyboardWithFallback {
     pub fn update_keymap(&self, connection: &xcb::Connection) -> anyhow::Result<()> {
         self.selected.update_keymap(connection)
     }
+
+    /// Returns the name of the layout that is currently in effect,
+    /// eg: "English (US)"
+    pub fn active_layout_name(&self) -> String {
+        self.selected.active_layout_name()
+    }
+
+    /// Called when the effective layout has changed, to discard any
+    /// partial composition that was started under the previous layout
+    pub fn layout_changed(&self) {
+        self.selected.compose_state.borrow_mut().reset();
+        self.fallback.compose_state.borrow_mut().reset();
+    }
 }
 
 impl Keyboard {
@@ -795,6 +832,14 @@ impl Keyboard {
         );
     }
 
+    fn active_layout_name(&self) -> String {
+        let layout = self
+            .state
+            .borrow()
+            .serialize_layout(xkb::STATE_LAYOUT_EFFECTIVE);
+        self.keymap.borrow().layout_get_name(layout).to_string()
+    }
+
     pub fn update_keymap(&self, connection: &xcb::Connection) -> anyhow::Result<()> {
         log::debug!("update_keymap({}) was called", self.label);
 
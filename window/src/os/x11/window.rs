@@ -10,6 +10,7 @@ use crate::{
 };
 use anyhow::{anyhow, Context as _};
 use async_trait::async_trait;
+use config::window::UserAttentionType;
 use config::ConfigHandle;
 use promise::{Future, Promise};
 use raw_window_handle::{
@@ -27,24 +28,30 @@ use wezterm_input_types::{KeyCode, KeyEvent, KeyboardLedStatus, Modifiers};
 use xcb::x::{Atom, PropMode};
 use xcb::{Event, Xid};
 
+/// The data that we have placed in a selection
+enum SelectionData {
+    Text(String),
+    Png(Vec<u8>),
+}
+
 #[derive(Default)]
 struct CopyAndPaste {
-    clipboard_owned: Option<String>,
-    primary_selection_owned: Option<String>,
+    clipboard_owned: Option<SelectionData>,
+    primary_selection_owned: Option<SelectionData>,
     clipboard_request: Option<Promise<String>>,
     selection_request: Option<Promise<String>>,
     time: u32,
 }
 
 impl CopyAndPaste {
-    fn clipboard(&self, clipboard: Clipboard) -> &Option<String> {
+    fn clipboard(&self, clipboard: Clipboard) -> &Option<SelectionData> {
         match clipboard {
             Clipboard::PrimarySelection => &self.primary_selection_owned,
             Clipboard::Clipboard => &self.clipboard_owned,
         }
     }
 
-    fn clipboard_mut(&mut self, clipboard: Clipboard) -> &mut Option<String> {
+    fn clipboard_mut(&mut self, clipboard: Clipboard) -> &mut Option<SelectionData> {
         match clipboard {
             Clipboard::PrimarySelection => &mut self.primary_selection_owned,
             Clipboard::Clipboard => &mut self.clipboard_owned,
@@ -108,6 +115,11 @@ pub(crate) struct XWindowInner {
     dragging: bool,
     outstanding_configure_requests: usize,
     pending_finished_resizes: usize,
+    attention_requested: bool,
+    /// The keyboard layout most recently reported to the gui
+    keyboard_layout: Option<String>,
+    /// Whether the window is entirely covered by other windows
+    fully_obscured: bool,
 }
 
 /// <https://specifications.freedesktop.org/wm-spec/wm-spec-latest.html#idm46409506331616>
@@ -204,6 +216,7 @@ impl XWindowInner {
             );
             self.sure_about_geometry = false;
         }
+        self.queue_pending(WindowEvent::Exposed);
         self.queue_pending(WindowEvent::NeedRepaint);
     }
 
@@ -261,6 +274,27 @@ impl XWindowInner {
         }
     }
 
+    /// Window managers don't necessarily send a ConfigureNotify when
+    /// the window is minimized or restored, and being obscured doesn't
+    /// generate one at all, so synthesize a resize to report a change
+    /// in visibility.
+    fn check_visibility_and_synthesize_resize(&mut self) {
+        let window_state = self.get_window_state().unwrap_or(WindowState::default());
+        if window_state ^ self.last_wm_state != WindowState::HIDDEN {
+            return;
+        }
+        self.last_wm_state = window_state;
+        self.events.dispatch(WindowEvent::Resized {
+            dimensions: Dimensions {
+                pixel_width: self.width as usize,
+                pixel_height: self.height as usize,
+                dpi: self.dpi as usize,
+            },
+            window_state,
+            live_resizing: false,
+        });
+    }
+
     fn queue_pending(&mut self, event: WindowEvent) {
         self.pending.push(event);
     }
@@ -444,7 +478,7 @@ impl XWindowInner {
                     MouseEventKind::Release(button)
                 }
             }
-            b @ 4..=5 => {
+            b @ 4..=7 => {
                 if !pressed {
                     return Ok(());
                 }
@@ -454,11 +488,13 @@ impl XWindowInner {
                 // default for now!
                 const LINES_PER_TICK: i16 = 5;
 
-                MouseEventKind::VertWheel(if b == 4 {
-                    LINES_PER_TICK
-                } else {
-                    -LINES_PER_TICK
-                })
+                match b {
+                    4 => MouseEventKind::VertWheel(LINES_PER_TICK),
+                    5 => MouseEventKind::VertWheel(-LINES_PER_TICK),
+                    // Buttons 6 and 7 are scroll left and right
+                    6 => MouseEventKind::HorzWheel(LINES_PER_TICK),
+                    _ => MouseEventKind::HorzWheel(-LINES_PER_TICK),
+                }
             }
             _ => {
                 log::trace!("button {} is not implemented", detail);
@@ -824,8 +860,15 @@ impl XWindowInner {
                     // events consistently/at all/in the same order.
                     self.sure_about_geometry = false;
                     self.verify_focus = true;
+                    self.check_visibility_and_synthesize_resize();
                 }
             }
+            Event::X(xcb::x::Event::VisibilityNotify(e)) => {
+                // Only reported when there is no compositor; with one,
+                // windows are always considered to be unobscured
+                self.fully_obscured = e.state() == xcb::x::Visibility::FullyObscured;
+                self.check_visibility_and_synthesize_resize();
+            }
             Event::X(xcb::x::Event::FocusIn(e)) => {
                 if !matches!(e.detail(), xcb::x::NotifyDetail::Pointer) {
                     self.focus_changed(true);
@@ -861,8 +904,27 @@ impl XWindowInner {
         if self.has_focus != Some(focused) {
             self.has_focus.replace(focused);
             self.update_ime_position();
+            if focused && self.attention_requested {
+                if let Err(err) = self.set_attention_hint(None) {
+                    log::error!("Failed to clear attention hint: {err:#}");
+                }
+            }
             log::trace!("Calling focus_change({focused})");
             self.events.dispatch(WindowEvent::FocusChanged(focused));
+            if focused {
+                let layout = self.conn().keyboard.active_layout_name();
+                self.advise_keyboard_layout(layout);
+            }
+        }
+    }
+
+    /// Tells the gui about the active keyboard layout, if it is
+    /// different from the one that we last told it about
+    pub fn advise_keyboard_layout(&mut self, layout: String) {
+        if self.keyboard_layout.as_ref() != Some(&layout) {
+            self.keyboard_layout.replace(layout.clone());
+            self.events
+                .dispatch(WindowEvent::KeyboardLayoutChanged(layout));
         }
     }
 
@@ -977,7 +1039,13 @@ impl XWindowInner {
 
         let selprop = if request.target() == conn.atom_targets {
             // They want to know which targets we support
-            let atoms: [Atom; 1] = [conn.atom_utf8_string];
+            let atoms: [Atom; 1] = match self
+                .selection_atom_to_clipboard(request.selection())
+                .and_then(|clipboard| self.copy_and_paste.clipboard(clipboard).as_ref())
+            {
+                Some(SelectionData::Png(_)) => [conn.atom_png],
+                _ => [conn.atom_utf8_string],
+            };
             log::trace!("SEL: window_id={window_id:?} requestor wants supported targets");
             conn.send_request_no_reply(&xcb::x::ChangeProperty {
                 mode: PropMode::Replace,
@@ -998,7 +1066,7 @@ impl XWindowInner {
                 // We don't and won't do any conversion from UTF-8 to
                 // whatever STRING represents; let's just assume that
                 // the other end is going to handle it correctly.
-                if let Some(text) = self.copy_and_paste.clipboard(clipboard) {
+                if let Some(SelectionData::Text(text)) = self.copy_and_paste.clipboard(clipboard) {
                     conn.send_request_no_reply(&xcb::x::ChangeProperty {
                         mode: PropMode::Replace,
                         window: request.requestor(),
@@ -1015,6 +1083,24 @@ impl XWindowInner {
             } else {
                 xcb::x::ATOM_NONE
             }
+        } else if request.target() == conn.atom_png {
+            log::trace!("SEL: window_id={window_id:?} requestor wants image data");
+            match self
+                .selection_atom_to_clipboard(request.selection())
+                .and_then(|clipboard| self.copy_and_paste.clipboard(clipboard).as_ref())
+            {
+                Some(SelectionData::Png(png)) => {
+                    conn.send_request_no_reply(&xcb::x::ChangeProperty {
+                        mode: PropMode::Replace,
+                        window: request.requestor(),
+                        property: request.property(),
+                        r#type: request.target(),
+                        data: png.as_slice(),
+                    })?;
+                    request.property()
+                }
+                _ => xcb::x::ATOM_NONE,
+            }
         } else {
             // We didn't support their request, so there is nothing
             // we can report back to them.
@@ -1203,6 +1289,9 @@ impl XWindowInner {
                 window_state |= WindowState::HIDDEN;
             }
         }
+        if self.fully_obscured {
+            window_state |= WindowState::HIDDEN;
+        }
 
         Ok(window_state)
     }
@@ -1256,6 +1345,40 @@ impl XWindowInner {
         )
     }
 
+    /// Sets or clears the _NET_WM_STATE_DEMANDS_ATTENTION state and,
+    /// for critical requests, the urgency flag in WM_HINTS that
+    /// window managers that predate EWMH look at
+    fn set_attention_hint(&mut self, attention: Option<UserAttentionType>) -> anyhow::Result<()> {
+        // <https://tronche.com/gui/x/icccm/sec-4.html#s-4.1.2.4>
+        const INPUT_HINT: u32 = 1 << 0;
+        const URGENCY_HINT: u32 = 1 << 8;
+
+        self.attention_requested = attention.is_some();
+        self.set_wm_state(
+            NetWmStateAction::with_bool(attention.is_some()),
+            self.conn().atom_state_demands_attention,
+            None,
+        )?;
+
+        let flags = match attention {
+            Some(UserAttentionType::Critical) => INPUT_HINT | URGENCY_HINT,
+            _ => INPUT_HINT,
+        };
+        // flags, input, initial_state, icon_pixmap, icon_window,
+        // icon_x, icon_y, icon_mask, window_group
+        let hints: [u32; 9] = [flags, 1, 0, 0, 0, 0, 0, 0, 0];
+        let conn = self.conn();
+        conn.send_request_no_reply(&xcb::x::ChangeProperty {
+            mode: PropMode::Replace,
+            window: self.window_id,
+            property: xcb::x::ATOM_WM_HINTS,
+            r#type: xcb::x::ATOM_WM_HINTS,
+            data: &hints,
+        })?;
+        conn.flush()?;
+        Ok(())
+    }
+
     #[allow(clippy::identity_op)]
     fn adjust_decorations(&mut self, decorations: WindowDecorations) -> anyhow::Result<()> {
         // Set the motif hints to disable decorations.
@@ -1424,7 +1547,8 @@ impl XWindow {
                             | xcb::x::EventMask::BUTTON_MOTION
                             | xcb::x::EventMask::KEY_RELEASE
                             | xcb::x::EventMask::PROPERTY_CHANGE
-                            | xcb::x::EventMask::STRUCTURE_NOTIFY,
+                            | xcb::x::EventMask::STRUCTURE_NOTIFY
+                            | xcb::x::EventMask::VISIBILITY_CHANGE,
                     ),
                     xcb::x::Cw::Colormap(color_map_id),
                 ],
@@ -1489,6 +1613,9 @@ impl XWindow {
                 dragging: false,
                 outstanding_configure_requests: 0,
                 pending_finished_resizes: 0,
+                attention_requested: false,
+                keyboard_layout: None,
+                fully_obscured: false,
             }))
         };
 
@@ -1656,6 +1783,15 @@ impl XWindowInner {
         }
     }
 
+    fn request_attention(&mut self, attention: UserAttentionType) {
+        if self.has_focus == Some(true) {
+            return;
+        }
+        if let Err(err) = self.set_attention_hint(Some(attention)) {
+            log::error!("Failed to request attention: {err:#}");
+        }
+    }
+
     fn invalidate(&mut self) {
         self.queue_pending(WindowEvent::NeedRepaint);
         self.dispatch_pending_events().ok();
@@ -2012,6 +2148,13 @@ impl WindowOps for XWindow {
         });
     }
 
+    fn request_attention(&self, attention: UserAttentionType) {
+        XConnection::with_window_inner(self.0, move |inner| {
+            inner.request_attention(attention);
+            Ok(())
+        });
+    }
+
     fn show(&self) {
         XConnection::with_window_inner(self.0, |inner| {
             inner.show();
@@ -2154,7 +2297,25 @@ impl WindowOps for XWindow {
             inner
                 .copy_and_paste
                 .clipboard_mut(clipboard)
-                .replace(text.clone());
+                .replace(SelectionData::Text(text.clone()));
+            inner.update_selection_owner(clipboard)?;
+            Ok(())
+        });
+    }
+
+    /// Set a PNG encoded image in the clipboard
+    fn set_clipboard_image(&self, clipboard: Clipboard, png: Vec<u8>) {
+        let window_id = self.0;
+        XConnection::with_window_inner(window_id, move |inner| {
+            log::trace!(
+                "SEL: window_id={window_id:?} now owns selection \
+                for {clipboard:?} with {} bytes of image data",
+                png.len()
+            );
+            inner
+                .copy_and_paste
+                .clipboard_mut(clipboard)
+                .replace(SelectionData::Png(png.clone()));
             inner.update_selection_owner(clipboard)?;
             Ok(())
         });
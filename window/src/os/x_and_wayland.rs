@@ -13,15 +13,57 @@ use crate::{
     ScreenPoint, WindowEvent, WindowOps,
 };
 use async_trait::async_trait;
+use config::window::UserAttentionType;
 use config::ConfigHandle;
 use promise::*;
 use raw_window_handle::{
     HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle,
 };
 use std::any::Any;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::rc::Rc;
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::Mutex;
 use wezterm_font::FontConfiguration;
 
+lazy_static::lazy_static! {
+    static ref SOUND_PLAYER: Option<Mutex<SyncSender<PathBuf>>> = start_sound_player();
+}
+
+/// Neither X11 nor Wayland provide a way to play sounds, so we hand
+/// sound files off to the player for the running sound server.
+/// This starts a thread that plays the files sent to it one at a time.
+/// At most one request is queued while a sound is playing, so that a
+/// flood of bells is coalesced rather than spawning a player for each.
+fn start_sound_player() -> Option<Mutex<SyncSender<PathBuf>>> {
+    let search_path = std::env::var_os("PATH")?;
+    let player = ["pw-play", "paplay", "aplay"].iter().find_map(|name| {
+        std::env::split_paths(&search_path)
+            .map(|dir| dir.join(name))
+            .find(|candidate| candidate.is_file())
+    })?;
+
+    let (tx, rx) = sync_channel::<PathBuf>(1);
+    std::thread::Builder::new()
+        .name("sound-player".to_string())
+        .spawn(move || {
+            for path in rx {
+                if let Err(err) = Command::new(&player)
+                    .arg(&path)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status()
+                {
+                    log::error!("failed to run {}: {:#}", player.display(), err);
+                }
+            }
+        })
+        .ok()?;
+    Some(Mutex::new(tx))
+}
+
 pub enum Connection {
     X11(Rc<XConnection>),
     #[cfg(feature = "wayland")]
@@ -167,6 +209,17 @@ impl ConnectionOps for Connection {
         }
     }
 
+    fn play_sound_file(&self, path: &Path) -> anyhow::Result<()> {
+        let player = SOUND_PLAYER
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("none of pw-play, paplay or aplay are installed"))?;
+        match player.lock().unwrap().try_send(path.to_path_buf()) {
+            // If a sound is already queued, this one is coalesced with it
+            Ok(()) | Err(TrySendError::Full(_)) => Ok(()),
+            Err(TrySendError::Disconnected(_)) => anyhow::bail!("the sound player has stopped"),
+        }
+    }
+
     fn screens(&self) -> anyhow::Result<Screens> {
         match self {
             Self::X11(x) => x.screens(),
@@ -274,6 +327,14 @@ impl WindowOps for Window {
         }
     }
 
+    fn request_attention(&self, attention: UserAttentionType) {
+        match self {
+            Self::X11(x) => x.request_attention(attention),
+            #[cfg(feature = "wayland")]
+            Self::Wayland(w) => w.request_attention(attention),
+        }
+    }
+
     fn toggle_fullscreen(&self) {
         match self {
             Self::X11(x) => x.toggle_fullscreen(),
@@ -330,6 +391,22 @@ impl WindowOps for Window {
         }
     }
 
+    fn start_text_drag(&self, text: String) {
+        match self {
+            Self::X11(x) => x.start_text_drag(text),
+            #[cfg(feature = "wayland")]
+            Self::Wayland(w) => w.start_text_drag(text),
+        }
+    }
+
+    fn supports_text_drag(&self) -> bool {
+        match self {
+            Self::X11(x) => x.supports_text_drag(),
+            #[cfg(feature = "wayland")]
+            Self::Wayland(w) => w.supports_text_drag(),
+        }
+    }
+
     fn set_icon(&self, image: crate::bitmaps::Image) {
         match self {
             Self::X11(x) => x.set_icon(image),
@@ -408,4 +485,11 @@ impl WindowOps for Window {
             Self::Wayland(w) => w.set_clipboard(clipboard, text),
         }
     }
+    fn set_clipboard_image(&self, clipboard: Clipboard, png: Vec<u8>) {
+        match self {
+            Self::X11(x) => x.set_clipboard_image(clipboard, png),
+            #[cfg(feature = "wayland")]
+            Self::Wayland(w) => w.set_clipboard_image(clipboard, png),
+        }
+    }
 }